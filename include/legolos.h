@@ -0,0 +1,49 @@
+/*
+ * The C interface to legolos, from `cargo build --release --features ffi`.
+ * Link against `target/release/liblegolos.so` (or `.dylib`, or `.dll`).
+ */
+
+#ifndef LEGOLOS_H
+#define LEGOLOS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A machine running a program, and the screen it draws on */
+typedef struct LegolosMachine LegolosMachine;
+
+/* Assembles the program, or returns NULL if it doesn't assemble */
+LegolosMachine *legolos_machine_new(const char *program);
+
+void legolos_machine_free(LegolosMachine *machine);
+
+/* Runs a single cycle. Returns false if the program had already finished. */
+bool legolos_machine_cycle(LegolosMachine *machine);
+
+/* Runs the program to the end and returns how many cycles that took */
+size_t legolos_machine_run(LegolosMachine *machine);
+
+/* The register during the cycle about to run */
+intptr_t legolos_machine_register(const LegolosMachine *machine);
+
+/* The number of the cycle about to run, starting at 1 */
+size_t legolos_machine_ticks(const LegolosMachine *machine);
+
+bool legolos_machine_is_executing(const LegolosMachine *machine);
+
+/*
+ * Writes the screen as NUL-terminated text into the buffer, like snprintf():
+ * at most `capacity` bytes, returning the length of the whole screen
+ */
+size_t legolos_machine_screen(const LegolosMachine *machine, char *buffer, size_t capacity);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
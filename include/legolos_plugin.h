@@ -0,0 +1,56 @@
+/*
+ * The interface legolos plugins implement, for writing them in C. Build a
+ * shared library exporting `legolos_plugin()` and pass it to
+ * `legolos run --plugin`, from a build with `--features plugins`.
+ */
+
+#ifndef LEGOLOS_PLUGIN_H
+#define LEGOLOS_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define LEGOLOS_PLUGIN_ABI_VERSION 1
+
+/* A kind of device. Every function gets back the state `new` returned. */
+typedef struct LegolosDeviceDescriptor {
+    const char *name;
+    size_t length; /* how many addresses it takes on the bus */
+    void *(*new_)(void);
+    void (*free)(void *state);
+    void (*tick)(void *state);
+    intptr_t (*read)(void *state, size_t offset);
+    void (*write)(void *state, size_t offset, intptr_t value);
+} LegolosDeviceDescriptor;
+
+/* A custom instruction, used in programs as `op<opcode> <operand>` */
+typedef struct LegolosOpDescriptor {
+    const char *name;
+    uint8_t opcode;
+    void *(*new_)(void);
+    void (*free)(void *state);
+
+    /* Returns the register's new value */
+    intptr_t (*execute)(void *state, intptr_t register_, intptr_t operand);
+} LegolosOpDescriptor;
+
+typedef struct LegolosPluginDescriptor {
+    uint32_t abi_version; /* LEGOLOS_PLUGIN_ABI_VERSION */
+    const LegolosDeviceDescriptor *devices;
+    size_t device_count;
+    const LegolosOpDescriptor *ops;
+    size_t op_count;
+} LegolosPluginDescriptor;
+
+/* What the plugin exports */
+LegolosPluginDescriptor legolos_plugin(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
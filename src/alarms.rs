@@ -0,0 +1,102 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+use core::ops::RangeInclusive;
+
+use crate::observers::{Cycle, CycleObserver};
+use crate::screens::SCREEN_WIDTH;
+
+/// What an alarm calls when it goes off
+type Hook = Box<dyn FnMut(&Cycle)>;
+
+/// Goes off when the register leaves a range, e.g. `offscreen()`'s, which
+/// catches drawing programs whose sprite has drifted off the screen. Attach
+/// it to a `Fanout` to learn of every cycle it went off on, or to call a hook
+/// right then, or give the range to `Debugger::with_alarm()` to pause there.
+///
+/// It goes off once each time the register leaves, on the first cycle
+/// outside the range, and not again until the register has come back.
+pub struct Alarm {
+    range: RangeInclusive<isize>,
+
+    /// Whether the register was outside the range during the last cycle
+    outside: bool,
+
+    /// The cycles it went off on
+    trips: Vec<Cycle>,
+
+    hook: Option<Hook>,
+}
+
+impl Alarm {
+    /// Creates an alarm that goes off whenever the register leaves the range
+    pub fn new(range: RangeInclusive<isize>) -> Self {
+        Alarm {
+            range,
+            outside: false,
+            trips: Vec::new(),
+            hook: None,
+        }
+    }
+
+    /// Creates an alarm that goes off when the sprite can't light any pixel
+    /// on the specification's screen. The sprite is three pixels wide, so
+    /// that's a register below `-1` or above `40`.
+    pub fn offscreen() -> Self {
+        Alarm::new(onscreen())
+    }
+
+    /// Calls the hook with the cycle every time the alarm goes off
+    pub fn with_hook(mut self, hook: impl FnMut(&Cycle) + 'static) -> Self {
+        self.hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Returns the range the register is meant to stay in
+    pub fn range(&self) -> &RangeInclusive<isize> {
+        &self.range
+    }
+
+    /// Returns every cycle the alarm went off on, oldest first
+    pub fn trips(&self) -> &[Cycle] {
+        &self.trips
+    }
+
+    /// Returns `true` if the alarm has gone off at all
+    pub fn is_tripped(&self) -> bool {
+        !self.trips.is_empty()
+    }
+}
+
+/// The registers for which a three pixel sprite lights a pixel somewhere in
+/// a row of the specification's screen
+pub fn onscreen() -> RangeInclusive<isize> {
+    -1..=SCREEN_WIDTH as isize
+}
+
+impl CycleObserver for Alarm {
+    fn observe(&mut self, cycle: &Cycle) {
+        let outside = !self.range.contains(&cycle.register);
+
+        if outside && !self.outside {
+            self.trips.push(*cycle);
+
+            if let Some(hook) = &mut self.hook {
+                hook(cycle);
+            }
+        }
+
+        self.outside = outside;
+    }
+}
+
+/// Hooks can't be printed, so show the range and trips instead
+impl Debug for Alarm {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter
+            .debug_struct("Alarm")
+            .field("range", &self.range)
+            .field("trips", &self.trips)
+            .finish_non_exhaustive()
+    }
+}
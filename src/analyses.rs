@@ -0,0 +1,483 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+
+use crate::instructions::Instruction;
+use crate::interrupts::InterruptController;
+use crate::programs::Program;
+use crate::screens::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// What's known about an instruction before it runs, without running it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entering {
+    /// The cycle the instruction starts on. The first is `1`.
+    pub cycle: usize,
+
+    /// The register as the instruction starts, if it can be known without
+    /// running the program. Once something is loaded into it from the bus,
+    /// the keyboard, or a custom op, it can't be.
+    pub register: Option<isize>,
+}
+
+/// Works out when every instruction starts and the register it starts with,
+/// in program order. Every instruction takes the same cycles every time and
+/// nothing jumps, so the cycles are always known.
+pub fn entering(program: &[Instruction]) -> Vec<Entering> {
+    let mut cycle = 1;
+    let mut register = Some(1);
+
+    program
+        .iter()
+        .map(|instruction| {
+            let entering = Entering { cycle, register };
+
+            cycle += instruction.cycles();
+            register = after(instruction, register);
+
+            entering
+        })
+        .collect()
+}
+
+/// Propagates the register through the program, returning its value once
+/// every instruction is done, if that can be known without running it
+pub fn leaving(program: &[Instruction]) -> Vec<Option<isize>> {
+    let mut register = Some(1);
+
+    program
+        .iter()
+        .map(|instruction| {
+            register = after(instruction, register);
+            register
+        })
+        .collect()
+}
+
+/// Returns the register once the instruction is done with it
+fn after(instruction: &Instruction, register: Option<isize>) -> Option<isize> {
+    match instruction {
+        // The machine wraps past the ends of an `isize`, which a program
+        // hardly ever means to do, so rather than follow it the register is
+        // left unknown from there
+        Instruction::Addx(number) => register.and_then(|register| register.checked_add(*number)),
+        Instruction::Ldx(_) | Instruction::In | Instruction::Custom(..) => None,
+        Instruction::Noop | Instruction::Out | Instruction::Tone(_) | Instruction::Stx(_) => {
+            register
+        }
+    }
+}
+
+/// Bounds the number of cycles the program can take, over every path through
+/// it. Nothing jumps, so there's only the one path and the bound is exact.
+///
+/// That also proves the program terminates. Without jumps there are no loops,
+/// infinite or otherwise; every instruction runs once and the machine stops
+/// after the last. It's for the bare program, though. Custom ops only trade
+/// values with the register, but devices can raise interrupts, and a machine
+/// servicing them runs their handlers on top of the program: it takes longer,
+/// and if a handler sets its own interrupt off again, e.g. by starting another
+/// `Dma` transfer that raises it when done, it may never stop. See
+/// `termination()` for a machine with handlers.
+pub fn worst_case_cycles(program: &[Instruction]) -> usize {
+    program.iter().map(Instruction::cycles).sum()
+}
+
+/// Returns `true` if the program is sure to finish within a frame, the 240
+/// cycles a screen of the specification's size takes to draw, as long as no
+/// interrupt handlers run; see `worst_case_cycles()`
+pub fn fits_frame(program: &[Instruction]) -> bool {
+    worst_case_cycles(program) <= SCREEN_WIDTH * SCREEN_HEIGHT
+}
+
+/// A store that sets off an interrupt: storing anything at `address` raises
+/// `line` sooner or later, e.g. at the `dma::CONTROL` register of a `Dma`
+/// engine given the line with `Dma::with_interrupt()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trigger {
+    pub address: usize,
+    pub line: usize,
+}
+
+/// Whether a program is sure to finish on a machine with interrupt handlers,
+/// as `termination()` works out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Termination {
+    /// It finishes within this many cycles, handlers included, or
+    /// `usize::MAX` if there are more than that
+    Finishes { cycles: usize },
+
+    /// It may never finish. Each of these lines' handlers sets off the next,
+    /// and the last's sets off the first, so once one of them runs they can
+    /// keep the machine busy for good; a handler setting off its own line is
+    /// a loop of one. Whether they do depends on timing, e.g. whether the
+    /// next interrupt comes before the handler is done, which can't be known
+    /// without running the program.
+    MayLoop { lines: Vec<usize> },
+}
+
+impl Display for Termination {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Termination::Finishes { cycles } => {
+                write!(formatter, "finishes within {} cycles", cycles)
+            }
+            Termination::MayLoop { lines } => match lines.as_slice() {
+                [line] => write!(
+                    formatter,
+                    "may never finish: line {}'s handler can keep setting it off again",
+                    line
+                ),
+                [rest @ .., last] => {
+                    let rest: Vec<String> = rest.iter().map(|line| format!("{}", line)).collect();
+
+                    write!(
+                        formatter,
+                        "may never finish: the handlers of lines {} and {} can keep setting each other off",
+                        rest.join(", "),
+                        last
+                    )
+                }
+                [] => write!(formatter, "may never finish"),
+            },
+        }
+    }
+}
+
+/// Works out whether the program is sure to finish on a machine with the
+/// interrupt controller's handlers, whose lines are set off by storing at
+/// the triggers' addresses. Nothing jumps, so the program and every handler
+/// finish on their own; the only way to run forever is for handlers to keep
+/// setting each other off. If none can, the program finishes, and every
+/// store to a trigger runs its line's handler at most once more, which bounds
+/// how long that takes.
+///
+/// Those are the only interrupts it knows about. Lines raised any other way,
+/// e.g. by a device of its own accord or by `Debugger::raise()`, aren't
+/// counted, and masked lines are counted as if they weren't, since they may
+/// be unmasked.
+pub fn termination(
+    program: &[Instruction],
+    interrupts: &InterruptController,
+    triggers: &[Trigger],
+) -> Termination {
+    let handlers: Vec<&[Instruction]> = (0..interrupts.len())
+        .map(|line| interrupts.handler(line).unwrap_or_default())
+        .collect();
+
+    // How many times each handler sets off every line, at most
+    let sets_off: Vec<Vec<usize>> = handlers
+        .iter()
+        .map(|handler| set_off(handler, triggers, handlers.len()))
+        .collect();
+
+    let mut search = Search {
+        sets_off: &sets_off,
+        visited: vec![Visited::Not; handlers.len()],
+        path: Vec::new(),
+        finished: Vec::new(),
+    };
+
+    for line in 0..handlers.len() {
+        if let Some(lines) = search.visit(line) {
+            return Termination::MayLoop { lines };
+        }
+    }
+
+    // Lines that set others off come before them this way round, so each
+    // line's runs are all counted by the time it's reached
+    let mut runs = set_off(program, triggers, handlers.len());
+
+    for &line in search.finished.iter().rev() {
+        for next in 0..handlers.len() {
+            let more = runs[line].saturating_mul(sets_off[line][next]);
+            runs[next] = runs[next].saturating_add(more);
+        }
+    }
+
+    let cycles =
+        handlers
+            .iter()
+            .zip(runs)
+            .fold(worst_case_cycles(program), |cycles, (handler, runs)| {
+                cycles.saturating_add(runs.saturating_mul(worst_case_cycles(handler)))
+            });
+
+    Termination::Finishes { cycles }
+}
+
+/// Counts the stores the code makes that set off each of `lines` lines
+fn set_off(code: &[Instruction], triggers: &[Trigger], lines: usize) -> Vec<usize> {
+    let mut counts = vec![0; lines];
+
+    for instruction in code {
+        if let Instruction::Stx(address) = instruction {
+            for trigger in triggers {
+                if trigger.address == *address && trigger.line < lines {
+                    counts[trigger.line] += 1;
+                }
+            }
+        }
+    }
+
+    counts
+}
+
+/// A depth-first search of which handlers set off which, for `termination()`
+struct Search<'a> {
+    sets_off: &'a [Vec<usize>],
+    visited: Vec<Visited>,
+
+    /// The lines from where the search started to where it is
+    path: Vec<usize>,
+
+    /// Lines whose every successor has been searched, in the order they were.
+    /// A line always comes after those it sets off.
+    finished: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visited {
+    Not,
+    OnPath,
+    Finished,
+}
+
+impl Search<'_> {
+    /// Searches from the line, returning the first loop it finds
+    fn visit(&mut self, line: usize) -> Option<Vec<usize>> {
+        match self.visited[line] {
+            Visited::Finished => return None,
+            Visited::OnPath => {
+                let start = self.path.iter().position(|&on| on == line)?;
+                return Some(self.path[start..].to_vec());
+            }
+            Visited::Not => (),
+        }
+
+        self.visited[line] = Visited::OnPath;
+        self.path.push(line);
+
+        for next in 0..self.sets_off.len() {
+            if self.sets_off[line][next] > 0 {
+                if let Some(lines) = self.visit(next) {
+                    return Some(lines);
+                }
+            }
+        }
+
+        self.path.pop();
+        self.visited[line] = Visited::Finished;
+        self.finished.push(line);
+
+        None
+    }
+}
+
+/// Finds the instructions that never run when the program is drawn on a
+/// screen of `pixels` pixels that stops at the end of a frame, as screens do
+/// by default: those that would start after the last pixel. Nothing jumps or
+/// halts, so everything else runs, and what doesn't is always the end of the
+/// program. Returns their indices. Interrupt handlers would take cycles of
+/// their own and push more of the program past the frame; like
+/// `worst_case_cycles()`, this is for the bare program.
+pub fn unreachable(program: &[Instruction], pixels: usize) -> Range<usize> {
+    let start = entering(program)
+        .iter()
+        .position(|entering| entering.cycle > pixels)
+        .unwrap_or(program.len());
+
+    start..program.len()
+}
+
+/// Draws the program's control-flow graph in Graphviz's DOT language, e.g.
+/// for `dot -Tsvg`: a box for every basic block, listing its instructions by
+/// index, with an edge wherever control can pass from one to another. Nothing
+/// jumps, so a program is a single block between its entry and exit.
+pub fn export_cfg_dot(program: &Program) -> String {
+    let mut dot = String::from(
+        "digraph cfg {\n    node [shape=box, fontname=monospace];\n    entry [shape=point];\n    exit [shape=point];\n",
+    );
+
+    if program.is_empty() {
+        dot.push_str("    entry -> exit;\n}\n");
+        return dot;
+    }
+
+    // `\l` ends a line of the label, aligned to the left
+    let listing: String = program
+        .instructions()
+        .iter()
+        .enumerate()
+        .map(|(index, instruction)| format!("{}: {}\\l", index, instruction))
+        .collect();
+
+    dot.push_str(&format!("    block0 [label=\"{}\"];\n", listing));
+    dot.push_str("    entry -> block0;\n    block0 -> exit;\n}\n");
+
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn registers_overflowing_are_unknown() {
+        let program = [
+            Instruction::Addx(isize::MAX),
+            Instruction::Addx(1),
+            Instruction::Noop,
+        ];
+
+        assert_eq!(leaving(&program), vec![None, None, None]);
+        assert_eq!(entering(&program)[2].register, None);
+        assert_eq!(entering(&program)[2].cycle, 5);
+    }
+
+    #[test]
+    fn handlers_setting_each_other_off_may_loop() {
+        let program = [
+            Instruction::Stx(10),
+            Instruction::Addx(1),
+            Instruction::Stx(10),
+        ];
+        let triggers = [
+            Trigger {
+                address: 10,
+                line: 0,
+            },
+            Trigger {
+                address: 11,
+                line: 1,
+            },
+            Trigger {
+                address: 12,
+                line: 2,
+            },
+        ];
+
+        let mut interrupts = InterruptController::new();
+        interrupts.add_line(1, vec![Instruction::Stx(11), Instruction::Noop]);
+        interrupts.add_line(2, vec![Instruction::Addx(2)]);
+
+        // Both stores run the first handler, and it runs the second each time
+        assert_eq!(
+            termination(&program, &interrupts, &triggers),
+            Termination::Finishes {
+                cycles: 4 + 2 * 2 + 2 * 2
+            }
+        );
+        assert_eq!(
+            termination(&program, &InterruptController::new(), &triggers),
+            Termination::Finishes { cycles: 4 }
+        );
+
+        interrupts.add_line(3, vec![Instruction::Stx(12)]);
+        assert_eq!(
+            termination(&program, &interrupts, &triggers),
+            Termination::MayLoop { lines: vec![2] }
+        );
+
+        let mut interrupts = InterruptController::new();
+        interrupts.add_line(1, vec![Instruction::Stx(11)]);
+        interrupts.add_line(2, vec![Instruction::Noop, Instruction::Stx(10)]);
+        assert_eq!(
+            termination(&program, &interrupts, &triggers),
+            Termination::MayLoop { lines: vec![0, 1] }
+        );
+    }
+
+    #[test]
+    fn handlers_flagged_do_keep_machines_busy() {
+        use crate::devices::{dma, Dma, Ram};
+        use crate::machines::VirtualMachine;
+        use alloc::collections::VecDeque;
+
+        // Copying nothing finishes as soon as it starts, before the handler
+        // starting it again is done
+        let program = [Instruction::Stx(dma::CONTROL), Instruction::Noop];
+        let mut interrupts = InterruptController::new();
+        interrupts.add_line(1, vec![Instruction::Stx(dma::CONTROL), Instruction::Noop]);
+
+        let triggers = [Trigger {
+            address: dma::CONTROL,
+            line: 0,
+        }];
+        assert_eq!(
+            termination(&program, &interrupts, &triggers),
+            Termination::MayLoop { lines: vec![0] }
+        );
+
+        let engine = Dma::new(Ram::new(1), Ram::new(1)).with_interrupt(interrupts.line(0).unwrap());
+        let mut machine = VirtualMachine::new(VecDeque::from(program)).with_interrupts(interrupts);
+        machine.attach(0, dma::REGISTERS, engine).unwrap();
+
+        for _ in 0..1000 {
+            machine.cycle();
+        }
+
+        assert!(machine.is_executing());
+    }
+
+    #[test]
+    fn programs_a_frame_long_fit_it() {
+        let mut program = vec![Instruction::Addx(1); SCREEN_WIDTH * SCREEN_HEIGHT / 2];
+
+        assert_eq!(worst_case_cycles(&program), 240);
+        assert!(fits_frame(&program));
+
+        program.push(Instruction::Noop);
+        assert_eq!(worst_case_cycles(&program), 241);
+        assert!(!fits_frame(&program));
+    }
+
+    #[test]
+    fn instructions_starting_past_the_frame_never_run() {
+        let program = [Instruction::Addx(1); 3];
+
+        assert_eq!(unreachable(&program, 4), 2..3);
+        assert_eq!(unreachable(&program, 5), 3..3);
+        assert_eq!(unreachable(&program, 0), 0..3);
+        assert_eq!(unreachable(&[], 0), 0..0);
+    }
+
+    #[test]
+    fn registers_are_unknown_once_loaded() {
+        let program = [
+            Instruction::Addx(3),
+            Instruction::Noop,
+            Instruction::Ldx(0),
+            Instruction::Addx(1),
+        ];
+
+        assert_eq!(leaving(&program), [Some(4), Some(4), None, None]);
+        assert_eq!(
+            entering(&program)
+                .iter()
+                .map(|entering| (entering.cycle, entering.register))
+                .collect::<Vec<_>>(),
+            [(1, Some(1)), (3, Some(4)), (4, Some(4)), (5, None)]
+        );
+    }
+
+    #[test]
+    fn programs_are_drawn_as_a_single_block() {
+        const HEADER: &str = "digraph cfg {\n    node [shape=box, fontname=monospace];\n    entry [shape=point];\n    exit [shape=point];\n";
+
+        assert_eq!(
+            export_cfg_dot(&Program::from(vec![Instruction::Noop, Instruction::Addx(-2)])),
+            format!(
+                "{}    block0 [label=\"0: noop\\l1: addx -2\\l\"];\n    entry -> block0;\n    block0 -> exit;\n}}\n",
+                HEADER
+            )
+        );
+        assert_eq!(
+            export_cfg_dot(&Program::from(Vec::new())),
+            format!("{}    entry -> exit;\n}}\n", HEADER)
+        );
+    }
+}
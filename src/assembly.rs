@@ -0,0 +1,244 @@
+use alloc::collections::VecDeque;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use crate::analyses::{self, Entering};
+use crate::instructions::Instruction;
+
+/// Assembles a program written by hand. It's the same language as the
+/// puzzle's input, just friendlier: blank lines are skipped, anything after a
+/// `;` is a comment, and whitespace is flexible. Custom instructions are
+/// written `op<opcode> <operand>`, e.g. `op3 7`. Unlike `parse_instructions()`
+/// it never panics. The first problem is reported with its line number.
+pub fn assemble(source: &str) -> Result<VecDeque<Instruction>, AsmError> {
+    let lines = assemble_lines(source)?;
+    Ok(lines
+        .into_iter()
+        .map(|(_line, instruction)| instruction)
+        .collect())
+}
+
+/// Assembles the program like `assemble()`, keeping the number of the line
+/// (starting at `1`) every instruction came from, e.g. to put breakpoints on
+/// lines of the source
+pub fn assemble_lines(source: &str) -> Result<Vec<(usize, Instruction)>, AsmError> {
+    let mut program = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let code = match line.split_once(';') {
+            Some((code, _comment)) => code,
+            None => line,
+        };
+
+        let mut words = code.split_whitespace();
+
+        let Some(mnemonic) = words.next() else {
+            continue;
+        };
+
+        let operand = words.next();
+
+        let error = |problem| AsmError {
+            line: index + 1,
+            problem,
+        };
+
+        if let Some(extra) = words.next() {
+            return Err(error(AsmProblem::ExtraOperand(extra.to_string())));
+        }
+
+        let instruction = assemble_instruction(mnemonic, operand).map_err(error)?;
+        program.push((index + 1, instruction));
+    }
+
+    Ok(program)
+}
+
+/// Writes the program back out as assembly, one instruction per line. It's
+/// also valid puzzle input.
+pub fn disassemble<'a>(program: impl IntoIterator<Item = &'a Instruction>) -> String {
+    program
+        .into_iter()
+        .map(|instruction| format!("{}\n", instruction))
+        .collect()
+}
+
+/// Writes the program out like `disassemble()`, but with every line annotated
+/// with the cycle its instruction starts on and the register entering it,
+/// and leaving it if the instruction changes it, e.g. `; cycle 3, x = 1 -> 6`,
+/// for an overview of a run without running it. The annotations are
+/// comments, so the result still assembles.
+pub fn annotate<'a>(program: impl IntoIterator<Item = &'a Instruction>) -> Annotated {
+    let instructions: Vec<Instruction> = program.into_iter().copied().collect();
+    let entering = analyses::entering(&instructions);
+    let leaving = analyses::leaving(&instructions);
+
+    Annotated {
+        lines: instructions
+            .into_iter()
+            .zip(entering)
+            .zip(leaving)
+            .map(|((instruction, entering), leaving)| (instruction, entering, leaving))
+            .collect(),
+    }
+}
+
+/// An annotated disassembly, from `annotate()`. The alternate form (`{:#}`)
+/// also colors mnemonics, operands, and annotations for terminals.
+#[derive(Debug, Clone)]
+pub struct Annotated {
+    lines: Vec<(Instruction, Entering, Option<isize>)>,
+}
+
+impl Display for Annotated {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        // ANSI escapes for bold, yellow, dim, and back to normal
+        const BOLD: &str = "\x1b[1m";
+        const YELLOW: &str = "\x1b[33m";
+        const DIM: &str = "\x1b[2m";
+        const RESET: &str = "\x1b[0m";
+
+        let colored = formatter.alternate();
+
+        let texts: Vec<String> = self
+            .lines
+            .iter()
+            .map(|(instruction, _, _)| instruction.to_string())
+            .collect();
+        let width = texts.iter().map(String::len).max().unwrap_or(0);
+
+        for (text, (_, entering, leaving)) in texts.iter().zip(&self.lines) {
+            let (mnemonic, operand) = text.split_once(' ').unwrap_or((text, ""));
+            let padding = width - text.len();
+
+            let known = |register: Option<isize>| match register {
+                Some(register) => register.to_string(),
+                None => "?".to_string(),
+            };
+
+            let mut annotation = format!(
+                "; cycle {}, x = {}",
+                entering.cycle,
+                known(entering.register)
+            );
+
+            if *leaving != entering.register {
+                annotation.push_str(&format!(" -> {}", known(*leaving)));
+            }
+
+            match (colored, operand) {
+                (false, _) => write!(formatter, "{}", text)?,
+                (true, "") => write!(formatter, "{}{}{}", BOLD, mnemonic, RESET)?,
+                (true, _) => write!(
+                    formatter,
+                    "{}{}{} {}{}{}",
+                    BOLD, mnemonic, RESET, YELLOW, operand, RESET
+                )?,
+            }
+
+            match colored {
+                true => writeln!(formatter, "{:padding$}  {}{}{}", "", DIM, annotation, RESET)?,
+                false => writeln!(formatter, "{:padding$}  {}", "", annotation)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) fn assemble_instruction(
+    mnemonic: &str,
+    operand: Option<&str>,
+) -> Result<Instruction, AsmProblem> {
+    let instruction = match (mnemonic, operand) {
+        ("noop", None) => Instruction::Noop,
+        ("out", None) => Instruction::Out,
+        ("in", None) => Instruction::In,
+        ("noop" | "out" | "in", Some(operand)) => {
+            return Err(AsmProblem::ExtraOperand(operand.to_string()))
+        }
+        ("addx", Some(operand)) => Instruction::Addx(parse_operand(operand)?),
+        ("tone", Some(operand)) => Instruction::Tone(parse_operand(operand)?),
+        ("ldx", Some(operand)) => Instruction::Ldx(parse_operand(operand)?),
+        ("stx", Some(operand)) => Instruction::Stx(parse_operand(operand)?),
+        ("addx" | "tone" | "ldx" | "stx", None) => {
+            return Err(AsmProblem::MissingOperand(mnemonic.to_string()))
+        }
+        (custom, operand) => match custom.strip_prefix("op").map(str::parse::<u8>) {
+            Some(Ok(opcode)) => {
+                let operand = operand.ok_or(AsmProblem::MissingOperand(mnemonic.to_string()))?;
+                Instruction::Custom(opcode, parse_operand(operand)?)
+            }
+            _ => return Err(AsmProblem::UnknownMnemonic(mnemonic.to_string())),
+        },
+    };
+
+    Ok(instruction)
+}
+
+fn parse_operand<T: core::str::FromStr>(operand: &str) -> Result<T, AsmProblem> {
+    operand
+        .parse()
+        .map_err(|_| AsmProblem::BadOperand(operand.to_string()))
+}
+
+/// Where and why assembling a program failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    /// Counting from `1`, like an editor
+    pub line: usize,
+    pub problem: AsmProblem,
+}
+
+/// What was wrong with a line of assembly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsmProblem {
+    /// The line starts with something that isn't an instruction
+    UnknownMnemonic(String),
+
+    /// The instruction needs an operand but doesn't have one
+    MissingOperand(String),
+
+    /// The operand isn't a number the instruction accepts, e.g. a negative
+    /// address
+    BadOperand(String),
+
+    /// There's more on the line than the instruction takes
+    ExtraOperand(String),
+
+    /// The line starts with a directive `linking::Object` doesn't know
+    UnknownDirective(String),
+}
+
+impl Display for AsmError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "line {}: {}", self.line, self.problem)
+    }
+}
+
+impl Display for AsmProblem {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AsmProblem::UnknownMnemonic(mnemonic) => {
+                write!(formatter, "unknown instruction `{}`", mnemonic)
+            }
+            AsmProblem::MissingOperand(mnemonic) => {
+                write!(formatter, "`{}` needs an operand", mnemonic)
+            }
+            AsmProblem::BadOperand(operand) => write!(formatter, "bad operand `{}`", operand),
+            AsmProblem::ExtraOperand(operand) => {
+                write!(formatter, "unexpected operand `{}`", operand)
+            }
+            AsmProblem::UnknownDirective(directive) => {
+                write!(formatter, "unknown directive `{}`", directive)
+            }
+        }
+    }
+}
+
+impl Error for AsmError {}
@@ -0,0 +1,254 @@
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::images;
+use crate::instructions::Instruction;
+#[cfg(feature = "std")]
+use crate::metrics::Metrics;
+use crate::observers::{Cycle, CycleObserver, Fanout};
+use crate::programs::Program;
+use crate::screens::Frame;
+
+/// The cycles whose signal strengths the puzzle asks about: the 20th, then
+/// every 40th after it
+pub const INTERESTING_CYCLES: [usize; 6] = [20, 60, 100, 140, 180, 220];
+
+/// How a program's run went, in a form that's cheap to keep thousands of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunSummary {
+    /// How many cycles the program took
+    pub cycles: usize,
+
+    /// The register's value once the program finished
+    pub register: isize,
+
+    /// The sum of the signal strengths (the cycle's number times the register
+    /// during it) at the interesting cycles
+    pub signal_strength: isize,
+
+    /// The first frame the program drew, on the specification's screen
+    pub frame: Frame,
+}
+
+/// A program's run along with its first frame, ready to be saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendering {
+    pub summary: RunSummary,
+
+    /// The frame as a PNG, every pixel a square of the requested scale
+    pub png: Vec<u8>,
+
+    /// The frame as text, one row per line
+    pub text: String,
+}
+
+/// Runs every program to completion and summarizes each, in order. With the
+/// `rayon` feature the programs run in parallel across every core, which is
+/// what makes searching or fuzzing through many generated programs quick.
+pub fn run_batch(programs: &[Program]) -> Vec<RunSummary> {
+    map(programs, summarize)
+}
+
+/// How many programs `evaluate_batch()` advances together. Eight 64-bit lanes
+/// fill an AVX-512 register, or two of AVX2's.
+const LANES: usize = 8;
+
+/// Works out the register during `cycle` for every program, in order, or its
+/// final value for a program that finishes sooner. Each runs as it would on a
+/// machine with nothing attached.
+///
+/// It's built for searches that evaluate thousands of programs: rather than
+/// running machines, the programs are taken a few at a time and advanced
+/// together an instruction at a time, with the register of each in a lane of
+/// a wide integer that the compiler turns into SIMD. With the `rayon`
+/// feature the groups are spread across every core as well.
+pub fn evaluate_batch(programs: &[Program], cycle: usize) -> Vec<isize> {
+    let groups: Vec<&[Program]> = programs.chunks(LANES).collect();
+
+    map(&groups, |group| evaluate_lanes(group, cycle))
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Evaluates up to `LANES` programs for `evaluate_batch()`
+fn evaluate_lanes(programs: &[Program], cycle: usize) -> Vec<isize> {
+    let longest = programs.iter().map(Program::len).max().unwrap_or(0);
+
+    // Every instruction becomes how much of the register it keeps (all or
+    // none), what it adds, and how many cycles it takes, laid out lane by
+    // lane. Past the end of a shorter program, it keeps everything, adds
+    // nothing, and takes no time.
+    let mut keeps = vec![[1isize; LANES]; longest];
+    let mut adds = vec![[0isize; LANES]; longest];
+    let mut cycles = vec![[0usize; LANES]; longest];
+
+    for (lane, program) in programs.iter().enumerate() {
+        for (step, instruction) in program.instructions().iter().enumerate() {
+            // Without devices, a keyboard, or ops, loads and input read `0`
+            // and custom instructions leave the register be
+            let (keep, add) = match instruction {
+                Instruction::Addx(number) => (1, *number),
+                Instruction::Ldx(_) | Instruction::In => (0, 0),
+                Instruction::Noop
+                | Instruction::Out
+                | Instruction::Tone(_)
+                | Instruction::Stx(_)
+                | Instruction::Custom(..) => (1, 0),
+            };
+
+            keeps[step][lane] = keep;
+            adds[step][lane] = add;
+            cycles[step][lane] = instruction.cycles();
+        }
+    }
+
+    let mut registers = [1isize; LANES];
+    let mut elapsed = [0usize; LANES];
+
+    for step in 0..longest {
+        let (keep, add, taken) = (&keeps[step], &adds[step], &cycles[step]);
+
+        elapsed = core::array::from_fn(|lane| elapsed[lane] + taken[lane]);
+
+        // An instruction only counts if it finished before the cycle began.
+        // Choosing arithmetically keeps the lanes free of branches.
+        registers = core::array::from_fn(|lane| {
+            let finished = (elapsed[lane] < cycle) as isize;
+            // Lanes that have finished work the next register out too, only
+            // to throw it away, so it has to wrap rather than overflow
+            let next = registers[lane]
+                .wrapping_mul(keep[lane])
+                .wrapping_add(add[lane]);
+
+            finished * next + (1 - finished) * registers[lane]
+        });
+
+        if elapsed.iter().all(|&elapsed| elapsed >= cycle) {
+            break;
+        }
+    }
+
+    registers[..programs.len()].to_vec()
+}
+
+/// Runs every program like `run_batch()` and renders the frame each drew as
+/// a PNG and as text, in parallel with the `rayon` feature. Encoding the
+/// images is most of the work, so it's done alongside the runs.
+pub fn render_batch(programs: &[Program], scale: usize) -> Vec<Rendering> {
+    map(programs, |program| {
+        let summary = summarize(program);
+
+        Rendering {
+            png: images::png(&summary.frame, scale),
+            text: summary.frame.to_string(),
+            summary,
+        }
+    })
+}
+
+/// Runs every program like `run_batch()`, counting their runs in `metrics` as
+/// they go, e.g. for a server to report on while the batch is running
+#[cfg(feature = "std")]
+pub fn run_batch_metered(programs: &[Program], metrics: &Metrics) -> Vec<RunSummary> {
+    map(programs, |program| metered(program, metrics))
+}
+
+/// Renders every program like `render_batch()`, counting their runs in
+/// `metrics` as they go
+#[cfg(feature = "std")]
+pub fn render_batch_metered(
+    programs: &[Program],
+    scale: usize,
+    metrics: &Metrics,
+) -> Vec<Rendering> {
+    map(programs, |program| {
+        let summary = metered(program, metrics);
+
+        Rendering {
+            png: images::png(&summary.frame, scale),
+            text: summary.frame.to_string(),
+            summary,
+        }
+    })
+}
+
+/// Summarizes the program, telling `metrics` when it starts and finishes
+#[cfg(feature = "std")]
+fn metered(program: &Program, metrics: &Metrics) -> RunSummary {
+    metrics.record_start();
+    let summary = summarize(program);
+    metrics.record_finish(summary.cycles);
+
+    summary
+}
+
+/// Applies `f` to every item, e.g. a program, in parallel if possible, keeping
+/// the order
+pub(crate) fn map<I: Sync, T: Send>(items: &[I], f: impl Fn(&I) -> T + Sync + Send) -> Vec<T> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        items.par_iter().map(f).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Runs a single program to completion and summarizes it
+pub fn summarize(program: &Program) -> RunSummary {
+    let mut frame = Frame::new();
+    let mut strength = SignalStrength(0);
+
+    let mut fanout = Fanout::new(program.machine())
+        .with_observer(&mut frame)
+        .with_observer(&mut strength);
+
+    let cycles = fanout.run();
+    let register = fanout.machine().read_register();
+    drop(fanout);
+
+    RunSummary {
+        cycles,
+        register,
+        signal_strength: strength.0,
+        frame,
+    }
+}
+
+/// Adds up the signal strengths at the interesting cycles
+struct SignalStrength(isize);
+
+impl CycleObserver for SignalStrength {
+    fn observe(&mut self, cycle: &Cycle) {
+        if INTERESTING_CYCLES.contains(&cycle.number) {
+            self.0 += cycle.number as isize * cycle.register;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_wrap_like_the_machine() {
+        let programs = [
+            Program::from(vec![
+                Instruction::Addx(isize::MAX),
+                Instruction::Addx(isize::MAX),
+            ]),
+            Program::from(vec![Instruction::Noop]),
+        ];
+
+        // The second program has long finished by the last cycle
+        assert_eq!(evaluate_batch(&programs, 2), vec![1, 1]);
+        assert_eq!(evaluate_batch(&programs, 3), vec![isize::MIN, 1]);
+        assert_eq!(evaluate_batch(&programs, 5), vec![-1, 1]);
+    }
+}
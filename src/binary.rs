@@ -0,0 +1,165 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use crate::instructions::Instruction;
+use crate::varint;
+
+/// Every program in the binary format starts with these bytes
+pub const MAGIC: &[u8; 4] = b"LEGO";
+
+/// The version of the format written by `encode()`
+pub const VERSION: u8 = 1;
+
+/// Encodes the program in the binary (`.lego`) format: the magic bytes, the
+/// format's version, and the number of instructions, followed by each
+/// instruction's opcode and operand. Operands are variable-length, so a
+/// typical instruction takes two bytes. Custom instructions put their own
+/// opcode in a byte between the two.
+pub fn encode<'a>(program: impl IntoIterator<Item = &'a Instruction>) -> Vec<u8> {
+    let program: Vec<_> = program.into_iter().collect();
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    varint::write(&mut bytes, program.len());
+
+    for instruction in program {
+        bytes.push(opcode(instruction));
+
+        match *instruction {
+            Instruction::Noop | Instruction::Out | Instruction::In => (),
+            Instruction::Addx(number) => varint::write_signed(&mut bytes, number),
+            Instruction::Tone(operand) | Instruction::Ldx(operand) | Instruction::Stx(operand) => {
+                varint::write(&mut bytes, operand)
+            }
+            Instruction::Custom(opcode, operand) => {
+                bytes.push(opcode);
+                varint::write_signed(&mut bytes, operand)
+            }
+        }
+    }
+
+    bytes
+}
+
+/// The inverse of `encode()`
+pub fn decode(bytes: &[u8]) -> Result<VecDeque<Instruction>, DecodeError> {
+    let mut rest = bytes
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or(DecodeError::NotAProgram)?;
+
+    let (version, tail) = rest.split_first().ok_or(DecodeError::Truncated)?;
+    rest = tail;
+
+    if *version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(*version));
+    }
+
+    let length = varint::read(&mut rest).ok_or(DecodeError::Truncated)?;
+
+    // Every instruction takes at least a byte, which bounds a bogus length
+    let mut program = VecDeque::with_capacity(length.min(rest.len()));
+
+    for _ in 0..length {
+        let offset = bytes.len() - rest.len();
+
+        let (opcode, tail) = rest.split_first().ok_or(DecodeError::Truncated)?;
+        rest = tail;
+
+        let instruction = match opcode {
+            0 => Instruction::Noop,
+            1 => Instruction::Addx(varint::read_signed(&mut rest).ok_or(DecodeError::Truncated)?),
+            2 => Instruction::Out,
+            3 => Instruction::Tone(varint::read(&mut rest).ok_or(DecodeError::Truncated)?),
+            4 => Instruction::Ldx(varint::read(&mut rest).ok_or(DecodeError::Truncated)?),
+            5 => Instruction::Stx(varint::read(&mut rest).ok_or(DecodeError::Truncated)?),
+            6 => Instruction::In,
+            7 => {
+                let (custom, tail) = rest.split_first().ok_or(DecodeError::Truncated)?;
+                rest = tail;
+
+                Instruction::Custom(
+                    *custom,
+                    varint::read_signed(&mut rest).ok_or(DecodeError::Truncated)?,
+                )
+            }
+            _ => {
+                return Err(DecodeError::UnknownOpcode {
+                    offset,
+                    opcode: *opcode,
+                })
+            }
+        };
+
+        program.push_back(instruction);
+    }
+
+    match rest.is_empty() {
+        true => Ok(program),
+        false => Err(DecodeError::TrailingBytes(rest.len())),
+    }
+}
+
+/// Returns `true` if the bytes look like a program in the binary format, as
+/// opposed to text
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Returns the byte an instruction starts with in the binary format
+pub(crate) const fn opcode(instruction: &Instruction) -> u8 {
+    match instruction {
+        Instruction::Noop => 0,
+        Instruction::Addx(_) => 1,
+        Instruction::Out => 2,
+        Instruction::Tone(_) => 3,
+        Instruction::Ldx(_) => 4,
+        Instruction::Stx(_) => 5,
+        Instruction::In => 6,
+        Instruction::Custom(..) => 7,
+    }
+}
+
+/// The ways a binary program can be malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The bytes don't start with `MAGIC`
+    NotAProgram,
+
+    /// The program was written by a version of the format this one can't read
+    UnsupportedVersion(u8),
+
+    /// The bytes end in the middle of the program
+    Truncated,
+
+    /// The byte at `offset` should be an opcode but isn't
+    UnknownOpcode { offset: usize, opcode: u8 },
+
+    /// There are bytes left over after the last instruction
+    TrailingBytes(usize),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::NotAProgram => write!(formatter, "not a binary program"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(formatter, "unsupported format version {}", version)
+            }
+            DecodeError::Truncated => write!(formatter, "the program is cut short"),
+            DecodeError::UnknownOpcode { offset, opcode } => {
+                write!(
+                    formatter,
+                    "unknown opcode {:#04x} at byte {}",
+                    opcode, offset
+                )
+            }
+            DecodeError::TrailingBytes(count) => {
+                write!(formatter, "{} bytes left over after the program", count)
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
@@ -0,0 +1,184 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Formatter};
+
+use crate::machines::Detached;
+use crate::programs::{Fingerprint, Program};
+use crate::screens::{self, Screen};
+use crate::timelines;
+
+/// How many programs a cache remembers unless told otherwise
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Remembers what programs drew, by their `Program::fingerprint()`, so
+/// rendering one it has seen before returns at once rather than running it
+/// again. A watch mode or a web service sees the same few programs over and
+/// over. Screens are the specification's, stopping at the last pixel, just
+/// like `Screen::new()` draws.
+///
+/// Once it's full, the program cached longest ago is forgotten to make room.
+#[derive(Debug)]
+pub struct RenderCache {
+    capacity: usize,
+    entries: BTreeMap<Fingerprint, Entry>,
+
+    /// The fingerprints of the cached programs, oldest first
+    order: VecDeque<Fingerprint>,
+}
+
+/// A single program a cache remembers
+#[derive(Debug)]
+struct Entry {
+    /// The program itself, to tell it apart from another with the same
+    /// fingerprint
+    program: Program,
+    rendered: Arc<Rendered>,
+}
+
+/// What a program drew, as a `RenderCache` remembers it
+pub struct Rendered {
+    /// The register's value during every cycle, like
+    /// `timelines::register_timeline()` returns
+    pub timeline: Vec<isize>,
+    pub screen: Screen<Detached>,
+}
+
+impl Rendered {
+    /// Runs the program and draws what it does
+    pub fn new(program: &Program) -> Self {
+        let timeline = timelines::register_timeline(program.machine());
+        let screen = screens::render_from_timeline(&timeline);
+
+        Rendered { timeline, screen }
+    }
+}
+
+/// The screen can't be printed briefly, so only say how long the timeline is
+impl Debug for Rendered {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter
+            .debug_struct("Rendered")
+            .field("cycles", &self.timeline.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl RenderCache {
+    /// Creates a cache remembering up to `capacity` programs. A capacity of
+    /// `0` remembers only the last.
+    pub fn new(capacity: usize) -> Self {
+        RenderCache {
+            capacity,
+            entries: BTreeMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns the screen the program draws, running it only if it isn't
+    /// cached
+    pub fn screen(&mut self, program: &Program) -> &Screen<Detached> {
+        &self.rendered(program).screen
+    }
+
+    /// Returns the register's value during every cycle of the program, like
+    /// `timelines::register_timeline()`, running it only if it isn't cached
+    pub fn timeline(&mut self, program: &Program) -> &[isize] {
+        &self.rendered(program).timeline
+    }
+
+    /// Returns what the program drew if it's cached, without running it. Along
+    /// with `insert()`, it lets a cache shared between threads be locked only
+    /// to look in it and add to it, not while a program runs.
+    pub fn get(&self, program: &Program) -> Option<Arc<Rendered>> {
+        self.entries
+            .get(&program.fingerprint())
+            .filter(|entry| entry.program == *program)
+            .map(|entry| Arc::clone(&entry.rendered))
+    }
+
+    /// Remembers what the program drew, forgetting what it drew before
+    pub fn insert(&mut self, program: &Program, rendered: Arc<Rendered>) {
+        let fingerprint = program.fingerprint();
+
+        // A different program with the same fingerprint is simply replaced
+        if self.entries.remove(&fingerprint).is_some() {
+            self.order.retain(|other| *other != fingerprint);
+        }
+
+        while self.order.len() >= self.capacity.max(1) {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        let entry = Entry {
+            program: program.clone(),
+            rendered,
+        };
+
+        self.entries.insert(fingerprint, entry);
+        self.order.push_back(fingerprint);
+    }
+
+    /// Returns `true` if the program's rendering is cached
+    pub fn contains(&self, program: &Program) -> bool {
+        self.get(program).is_some()
+    }
+
+    /// Returns the number of programs cached
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets every program
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn rendered(&mut self, program: &Program) -> &Rendered {
+        if !self.contains(program) {
+            self.insert(program, Arc::new(Rendered::new(program)));
+        }
+
+        &self.entries[&program.fingerprint()].rendered
+    }
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        RenderCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::instructions::Instruction;
+
+    #[test]
+    fn renderings_are_shared_once_inserted() {
+        let program = Program::from(vec![Instruction::Addx(3), Instruction::Noop]);
+        let mut cache = RenderCache::new(1);
+
+        assert!(cache.get(&program).is_none());
+
+        let rendered = Arc::new(Rendered::new(&program));
+        cache.insert(&program, Arc::clone(&rendered));
+
+        assert!(Arc::ptr_eq(&cache.get(&program).unwrap(), &rendered));
+        assert_eq!(cache.timeline(&program), [1, 1, 4]);
+
+        // There's only room for one
+        let other = Program::from(vec![Instruction::Noop]);
+        assert_eq!(cache.timeline(&other), [1]);
+        assert!(!cache.contains(&program));
+    }
+}
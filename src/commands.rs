@@ -0,0 +1,446 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{self, Read, Write};
+
+use legolos::assembly::{self, AsmError};
+use legolos::binary::{self, DecodeError};
+use legolos::config::{Config, ConfigError};
+use legolos::containers::{self, ContainerError};
+use legolos::instructions::Instruction;
+use legolos::linking::LinkError;
+use legolos::machines::VirtualMachine;
+#[cfg(feature = "plugins")]
+use legolos::plugins::PluginError;
+use legolos::programs::{Program, ProgramError};
+use legolos::recorders::FlightRecorder;
+use legolos::screens::{OverflowError, Screen};
+#[cfg(feature = "rhai")]
+use legolos::scripts::ScriptError;
+
+mod asm;
+mod check;
+mod course;
+mod dap;
+mod debug;
+mod demos;
+mod diff;
+mod disasm;
+mod explain;
+mod gallery;
+mod gdb;
+mod http;
+mod link;
+mod lsp;
+mod mutate;
+mod pipe;
+mod profile;
+mod render;
+mod repl;
+mod report;
+mod run;
+mod serve;
+mod stream;
+mod trace;
+
+const USAGE: &str = "\
+usage: legolos <command> [arguments]
+
+commands:
+    run [program] [-s <script>]    run the program and summarize its signal strengths,
+      [-p <plugin>]...             with a rhai script hooked into every cycle and
+                                   devices and ops from plugins (or directories
+                                   of them)
+    render [program] [--watch]     run the program and print the screen it draws,
+                                   again every time the file changes if watching
+    gallery <programs or dirs>...  render every program to a PNG and text file,
+      [-o <dir>] [-s <scale>]      with an index.html of them all, serving
+      [-m <address>]               Prometheus metrics while it runs
+    asm <program> [-o <output>]    assemble the program into the binary format
+    link <objects>... [-o <output>]
+                                   link objects with labels and segments into
+                                   one program in the binary format
+    disasm [program] [--annotate]  print the program as assembly, annotating
+                                   every line with the cycle it starts on and
+                                   the register entering and leaving it
+    trace [program] [-f <format>] [-a <expression>]...
+                                   run the program and print every cycle, as
+                                   text (the default), jsonl, csv, vcd, or a
+                                   Gantt chart (gantt or gantt-svg), with what
+                                   the expressions work out as for each
+    profile [program]              run the program and print where its cycles
+                                   went: by opcode, per line, and the hottest
+    report [program]               print what the program costs without running
+                                   it: its opcodes, its cycles, and whether it
+                                   draws a whole frame
+    check [program] [-i <priority>:<handler>]... [-t <address>:<line>]...
+                                   check without running it that the program
+                                   finishes with the interrupt handlers, whose
+                                   lines are numbered from 0 and set off by
+                                   storing at the addresses (e.g. a DMA
+                                   engine's control register), exiting with 1
+                                   if they may keep setting each other off
+    diff <a> <b>                   run both programs and report the first cycle
+                                   their registers differ, and why, exiting
+                                   with 1 if they do
+    mutate [program]               run every single-instruction mutant of the
+                                   program and list those that change neither
+                                   the screen nor the signal strengths
+    explain [program]              run the program and narrate every cycle: the
+                                   instruction's timing, the sprite, and why
+                                   each pixel is lit or dark
+    debug [program] [-a <range>] [-b <line>]... [-w <expression>]...
+                                   step through the program in a terminal UI,
+                                   pausing when the register leaves the alarm
+                                   range, like `0..39` (both ends included),
+                                   if given, and at breakpoints on lines, like
+                                   `12`, or `12:x > 20` to pause only when the
+                                   condition holds, and showing the watched
+                                   expressions (needs the `tui` feature)
+    dap                            speak the Debug Adapter Protocol on standard
+                                   input and output, for editors to debug with
+    lsp                            speak the Language Server Protocol on
+                                   standard input and output, for editors to
+                                   check and complete assembly as it's written
+    gdb [program] [-l <address>]   serve the program to gdb or lldb over TCP
+                                   (at 127.0.0.1:1234 by default)
+    serve [-l <address>]           serve machines over TCP to anything that can
+                                   send lines of text (at 127.0.0.1:2022 by
+                                   default); send `help` for the commands
+    stream [program] [-l <address>] [-r <rate>]
+                                   stream the run to browsers over WebSockets,
+                                   a cycle at a time (at 127.0.0.1:2023 and 60
+                                   cycles a second by default; needs the
+                                   `websocket` feature)
+    http [-l <address>]            render programs POSTed to /render as text or
+                                   a PNG, and list their registers as CSV at
+                                   /timeline (at 127.0.0.1:8022 by default;
+                                   needs the `http` feature)
+    repl [--screen]                run instructions as they're typed in
+    pipe                           render the program on standard input to
+                                   standard output, exiting with the final
+                                   register clamped to 0-255
+    demos                          list the programs that come built in
+    course [exercise [program]]    list the exercises, or run the program and
+                                   grade it against one, exiting with 1 if it
+                                   fails
+
+Programs are assembly text, the binary (`.lego`) format, or a container
+(`.legc`) of the binary with metadata. They're read from the given file, or
+from standard input if it's `-`, or are the demo named after `demo:`, e.g.
+`demo:bounce`. Without one, the `sample` demo, a real puzzle input, is used.
+
+`render`, `pipe`, `trace`, and `explain` take `--config <path>` to read the
+screen's size, overflow policy, sprite width, and theme, and the trace's format
+from a TOML file.
+
+Expressions, for `debug` and `trace`, are about a cycle, e.g. `x * tick` or
+`x % 40 == col`: integers, the names `x`, `tick`, `col`, `row`, and `pc`, and
+the operators of Rust, with comparisons making `1` or `0` (see the
+`expressions` module).
+
+A `run` script defines `on_cycle(cycle)`, which can read the cycle's
+`number`, `register`, `instruction`, and `signal`, keep state on `this`, and
+call `annotate(text)` or `pause()` (needs the `rhai` feature).
+
+Plugins are shared libraries exporting `legolos_plugin()`; see the
+`plugins` module. Their devices are attached from address 0, in order, and
+their ops are used with `op<opcode> <operand>` (needs the `plugins` feature).
+
+`asm` writes next to its input, swapping the extension for `.lego`, unless an
+output is given. Input from standard input goes to standard output. `link`
+writes to `a.lego` unless an output is given; objects are laid out in the
+order they're listed, segment by segment (see the `linking` module).";
+
+/// Runs the command named by the first argument, writing what it prints to
+/// `out`. Returns the process's exit status, which is `0` unless the command
+/// says otherwise.
+pub fn dispatch(args: &[String], out: &mut dyn Write) -> Result<u8, CommandError> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return Err(CommandError::Usage("no command given".to_string())),
+    };
+
+    let (config, rest) = read_config(rest)?;
+
+    let result = match command {
+        "run" => run::run(&rest, out),
+        "render" => render::render(&rest, &config, out),
+        "gallery" => gallery::gallery(&rest, out),
+        "asm" => asm::asm(&rest, out),
+        "link" => link::link(&rest, out),
+        "disasm" => disasm::disasm(&rest, out),
+        "trace" => trace::trace(&rest, &config, out),
+        "debug" => debug::debug(&rest, out),
+        "dap" => dap::dap(&rest, out),
+        "lsp" => lsp::lsp(&rest, out),
+        "gdb" => gdb::gdb(&rest, out),
+        "serve" => serve::serve(&rest, out),
+        "stream" => stream::stream(&rest, out),
+        "http" => http::http(&rest, out),
+        "repl" => repl::repl(&rest, out),
+        "profile" => profile::profile(&rest, out),
+        "report" => report::report(&rest, out),
+        "explain" => explain::explain(&rest, &config, out),
+        "mutate" => mutate::mutate(&rest, out),
+        "diff" => return diff::diff(&rest, out),
+        "check" => return check::check(&rest, out),
+        "course" => return course::course(&rest, out),
+        "pipe" => return pipe::pipe(&rest, &config, out),
+        "demos" => demos::demos(&rest, out),
+        "help" | "-h" | "--help" => Ok(writeln!(out, "{}", USAGE)?),
+        _ => Err(CommandError::Usage(format!(
+            "unknown command `{}`",
+            command
+        ))),
+    };
+
+    result.map(|()| 0)
+}
+
+/// Takes `--config <path>` out of the arguments, if it's there, and reads the
+/// configuration it names. Without one, everything is the default.
+fn read_config(args: &[String]) -> Result<(Config, Vec<String>), CommandError> {
+    let mut config = Config::default();
+    let mut rest = Vec::with_capacity(args.len());
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" | "--config" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs a path", arg)))?;
+
+                config = Config::from_path(path).map_err(|error| CommandError::Config {
+                    path: path.clone(),
+                    error,
+                })?;
+            }
+            _ => rest.push(arg.clone()),
+        }
+    }
+
+    Ok((config, rest))
+}
+
+/// Reads the program from the single, optional path in `args`, which may
+/// name a demo instead, e.g. `demo:bounce`
+fn read_program(args: &[String]) -> Result<VecDeque<Instruction>, CommandError> {
+    let demo = |name: &str| {
+        let program = legolos::demos::by_name(name).ok_or_else(|| {
+            CommandError::Usage(format!("no demo named `{}` (see `legolos demos`)", name))
+        })?;
+
+        Ok(program.instructions().iter().copied().collect())
+    };
+
+    match args {
+        [] => demo("sample"),
+        [path] => match path.strip_prefix("demo:") {
+            Some(name) => demo(name),
+            None => parse_program(&read_source(path)?),
+        },
+        [_, extra, ..] => Err(unexpected(extra)),
+    }
+}
+
+/// Reads the program like `read_program()`, along with the line of the
+/// source (counting from `1`) every instruction came from, if it was
+/// assembled from text rather than decoded or taken from a demo
+fn read_listing(
+    args: &[String],
+) -> Result<(VecDeque<Instruction>, Option<Vec<usize>>), CommandError> {
+    let source = match args {
+        [path] if !path.starts_with("demo:") => read_source(path)?,
+        _ => return Ok((read_program(args)?, None)),
+    };
+
+    if containers::is_container(&source) || binary::is_binary(&source) {
+        return Ok((parse_program(&source)?, None));
+    }
+
+    let (lines, program) = assembly::assemble_lines(&String::from_utf8_lossy(&source))?
+        .into_iter()
+        .unzip();
+
+    Ok((program, Some(lines)))
+}
+
+/// Reads the file at `path`, or standard input if it's `-`
+fn read_source(path: &str) -> Result<Vec<u8>, CommandError> {
+    let io_error = |error| CommandError::Io {
+        path: path.to_string(),
+        error,
+    };
+
+    match path {
+        "-" => {
+            let mut source = Vec::new();
+            io::stdin().read_to_end(&mut source).map_err(io_error)?;
+            Ok(source)
+        }
+        _ => fs::read(path).map_err(io_error),
+    }
+}
+
+/// Unpacks a container, decodes a binary program, or assembles a textual one
+fn parse_program(source: &[u8]) -> Result<VecDeque<Instruction>, CommandError> {
+    let program = Program::from_bytes(source)?;
+    Ok(program.instructions().iter().copied().collect())
+}
+
+/// Refreshes the screen, dumping the machine's last cycles if it overflows
+fn refresh(screen: &mut Screen<VirtualMachine>) -> Result<(), CommandError> {
+    match screen.refresh() {
+        Ok(_) => Ok(()),
+        Err(error) => Err(CommandError::Overflow {
+            error,
+            recorder: screen.machine().flight_recorder().clone(),
+        }),
+    }
+}
+
+fn unexpected(argument: &str) -> CommandError {
+    CommandError::Usage(format!("unexpected argument `{}`", argument))
+}
+
+/// Why a command couldn't do its job
+#[derive(Debug)]
+pub enum CommandError {
+    /// The arguments didn't make sense
+    Usage(String),
+
+    /// A file couldn't be read or written
+    Io { path: String, error: io::Error },
+
+    /// The configuration file couldn't be used
+    Config { path: String, error: ConfigError },
+
+    /// The program's assembly didn't assemble
+    Asm(AsmError),
+
+    /// One of the objects being linked didn't assemble
+    Object { path: String, error: AsmError },
+
+    /// The objects didn't link
+    Link(LinkError),
+
+    /// The program's binary didn't decode
+    Decode(DecodeError),
+
+    /// The program's container was damaged or unreadable
+    Container(ContainerError),
+
+    /// The command isn't available in this build
+    #[cfg_attr(
+        all(feature = "tui", feature = "rhai", feature = "plugins"),
+        allow(dead_code)
+    )]
+    Unavailable(String),
+
+    /// The program drew past the end of the screen, with a configuration
+    /// saying that's an error. The machine's last cycles come along.
+    Overflow {
+        error: OverflowError,
+        recorder: FlightRecorder,
+    },
+
+    /// A plugin couldn't be loaded or installed
+    #[cfg(feature = "plugins")]
+    Plugin { path: String, error: PluginError },
+
+    /// The script hooked into the run failed
+    #[cfg(feature = "rhai")]
+    Script(ScriptError),
+
+    /// What the command printed couldn't be written, e.g. because whoever was
+    /// reading it went away
+    Output(io::Error),
+}
+
+impl From<AsmError> for CommandError {
+    fn from(error: AsmError) -> Self {
+        CommandError::Asm(error)
+    }
+}
+
+impl From<LinkError> for CommandError {
+    fn from(error: LinkError) -> Self {
+        CommandError::Link(error)
+    }
+}
+
+impl From<DecodeError> for CommandError {
+    fn from(error: DecodeError) -> Self {
+        CommandError::Decode(error)
+    }
+}
+
+impl From<ContainerError> for CommandError {
+    fn from(error: ContainerError) -> Self {
+        CommandError::Container(error)
+    }
+}
+
+impl From<ProgramError> for CommandError {
+    fn from(error: ProgramError) -> Self {
+        match error {
+            ProgramError::Asm(error) => CommandError::Asm(error),
+            ProgramError::Decode(error) => CommandError::Decode(error),
+            ProgramError::Container(error) => CommandError::Container(error),
+        }
+    }
+}
+
+#[cfg(feature = "rhai")]
+impl From<ScriptError> for CommandError {
+    fn from(error: ScriptError) -> Self {
+        CommandError::Script(error)
+    }
+}
+
+impl From<io::Error> for CommandError {
+    fn from(error: io::Error) -> Self {
+        CommandError::Output(error)
+    }
+}
+
+impl CommandError {
+    /// Returns the process's exit code for the error. Bad arguments are `2`,
+    /// like most command line tools, and everything else is `1`.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CommandError::Usage(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Usage(problem) => write!(formatter, "{}\n\n{}", problem, USAGE),
+            CommandError::Io { path, error } => write!(formatter, "{}: {}", path, error),
+            CommandError::Unavailable(problem) => write!(formatter, "{}", problem),
+            CommandError::Config { path, error } => write!(formatter, "{}: {}", path, error),
+            CommandError::Asm(error) => write!(formatter, "can't assemble: {}", error),
+            CommandError::Object { path, error } => {
+                write!(formatter, "{}: can't assemble: {}", path, error)
+            }
+            CommandError::Link(error) => write!(formatter, "can't link: {}", error),
+            CommandError::Decode(error) => write!(formatter, "can't decode: {}", error),
+            CommandError::Container(error) => write!(formatter, "can't unpack: {}", error),
+            CommandError::Overflow { error, recorder } => {
+                write!(formatter, "{}\n{}", error, recorder.to_string().trim_end())
+            }
+            #[cfg(feature = "plugins")]
+            CommandError::Plugin { path, error } => write!(formatter, "{}: {}", path, error),
+            #[cfg(feature = "rhai")]
+            CommandError::Script(error) => write!(formatter, "script failed: {}", error),
+            CommandError::Output(error) => write!(formatter, "can't write output: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
@@ -0,0 +1,52 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use legolos::{assembly, binary};
+
+use super::{read_source, unexpected, CommandError};
+
+/// Assembles a program and writes it in the binary format
+pub fn asm(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut input = None;
+    let mut output = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match args.next() {
+                Some(path) => output = Some(path.clone()),
+                None => return Err(CommandError::Usage(format!("`{}` needs a path", arg))),
+            },
+            _ if input.is_none() => input = Some(arg.clone()),
+            _ => return Err(unexpected(arg)),
+        }
+    }
+
+    let Some(input) = input else {
+        return Err(CommandError::Usage("`asm` needs a program".to_string()));
+    };
+
+    let source = read_source(&input)?;
+    let program = assembly::assemble(&String::from_utf8_lossy(&source))?;
+    let bytes = binary::encode(&program);
+
+    let output = match output {
+        Some(output) => output,
+        None if input == "-" => return Ok(out.write_all(&bytes)?),
+        None => Path::new(&input)
+            .with_extension("lego")
+            .to_string_lossy()
+            .into_owned(),
+    };
+
+    match output.as_str() {
+        "-" => out.write_all(&bytes)?,
+        _ => fs::write(&output, bytes).map_err(|error| CommandError::Io {
+            path: output.clone(),
+            error,
+        })?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::io::Write;
+
+use legolos::analyses::{self, Termination, Trigger};
+use legolos::interrupts::InterruptController;
+
+use super::{read_program, CommandError};
+
+/// Checks, without running it, that the program finishes on a machine with
+/// the interrupt handlers given with `--interrupt`, which are numbered from
+/// `0` in order, and whose lines are set off by storing at the addresses
+/// given with `--trigger`, e.g. a DMA engine's control register. It prints
+/// how long the program takes at most, or which handlers may keep setting
+/// each other off, in which case the exit status is `1`.
+pub fn check(args: &[String], out: &mut dyn Write) -> Result<u8, CommandError> {
+    let mut paths = Vec::new();
+    let mut interrupts = InterruptController::new();
+    let mut triggers = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--interrupt" => {
+                let usage =
+                    || CommandError::Usage(format!("`{}` needs `<priority>:<handler>`", arg));
+                let (priority, handler) = args
+                    .next()
+                    .and_then(|value| value.split_once(':'))
+                    .ok_or_else(usage)?;
+                let priority = priority.parse().map_err(|_| usage())?;
+
+                let handler: Vec<_> = read_program(&[handler.to_string()])?.into();
+                interrupts.add_line(priority, handler);
+            }
+            "-t" | "--trigger" => {
+                let usage = || CommandError::Usage(format!("`{}` needs `<address>:<line>`", arg));
+                let (address, line) = args
+                    .next()
+                    .and_then(|value| value.split_once(':'))
+                    .ok_or_else(usage)?;
+
+                triggers.push(Trigger {
+                    address: address.parse().map_err(|_| usage())?,
+                    line: line.parse().map_err(|_| usage())?,
+                });
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    let program: Vec<_> = read_program(&paths)?.into();
+    let termination = analyses::termination(&program, &interrupts, &triggers);
+
+    writeln!(out, "{}", termination)?;
+
+    match termination {
+        Termination::Finishes { .. } => Ok(0),
+        Termination::MayLoop { .. } => Ok(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exits_with_1_for_handlers_that_may_loop() {
+        let path = std::env::temp_dir().join(format!("legolos-check-{}.asm", std::process::id()));
+        std::fs::write(&path, "noop\nstx 3\n").unwrap();
+
+        let handler = format!("1:{}", path.display());
+        let check = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            let mut out = Vec::new();
+            let status = check(&args, &mut out).unwrap();
+
+            (status, String::from_utf8(out).unwrap())
+        };
+
+        assert_eq!(
+            check(&["demo:sample", "-i", &handler]),
+            (0, "finishes within 240 cycles\n".to_string())
+        );
+        assert_eq!(
+            check(&["demo:sample", "-i", &handler, "-t", "3:0"]),
+            (
+                1,
+                "may never finish: line 0's handler can keep setting it off again\n".to_string()
+            )
+        );
+        assert!(super::check(&["-t".to_string(), "3".to_string()], &mut Vec::new()).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,34 @@
+use std::io::Write;
+
+use legolos::course;
+use legolos::programs::Program;
+
+use super::{read_program, CommandError};
+
+/// Lists the exercises, or grades the program against the one named. The
+/// exit status is `1` if it fails and `0` if it passes.
+pub fn course(args: &[String], out: &mut dyn Write) -> Result<u8, CommandError> {
+    let Some((name, rest)) = args.split_first() else {
+        for exercise in course::exercises() {
+            writeln!(out, "{:<10} {}", exercise.name(), exercise.prompt())?;
+        }
+
+        return Ok(0);
+    };
+
+    let exercise = course::exercise(name).ok_or_else(|| {
+        CommandError::Usage(format!(
+            "no exercise named `{}` (see `legolos course`)",
+            name
+        ))
+    })?;
+
+    let program = Program::from(read_program(rest)?);
+    let grade = exercise.grade(&program);
+    write!(out, "{}", grade)?;
+
+    Ok(match grade.passed() {
+        true => 0,
+        false => 1,
+    })
+}
@@ -0,0 +1,15 @@
+use std::io::{self, Write};
+
+use legolos::dap::DapServer;
+
+use super::{unexpected, CommandError};
+
+/// Speaks the Debug Adapter Protocol over standard input and output, for an
+/// editor to launch programs with
+pub fn dap(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    if let Some(arg) = args.first() {
+        return Err(unexpected(arg));
+    }
+
+    Ok(DapServer::new(io::stdin().lock(), out).serve()?)
+}
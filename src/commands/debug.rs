@@ -0,0 +1,347 @@
+use std::io::Write;
+use std::ops::RangeInclusive;
+
+use legolos::expressions::Expression;
+
+use super::{read_program, CommandError};
+
+/// Debugs the program in a terminal UI
+#[cfg(feature = "tui")]
+pub fn debug(args: &[String], _out: &mut dyn Write) -> Result<(), CommandError> {
+    let options = parse_args(args)?;
+    let mut debugger = legolos::debugger::Debugger::new(read_program(&options.paths)?);
+
+    if let Some(range) = options.alarm {
+        debugger = debugger.with_alarm(range);
+    }
+
+    for (index, condition) in options.breakpoints {
+        match condition {
+            Some(condition) => debugger.set_condition(index, condition),
+            None => {
+                if !debugger.breakpoints().contains(&index) {
+                    debugger.toggle_breakpoint(index);
+                }
+            }
+        }
+    }
+
+    let mut terminal = ratatui::init();
+    let result = tui::App::new(debugger, options.watches).run(&mut terminal);
+    ratatui::restore();
+
+    Ok(result?)
+}
+
+#[cfg(not(feature = "tui"))]
+pub fn debug(args: &[String], _out: &mut dyn Write) -> Result<(), CommandError> {
+    let options = parse_args(args)?;
+    read_program(&options.paths)?;
+
+    Err(CommandError::Unavailable(
+        "`debug` needs legolos built with the `tui` feature".to_string(),
+    ))
+}
+
+/// What the command line asks of the debugger. Without the `tui` feature
+/// there's no debugger, and only the paths are used.
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+struct Options {
+    paths: Vec<String>,
+
+    /// The range of `--alarm`, if given
+    alarm: Option<RangeInclusive<isize>>,
+
+    /// The positions of the instructions given to `--break`, with their
+    /// conditions if they have them
+    breakpoints: Vec<(usize, Option<Expression>)>,
+
+    /// The expressions given to `--watch`, in order
+    watches: Vec<Expression>,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, CommandError> {
+    let mut paths = Vec::new();
+    let mut alarm = None;
+    let mut breakpoints = Vec::new();
+    let mut watches = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-a" | "--alarm" => {
+                let range = args
+                    .next()
+                    .and_then(|range| range.split_once(".."))
+                    .and_then(|(low, high)| Some(low.parse().ok()?..=high.parse().ok()?))
+                    .ok_or_else(|| {
+                        CommandError::Usage(format!("`{}` needs a range like `0..39`", arg))
+                    })?;
+
+                alarm = Some(range);
+            }
+            "-b" | "--break" => {
+                let breakpoint = args.next().ok_or_else(|| {
+                    CommandError::Usage(format!("`{}` needs a line like `12` or `12:x > 20`", arg))
+                })?;
+
+                let (line, condition) = match breakpoint.split_once(':') {
+                    Some((line, condition)) => (line, Some(parse_expression(condition)?)),
+                    None => (breakpoint.as_str(), None),
+                };
+
+                let index = line
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|line| line.checked_sub(1))
+                    .ok_or_else(|| {
+                        CommandError::Usage(format!("`{}` isn't a line number", line))
+                    })?;
+
+                breakpoints.push((index, condition));
+            }
+            "-w" | "--watch" => {
+                let expression = args.next().ok_or_else(|| {
+                    CommandError::Usage(format!("`{}` needs an expression like `x * tick`", arg))
+                })?;
+
+                watches.push(parse_expression(expression)?);
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    Ok(Options {
+        paths,
+        alarm,
+        breakpoints,
+        watches,
+    })
+}
+
+fn parse_expression(expression: &str) -> Result<Expression, CommandError> {
+    expression
+        .parse()
+        .map_err(|problem| CommandError::Usage(format!("`{}`: {}", expression, problem)))
+}
+
+#[cfg(feature = "tui")]
+mod tui {
+    use std::io;
+
+    use legolos::debugger::{Debugger, Stop};
+    use legolos::expressions::Expression;
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Modifier, Style, Stylize};
+    use ratatui::text::Line;
+    use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+    use ratatui::{DefaultTerminal, Frame};
+
+    const HELP: &str = "s/→ step  r/← back  c continue  b breakpoint  ↑↓ select  g restart  q quit";
+
+    pub struct App {
+        debugger: Debugger,
+
+        /// The line of the disassembly breakpoints are toggled on
+        selected: usize,
+
+        /// What happened last, shown along with the registers
+        status: String,
+
+        /// Shown with their values for the cycle about to run
+        watches: Vec<Expression>,
+    }
+
+    impl App {
+        pub fn new(debugger: Debugger, watches: Vec<Expression>) -> Self {
+            App {
+                debugger,
+                selected: 0,
+                status: "ready".to_string(),
+                watches,
+            }
+        }
+
+        pub fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+            loop {
+                terminal.draw(|frame| self.draw(frame))?;
+
+                let Event::Key(key) = event::read()? else {
+                    continue;
+                };
+
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('s') | KeyCode::Right => self.step(),
+                    KeyCode::Char('r') | KeyCode::Left => self.reverse_step(),
+                    KeyCode::Char('c') => self.resume(),
+                    KeyCode::Char('g') => {
+                        self.debugger.seek(0);
+                        self.status = "restarted".to_string();
+                    }
+                    KeyCode::Char('b') => self.toggle_breakpoint(),
+                    KeyCode::Up | KeyCode::Char('k') => {
+                        self.selected = self.selected.saturating_sub(1);
+                    }
+                    KeyCode::Down | KeyCode::Char('j') => {
+                        let last = self.debugger.program().len().saturating_sub(1);
+                        self.selected = (self.selected + 1).min(last);
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        fn step(&mut self) {
+            self.status = match self.debugger.step() {
+                Some(cycle) => format!("stepped cycle {}", cycle.number),
+                None => "finished".to_string(),
+            };
+        }
+
+        fn reverse_step(&mut self) {
+            self.status = match self.debugger.reverse_step() {
+                true => "stepped back".to_string(),
+                false => "at the start".to_string(),
+            };
+        }
+
+        fn resume(&mut self) {
+            self.status = match self.debugger.resume() {
+                Stop::Breakpoint(index) => format!("breakpoint at line {}", index + 1),
+                Stop::Alarm(register) => format!("register leaving range: {}", register),
+                Stop::Finished => "finished".to_string(),
+            };
+        }
+
+        fn toggle_breakpoint(&mut self) {
+            let line = self.selected + 1;
+
+            self.status = match self.debugger.toggle_breakpoint(self.selected) {
+                true => format!("breakpoint set at line {}", line),
+                false => format!("breakpoint cleared at line {}", line),
+            };
+        }
+
+        fn draw(&self, frame: &mut Frame) {
+            let [body, help] =
+                Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+            let screen = self.debugger.screen();
+            let [left, listing] = Layout::horizontal([
+                Constraint::Length(screen.width() as u16 + 2),
+                Constraint::Min(24),
+            ])
+            .areas(body);
+
+            let [display, registers] = Layout::vertical([
+                Constraint::Length(screen.height() as u16 + 2),
+                Constraint::Min(0),
+            ])
+            .areas(left);
+
+            let block = Block::bordered().title(" screen ");
+            frame.render_widget(screen.widget(), block.inner(display));
+            frame.render_widget(block, display);
+
+            frame.render_widget(self.registers(), registers);
+            self.draw_listing(frame, listing);
+
+            frame.render_widget(Line::from(HELP).dim(), help);
+        }
+
+        fn registers(&self) -> Paragraph<'_> {
+            let machine = self.debugger.machine();
+
+            let current = match machine.instruction_index() {
+                Some(index) => format!("{}", index + 1),
+                None => "-".to_string(),
+            };
+
+            // Nested handlers read outermost first, e.g. `1 > 3`
+            let interrupts: Vec<_> = machine
+                .interrupts_servicing()
+                .map(|line| line.to_string())
+                .collect();
+
+            let interrupt = match interrupts.is_empty() {
+                true => "-".to_string(),
+                false => interrupts.join(" > "),
+            };
+
+            let mut lines = vec![
+                Line::from(format!("cycle        {}", machine.get_ticks())),
+                Line::from(format!("register     {}", machine.read_register())),
+                Line::from(format!("line         {}", current)),
+                Line::from(format!("interrupt    {}", interrupt)),
+                Line::from(format!("executing    {}", machine.is_executing())),
+                Line::from(""),
+            ];
+
+            if !self.watches.is_empty() {
+                let upcoming = self.debugger.upcoming();
+
+                for watch in &self.watches {
+                    lines.push(Line::from(format!(
+                        "{} = {}",
+                        watch,
+                        watch.evaluate(&upcoming)
+                    )));
+                }
+
+                lines.push(Line::from(""));
+            }
+
+            lines.push(Line::from(self.status.as_str()).bold());
+
+            Paragraph::new(lines).block(Block::bordered().title(" machine "))
+        }
+
+        fn draw_listing(&self, frame: &mut Frame, area: ratatui::layout::Rect) {
+            let current = self.debugger.machine().instruction_index();
+            let breakpoints = self.debugger.breakpoints();
+
+            let items: Vec<_> = self
+                .debugger
+                .program()
+                .iter()
+                .enumerate()
+                .map(|(index, instruction)| {
+                    // Conditional breakpoints are hollow
+                    let marker = match breakpoints.contains(&index) {
+                        true if self.debugger.condition(index).is_some() => '○',
+                        true => '●',
+                        false => ' ',
+                    };
+
+                    let pointer = match current == Some(index) {
+                        true => '▶',
+                        false => ' ',
+                    };
+
+                    let text = format!("{}{} {:>4}  {}", marker, pointer, index + 1, instruction);
+                    let mut item = ListItem::new(text);
+
+                    if current == Some(index) {
+                        item = item.style(Style::new().add_modifier(Modifier::REVERSED));
+                    }
+
+                    item
+                })
+                .collect();
+
+            let list = List::new(items)
+                .block(Block::bordered().title(" program "))
+                .highlight_style(Style::new().add_modifier(Modifier::UNDERLINED));
+
+            let mut state = ListState::default().with_selected(Some(self.selected));
+            frame.render_stateful_widget(list, area, &mut state);
+        }
+    }
+}
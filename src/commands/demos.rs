@@ -0,0 +1,18 @@
+use std::io::Write;
+
+use legolos::demos::DEMOS;
+
+use super::{unexpected, CommandError};
+
+/// Lists the demos by name, with what each does
+pub fn demos(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    if let Some(extra) = args.first() {
+        return Err(unexpected(extra));
+    }
+
+    for demo in DEMOS {
+        writeln!(out, "{:<8} {}", demo.name, demo.description)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use std::io::Write;
+
+use legolos::programs::Program;
+use legolos::timelines;
+
+use super::{parse_program, read_source, unexpected, CommandError};
+
+/// Runs two programs and reports the first cycle their registers differ.
+/// Like `diff`, the exit status is `1` if they do and `0` if they don't.
+pub fn diff(args: &[String], out: &mut dyn Write) -> Result<u8, CommandError> {
+    let (a, b) = match args {
+        [a, b] => (a, b),
+        [_, _, extra, ..] => return Err(unexpected(extra)),
+        _ => return Err(CommandError::Usage("`diff` needs two programs".to_string())),
+    };
+
+    let a = Program::from(parse_program(&read_source(a)?)?);
+    let b = Program::from(parse_program(&read_source(b)?)?);
+
+    match timelines::diff_programs(&a, &b) {
+        Some(divergence) => {
+            writeln!(out, "{}", divergence)?;
+            Ok(1)
+        }
+        None => {
+            writeln!(out, "same register on every cycle")?;
+            Ok(0)
+        }
+    }
+}
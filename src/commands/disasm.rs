@@ -0,0 +1,32 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+use legolos::assembly;
+
+use super::{read_program, CommandError};
+
+/// Prints a program, binary or not, as assembly. With `--annotate`, every
+/// line also says when it starts and the register entering and leaving it,
+/// in color if printing to a terminal (and `NO_COLOR` isn't set).
+pub fn disasm(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let annotate = args.iter().any(|arg| arg == "-a" || arg == "--annotate");
+
+    let args: Vec<_> = args
+        .iter()
+        .filter(|arg| *arg != "-a" && *arg != "--annotate")
+        .cloned()
+        .collect();
+
+    let program = read_program(&args)?;
+
+    if !annotate {
+        return Ok(write!(out, "{}", assembly::disassemble(&program))?);
+    }
+
+    let annotated = assembly::annotate(&program);
+
+    match io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none() {
+        true => Ok(write!(out, "{:#}", annotated)?),
+        false => Ok(write!(out, "{}", annotated)?),
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::Write;
+
+use legolos::config::Config;
+use legolos::explanations::Explainer;
+use legolos::machines::VirtualMachine;
+use legolos::observers::Fanout;
+
+use super::{read_program, CommandError};
+
+/// Runs the program and narrates every cycle, on the configured screen
+pub fn explain(args: &[String], config: &Config, out: &mut dyn Write) -> Result<(), CommandError> {
+    let program = read_program(args)?;
+    let listing: Vec<_> = program.iter().copied().collect();
+
+    let mut explainer = Explainer::new(&listing)
+        .with_size(config.width, config.height)
+        .with_sprite_width(config.sprite_width);
+
+    Fanout::new(VirtualMachine::new(program))
+        .with_observer(&mut explainer)
+        .run();
+
+    for line in explainer.lines() {
+        writeln!(out, "{}", line)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use legolos::batches::{self, Rendering};
+use legolos::metrics::{self, Metrics};
+use legolos::programs::Program;
+
+use super::{parse_program, read_source, CommandError};
+
+/// How big every pixel is in the images, unless told otherwise
+const DEFAULT_SCALE: usize = 4;
+
+/// Renders every program given (or found in the directories given) to a PNG
+/// and a text file in the output directory, along with an `index.html` of
+/// them all. With `--metrics`, how it's going is served for Prometheus while
+/// it runs.
+pub fn gallery(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut output = PathBuf::from("gallery");
+    let mut scale = DEFAULT_SCALE;
+    let mut inputs = Vec::new();
+    let mut serving = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match args.next() {
+                Some(path) => output = PathBuf::from(path),
+                None => return Err(CommandError::Usage(format!("`{}` needs a path", arg))),
+            },
+            "-s" | "--scale" => {
+                scale = args
+                    .next()
+                    .and_then(|scale| scale.parse().ok())
+                    .filter(|&scale| scale > 0)
+                    .ok_or_else(|| {
+                        CommandError::Usage(format!("`{}` needs a positive number", arg))
+                    })?;
+            }
+            "-m" | "--metrics" => match args.next() {
+                Some(address) => serving = Some(address.clone()),
+                None => return Err(CommandError::Usage(format!("`{}` needs an address", arg))),
+            },
+            _ => inputs.push(arg.clone()),
+        }
+    }
+
+    if inputs.is_empty() {
+        return Err(CommandError::Usage(
+            "`gallery` needs programs or directories of them".to_string(),
+        ));
+    }
+
+    let paths = find_programs(&inputs)?;
+    let metrics = Arc::new(Metrics::new());
+
+    if let Some(address) = serving {
+        let bound = metrics::serve(Arc::clone(&metrics), address.as_str()).map_err(|error| {
+            CommandError::Io {
+                path: address,
+                error,
+            }
+        })?;
+
+        writeln!(out, "serving metrics at http://{}/metrics", bound)?;
+    }
+
+    // Programs that don't parse are left out of the gallery rather than
+    // stopping it, since generated programs often don't
+    let mut names = Vec::new();
+    let mut programs = Vec::new();
+
+    for path in &paths {
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+
+        match read_source(&path.to_string_lossy()).and_then(|source| parse_program(&source)) {
+            Ok(program) => {
+                names.push(name.into_owned());
+                programs.push(Program::from(program));
+            }
+            Err(error) => {
+                metrics.record_fault();
+                writeln!(out, "{}: skipped: {}", path.display(), error)?
+            }
+        }
+    }
+
+    let renderings = batches::render_batch_metered(&programs, scale, &metrics);
+
+    let io_error = |path: &Path| {
+        let path = path.display().to_string();
+        move |error| CommandError::Io { path, error }
+    };
+
+    fs::create_dir_all(&output).map_err(io_error(&output))?;
+
+    for (name, rendering) in names.iter().zip(&renderings) {
+        let png = output.join(format!("{}.png", name));
+        let text = output.join(format!("{}.txt", name));
+
+        fs::write(&png, &rendering.png).map_err(io_error(&png))?;
+        fs::write(&text, &rendering.text).map_err(io_error(&text))?;
+
+        writeln!(
+            out,
+            "{}: {} cycles, register {} -> {}",
+            name,
+            rendering.summary.cycles,
+            rendering.summary.register,
+            png.display()
+        )?;
+    }
+
+    let index = output.join("index.html");
+    fs::write(&index, index_html(&names, &renderings)).map_err(io_error(&index))?;
+
+    writeln!(
+        out,
+        "{} programs rendered to {}",
+        renderings.len(),
+        index.display()
+    )?;
+
+    Ok(())
+}
+
+/// Expands directories into the files in them, sorted by name. Files are
+/// kept as they are.
+fn find_programs(inputs: &[String]) -> Result<Vec<PathBuf>, CommandError> {
+    let mut paths = Vec::new();
+
+    for input in inputs {
+        let path = Path::new(input);
+
+        if !path.is_dir() {
+            paths.push(path.to_path_buf());
+            continue;
+        }
+
+        let io_error = |error| CommandError::Io {
+            path: input.clone(),
+            error,
+        };
+
+        let mut found = Vec::new();
+
+        for entry in fs::read_dir(path).map_err(io_error)? {
+            let path = entry.map_err(io_error)?.path();
+
+            if path.is_file() {
+                found.push(path);
+            }
+        }
+
+        found.sort();
+        paths.extend(found);
+    }
+
+    Ok(paths)
+}
+
+/// A page with every frame and what its program did
+fn index_html(names: &[String], renderings: &[Rendering]) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>legolos gallery</title>\n\
+         <style>body { background: #111; color: #ccc; font-family: monospace; } \
+         figure { display: inline-block; margin: 1em; } img { image-rendering: pixelated; }</style>\n\
+         </head>\n<body>\n",
+    );
+
+    for (name, rendering) in names.iter().zip(renderings) {
+        let name = escape(name);
+
+        let _ = writeln!(
+            html,
+            "<figure><img src=\"{name}.png\" alt=\"{name}\"><figcaption>{name}: {} cycles, \
+             register {}, signal strength {}</figcaption></figure>",
+            rendering.summary.cycles, rendering.summary.register, rendering.summary.signal_strength,
+        );
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Escapes the characters HTML gives meaning to
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
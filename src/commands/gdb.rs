@@ -0,0 +1,48 @@
+use std::io::Write;
+
+use legolos::debugger::Debugger;
+use legolos::gdb;
+
+use super::{read_program, CommandError};
+
+/// Where the server listens unless told otherwise, GDB's customary port
+const DEFAULT_ADDRESS: &str = "127.0.0.1:1234";
+
+/// Serves the program to a single GDB (or LLDB) connection at the address
+pub fn gdb(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut address = DEFAULT_ADDRESS.to_string();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-l" | "--listen" => {
+                address = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs an address", arg)))?
+                    .clone();
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    let debugger = Debugger::new(read_program(&paths)?);
+
+    writeln!(out, "waiting for a debugger on {}", address)?;
+    writeln!(out, "(e.g. `gdb -ex 'target remote {}'`)", address)?;
+    out.flush()?;
+
+    let debugger = gdb::listen(&address, debugger).map_err(|error| CommandError::Io {
+        path: address.clone(),
+        error,
+    })?;
+
+    writeln!(
+        out,
+        "debugger left after {} cycles, with the register at {}",
+        debugger.cycles(),
+        debugger.machine().read_register()
+    )?;
+
+    Ok(())
+}
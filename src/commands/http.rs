@@ -0,0 +1,52 @@
+use std::io::Write;
+
+#[cfg(feature = "http")]
+use super::unexpected;
+use super::CommandError;
+
+/// Where the server listens unless told otherwise
+#[cfg(feature = "http")]
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8022";
+
+/// Renders the programs POSTed to the address, until interrupted
+#[cfg(feature = "http")]
+pub fn http(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut address = DEFAULT_ADDRESS.to_string();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-l" | "--listen" => {
+                address = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs an address", arg)))?
+                    .clone();
+            }
+            _ => return Err(unexpected(arg)),
+        }
+    }
+
+    let listening = legolos::http::serve(&address).map_err(|error| CommandError::Io {
+        path: address.clone(),
+        error,
+    })?;
+
+    writeln!(out, "rendering on http://{}", listening)?;
+    writeln!(
+        out,
+        "(e.g. `curl --data-binary @program.txt {}/render`)",
+        listening
+    )?;
+    out.flush()?;
+
+    loop {
+        std::thread::park();
+    }
+}
+
+#[cfg(not(feature = "http"))]
+pub fn http(_args: &[String], _out: &mut dyn Write) -> Result<(), CommandError> {
+    Err(CommandError::Unavailable(
+        "`http` needs legolos built with the `http` feature".to_string(),
+    ))
+}
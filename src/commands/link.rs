@@ -0,0 +1,54 @@
+use std::fs;
+use std::io::Write;
+
+use legolos::binary;
+use legolos::linking::{Linker, Object};
+
+use super::{read_source, CommandError};
+
+/// Assembles every object, links them into one program in the order given,
+/// and writes it in the binary format
+pub fn link(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut inputs = Vec::new();
+    let mut output = "a.lego".to_string();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match args.next() {
+                Some(path) => output = path.clone(),
+                None => return Err(CommandError::Usage(format!("`{}` needs a path", arg))),
+            },
+            _ => inputs.push(arg.clone()),
+        }
+    }
+
+    if inputs.is_empty() {
+        return Err(CommandError::Usage(
+            "`link` needs at least one object".to_string(),
+        ));
+    }
+
+    let mut linker = Linker::new();
+
+    for input in inputs {
+        let source = read_source(&input)?;
+        let object = Object::assemble(input.clone(), &String::from_utf8_lossy(&source))
+            .map_err(|error| CommandError::Object { path: input, error })?;
+
+        linker = linker.with_object(object);
+    }
+
+    let linked = linker.link()?;
+    let bytes = binary::encode(linked.program.instructions());
+
+    match output.as_str() {
+        "-" => out.write_all(&bytes)?,
+        _ => fs::write(&output, bytes).map_err(|error| CommandError::Io {
+            path: output.clone(),
+            error,
+        })?,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use std::io::{self, Write};
+
+use legolos::lsp::LspServer;
+
+use super::{unexpected, CommandError};
+
+/// Speaks the Language Server Protocol over standard input and output, for an
+/// editor to check assembly with
+pub fn lsp(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    if let Some(arg) = args.first() {
+        return Err(unexpected(arg));
+    }
+
+    Ok(LspServer::new(io::stdin().lock(), out).serve()?)
+}
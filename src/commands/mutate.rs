@@ -0,0 +1,14 @@
+use std::io::Write;
+
+use legolos::mutations;
+use legolos::programs::Program;
+
+use super::{read_program, CommandError};
+
+/// Runs every single-instruction mutant of the program and lists those that
+/// draw the same screen with the same signal strengths
+pub fn mutate(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let program = Program::from(read_program(args)?);
+
+    Ok(writeln!(out, "{}", mutations::mutate(&program))?)
+}
@@ -0,0 +1,27 @@
+use std::io::Write;
+
+use legolos::config::Config;
+use legolos::machines::VirtualMachine;
+
+use super::{parse_program, read_source, refresh, unexpected, CommandError};
+
+/// Renders the program on standard input to standard output, so the tool
+/// can sit in the middle of a shell pipeline. The exit status is the final
+/// register, which lets scripts branch on what the program computed. It's
+/// clamped to what an exit status can hold, so negative registers exit `0`
+/// and anything past `255` exits `255`.
+pub fn pipe(args: &[String], config: &Config, out: &mut dyn Write) -> Result<u8, CommandError> {
+    if let Some(arg) = args.first() {
+        return Err(unexpected(arg));
+    }
+
+    let program = parse_program(&read_source("-")?)?;
+
+    let mut screen = config.screen(VirtualMachine::new(program));
+    refresh(&mut screen)?;
+
+    write!(out, "{}", screen.display_with(&config.display_options()))?;
+
+    let register = screen.machine().read_register();
+    Ok(register.clamp(0, u8::MAX as isize) as u8)
+}
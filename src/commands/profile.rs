@@ -0,0 +1,139 @@
+use std::io::Write;
+
+use legolos::machines::VirtualMachine;
+use legolos::observers::{Cycle, Fanout};
+use legolos::statistics::Statistics;
+use legolos::timelines;
+
+use super::{read_listing, CommandError};
+
+/// How many of the hottest instructions to list
+const HOT_LINES: usize = 10;
+
+/// How many of the cycles the register hit an extreme to list
+const EXTREME_CYCLES: usize = 5;
+
+/// Runs the program and prints where it spent its cycles: by opcode, its
+/// hottest instructions, and every instruction's cost next to the estimate
+/// from how many cycles it takes. The register's range, mean, and variance
+/// come first. Instructions go by the line of the source they're on, or for
+/// a program without one, e.g. a binary, by their position in the program.
+pub fn profile(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let (program, lines) = read_listing(args)?;
+    let line = |index: usize| lines.as_ref().map_or(index, |lines| lines[index]);
+    let heading = match lines {
+        Some(_) => "line",
+        None => "#",
+    };
+    let mut statistics = Statistics::new(program.iter().copied());
+    let mut cycles: Vec<Cycle> = Vec::new();
+
+    Fanout::new(VirtualMachine::new(program))
+        .with_observer(&mut statistics)
+        .with_observer(&mut cycles)
+        .run();
+
+    let timeline: Vec<_> = cycles.iter().map(|cycle| cycle.register).collect();
+
+    let total = statistics.cycles();
+    let share = |cycles: usize| match total {
+        0 => 0.0,
+        _ => cycles as f64 * 100.0 / total as f64,
+    };
+
+    writeln!(out, "cycles: {}", total)?;
+    writeln!(out, "instructions executed: {}", statistics.executions())?;
+
+    match statistics.cycles_per_instruction() {
+        Some(cpi) => writeln!(out, "cycles per instruction: {:.2}", cpi)?,
+        None => writeln!(out, "cycles per instruction: -")?,
+    }
+
+    if let Some(stats) = timelines::timeline_stats(&timeline) {
+        writeln!(out, "\nregister:")?;
+        writeln!(
+            out,
+            "    min       {:>7} during {}",
+            stats.min,
+            list(&stats.min_cycles)
+        )?;
+        writeln!(
+            out,
+            "    max       {:>7} during {}",
+            stats.max,
+            list(&stats.max_cycles)
+        )?;
+        writeln!(out, "    mean      {:>7.2}", stats.mean)?;
+        writeln!(out, "    variance  {:>7.2}", stats.variance)?;
+    }
+
+    writeln!(out, "\nopcodes:")?;
+
+    for (mnemonic, opcode) in statistics.histogram() {
+        writeln!(
+            out,
+            "    {:<5} {:>5} in program {:>6} executed {:>7} cycles ({:.1}%)",
+            mnemonic,
+            opcode.instructions,
+            opcode.executions,
+            opcode.cycles,
+            share(opcode.cycles)
+        )?;
+    }
+
+    writeln!(out, "\nhot lines:")?;
+
+    for (index, hot) in statistics.hot_lines(HOT_LINES) {
+        writeln!(
+            out,
+            "    {:<11} {:<12} {:>7} cycles ({:.1}%)",
+            match lines {
+                Some(_) => format!("line {}", line(index)),
+                None => format!("#{}", index),
+            },
+            statistics.program()[index].to_string(),
+            hot.cycles,
+            share(hot.cycles)
+        )?;
+    }
+
+    writeln!(out, "\nper line:")?;
+    writeln!(
+        out,
+        "    {:<6} {:<12} {:>6} {:>7} {:>9}",
+        heading, "instruction", "runs", "cycles", "estimated"
+    )?;
+
+    for (index, cost) in statistics.lines() {
+        writeln!(
+            out,
+            "    {:<6} {:<12} {:>6} {:>7} {:>9}",
+            line(index),
+            statistics.program()[index].to_string(),
+            cost.executions,
+            cost.cycles,
+            statistics.estimated_cycles(index).unwrap_or(0)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Lists the first few cycles, e.g. `cycles 1, 2, 3 and 4 more`
+fn list(cycles: &[usize]) -> String {
+    let shown: Vec<_> = cycles
+        .iter()
+        .take(EXTREME_CYCLES)
+        .map(|cycle| cycle.to_string())
+        .collect();
+
+    let noun = match cycles.len() {
+        1 => "cycle",
+        _ => "cycles",
+    };
+
+    match cycles.len().saturating_sub(EXTREME_CYCLES) {
+        0 => format!("{} {}", noun, shown.join(", ")),
+        more => format!("{} {} and {} more", noun, shown.join(", "), more),
+    }
+}
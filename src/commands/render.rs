@@ -0,0 +1,90 @@
+use std::fs;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use legolos::config::Config;
+use legolos::machines::VirtualMachine;
+
+use super::{parse_program, read_program, read_source, refresh, CommandError};
+
+/// How often `--watch` checks whether the program changed
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Runs the program and prints the screen it draws. With `--watch`, it does
+/// so again every time the program's file changes, until interrupted.
+pub fn render(args: &[String], config: &Config, out: &mut dyn Write) -> Result<(), CommandError> {
+    let watch = args.iter().any(|arg| arg == "-w" || arg == "--watch");
+
+    let args: Vec<_> = args
+        .iter()
+        .filter(|arg| *arg != "-w" && *arg != "--watch")
+        .cloned()
+        .collect();
+
+    match (watch, args.as_slice()) {
+        (false, _) => render_once(&args, config, out),
+        (true, [path]) if path != "-" && !path.starts_with("demo:") => {
+            watch_file(path, config, out)
+        }
+        (true, _) => Err(CommandError::Usage(
+            "`--watch` needs the path of a program".to_string(),
+        )),
+    }
+}
+
+fn render_once(args: &[String], config: &Config, out: &mut dyn Write) -> Result<(), CommandError> {
+    let machine = VirtualMachine::new(read_program(args)?);
+
+    let mut screen = config.screen(machine);
+    refresh(&mut screen)?;
+
+    Ok(write!(
+        out,
+        "{}",
+        screen.display_with(&config.display_options())
+    )?)
+}
+
+/// Re-renders the file whenever its modification time changes. Problems with
+/// the program are shown in place of the screen rather than ending the
+/// watch, since they're usually fixed in the next save.
+fn watch_file(path: &str, config: &Config, out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut rendered: Option<SystemTime> = None;
+
+    loop {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|error| CommandError::Io {
+                path: path.to_string(),
+                error,
+            })?;
+
+        if rendered != Some(modified) {
+            rendered = Some(modified);
+
+            // Clear the terminal and start over at the top
+            write!(out, "\x1b[2J\x1b[H")?;
+
+            match render_file(path, config) {
+                Ok(screen) => write!(out, "{}", screen)?,
+                Err(CommandError::Output(error)) => return Err(CommandError::Output(error)),
+                Err(error) => writeln!(out, "{}", error)?,
+            }
+
+            writeln!(out, "\nwatching {} for changes...", path)?;
+            out.flush()?;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn render_file(path: &str, config: &Config) -> Result<String, CommandError> {
+    let program = parse_program(&read_source(path)?)?;
+
+    let mut screen = config.screen(VirtualMachine::new(program));
+    refresh(&mut screen)?;
+
+    Ok(screen.display_with(&config.display_options()).to_string())
+}
@@ -0,0 +1,131 @@
+use std::io::{self, BufRead, Write};
+
+use legolos::assembly;
+use legolos::machines::{Detached, VirtualMachine};
+use legolos::observers::Fanout;
+use legolos::screens::Screen;
+
+use super::{unexpected, CommandError};
+
+const HELP: &str = "\
+Type an instruction to run it, e.g. `addx 3`. Commands:
+    :screen    show or hide the screen after every instruction
+    :reset     start over with a fresh machine
+    :help      show this
+    :quit      leave (so does end of input)";
+
+/// Runs instructions as they're typed in, one line at a time
+pub fn repl(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut live_screen = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--screen" => live_screen = true,
+            _ => return Err(unexpected(arg)),
+        }
+    }
+
+    let mut repl = Repl::new(live_screen);
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    writeln!(out, "{}", HELP)?;
+
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+
+        let Some(line) = lines.next() else {
+            writeln!(out)?;
+            return Ok(());
+        };
+
+        let line = line.map_err(|error| CommandError::Io {
+            path: "-".to_string(),
+            error,
+        })?;
+
+        if !repl.enter(line.trim(), out)? {
+            return Ok(());
+        }
+    }
+}
+
+struct Repl {
+    machine: VirtualMachine,
+    screen: Screen<Detached>,
+    live_screen: bool,
+}
+
+impl Repl {
+    fn new(live_screen: bool) -> Self {
+        Repl {
+            machine: VirtualMachine::default(),
+            screen: Screen::new(Detached),
+            live_screen,
+        }
+    }
+
+    /// Handles a line of input. Returns `false` once it's time to leave.
+    fn enter(&mut self, line: &str, out: &mut dyn Write) -> io::Result<bool> {
+        match line {
+            ":quit" | ":q" => return Ok(false),
+            ":help" | ":h" => writeln!(out, "{}", HELP)?,
+            ":screen" => {
+                self.live_screen = !self.live_screen;
+
+                if self.live_screen {
+                    write!(out, "{}", self.screen)?;
+                }
+            }
+            ":reset" => {
+                *self = Repl::new(self.live_screen);
+                writeln!(out, "reset")?;
+            }
+            _ if line.starts_with(':') => {
+                writeln!(out, "unknown command `{}`, try :help", line)?;
+            }
+            _ => self.execute(line, out)?,
+        }
+
+        Ok(true)
+    }
+
+    /// Runs whatever instruction is on the line until it completes
+    fn execute(&mut self, line: &str, out: &mut dyn Write) -> io::Result<()> {
+        let program = match assembly::assemble(line) {
+            Ok(program) => program,
+            Err(error) => return writeln!(out, "{}", error.problem),
+        };
+
+        // Blank lines and comments assemble to nothing at all
+        if program.is_empty() {
+            return Ok(());
+        }
+
+        for instruction in program {
+            self.machine.push_instruction(instruction);
+        }
+
+        let mut fanout = Fanout::new(&mut self.machine).with_observer(&mut self.screen);
+        let cycles = fanout.run();
+
+        if let Some(signal) = self.machine.signal() {
+            writeln!(out, "{}", signal)?;
+        }
+
+        writeln!(
+            out,
+            "register {}  cycle {}  (+{})",
+            self.machine.read_register(),
+            self.machine.get_ticks(),
+            cycles
+        )?;
+
+        if self.live_screen {
+            write!(out, "{}", self.screen)?;
+        }
+
+        Ok(())
+    }
+}
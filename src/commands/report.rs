@@ -0,0 +1,12 @@
+use std::io::Write;
+
+use legolos::programs::Program;
+
+use super::{read_program, CommandError};
+
+/// Prints what the program costs, worked out without running it
+pub fn report(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let program = Program::from(read_program(args)?);
+
+    Ok(write!(out, "{}", program.report())?)
+}
@@ -0,0 +1,233 @@
+use std::io::Write;
+
+use legolos::batches::INTERESTING_CYCLES;
+use legolos::machines::VirtualMachine;
+use legolos::observers::Fanout;
+
+use super::{read_program, CommandError};
+
+/// Runs the program and prints the signal strength (the cycle's number times
+/// the register's value during it) at each interesting cycle, their sum, and
+/// how the machine finished. With `--script`, a rhai script is hooked into
+/// every cycle. Every `--plugin` attaches its devices to the bus, one after
+/// another from address `0`, and defines its ops.
+pub fn run(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut paths = Vec::new();
+    let mut script = None;
+    let mut plugins = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-s" | "--script" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs a script", arg)))?;
+
+                script = Some(hooks::load(path)?);
+            }
+            "-p" | "--plugin" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs a plugin", arg)))?;
+
+                plugins.push(path.clone());
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    let mut machine = VirtualMachine::new(read_program(&paths)?);
+
+    if !plugins.is_empty() {
+        extensions::install(&mut machine, &plugins, out)?;
+    }
+
+    let mut fanout = Fanout::new(&mut machine);
+    let mut total = 0;
+
+    while let Some(cycle) = fanout.cycle() {
+        if INTERESTING_CYCLES.contains(&cycle.number) {
+            let strength = cycle.number as isize * cycle.register;
+            total += strength;
+
+            writeln!(
+                out,
+                "cycle {:>3}: signal strength {}",
+                cycle.number, strength
+            )?;
+        }
+
+        if let Some(script) = &mut script {
+            hooks::after(script, &cycle, out)?;
+        }
+    }
+
+    writeln!(out, "total signal strength: {}", total)?;
+    writeln!(out, "cycles: {}", machine.get_ticks() - 1)?;
+    writeln!(out, "final register: {}", machine.read_register())?;
+
+    Ok(())
+}
+
+#[cfg(feature = "rhai")]
+mod hooks {
+    use std::io::{self, Write};
+
+    use legolos::observers::{Cycle, CycleObserver};
+    use legolos::scripts::Script;
+
+    use super::super::{read_source, CommandError};
+
+    pub fn load(path: &str) -> Result<Script, CommandError> {
+        let source = read_source(path)?;
+        Ok(Script::new(&String::from_utf8_lossy(&source))?)
+    }
+
+    /// Hooks the script into the cycle and prints what it noted. A pause
+    /// waits for a line on standard input.
+    pub fn after(
+        script: &mut Script,
+        cycle: &Cycle,
+        out: &mut dyn Write,
+    ) -> Result<(), CommandError> {
+        let noted = script.annotations().len();
+        script.observe(cycle);
+
+        if let Some(error) = script.error() {
+            return Err(error.clone().into());
+        }
+
+        for annotation in &script.annotations()[noted..] {
+            writeln!(out, "cycle {:>3}: {}", annotation.cycle, annotation.text)?;
+        }
+
+        if script.take_pause() {
+            writeln!(
+                out,
+                "paused at cycle {}; press enter to continue",
+                cycle.number
+            )?;
+            out.flush()?;
+
+            io::stdin().read_line(&mut String::new())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "rhai"))]
+mod hooks {
+    use std::convert::Infallible;
+    use std::io::Write;
+
+    use legolos::observers::Cycle;
+
+    use super::super::CommandError;
+
+    pub fn load(_path: &str) -> Result<Infallible, CommandError> {
+        Err(CommandError::Unavailable(
+            "`--script` needs legolos built with the `rhai` feature".to_string(),
+        ))
+    }
+
+    pub fn after(
+        script: &mut Infallible,
+        _cycle: &Cycle,
+        _out: &mut dyn Write,
+    ) -> Result<(), CommandError> {
+        match *script {}
+    }
+}
+
+#[cfg(feature = "plugins")]
+mod extensions {
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    use legolos::machines::VirtualMachine;
+    use legolos::plugins::{self, Plugin};
+
+    use super::super::CommandError;
+
+    /// Loads every plugin, or every plugin in a directory, and installs them
+    /// in order, saying where everything went
+    pub fn install(
+        machine: &mut VirtualMachine,
+        paths: &[String],
+        out: &mut dyn Write,
+    ) -> Result<(), CommandError> {
+        let mut address = 0;
+
+        for path in expand(paths)? {
+            let display = path.display().to_string();
+            let plugin_error = |error| CommandError::Plugin {
+                path: display.clone(),
+                error,
+            };
+
+            // SAFETY: whoever asked for the plugin trusts it to be one
+            let plugin = unsafe { Plugin::load(&path) }.map_err(plugin_error)?;
+
+            let mut start = address;
+            for (name, length) in plugin.devices() {
+                writeln!(
+                    out,
+                    "{}: `{}` at {}..{}",
+                    display,
+                    name,
+                    start,
+                    start + length
+                )?;
+                start += length;
+            }
+
+            for (name, opcode) in plugin.ops() {
+                writeln!(out, "{}: `{}` as op{}", display, name, opcode)?;
+            }
+
+            address = plugin.install(machine, address).map_err(plugin_error)?;
+        }
+
+        Ok(())
+    }
+
+    /// Swaps every directory for the plugins in it
+    fn expand(paths: &[String]) -> Result<Vec<PathBuf>, CommandError> {
+        let mut expanded = Vec::new();
+
+        for path in paths {
+            match Path::new(path).is_dir() {
+                true => {
+                    expanded.extend(plugins::discover(path).map_err(|error| CommandError::Io {
+                        path: path.clone(),
+                        error,
+                    })?)
+                }
+                false => expanded.push(PathBuf::from(path)),
+            }
+        }
+
+        Ok(expanded)
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+mod extensions {
+    use std::io::Write;
+
+    use legolos::machines::VirtualMachine;
+
+    use super::super::CommandError;
+
+    pub fn install(
+        _machine: &mut VirtualMachine,
+        _paths: &[String],
+        _out: &mut dyn Write,
+    ) -> Result<(), CommandError> {
+        Err(CommandError::Unavailable(
+            "`--plugin` needs legolos built with the `plugins` feature".to_string(),
+        ))
+    }
+}
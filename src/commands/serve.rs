@@ -0,0 +1,45 @@
+use std::io::Write;
+use std::thread;
+
+use legolos::remote;
+
+use super::{unexpected, CommandError};
+
+/// Where the server listens unless told otherwise
+const DEFAULT_ADDRESS: &str = "127.0.0.1:2022";
+
+/// Serves a machine to every connection at the address, until interrupted
+pub fn serve(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut address = DEFAULT_ADDRESS.to_string();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-l" | "--listen" => {
+                address = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs an address", arg)))?
+                    .clone();
+            }
+            _ => return Err(unexpected(arg)),
+        }
+    }
+
+    let listening = remote::serve(&address).map_err(|error| CommandError::Io {
+        path: address.clone(),
+        error,
+    })?;
+
+    writeln!(out, "serving machines on {}", listening)?;
+    writeln!(
+        out,
+        "(e.g. `nc {} {}`, then `help`)",
+        listening.ip(),
+        listening.port()
+    )?;
+    out.flush()?;
+
+    loop {
+        thread::park();
+    }
+}
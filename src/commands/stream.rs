@@ -0,0 +1,64 @@
+use std::io::Write;
+
+use super::{read_program, CommandError};
+
+/// Where the server listens unless told otherwise
+#[cfg(feature = "websocket")]
+const DEFAULT_ADDRESS: &str = "127.0.0.1:2023";
+
+/// Streams the program's run to every browser that connects, until
+/// interrupted
+#[cfg(feature = "websocket")]
+pub fn stream(args: &[String], out: &mut dyn Write) -> Result<(), CommandError> {
+    use legolos::programs::Program;
+    use legolos::streaming::Streamer;
+
+    let mut address = DEFAULT_ADDRESS.to_string();
+    let mut rate = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-l" | "--listen" => {
+                address = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs an address", arg)))?
+                    .clone();
+            }
+            "-r" | "--rate" => {
+                let value = args.next().and_then(|value| value.parse().ok());
+                rate = Some(value.ok_or_else(|| {
+                    CommandError::Usage(format!("`{}` needs a number of cycles a second", arg))
+                })?);
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    let mut streamer = Streamer::new(Program::from(read_program(&paths)?));
+    if let Some(rate) = rate {
+        streamer = streamer.with_rate(rate);
+    }
+
+    let listening = streamer.serve(&address).map_err(|error| CommandError::Io {
+        path: address.clone(),
+        error,
+    })?;
+
+    writeln!(out, "streaming on ws://{}", listening)?;
+    out.flush()?;
+
+    loop {
+        std::thread::park();
+    }
+}
+
+#[cfg(not(feature = "websocket"))]
+pub fn stream(args: &[String], _out: &mut dyn Write) -> Result<(), CommandError> {
+    read_program(args)?;
+
+    Err(CommandError::Unavailable(
+        "`stream` needs legolos built with the `websocket` feature".to_string(),
+    ))
+}
@@ -0,0 +1,53 @@
+use std::io::Write;
+
+use legolos::config::Config;
+use legolos::machines::VirtualMachine;
+use legolos::traces;
+
+use super::{read_program, CommandError};
+
+/// Runs the program and writes its trace in the chosen format
+pub fn trace(args: &[String], config: &Config, out: &mut dyn Write) -> Result<(), CommandError> {
+    let mut format = config.trace_format;
+    let mut annotations = Vec::new();
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--format" => {
+                let name = args
+                    .next()
+                    .ok_or_else(|| CommandError::Usage(format!("`{}` needs a format", arg)))?;
+
+                format = name.parse().map_err(|error: traces::UnknownTraceFormat| {
+                    CommandError::Usage(error.to_string())
+                })?;
+            }
+            "-a" | "--annotate" => {
+                let expression = args.next().ok_or_else(|| {
+                    CommandError::Usage(format!("`{}` needs an expression like `x * tick`", arg))
+                })?;
+
+                let annotation = expression.parse().map_err(|problem| {
+                    CommandError::Usage(format!("`{}`: {}", expression, problem))
+                })?;
+
+                annotations.push(annotation);
+            }
+            _ => paths.push(arg.clone()),
+        }
+    }
+
+    let program = read_program(&paths)?;
+    let listing: Vec<_> = program.iter().copied().collect();
+    let trace = traces::record(VirtualMachine::new(program));
+
+    Ok(traces::write_annotated_trace(
+        out,
+        &trace,
+        &listing,
+        format,
+        &annotations,
+    )?)
+}
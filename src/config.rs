@@ -0,0 +1,216 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::machines::RegisterSource;
+use crate::screens::{self, DisplayOptions, OverflowPolicy, Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::traces::TraceFormat;
+
+mod toml;
+
+use toml::Value;
+
+/// Settings for machines, screens, and traces, usually read from a TOML file
+/// so a setup doesn't take a dozen flags. Anything the file leaves out
+/// keeps the specification's default:
+///
+/// ```toml
+/// [screen]
+/// width = 40
+/// height = 6
+/// overflow = "stop"   # or "wrap" or "error"
+/// sprite_width = 3
+///
+/// [theme]
+/// lit = "#"
+/// dark = "."
+///
+/// [trace]
+/// format = "text"     # or "jsonl", "csv", "vcd", "gantt", or "gantt-svg"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub width: usize,
+    pub height: usize,
+    pub overflow: OverflowPolicy,
+    pub sprite_width: usize,
+    pub lit: char,
+    pub dark: char,
+    pub trace_format: TraceFormat,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            overflow: OverflowPolicy::default(),
+            sprite_width: 3,
+            lit: '#',
+            dark: '.',
+            trace_format: TraceFormat::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from a TOML file
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        fs::read_to_string(path).map_err(ConfigError::Io)?.parse()
+    }
+
+    /// Creates a screen for the machine with the configured size, overflow
+    /// policy, and sprite
+    pub fn screen<M: RegisterSource>(&self, machine: M) -> Screen<M> {
+        Screen::with_size(machine, self.width, self.height)
+            .with_overflow(self.overflow)
+            .with_sprite_width(self.sprite_width)
+    }
+
+    /// Returns options displaying screens with the configured theme
+    pub fn display_options(&self) -> DisplayOptions {
+        DisplayOptions::default().pixels(self.lit, self.dark)
+    }
+
+    /// Applies a single setting from the file
+    fn set(&mut self, section: &str, key: &str, value: Value) -> Result<(), Problem> {
+        match (section, key) {
+            ("screen", "width") => self.width = value.into_usize()?,
+            ("screen", "height") => self.height = value.into_usize()?,
+            ("screen", "sprite_width") => self.sprite_width = value.into_usize()?,
+            ("screen", "overflow") => {
+                self.overflow = match value.into_string()?.as_str() {
+                    "wrap" => OverflowPolicy::Wrap,
+                    "stop" => OverflowPolicy::Stop,
+                    "error" => OverflowPolicy::Error,
+                    other => return Err(Problem::BadValue(other.to_string())),
+                }
+            }
+            ("theme", "lit") => self.lit = value.into_char()?,
+            ("theme", "dark") => self.dark = value.into_char()?,
+            ("trace", "format") => {
+                let name = value.into_string()?;
+                self.trace_format = name.parse().map_err(|_| Problem::BadValue(name))?;
+            }
+            _ => return Err(Problem::UnknownKey(format!("{}.{}", section, key))),
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Config::default();
+
+        for (line, entry) in toml::entries(s) {
+            let error = |problem| ConfigError::Invalid { line, problem };
+            let entry = entry.map_err(error)?;
+
+            config
+                .set(&entry.section, &entry.key, entry.value)
+                .map_err(error)?;
+        }
+
+        if config.width == 0 || config.height == 0 {
+            return Err(ConfigError::Size(format!(
+                "a {}×{} screen has no pixels",
+                config.width, config.height
+            )));
+        }
+
+        if !screens::fits(config.width, config.height) {
+            return Err(ConfigError::Size(format!(
+                "a {}×{} screen is too big (at most {} pixels)",
+                config.width,
+                config.height,
+                screens::MAX_PIXELS
+            )));
+        }
+
+        // Screens go over every column the sprite covers on every cycle
+        if config.sprite_width > config.width {
+            return Err(ConfigError::Size(format!(
+                "a sprite {} pixels wide doesn't fit on a screen {} wide",
+                config.sprite_width, config.width
+            )));
+        }
+
+        Ok(config)
+    }
+}
+
+/// Why a configuration couldn't be read
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file couldn't be read at all
+    Io(io::Error),
+
+    /// The line (counting from `1`) has a problem
+    Invalid { line: usize, problem: Problem },
+
+    /// The settings are fine one by one, but the screen they make isn't
+    Size(String),
+}
+
+/// What's wrong with a line of configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// The line isn't a section header, a `key = value` pair, or a comment
+    Syntax(String),
+
+    /// There's no such setting
+    UnknownKey(String),
+
+    /// The value is the wrong type or out of range for the setting
+    BadValue(String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(formatter, "{}", error),
+            ConfigError::Invalid { line, problem } => {
+                write!(formatter, "line {}: {}", line, problem)
+            }
+            ConfigError::Size(problem) => write!(formatter, "{}", problem),
+        }
+    }
+}
+
+impl Display for Problem {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Syntax(line) => write!(formatter, "can't make sense of `{}`", line),
+            Problem::UnknownKey(key) => write!(formatter, "unknown setting `{}`", key),
+            Problem::BadValue(value) => write!(formatter, "bad value `{}`", value),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_screens_that_cant_be_drawn() {
+        for screen in [
+            "width = 0\noverflow = \"wrap\"",
+            "height = 0",
+            "width = 4294967296\nheight = 4294967296",
+            "width = 2\nsprite_width = 3",
+        ] {
+            let config = format!("[screen]\n{}\n", screen).parse::<Config>();
+            assert!(matches!(config, Err(ConfigError::Size(_))), "{}", screen);
+        }
+
+        assert!("[screen]\nwidth = 80\n".parse::<Config>().is_ok());
+    }
+}
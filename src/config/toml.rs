@@ -0,0 +1,153 @@
+use super::Problem;
+
+/// Just enough TOML for a configuration file: `[section]` headers, `key =
+/// value` pairs, and `#` comments. Values are strings, integers, or booleans.
+pub(super) struct Entry {
+    pub(super) section: String,
+    pub(super) key: String,
+    pub(super) value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+}
+
+impl Value {
+    pub(super) fn into_string(self) -> Result<String, Problem> {
+        match self {
+            Value::String(string) => Ok(string),
+            other => Err(Problem::BadValue(other.to_string())),
+        }
+    }
+
+    pub(super) fn into_usize(self) -> Result<usize, Problem> {
+        match self {
+            Value::Integer(number) => {
+                usize::try_from(number).map_err(|_| Problem::BadValue(number.to_string()))
+            }
+            other => Err(Problem::BadValue(other.to_string())),
+        }
+    }
+
+    pub(super) fn into_char(self) -> Result<char, Problem> {
+        let string = self.into_string()?;
+        let mut chars = string.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(Problem::BadValue(string)),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(string) => write!(formatter, "{:?}", string),
+            Value::Integer(number) => write!(formatter, "{}", number),
+            Value::Boolean(boolean) => write!(formatter, "{}", boolean),
+        }
+    }
+}
+
+/// Returns every `key = value` pair in the source along with its line number,
+/// counting from `1`
+pub(super) fn entries(source: &str) -> Vec<(usize, Result<Entry, Problem>)> {
+    let mut section = String::new();
+    let mut entries = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name.trim().to_string();
+            continue;
+        }
+
+        let entry = match line.split_once('=') {
+            Some((key, value)) => parse_value(value.trim()).map(|value| Entry {
+                section: section.clone(),
+                key: key.trim().to_string(),
+                value,
+            }),
+            None => Err(Problem::Syntax(line.to_string())),
+        };
+
+        entries.push((index + 1, entry));
+    }
+
+    entries
+}
+
+/// Cuts the line off at the first `#` that isn't inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => (),
+        }
+    }
+
+    line
+}
+
+fn parse_value(value: &str) -> Result<Value, Problem> {
+    if let Some(quoted) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        return unescape(quoted)
+            .map(Value::String)
+            .ok_or_else(|| Problem::Syntax(value.to_string()));
+    }
+
+    match value {
+        "true" => return Ok(Value::Boolean(true)),
+        "false" => return Ok(Value::Boolean(false)),
+        _ => (),
+    }
+
+    // TOML allows underscores between digits, e.g. `1_000`
+    value
+        .replace('_', "")
+        .parse()
+        .map(Value::Integer)
+        .map_err(|_| Problem::Syntax(value.to_string()))
+}
+
+/// Resolves the escapes in a basic string. Unknown escapes are an error.
+fn unescape(quoted: &str) -> Option<String> {
+    let mut string = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            string.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            '"' => string.push('"'),
+            '\\' => string.push('\\'),
+            'n' => string.push('\n'),
+            't' => string.push('\t'),
+            'u' => {
+                let code: String = chars.by_ref().take(4).collect();
+                string.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(string)
+}
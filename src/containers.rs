@@ -0,0 +1,440 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::{fs, io, path::Path};
+
+use crate::binary::{self, DecodeError};
+use crate::images;
+use crate::machines::VirtualMachine;
+use crate::programs::{Fingerprint, FingerprintMismatch, Program};
+use crate::screens::{self, Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::timelines;
+use crate::varint;
+
+/// Every container starts with these bytes
+pub const MAGIC: &[u8; 4] = b"LEGC";
+
+/// The version of the container written by `to_bytes()`. Version 1, without
+/// the program's fingerprint, is still read.
+pub const VERSION: u8 = 2;
+
+/// A program packaged with what someone needs to run and check it: who wrote
+/// it, the screen it draws on, and how many cycles it should take. After the
+/// magic bytes and version come the name and author (each a length and UTF-8),
+/// the expected cycles (plus one, so `0` means unknown), the screen's width
+/// and height, the program in the binary format, with its length first, and
+/// the program's `Fingerprint` as eight little-endian bytes. A CRC-32 of
+/// everything before it ends the container, so a damaged download doesn't
+/// run as some other program.
+///
+/// The CRC catches the bytes changing; the fingerprint, checked against the
+/// program once it's decoded, catches them being decoded differently from
+/// how they were encoded, and tells whoever holds a container which program
+/// to expect without decoding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    program: Program,
+    name: String,
+    author: String,
+    expected_cycles: Option<usize>,
+    width: usize,
+    height: usize,
+}
+
+impl Container {
+    /// Packages the program with no name or author, for the puzzle's screen
+    pub fn new(program: Program) -> Self {
+        Container {
+            program,
+            name: String::new(),
+            author: String::new(),
+            expected_cycles: None,
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Records how many cycles the program takes, for `verify()` to check.
+    /// `usize::MAX` is too many for `to_bytes()` to record.
+    pub fn with_expected_cycles(mut self, cycles: usize) -> Self {
+        self.expected_cycles = Some(cycles);
+        self
+    }
+
+    /// Sets the size of the screen the program is meant to draw on. It needs
+    /// pixels, and no more than `screens::MAX_PIXELS`, for the container to
+    /// load again.
+    pub fn with_screen_size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    pub fn expected_cycles(&self) -> Option<usize> {
+        self.expected_cycles
+    }
+
+    /// Returns the program's fingerprint, which `to_bytes()` records
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.program.fingerprint()
+    }
+
+    /// Returns the width and height of the screen the program is meant for
+    pub fn screen_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Returns a screen of the intended size, showing a machine ready to run
+    /// the program
+    pub fn screen(&self) -> Screen<VirtualMachine> {
+        Screen::with_size(self.program.machine(), self.width, self.height)
+    }
+
+    /// Runs the program and checks it takes the expected number of cycles,
+    /// if the container says. Returns how many it took.
+    pub fn verify(&self) -> Result<usize, ContainerError> {
+        let cycles = timelines::register_timeline(self.program.machine()).len();
+
+        match self.expected_cycles {
+            Some(expected) if expected != cycles => Err(ContainerError::Cycles {
+                expected,
+                actual: cycles,
+            }),
+            _ => Ok(cycles),
+        }
+    }
+
+    /// Encodes the container, checksum and all. Expected cycles are written
+    /// plus one, so there's no recording `usize::MAX` of them.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ContainerError> {
+        let expected_cycles = match self.expected_cycles {
+            Some(cycles) => cycles
+                .checked_add(1)
+                .ok_or(ContainerError::TooManyCycles(cycles))?,
+            None => 0,
+        };
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+
+        write_text(&mut bytes, &self.name);
+        write_text(&mut bytes, &self.author);
+        varint::write(&mut bytes, expected_cycles);
+        varint::write(&mut bytes, self.width);
+        varint::write(&mut bytes, self.height);
+
+        let program = binary::encode(self.program.instructions());
+        varint::write(&mut bytes, program.len());
+        bytes.extend(program);
+        bytes.extend(self.fingerprint().to_u64().to_le_bytes());
+
+        let checksum = images::crc32(&bytes);
+        bytes.extend(checksum.to_le_bytes());
+
+        Ok(bytes)
+    }
+
+    /// The inverse of `to_bytes()`. The checksum is checked before anything
+    /// else is read, and the fingerprint once the program is decoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ContainerError> {
+        let mut rest = bytes
+            .strip_prefix(MAGIC.as_slice())
+            .ok_or(ContainerError::NotAContainer)?;
+
+        let (version, tail) = rest.split_first().ok_or(ContainerError::Truncated)?;
+        rest = tail;
+
+        if !(1..=VERSION).contains(version) {
+            return Err(ContainerError::UnsupportedVersion(*version));
+        }
+
+        let (body, checksum) = bytes
+            .split_last_chunk::<4>()
+            .filter(|(body, _)| body.len() > MAGIC.len())
+            .ok_or(ContainerError::Truncated)?;
+        let expected = u32::from_le_bytes(*checksum);
+        let actual = images::crc32(body);
+
+        if expected != actual {
+            return Err(ContainerError::Checksum { expected, actual });
+        }
+
+        rest = &rest[..rest.len() - checksum.len()];
+
+        let fingerprint = match *version {
+            1 => None,
+            _ => {
+                let (program, fingerprint) = rest
+                    .split_last_chunk::<8>()
+                    .ok_or(ContainerError::Truncated)?;
+                rest = program;
+
+                Some(Fingerprint::from_u64(u64::from_le_bytes(*fingerprint)))
+            }
+        };
+
+        let name = read_text(&mut rest)?;
+        let author = read_text(&mut rest)?;
+        let expected_cycles = varint::read(&mut rest)
+            .ok_or(ContainerError::Truncated)?
+            .checked_sub(1);
+        let width = varint::read(&mut rest).ok_or(ContainerError::Truncated)?;
+        let height = varint::read(&mut rest).ok_or(ContainerError::Truncated)?;
+
+        if width == 0 || height == 0 || !screens::fits(width, height) {
+            return Err(ContainerError::ScreenSize { width, height });
+        }
+
+        let length = varint::read(&mut rest).ok_or(ContainerError::Truncated)?;
+        if rest.len() != length {
+            return Err(ContainerError::Program(match rest.len() < length {
+                true => DecodeError::Truncated,
+                false => DecodeError::TrailingBytes(rest.len() - length),
+            }));
+        }
+
+        let program: Program = binary::decode(rest)?.into();
+
+        if let Some(fingerprint) = fingerprint {
+            fingerprint.check(&program)?;
+        }
+
+        Ok(Container {
+            program,
+            name,
+            author,
+            expected_cycles,
+            width,
+            height,
+        })
+    }
+
+    /// Writes the container to a file, conventionally with a `.legc`
+    /// extension
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ContainerError> {
+        fs::write(path, self.to_bytes()?).map_err(ContainerError::Io)
+    }
+
+    /// Reads a container written by `save()`
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ContainerError> {
+        Container::from_bytes(&fs::read(path).map_err(ContainerError::Io)?)
+    }
+}
+
+/// Returns `true` if the bytes look like a container, as opposed to a bare
+/// program
+pub fn is_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+fn write_text(bytes: &mut Vec<u8>, text: &str) {
+    varint::write(bytes, text.len());
+    bytes.extend_from_slice(text.as_bytes());
+}
+
+fn read_text(bytes: &mut &[u8]) -> Result<String, ContainerError> {
+    let length = varint::read(bytes).ok_or(ContainerError::Truncated)?;
+
+    if bytes.len() < length {
+        return Err(ContainerError::Truncated);
+    }
+
+    let (text, rest) = bytes.split_at(length);
+    *bytes = rest;
+
+    String::from_utf8(text.to_vec()).map_err(|_| ContainerError::BadText)
+}
+
+/// Why a container couldn't be read or verified
+#[derive(Debug)]
+pub enum ContainerError {
+    /// The file couldn't be read at all
+    #[cfg(feature = "std")]
+    Io(io::Error),
+
+    /// The bytes don't start with `MAGIC`
+    NotAContainer,
+
+    /// The container was written by a version this one can't read
+    UnsupportedVersion(u8),
+
+    /// The bytes end in the middle of the container
+    Truncated,
+
+    /// The bytes don't add up to the checksum they end with, so something
+    /// changed them
+    Checksum { expected: u32, actual: u32 },
+
+    /// The name or author isn't UTF-8
+    BadText,
+
+    /// The screen has no pixels, or more than `screens::MAX_PIXELS`
+    ScreenSize { width: usize, height: usize },
+
+    /// The program inside didn't decode
+    Program(DecodeError),
+
+    /// The program inside decoded as a different one from the one whose
+    /// fingerprint the container recorded
+    Fingerprint(FingerprintMismatch),
+
+    /// The program didn't take as many cycles as the container says
+    Cycles { expected: usize, actual: usize },
+
+    /// The container expects more cycles than it can record
+    TooManyCycles(usize),
+}
+
+impl From<DecodeError> for ContainerError {
+    fn from(error: DecodeError) -> Self {
+        ContainerError::Program(error)
+    }
+}
+
+impl From<FingerprintMismatch> for ContainerError {
+    fn from(error: FingerprintMismatch) -> Self {
+        ContainerError::Fingerprint(error)
+    }
+}
+
+impl Display for ContainerError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            ContainerError::Io(error) => write!(formatter, "{}", error),
+            ContainerError::NotAContainer => write!(formatter, "not a program container"),
+            ContainerError::UnsupportedVersion(version) => {
+                write!(formatter, "unsupported container version {}", version)
+            }
+            ContainerError::Truncated => write!(formatter, "the container is cut short"),
+            ContainerError::Checksum { expected, actual } => write!(
+                formatter,
+                "checksum mismatch: the container says {:#010x} but its bytes are {:#010x}",
+                expected, actual
+            ),
+            ContainerError::BadText => write!(formatter, "the name or author isn't UTF-8"),
+            ContainerError::ScreenSize { width, height } => write!(
+                formatter,
+                "a {}×{} screen isn't one to draw on (it needs between 1 and {} pixels)",
+                width,
+                height,
+                screens::MAX_PIXELS
+            ),
+            ContainerError::Program(error) => write!(formatter, "can't decode: {}", error),
+            ContainerError::Fingerprint(error) => {
+                write!(
+                    formatter,
+                    "the program inside isn't the one recorded: {}",
+                    error
+                )
+            }
+            ContainerError::Cycles { expected, actual } => write!(
+                formatter,
+                "the program took {} cycles, not the {} expected",
+                actual, expected
+            ),
+            ContainerError::TooManyCycles(cycles) => {
+                write!(formatter, "{} cycles are too many to record", cycles)
+            }
+        }
+    }
+}
+
+impl Error for ContainerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            ContainerError::Io(error) => Some(error),
+            ContainerError::Program(error) => Some(error),
+            ContainerError::Fingerprint(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::Instruction;
+    use crate::screens::MAX_PIXELS;
+    use alloc::vec;
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let container =
+            Container::new(Program::from(vec![Instruction::Addx(3), Instruction::Noop]))
+                .with_name("fireworks")
+                .with_author("jneufeld")
+                .with_expected_cycles(3);
+
+        let loaded = Container::from_bytes(&container.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(loaded, container);
+        assert_eq!(loaded.verify().unwrap(), 3);
+    }
+
+    #[test]
+    fn cycles_past_the_last_arent_recorded() {
+        let program = Program::from(vec![Instruction::Noop]);
+
+        assert!(matches!(
+            Container::new(program.clone())
+                .with_expected_cycles(usize::MAX)
+                .to_bytes(),
+            Err(ContainerError::TooManyCycles(usize::MAX))
+        ));
+
+        let container = Container::new(program).with_expected_cycles(usize::MAX - 1);
+        let loaded = Container::from_bytes(&container.to_bytes().unwrap()).unwrap();
+        assert_eq!(loaded.expected_cycles(), Some(usize::MAX - 1));
+    }
+
+    #[test]
+    fn refuses_screens_too_big_to_make() {
+        let program = Program::from(vec![Instruction::Noop]);
+
+        for (width, height) in [
+            (1 << 33, 1 << 33),
+            (MAX_PIXELS + 1, 1),
+            (0, 6),
+            (1 << 40, 0),
+        ] {
+            let container = Container::new(program.clone()).with_screen_size(width, height);
+
+            assert!(matches!(
+                Container::from_bytes(&container.to_bytes().unwrap()),
+                Err(ContainerError::ScreenSize { .. })
+            ));
+        }
+
+        let container = Container::new(program).with_screen_size(80, 12);
+        let loaded = Container::from_bytes(&container.to_bytes().unwrap()).unwrap();
+        assert_eq!(loaded.screen_size(), (80, 12));
+    }
+}
@@ -0,0 +1,371 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt::{Display, Formatter};
+
+use crate::font::{self, GLYPH_ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::observers::{Cycle, Fanout};
+use crate::programs::Program;
+use crate::screens::{self, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// How many of the pixels that are wrong a diagnostic points out
+const PIXELS_SHOWN: usize = 3;
+
+/// A task for someone learning to write programs, e.g. "make the register
+/// 42 during cycle 100", with the goals a program has to meet to pass.
+/// `grade()` runs a program submitted for it, as a machine with nothing
+/// attached, and says which goals it met and, for those it didn't, why not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exercise {
+    name: String,
+    prompt: String,
+    goals: Vec<Goal>,
+}
+
+/// Something a program has to do to pass an exercise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Goal {
+    /// The register has the value during the cycle
+    Register { cycle: usize, value: isize },
+
+    /// The specification's screen shows the picture, a row of text per row
+    /// of the screen: `#` where it's lit, `.` where it's dark, and anything
+    /// else, e.g. `?`, where it doesn't matter. Pixels past the ends of the
+    /// rows don't matter either.
+    Picture(Vec<String>),
+
+    /// The program takes no more than this many cycles
+    Cycles(usize),
+
+    /// The program has no more than this many instructions
+    Instructions(usize),
+}
+
+/// How a program did at each of an exercise's goals, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grade {
+    pub exercise: String,
+    pub checks: Vec<Check>,
+}
+
+/// How a program did at a single goal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub goal: Goal,
+    pub passed: bool,
+
+    /// What the program did, and if it failed, where it went wrong
+    pub diagnostic: String,
+}
+
+impl Exercise {
+    /// Creates an exercise with nothing to do yet, so any program passes
+    pub fn new(name: impl Into<String>, prompt: impl Into<String>) -> Self {
+        Exercise {
+            name: name.into(),
+            prompt: prompt.into(),
+            goals: Vec::new(),
+        }
+    }
+
+    pub fn with_goal(mut self, goal: Goal) -> Self {
+        self.goals.push(goal);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns what to tell the student to do
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    pub fn goals(&self) -> &[Goal] {
+        &self.goals
+    }
+
+    /// Runs the program and checks it against every goal
+    pub fn grade(&self, program: &Program) -> Grade {
+        let mut cycles: Vec<Cycle> = Vec::new();
+        Fanout::new(program.machine())
+            .with_observer(&mut cycles)
+            .run();
+
+        let run = Run { program, cycles };
+
+        Grade {
+            exercise: self.name.clone(),
+            checks: self.goals.iter().map(|goal| run.check(goal)).collect(),
+        }
+    }
+}
+
+impl Goal {
+    /// A picture of the letter in the font, in the given place on the screen
+    /// counting from `0`, like the puzzle's answers are laid out. The rest of
+    /// the screen doesn't matter. Returns `None` if the font has no glyph for
+    /// the letter or the place is off the screen.
+    pub fn letter(letter: char, place: usize) -> Option<Goal> {
+        let glyph = font::glyph(letter)?;
+        let left = place * GLYPH_ADVANCE;
+
+        if left + GLYPH_WIDTH > SCREEN_WIDTH {
+            return None;
+        }
+
+        let rows = (0..GLYPH_HEIGHT)
+            .map(|y| {
+                let pixels = (0..GLYPH_WIDTH).map(|x| match glyph.is_lit(x, y) {
+                    true => '#',
+                    false => '.',
+                });
+
+                "?".repeat(left).chars().chain(pixels).collect()
+            })
+            .collect();
+
+        Some(Goal::Picture(rows))
+    }
+}
+
+/// A program and every cycle it went through
+struct Run<'a> {
+    program: &'a Program,
+    cycles: Vec<Cycle>,
+}
+
+impl Run<'_> {
+    fn check(&self, goal: &Goal) -> Check {
+        let (passed, diagnostic) = match goal {
+            Goal::Register { cycle, value } => self.register(*cycle, *value),
+            Goal::Picture(rows) => self.picture(rows),
+            Goal::Cycles(most) => {
+                let cycles = self.cycles.len();
+
+                match cycles <= *most {
+                    true => (true, format!("took {} cycles", cycles)),
+                    false => (
+                        false,
+                        format!(
+                            "took {} cycles, {} more than the {} allowed",
+                            cycles,
+                            cycles - most,
+                            most
+                        ),
+                    ),
+                }
+            }
+            Goal::Instructions(most) => {
+                let instructions = self.program.len();
+
+                match instructions <= *most {
+                    true => (true, format!("has {} instructions", instructions)),
+                    false => (
+                        false,
+                        format!(
+                            "has {} instructions, {} more than the {} allowed",
+                            instructions,
+                            instructions - most,
+                            most
+                        ),
+                    ),
+                }
+            }
+        };
+
+        Check {
+            goal: goal.clone(),
+            passed,
+            diagnostic,
+        }
+    }
+
+    fn register(&self, cycle: usize, value: isize) -> (bool, String) {
+        if cycle == 0 {
+            return (false, "there's no cycle 0; the first is 1".to_string());
+        }
+
+        let Some(during) = self.cycles.get(cycle - 1) else {
+            return (
+                false,
+                format!(
+                    "finished after {} cycles, before cycle {}",
+                    self.cycles.len(),
+                    cycle
+                ),
+            );
+        };
+
+        if during.register == value {
+            return (
+                true,
+                format!("the register is {} during cycle {}", value, cycle),
+            );
+        }
+
+        // The last instruction to change the register before the cycle did
+        // so as it finished, i.e. the register differs on the cycle after
+        let changed = self.cycles[..cycle]
+            .windows(2)
+            .rev()
+            .find(|pair| pair[0].register != pair[1].register)
+            .map(|pair| pair[0]);
+
+        let cause = match changed.and_then(|cycle| Some((cycle, cycle.instruction?))) {
+            Some((changed, index)) => format!(
+                "; it was last changed by #{} `{}`, finishing on cycle {}",
+                index,
+                self.program.instructions()[index],
+                changed.number
+            ),
+            None => "; nothing changed it since the start".to_string(),
+        };
+
+        (
+            false,
+            format!(
+                "the register is {} during cycle {}, not {}{}",
+                during.register, cycle, value, cause
+            ),
+        )
+    }
+
+    fn picture(&self, rows: &[String]) -> (bool, String) {
+        let timeline: Vec<_> = self.cycles.iter().map(|cycle| cycle.register).collect();
+        let screen = screens::render_from_timeline(&timeline);
+
+        let mut wrong = vec![];
+
+        for (y, row) in rows.iter().take(SCREEN_HEIGHT).enumerate() {
+            for (x, pixel) in row.chars().take(SCREEN_WIDTH).enumerate() {
+                let lit = match pixel {
+                    '#' => true,
+                    '.' => false,
+                    _ => continue,
+                };
+
+                if screen.is_lit(x, y) != lit {
+                    wrong.push((x, y, lit));
+                }
+            }
+        }
+
+        if wrong.is_empty() {
+            return (true, "the screen shows the picture".to_string());
+        }
+
+        let shown: Vec<_> = wrong
+            .iter()
+            .take(PIXELS_SHOWN)
+            .map(|(x, y, lit)| {
+                let (should, is) = match lit {
+                    true => ("lit", "dark"),
+                    false => ("dark", "lit"),
+                };
+
+                format!(
+                    "column {} of row {} (cycle {}) is {}, not {}",
+                    x,
+                    y,
+                    y * SCREEN_WIDTH + x + 1,
+                    is,
+                    should
+                )
+            })
+            .collect();
+
+        let noun = match wrong.len() {
+            1 => "pixel is",
+            _ => "pixels are",
+        };
+
+        (
+            false,
+            format!("{} {} wrong: {}", wrong.len(), noun, shown.join("; ")),
+        )
+    }
+}
+
+impl Grade {
+    /// Returns `true` if the program met every goal
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+}
+
+/// Writes a line for the exercise and one for every goal, e.g. `pass: took
+/// 240 cycles`
+impl Display for Grade {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        let passed = self.checks.iter().filter(|check| check.passed).count();
+
+        writeln!(
+            formatter,
+            "{}: {} ({} of {} goals met)",
+            self.exercise,
+            match self.passed() {
+                true => "passed",
+                false => "failed",
+            },
+            passed,
+            self.checks.len()
+        )?;
+
+        for check in &self.checks {
+            let verdict = match check.passed {
+                true => "pass",
+                false => "FAIL",
+            };
+
+            writeln!(formatter, "    {}: {}", verdict, check.diagnostic)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the exercises that come with the crate, easiest first
+pub fn exercises() -> Vec<Exercise> {
+    let letter_h = Goal::letter('H', 1).expect("the font has an H");
+    let lights_on = Goal::Picture(vec!["#".repeat(SCREEN_WIDTH); SCREEN_HEIGHT]);
+
+    vec![
+        Exercise::new("forty-two", "Make the register 42 during cycle 100.").with_goal(
+            Goal::Register {
+                cycle: 100,
+                value: 42,
+            },
+        ),
+        Exercise::new(
+            "frugal",
+            "Make the register 42 during cycle 100 again, in 50 instructions or fewer. \
+             Which instruction covers the most cycles?",
+        )
+        .with_goal(Goal::Register {
+            cycle: 100,
+            value: 42,
+        })
+        .with_goal(Goal::Instructions(50)),
+        Exercise::new(
+            "letter-h",
+            "Draw the letter H as the second letter on the screen, where the puzzle's \
+             answers put it. Why can't it be the first?",
+        )
+        .with_goal(letter_h),
+        Exercise::new(
+            "lights-on",
+            "Light every pixel of the screen, in a single frame.",
+        )
+        .with_goal(lights_on)
+        .with_goal(Goal::Cycles(SCREEN_WIDTH * SCREEN_HEIGHT)),
+    ]
+}
+
+/// Returns the exercise that comes with the crate by that name, if any
+pub fn exercise(name: &str) -> Option<Exercise> {
+    exercises()
+        .into_iter()
+        .find(|exercise| exercise.name == name)
+}
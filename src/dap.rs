@@ -0,0 +1,559 @@
+use std::fs;
+use std::io::{self, BufRead, ErrorKind, Write};
+
+use crate::assembly;
+use crate::binary;
+use crate::debugger::{Debugger, Stop};
+use crate::json::Json;
+
+/// The only thread there is
+const THREAD: usize = 1;
+
+/// The only scope of variables there is: the machine's registers
+const REGISTERS: usize = 1;
+
+/// The output category the screen is sent under after every stop. Editors
+/// that don't know it show it like console output.
+pub const SCREEN_CATEGORY: &str = "screen";
+
+/// A server for the Debug Adapter Protocol, so editors like VS Code can debug
+/// assembly programs: launch one, put breakpoints on lines of its source,
+/// step (forwards and backwards) a cycle at a time, continue, and inspect the
+/// register. The screen is sent as output after every stop.
+///
+/// Messages are JSON with a `Content-Length` header, like HTTP, usually over
+/// the standard input and output of a process the editor starts.
+pub struct DapServer<R, W> {
+    input: R,
+    output: W,
+
+    /// The sequence number of the next message sent
+    seq: usize,
+
+    /// The launched program, if there is one yet
+    session: Option<Session>,
+}
+
+/// A launched program
+struct Session {
+    /// The path of the program's source, as the editor gave it
+    path: String,
+
+    /// The line of the source every instruction came from
+    lines: Vec<usize>,
+
+    debugger: Debugger,
+
+    /// Whether to stop before the first cycle instead of running once the
+    /// editor is done configuring
+    stop_on_entry: bool,
+}
+
+impl<R: BufRead, W: Write> DapServer<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        DapServer {
+            input,
+            output,
+            seq: 1,
+            session: None,
+        }
+    }
+
+    /// Serves requests until the editor disconnects or closes the input
+    pub fn serve(mut self) -> io::Result<()> {
+        while let Some(message) = self.read_message()? {
+            let Ok(message) = Json::parse(&message) else {
+                continue;
+            };
+
+            if message.get("type").and_then(Json::as_str) != Some("request") {
+                continue;
+            }
+
+            if !self.handle(&message)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles a request, responding to it and sending whatever events it
+    /// leads to. Returns `false` once the editor is done.
+    fn handle(&mut self, request: &Json) -> io::Result<bool> {
+        let command = request.get("command").and_then(Json::as_str).unwrap_or("");
+        let arguments = request.get("arguments").cloned().unwrap_or(Json::Null);
+
+        match command {
+            "initialize" => {
+                let capabilities = Json::object([
+                    ("supportsConfigurationDoneRequest", true.into()),
+                    ("supportsStepBack", true.into()),
+                ]);
+
+                self.respond(request, Ok(capabilities))?;
+                self.event("initialized", Json::object([]))?;
+            }
+            "launch" => {
+                let result = self.launch(&arguments);
+                self.respond(request, result.map(|()| Json::object([])))?;
+            }
+            "setBreakpoints" => {
+                let result = self.set_breakpoints(&arguments);
+                self.respond(request, result)?;
+            }
+            "configurationDone" => {
+                self.respond(request, Ok(Json::object([])))?;
+
+                match &self.session {
+                    Some(session) if session.stop_on_entry => self.stopped("entry")?,
+                    Some(_) => self.resume()?,
+                    None => (),
+                }
+            }
+            "threads" => {
+                let thread = Json::object([("id", THREAD.into()), ("name", "machine".into())]);
+                let body = Json::object([("threads", vec![thread].into())]);
+
+                self.respond(request, Ok(body))?;
+            }
+            "stackTrace" => {
+                let result = self.stack_trace();
+                self.respond(request, result)?;
+            }
+            "scopes" => {
+                let scope = Json::object([
+                    ("name", "Registers".into()),
+                    ("variablesReference", REGISTERS.into()),
+                    ("expensive", false.into()),
+                ]);
+
+                self.respond(request, Ok(Json::object([("scopes", vec![scope].into())])))?;
+            }
+            "variables" => {
+                let result = self.variables();
+                self.respond(request, result)?;
+            }
+            "continue" => {
+                let body = Json::object([("allThreadsContinued", true.into())]);
+
+                self.respond(request, Ok(body))?;
+                self.resume()?;
+            }
+            "next" | "stepIn" | "stepOut" => {
+                self.respond(request, Ok(Json::object([])))?;
+                self.step()?;
+            }
+            "stepBack" => {
+                self.respond(request, Ok(Json::object([])))?;
+
+                if let Some(session) = &mut self.session {
+                    session.debugger.reverse_step();
+                }
+
+                self.stopped("step")?;
+            }
+            "reverseContinue" => {
+                self.respond(request, Ok(Json::object([])))?;
+
+                let reason = match &mut self.session {
+                    Some(session) => match session.debugger.reverse_resume() {
+                        Some(_) => "breakpoint",
+                        None => "entry",
+                    },
+                    None => "entry",
+                };
+
+                self.stopped(reason)?;
+            }
+            "pause" => {
+                // Programs only ever run to a stop, so they're always paused
+                self.respond(request, Ok(Json::object([])))?;
+                self.stopped("pause")?;
+            }
+            "disconnect" | "terminate" => {
+                self.respond(request, Ok(Json::object([])))?;
+                return Ok(false);
+            }
+            _ => {
+                let problem = format!("`{}` isn't supported", command);
+                self.respond(request, Err(problem))?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Reads and assembles (or decodes) the program the editor asked for
+    fn launch(&mut self, arguments: &Json) -> Result<(), String> {
+        let path = arguments
+            .get("program")
+            .and_then(Json::as_str)
+            .ok_or("the launch configuration needs a `program`")?;
+
+        let source = fs::read(path).map_err(|error| format!("{}: {}", path, error))?;
+
+        // Binaries have no lines, so each instruction gets the next one
+        let listing = match binary::is_binary(&source) {
+            true => binary::decode(&source)
+                .map_err(|error| error.to_string())?
+                .into_iter()
+                .enumerate()
+                .map(|(index, instruction)| (index + 1, instruction))
+                .collect(),
+            false => assembly::assemble_lines(&String::from_utf8_lossy(&source))
+                .map_err(|error| error.to_string())?,
+        };
+
+        let (lines, program): (Vec<_>, Vec<_>) = listing.into_iter().unzip();
+
+        self.session = Some(Session {
+            path: path.to_string(),
+            lines,
+            debugger: Debugger::new(program),
+            stop_on_entry: arguments
+                .get("stopOnEntry")
+                .and_then(Json::as_bool)
+                .unwrap_or(false),
+        });
+
+        Ok(())
+    }
+
+    /// Replaces every breakpoint with the requested ones. A breakpoint on a
+    /// line without an instruction moves down to the next line with one.
+    fn set_breakpoints(&mut self, arguments: &Json) -> Result<Json, String> {
+        let session = self.session.as_mut().ok_or("nothing has been launched")?;
+
+        let requested = arguments
+            .get("breakpoints")
+            .and_then(Json::as_array)
+            .unwrap_or(&[]);
+
+        for index in session.debugger.breakpoints().clone() {
+            session.debugger.toggle_breakpoint(index);
+        }
+
+        let mut breakpoints = Vec::new();
+
+        for breakpoint in requested {
+            let line = breakpoint.get("line").and_then(Json::as_usize).unwrap_or(0);
+            let index = session.lines.iter().position(|&l| l >= line);
+
+            let breakpoint = match index {
+                Some(index) => {
+                    if !session.debugger.breakpoints().contains(&index) {
+                        session.debugger.toggle_breakpoint(index);
+                    }
+
+                    Json::object([
+                        ("verified", true.into()),
+                        ("line", session.lines[index].into()),
+                    ])
+                }
+                None => Json::object([
+                    ("verified", false.into()),
+                    ("line", line.into()),
+                    ("message", "no instruction at or after this line".into()),
+                ]),
+            };
+
+            breakpoints.push(breakpoint);
+        }
+
+        Ok(Json::object([("breakpoints", breakpoints.into())]))
+    }
+
+    /// Describes the only frame there is: the instruction about to run, or the
+    /// last one once the program has finished
+    fn stack_trace(&self) -> Result<Json, String> {
+        let session = self.session.as_ref().ok_or("nothing has been launched")?;
+        let program = session.debugger.program();
+
+        let index = session
+            .debugger
+            .machine()
+            .instruction_index()
+            .unwrap_or(program.len().saturating_sub(1));
+
+        let name = program
+            .get(index)
+            .map_or("finished".to_string(), |instruction| {
+                instruction.to_string()
+            });
+
+        let frame = Json::object([
+            ("id", 1usize.into()),
+            ("name", name.into()),
+            (
+                "source",
+                Json::object([("path", session.path.as_str().into())]),
+            ),
+            (
+                "line",
+                session.lines.get(index).copied().unwrap_or(1).into(),
+            ),
+            ("column", 1usize.into()),
+        ]);
+
+        Ok(Json::object([
+            ("stackFrames", vec![frame].into()),
+            ("totalFrames", 1usize.into()),
+        ]))
+    }
+
+    /// Lists the register, the cycle it's in, and the instruction's position
+    fn variables(&self) -> Result<Json, String> {
+        let session = self.session.as_ref().ok_or("nothing has been launched")?;
+        let machine = session.debugger.machine();
+
+        let variable = |name: &str, value: String| {
+            Json::object([
+                ("name", name.into()),
+                ("value", value.into()),
+                ("variablesReference", 0usize.into()),
+            ])
+        };
+
+        let instruction = machine
+            .instruction_index()
+            .map_or("-".to_string(), |index| index.to_string());
+
+        let variables = vec![
+            variable("x", machine.read_register().to_string()),
+            variable("cycle", machine.get_ticks().to_string()),
+            variable("instruction", instruction),
+        ];
+
+        Ok(Json::object([("variables", variables.into())]))
+    }
+
+    /// Runs a single cycle, then reports the stop (or the end)
+    fn step(&mut self) -> io::Result<()> {
+        let Some(session) = &mut self.session else {
+            return Ok(());
+        };
+
+        match session.debugger.step() {
+            Some(_) if session.debugger.machine().is_executing() => self.stopped("step"),
+            _ => self.finished(),
+        }
+    }
+
+    /// Runs to the next breakpoint (or alarm) or the end, then reports which
+    /// it was
+    fn resume(&mut self) -> io::Result<()> {
+        let Some(session) = &mut self.session else {
+            return Ok(());
+        };
+
+        match session.debugger.resume() {
+            Stop::Breakpoint(_) => self.stopped("breakpoint"),
+            Stop::Alarm(_) => self.stopped("data breakpoint"),
+            Stop::Finished => self.finished(),
+        }
+    }
+
+    /// Tells the editor the program stopped, after sending it the screen
+    fn stopped(&mut self, reason: &str) -> io::Result<()> {
+        self.send_screen()?;
+
+        self.event(
+            "stopped",
+            Json::object([
+                ("reason", reason.into()),
+                ("threadId", THREAD.into()),
+                ("allThreadsStopped", true.into()),
+            ]),
+        )
+    }
+
+    /// Tells the editor the program finished, with the final register
+    /// (clamped to a byte) for an exit code
+    fn finished(&mut self) -> io::Result<()> {
+        self.send_screen()?;
+
+        let register = self
+            .session
+            .as_ref()
+            .map_or(0, |session| session.debugger.machine().read_register());
+
+        self.event(
+            "exited",
+            Json::object([("exitCode", register.clamp(0, 255).into())]),
+        )?;
+
+        self.event("terminated", Json::object([]))
+    }
+
+    fn send_screen(&mut self) -> io::Result<()> {
+        let Some(session) = &self.session else {
+            return Ok(());
+        };
+
+        let screen = format!("{}\n", session.debugger.screen());
+
+        self.event(
+            "output",
+            Json::object([
+                ("category", SCREEN_CATEGORY.into()),
+                ("output", screen.into()),
+            ]),
+        )
+    }
+
+    /// Responds to the request with a body, or a problem explaining why it
+    /// failed
+    fn respond(&mut self, request: &Json, result: Result<Json, String>) -> io::Result<()> {
+        let request_seq = request.get("seq").cloned().unwrap_or(Json::Null);
+        let command = request.get("command").cloned().unwrap_or(Json::Null);
+
+        let mut response = vec![
+            ("type", "response".into()),
+            ("request_seq", request_seq),
+            ("command", command),
+        ];
+
+        match result {
+            Ok(body) => response.extend([("success", true.into()), ("body", body)]),
+            Err(problem) => {
+                response.extend([("success", false.into()), ("message", problem.into())])
+            }
+        }
+
+        self.send(response)
+    }
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(vec![
+            ("type", "event".into()),
+            ("event", event.into()),
+            ("body", body),
+        ])
+    }
+
+    /// Numbers the message and writes it with its header
+    fn send(&mut self, fields: Vec<(&str, Json)>) -> io::Result<()> {
+        let seq = ("seq", self.seq.into());
+        self.seq += 1;
+
+        let message = Json::object([seq].into_iter().chain(fields)).to_string();
+
+        write!(
+            self.output,
+            "Content-Length: {}\r\n\r\n{}",
+            message.len(),
+            message
+        )?;
+        self.output.flush()
+    }
+
+    /// Reads the next message's content, or `None` once the input is closed
+    fn read_message(&mut self) -> io::Result<Option<String>> {
+        let mut length = None;
+
+        loop {
+            let mut header = String::new();
+
+            if self.input.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+
+            let header = header.trim_end();
+
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+
+        let Some(length) = length else {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "a message had no Content-Length",
+            ));
+        };
+
+        let mut content = vec![0; length];
+        self.input.read_exact(&mut content)?;
+
+        Ok(Some(String::from_utf8_lossy(&content).into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_nested_too_deep_are_ignored() {
+        let output = serve(&[
+            format!("{}{}", "[".repeat(1 << 16), "]".repeat(1 << 16)),
+            r#"{"seq":1,"type":"request","command":"initialize"}"#.to_string(),
+        ]);
+
+        assert!(output.contains(r#""command":"initialize","success":true"#));
+    }
+
+    #[test]
+    fn runs_to_breakpoints_and_back() {
+        let path = std::env::temp_dir().join(format!("legolos-dap-{}.asm", std::process::id()));
+        fs::write(&path, "noop\n\naddx 3\naddx -1\nnoop\n").unwrap();
+
+        let launch = Json::object([
+            ("seq", 2usize.into()),
+            ("type", "request".into()),
+            ("command", "launch".into()),
+            (
+                "arguments",
+                Json::object([("program", path.to_str().unwrap().into())]),
+            ),
+        ]);
+        let request = |seq: usize, command: &str| {
+            format!(
+                r#"{{"seq":{},"type":"request","command":"{}"}}"#,
+                seq, command
+            )
+        };
+
+        let output = serve(&[
+            request(1, "initialize"),
+            launch.to_string(),
+            // Line 2 is blank, so the breakpoint moves down to the `addx 3`
+            r#"{"seq":3,"type":"request","command":"setBreakpoints","arguments":{"breakpoints":[{"line":2},{"line":9}]}}"#.to_string(),
+            request(4, "configurationDone"),
+            request(5, "variables"),
+            request(6, "continue"),
+            request(7, "reverseContinue"),
+            request(8, "disconnect"),
+        ]);
+        fs::remove_file(&path).unwrap();
+
+        assert!(output.contains(r#""breakpoints":[{"verified":true,"line":3},{"verified":false"#));
+        assert_eq!(output.matches(r#""reason":"breakpoint""#).count(), 2);
+        assert!(output.contains(
+            r#"{"name":"x","value":"1","variablesReference":0},{"name":"cycle","value":"2""#
+        ));
+        assert!(output.contains(r#""event":"exited","body":{"exitCode":3}"#));
+    }
+
+    /// Serves the messages, framed, and returns everything sent back
+    fn serve(messages: &[String]) -> String {
+        let input: String = messages
+            .iter()
+            .map(|message| format!("Content-Length: {}\r\n\r\n{}", message.len(), message))
+            .collect();
+        let mut output = Vec::new();
+
+        DapServer::new(input.as_bytes(), &mut output)
+            .serve()
+            .unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+}
@@ -0,0 +1,452 @@
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use crate::expressions::Expression;
+use crate::instructions::Instruction;
+use crate::interrupts::InterruptController;
+use crate::machines::{Checkpoint, Detached, VirtualMachine};
+use crate::observers::{Cycle, Fanout};
+use crate::screens::Screen;
+
+/// Steps through a program one cycle at a time, forwards and backwards, and
+/// stops at breakpoints, which can have conditions. It's the core of the
+/// `debug` command, free of any user interface.
+///
+/// The machine can't run backwards, so going back restores the last
+/// checkpoint before the cycle and replays the program from there. One is
+/// taken every `CHECKPOINT_INTERVAL` cycles, so however long the program,
+/// stepping back replays no more than that many, as long as it's within the
+/// last `CHECKPOINT_CAPACITY` checkpoints.
+pub struct Debugger {
+    program: Vec<Instruction>,
+    machine: VirtualMachine,
+    screen: Screen<Detached>,
+    breakpoints: BTreeSet<usize>,
+
+    /// The conditions of breakpoints that only stop when they hold
+    conditions: BTreeMap<usize, Expression>,
+
+    last: Option<Cycle>,
+
+    /// The range `resume()` stops at the register leaving, if any
+    alarm: Option<RangeInclusive<isize>>,
+
+    /// The machine every `interval` cycles, oldest first
+    checkpoints: VecDeque<Saved>,
+    interval: usize,
+
+    /// How many checkpoints are kept before the oldest is forgotten
+    capacity: usize,
+
+    /// The machine's interrupt controller, to connect again on restarting
+    interrupts: InterruptController,
+
+    /// The lines raised with `raise()`, by the number of cycles run when they
+    /// were, to raise them again when those cycles are replayed
+    raised: BTreeMap<usize, Vec<usize>>,
+}
+
+/// Everything needed to go back to a cycle
+struct Saved {
+    machine: Checkpoint,
+    last: Option<Cycle>,
+
+    /// The interrupt lines raised but not yet taken
+    pending: Vec<usize>,
+}
+
+/// How many cycles apart the debugger takes checkpoints unless told otherwise
+pub const CHECKPOINT_INTERVAL: usize = 64;
+
+/// How many checkpoints the debugger keeps unless told otherwise. With the
+/// default interval, they cover the first 16,384 cycles.
+pub const CHECKPOINT_CAPACITY: usize = 256;
+
+/// Why the debugger stopped running the program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The instruction at this position in the program is about to start
+    Breakpoint(usize),
+
+    /// The register is about to leave the alarm's range, taking this value
+    /// during the next cycle
+    Alarm(isize),
+
+    /// The program has finished
+    Finished,
+}
+
+impl Debugger {
+    pub fn new(program: impl IntoIterator<Item = Instruction>) -> Self {
+        let program: Vec<_> = program.into_iter().collect();
+
+        Debugger {
+            machine: VirtualMachine::new(VecDeque::from(program.clone())),
+            program,
+            screen: Screen::new(Detached),
+            breakpoints: BTreeSet::new(),
+            conditions: BTreeMap::new(),
+            last: None,
+            alarm: None,
+            checkpoints: VecDeque::new(),
+            interval: CHECKPOINT_INTERVAL,
+            capacity: CHECKPOINT_CAPACITY,
+            interrupts: InterruptController::new(),
+            raised: BTreeMap::new(),
+        }
+    }
+
+    /// Connects an interrupt controller to the machine, for `raise()` to
+    /// raise its lines
+    pub fn with_interrupts(mut self, interrupts: InterruptController) -> Self {
+        self.interrupts = interrupts;
+        self.machine = self.restart();
+        self
+    }
+
+    /// Takes a checkpoint every `interval` cycles rather than every
+    /// `CHECKPOINT_INTERVAL`, and keeps the last `capacity` of them. Going
+    /// back to before the oldest one replays the program from the start, and
+    /// an interval of `0` takes none at all.
+    pub fn with_checkpoints(mut self, interval: usize, capacity: usize) -> Self {
+        self.interval = interval;
+        self.capacity = capacity;
+        self.checkpoints.clear();
+        self
+    }
+
+    /// Makes `resume()` stop when the register is about to leave the range,
+    /// like an `alarms::Alarm` going off, e.g. `alarms::onscreen()`
+    pub fn with_alarm(mut self, range: RangeInclusive<isize>) -> Self {
+        self.alarm = Some(range);
+        self
+    }
+
+    /// Returns the program being debugged
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// Returns the machine as it is between cycles
+    pub fn machine(&self) -> &VirtualMachine {
+        &self.machine
+    }
+
+    /// Returns the screen, drawn up to the last cycle
+    pub fn screen(&self) -> &Screen<Detached> {
+        &self.screen
+    }
+
+    /// Returns the last cycle, if any have run
+    pub fn last_cycle(&self) -> Option<Cycle> {
+        self.last
+    }
+
+    /// Returns the number of cycles that have run
+    pub fn cycles(&self) -> usize {
+        self.machine.get_ticks() - 1
+    }
+
+    /// Raises an interrupt line between this cycle and the next. It's
+    /// remembered, so going back before now and running forward again
+    /// raises it again at the same time.
+    pub fn raise(&mut self, line: usize) {
+        self.raised.entry(self.cycles()).or_default().push(line);
+        self.machine.interrupts().raise(line);
+    }
+
+    /// Returns the cycle about to run, as far as it's known before it does:
+    /// it never has a signal, and if the program has finished, it's the
+    /// cycle that would come next
+    pub fn upcoming(&self) -> Cycle {
+        Cycle {
+            number: self.machine.get_ticks(),
+            register: self.machine.read_register(),
+            instruction: self.machine.instruction_index(),
+            signal: None,
+            interrupt: self.machine.interrupt(),
+        }
+    }
+
+    /// Runs a single cycle and returns it, unless the program has finished
+    pub fn step(&mut self) -> Option<Cycle> {
+        for line in self.raised.get(&self.cycles()).into_iter().flatten() {
+            self.machine.interrupts().raise(*line);
+        }
+
+        let cycle = Fanout::new(&mut self.machine)
+            .with_observer(&mut self.screen)
+            .cycle();
+
+        if cycle.is_some() {
+            self.last = cycle;
+            self.take_checkpoint();
+        }
+
+        cycle
+    }
+
+    /// Runs the program until it finishes, is about to start an instruction
+    /// with a breakpoint, or its register is about to leave the alarm's
+    /// range. It always runs at least one cycle, so continuing from a
+    /// breakpoint doesn't stop at it again straight away.
+    pub fn resume(&mut self) -> Stop {
+        if self.step().is_none() {
+            return Stop::Finished;
+        }
+
+        loop {
+            if let Some(index) = self.breakpoint_ahead() {
+                return Stop::Breakpoint(index);
+            }
+
+            if let Some(register) = self.alarm_ahead() {
+                return Stop::Alarm(register);
+            }
+
+            if self.step().is_none() {
+                return Stop::Finished;
+            }
+        }
+    }
+
+    /// Undoes the last cycle. Returns `false` if none have run.
+    pub fn reverse_step(&mut self) -> bool {
+        match self.cycles() {
+            0 => false,
+            cycles => {
+                self.seek(cycles - 1);
+                true
+            }
+        }
+    }
+
+    /// Runs the program backwards until it's about to start an instruction
+    /// with a breakpoint, and returns its position. Like `resume()`, it
+    /// always undoes at least one cycle. Without a breakpoint to stop at, it
+    /// goes all the way back to the start and returns `None`.
+    ///
+    /// It goes back a checkpoint at a time, replaying the cycles from there
+    /// to where it went back from and stopping at the last breakpoint among
+    /// them, so one a little way back is found without replaying the whole
+    /// program.
+    pub fn reverse_resume(&mut self) -> Option<usize> {
+        let mut end = self.cycles();
+
+        while end > 0 {
+            let start = self
+                .checkpoints
+                .iter()
+                .rev()
+                .map(|saved| saved.machine.ticks() - 1)
+                .find(|&cycles| cycles < end)
+                .unwrap_or(0);
+
+            let mut stop = None;
+            self.seek(start);
+
+            for cycles in start..end {
+                if let Some(index) = self.breakpoint_ahead() {
+                    stop = Some((cycles, index));
+                }
+
+                if cycles + 1 < end {
+                    self.step();
+                }
+            }
+
+            if let Some((cycles, index)) = stop {
+                self.seek(cycles);
+                return Some(index);
+            }
+
+            end = start;
+        }
+
+        self.seek(0);
+        None
+    }
+
+    /// Goes back or forward to when the given number of cycles had run, or
+    /// to the end if the program finishes sooner. Breakpoints are kept.
+    pub fn seek(&mut self, cycles: usize) {
+        if cycles < self.cycles() {
+            let checkpoint = self
+                .checkpoints
+                .iter()
+                .rev()
+                .find(|saved| saved.machine.ticks() - 1 <= cycles);
+
+            match checkpoint {
+                Some(saved) => {
+                    self.machine.restore(&saved.machine);
+                    self.last = saved.last;
+
+                    for line in self.machine.interrupts().pending() {
+                        self.machine.interrupts().lower(line);
+                    }
+
+                    for line in &saved.pending {
+                        self.machine.interrupts().raise(*line);
+                    }
+                }
+                None => {
+                    self.machine = self.restart();
+                    self.last = None;
+                }
+            }
+
+            self.screen.rewind(self.cycles());
+        }
+
+        while self.cycles() < cycles && self.step().is_some() {}
+    }
+
+    /// Sets a breakpoint on the instruction at `index` if there isn't one, or
+    /// removes it if there is. Returns whether there's a breakpoint now.
+    pub fn toggle_breakpoint(&mut self, index: usize) -> bool {
+        if self.breakpoints.remove(&index) {
+            self.conditions.remove(&index);
+            return false;
+        }
+
+        self.breakpoints.insert(index);
+        true
+    }
+
+    /// Sets a breakpoint on the instruction at `index` that only stops when
+    /// the condition holds for the cycle the instruction is about to start
+    /// on, e.g. `x > 20`. Setting one on a breakpoint that already has one
+    /// replaces it.
+    pub fn set_condition(&mut self, index: usize, condition: Expression) {
+        self.breakpoints.insert(index);
+        self.conditions.insert(index, condition);
+    }
+
+    /// Returns the condition of the breakpoint on the instruction at `index`,
+    /// if it has one
+    pub fn condition(&self, index: usize) -> Option<&Expression> {
+        self.conditions.get(&index)
+    }
+
+    /// Returns the positions of every instruction with a breakpoint, in order
+    pub fn breakpoints(&self) -> &BTreeSet<usize> {
+        &self.breakpoints
+    }
+
+    /// Remembers the machine if a checkpoint is due and it hasn't been
+    /// remembered at this cycle before, forgetting the oldest if it's full
+    fn take_checkpoint(&mut self) {
+        let cycles = self.cycles();
+
+        if self.interval == 0 || self.capacity == 0 || !cycles.is_multiple_of(self.interval) {
+            return;
+        }
+
+        let newest = self.checkpoints.back();
+
+        if newest.is_some_and(|saved| saved.machine.ticks() > cycles) {
+            return;
+        }
+
+        if self.checkpoints.len() == self.capacity {
+            self.checkpoints.pop_front();
+        }
+
+        self.checkpoints.push_back(Saved {
+            machine: self.machine.checkpoint(),
+            last: self.last,
+            pending: self.machine.interrupts().pending(),
+        });
+    }
+
+    /// Returns a machine about to run the program's first cycle, with none of
+    /// its interrupt lines raised
+    fn restart(&self) -> VirtualMachine {
+        for line in self.interrupts.pending() {
+            self.interrupts.lower(line);
+        }
+
+        VirtualMachine::new(VecDeque::from(self.program.clone()))
+            .with_interrupts(self.interrupts.clone())
+    }
+
+    /// Returns the register during the next cycle, if it's leaving the
+    /// alarm's range: it's outside now, but the last cycle's was inside
+    fn alarm_ahead(&self) -> Option<isize> {
+        let range = self.alarm.as_ref()?;
+        let next = self.machine.read_register();
+        let last = self.last?.register;
+
+        match self.machine.is_executing() && range.contains(&last) && !range.contains(&next) {
+            true => Some(next),
+            false => None,
+        }
+    }
+
+    /// Returns the position of the instruction about to start, if it has a
+    /// breakpoint whose condition, if any, holds. Instructions only start
+    /// when nothing is in flight, i.e. when the next cycle's instruction
+    /// differs from the last one's.
+    fn breakpoint_ahead(&self) -> Option<usize> {
+        let next = self.machine.instruction_index()?;
+        let starting = self.last.and_then(|last| last.instruction) != Some(next);
+        let holds = || {
+            self.conditions
+                .get(&next)
+                .is_none_or(|condition| condition.holds(&self.upcoming()))
+        };
+
+        match starting && self.breakpoints.contains(&next) && holds() {
+            true => Some(next),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_back_to_breakpoints_a_checkpoint_at_a_time() {
+        for (interval, capacity) in [(4, 64), (4, 2), (0, 0), (1, 1000)] {
+            let mut debugger =
+                Debugger::new([Instruction::Noop; 40]).with_checkpoints(interval, capacity);
+            debugger.toggle_breakpoint(2);
+            debugger.toggle_breakpoint(9);
+            debugger.toggle_breakpoint(10);
+
+            debugger.seek(30);
+
+            assert_eq!(debugger.reverse_resume(), Some(10));
+            assert_eq!(debugger.cycles(), 10);
+            assert_eq!(debugger.reverse_resume(), Some(9));
+            assert_eq!(debugger.reverse_resume(), Some(2));
+            assert_eq!(debugger.cycles(), 2);
+            assert_eq!(debugger.reverse_resume(), None);
+            assert_eq!(debugger.cycles(), 0);
+            assert_eq!(debugger.reverse_resume(), None);
+        }
+    }
+
+    #[test]
+    fn runs_back_past_breakpoints_whose_conditions_fail() {
+        let program = [
+            Instruction::Addx(5),
+            Instruction::Noop,
+            Instruction::Addx(-5),
+            Instruction::Noop,
+        ];
+        let mut debugger = Debugger::new(program).with_checkpoints(1, 10);
+        debugger.set_condition(1, "x > 1".parse().unwrap());
+        debugger.set_condition(3, "x > 1".parse().unwrap());
+
+        while debugger.step().is_some() {}
+
+        assert_eq!(debugger.reverse_resume(), Some(1));
+        assert_eq!(debugger.upcoming().register, 6);
+        assert_eq!(debugger.cycles(), 2);
+    }
+}
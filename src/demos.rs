@@ -0,0 +1,137 @@
+use alloc::vec::Vec;
+
+use crate::assembly;
+use crate::instructions::Instruction;
+use crate::programs::Program;
+use crate::screens::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// A program that comes with the crate, to run before writing one, e.g. with
+/// `legolos render demo:banner`
+#[derive(Debug, Clone, Copy)]
+pub struct Demo {
+    /// What `by_name()` finds it by
+    pub name: &'static str,
+
+    /// What it does, in a few words
+    pub description: &'static str,
+
+    build: fn() -> Program,
+}
+
+impl Demo {
+    pub fn program(&self) -> Program {
+        (self.build)()
+    }
+}
+
+/// Every demo, the puzzle's sample first
+pub const DEMOS: [Demo; 4] = [
+    Demo {
+        name: "sample",
+        description: "a real puzzle input, which draws BRJLFULP",
+        build: || assemble(include_str!("demos/sample.asm")),
+    },
+    Demo {
+        name: "banner",
+        description: "draws BRICKS, in the font the puzzle's answers use",
+        build: || assemble(include_str!("demos/banner.asm")),
+    },
+    Demo {
+        name: "bounce",
+        description: "a sprite bouncing off the sides as it goes down the screen, for four frames",
+        build: bounce,
+    },
+    Demo {
+        name: "stress",
+        description: "the register jumping on and off the screen, for 210,000 cycles",
+        build: stress,
+    },
+];
+
+/// How far the bouncing sprite moves from one row to the next
+const BOUNCE_STEP: isize = 9;
+
+/// How many frames the sprite bounces for. It's back where it started after
+/// this many.
+const BOUNCE_FRAMES: usize = 4;
+
+/// How many times the stress test moves the register. Each move takes seven
+/// cycles.
+const STRESS_MOVES: usize = 30_000;
+
+/// Returns the demo's program, if there's a demo of that name
+pub fn by_name(name: &str) -> Option<Program> {
+    DEMOS
+        .iter()
+        .find(|demo| demo.name == name)
+        .map(Demo::program)
+}
+
+/// The demos' sources are part of the crate, so they always assemble
+fn assemble(source: &str) -> Program {
+    assembly::assemble(source)
+        .expect("the demos assemble")
+        .into()
+}
+
+/// Keeps the sprite still for a row at a time, moving it between rows: the
+/// last two cycles of every row are an `addx`, taking effect as the next
+/// starts. It starts at the left edge and turns back at either side.
+fn bounce() -> Program {
+    let rows = SCREEN_HEIGHT * BOUNCE_FRAMES;
+    let rightmost = SCREEN_WIDTH as isize - 3;
+
+    let mut program = Vec::new();
+    let mut position = 1;
+    let mut step = BOUNCE_STEP;
+
+    for row in 0..rows {
+        program.extend([Instruction::Noop; SCREEN_WIDTH - 2]);
+
+        if row + 1 == rows {
+            program.extend([Instruction::Noop; 2]);
+            break;
+        }
+
+        if !(1..=rightmost).contains(&(position + step)) {
+            step = -step;
+        }
+
+        program.push(Instruction::Addx(step));
+        position += step;
+    }
+
+    program.into()
+}
+
+/// Sends the register somewhere new every few cycles, as often off the
+/// screen as on it, and goes through every instruction but those loading the
+/// register on the way, which would leave it to whatever's attached. The
+/// destinations come from a small linear congruential generator, so the
+/// program is the same every time.
+fn stress() -> Program {
+    let mut program = Vec::with_capacity(STRESS_MOVES * 6);
+    let mut state: u32 = 2022;
+    let mut register = 1;
+
+    for _ in 0..STRESS_MOVES {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+
+        // Anywhere from well left of the screen to well right of it
+        let target = (state >> 16) as isize % 120 - 40;
+        let operand = (state >> 8) as usize % 16;
+
+        program.extend([
+            Instruction::Addx(target - register),
+            Instruction::Noop,
+            Instruction::Out,
+            Instruction::Tone(operand),
+            Instruction::Stx(operand),
+            Instruction::Custom(operand as u8, target),
+        ]);
+
+        register = target;
+    }
+
+    program.into()
+}
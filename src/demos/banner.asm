@@ -0,0 +1,185 @@
+; Draws BRICKS, written by `lettering::program_for_text()`
+
+noop
+noop
+noop
+addx 4
+addx 1
+noop
+noop
+addx 5
+addx 1
+noop
+addx 4
+noop
+noop
+addx 3
+noop
+addx 3
+noop
+addx 4
+addx 1
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+addx -28
+noop
+addx 3
+addx 2
+noop
+addx 3
+noop
+noop
+addx 4
+noop
+addx 3
+noop
+addx 3
+addx 2
+addx 2
+noop
+addx 3
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+addx -24
+addx 1
+noop
+addx 3
+noop
+addx 3
+noop
+noop
+addx 4
+noop
+addx 3
+noop
+noop
+noop
+addx 6
+noop
+noop
+noop
+addx 4
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+addx -25
+noop
+addx 3
+addx 3
+addx 1
+noop
+noop
+noop
+addx 5
+noop
+addx 3
+noop
+noop
+noop
+addx 5
+addx 2
+noop
+noop
+addx 5
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+addx -27
+noop
+addx 3
+addx 2
+addx 2
+noop
+noop
+noop
+addx 5
+noop
+addx 3
+noop
+addx 3
+addx 2
+addx 2
+noop
+noop
+noop
+noop
+addx 6
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+addx -27
+addx 1
+noop
+addx 3
+noop
+addx 3
+noop
+addx 4
+addx 1
+noop
+addx 4
+noop
+noop
+addx 3
+noop
+addx 3
+addx 3
+addx 1
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
+noop
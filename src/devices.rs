@@ -0,0 +1,57 @@
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+mod address_map;
+mod beeper;
+mod bus;
+pub mod dma;
+mod keyboard;
+mod led_matrix;
+mod plotter;
+mod ram;
+mod seven_segment;
+mod teletype;
+
+pub use address_map::{AddressMap, AddressMapError};
+pub use beeper::{Beeper, Note};
+pub use bus::{Bus, BusError};
+pub use dma::Dma;
+pub use keyboard::Keyboard;
+pub use led_matrix::LedMatrix;
+pub use plotter::Plotter;
+pub use ram::Ram;
+pub use seven_segment::SevenSegment;
+pub use teletype::{Teletype, TeletypeMode};
+
+/// A peripheral the machine talks to over its bus. Each device is attached to
+/// a range of addresses. Programs read and write them with `ldx` and `stx`,
+/// and the device sees the offset into its own range, so it doesn't care where
+/// it ends up on the bus.
+pub trait Device {
+    /// Called once at the end of every CPU cycle, after the instruction has
+    /// had its effect. Most devices only react to reads and writes.
+    fn tick(&mut self) {}
+
+    /// Returns the value at `offset`. Reading may change the device, like
+    /// taking a key from a queue.
+    fn read(&mut self, offset: usize) -> isize;
+
+    /// Stores `value` at `offset`
+    fn write(&mut self, offset: usize, value: isize);
+}
+
+/// A shared device, so whoever attached it can still look at it after the
+/// machine is done with it
+impl<D: Device> Device for Rc<RefCell<D>> {
+    fn tick(&mut self) {
+        self.borrow_mut().tick()
+    }
+
+    fn read(&mut self, offset: usize) -> isize {
+        self.borrow_mut().read(offset)
+    }
+
+    fn write(&mut self, offset: usize, value: isize) {
+        self.borrow_mut().write(offset, value)
+    }
+}
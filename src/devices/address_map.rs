@@ -0,0 +1,169 @@
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt::{Debug, Display, Formatter};
+use core::ops::Range;
+
+use super::{Bus, Device};
+
+/// Lays out a bus in one place: which device answers at which addresses,
+/// each under a name. Nothing is attached until `build()`, which checks the
+/// whole layout first, so a map can be written down (and printed, or checked
+/// in a test) as the single description of a machine's memory.
+///
+/// ```ignore
+/// let bus = AddressMap::new()
+///     .with_device("teletype", 0..1, teletype)
+///     .with_device("leds", 0x100..0x118, leds)
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct AddressMap {
+    regions: Vec<Region>,
+}
+
+/// A device and where it goes
+struct Region {
+    name: String,
+    addresses: Range<usize>,
+    device: Box<dyn Device>,
+}
+
+/// Why an address map couldn't be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressMapError {
+    /// The named device was given no addresses at all
+    Empty { name: String },
+
+    /// Two devices were given some of the same addresses
+    Overlap {
+        name: String,
+        addresses: Range<usize>,
+        other: String,
+        taken: Range<usize>,
+    },
+}
+
+impl AddressMap {
+    pub fn new() -> Self {
+        AddressMap::default()
+    }
+
+    /// Assigns the addresses to the device, under a name to report it by
+    pub fn with_device(
+        mut self,
+        name: impl Into<String>,
+        addresses: Range<usize>,
+        device: impl Device + 'static,
+    ) -> Self {
+        self.regions.push(Region {
+            name: name.into(),
+            addresses,
+            device: Box::new(device),
+        });
+
+        self
+    }
+
+    /// Returns every device's name and addresses, lowest addresses first
+    pub fn layout(&self) -> Vec<(&str, Range<usize>)> {
+        let mut layout: Vec<_> = self
+            .regions
+            .iter()
+            .map(|region| (region.name.as_str(), region.addresses.clone()))
+            .collect();
+
+        layout.sort_by_key(|(_, addresses)| (addresses.start, addresses.end));
+        layout
+    }
+
+    /// Checks that every device has addresses and that no two share any
+    pub fn validate(&self) -> Result<(), AddressMapError> {
+        for (index, region) in self.regions.iter().enumerate() {
+            if region.addresses.is_empty() {
+                return Err(AddressMapError::Empty {
+                    name: region.name.clone(),
+                });
+            }
+
+            let overlap = self.regions[..index].iter().find(|other| {
+                other.addresses.start < region.addresses.end
+                    && region.addresses.start < other.addresses.end
+            });
+
+            if let Some(other) = overlap {
+                return Err(AddressMapError::Overlap {
+                    name: region.name.clone(),
+                    addresses: region.addresses.clone(),
+                    other: other.name.clone(),
+                    taken: other.addresses.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the map, then attaches every device to a new bus in the
+    /// order they were assigned, ready for `VirtualMachine::with_bus()`
+    pub fn build(self) -> Result<Bus, AddressMapError> {
+        self.validate()?;
+
+        let mut bus = Bus::new();
+
+        for region in self.regions {
+            let length = region.addresses.len();
+
+            bus.attach_boxed(region.addresses.start, length, region.device)
+                .expect("the map was validated");
+        }
+
+        Ok(bus)
+    }
+}
+
+/// Devices can't be printed, so show the layout instead
+impl Debug for AddressMap {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter
+            .debug_struct("AddressMap")
+            .field("layout", &self.layout())
+            .finish()
+    }
+}
+
+/// Lists the devices one per line, lowest addresses first, e.g.
+/// `0x0100..0x0118 leds`
+impl Display for AddressMap {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        for (name, addresses) in self.layout() {
+            writeln!(
+                formatter,
+                "{:#06x}..{:#06x} {}",
+                addresses.start, addresses.end, name
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for AddressMapError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AddressMapError::Empty { name } => {
+                write!(formatter, "{} was given no addresses", name)
+            }
+            AddressMapError::Overlap {
+                name,
+                addresses,
+                other,
+                taken,
+            } => write!(
+                formatter,
+                "{}'s addresses {:?} overlap {}'s {:?}",
+                name, addresses, other, taken
+            ),
+        }
+    }
+}
+
+impl core::error::Error for AddressMapError {}
@@ -0,0 +1,150 @@
+#[cfg(feature = "wav")]
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "wav")]
+use std::io::{self, Write};
+
+use crate::machines::{Signal, VirtualMachine};
+use crate::observers::{Cycle, CycleObserver};
+
+/// A single tone played by a beeper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// The cycle the `tone` instruction executed on, which is when it starts
+    pub cycle: usize,
+
+    /// In hertz. Zero or less is silence.
+    pub frequency: isize,
+
+    /// In cycles
+    pub duration: usize,
+}
+
+/// A speaker for programs. Every `tone` instruction plays the register's value
+/// as a frequency for the instruction's number of cycles. The beeper keeps a
+/// log of the notes so they can be lined up against the screen, or written out
+/// as audio with the `wav` feature.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Beeper {
+    notes: Vec<Note>,
+}
+
+impl Beeper {
+    pub fn new() -> Self {
+        Beeper::default()
+    }
+
+    /// Plays whatever tone the machine asked for during its last cycle, if
+    /// any
+    pub fn latch(&mut self, machine: &VirtualMachine) {
+        if let Some(Signal::Tone {
+            frequency,
+            duration,
+        }) = machine.signal()
+        {
+            // The machine has already counted the cycle the tone played on
+            let cycle = machine.get_ticks() - 1;
+
+            self.notes.push(Note {
+                cycle,
+                frequency,
+                duration,
+            });
+        }
+    }
+
+    /// Cycles the machine until it finishes, playing every tone it asks for
+    pub fn run(&mut self, machine: &mut VirtualMachine) {
+        while machine.is_executing() {
+            machine.cycle();
+            self.latch(machine);
+        }
+    }
+
+    /// Returns the notes played so far, in order
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Writes the notes as a mono, 8 bit WAV file of square waves. Each cycle
+    /// lasts `cycle_seconds` and the audio starts at the first cycle. A note
+    /// interrupts any note still playing.
+    #[cfg(feature = "wav")]
+    pub fn write_wav(&self, mut writer: impl Write, cycle_seconds: f64) -> io::Result<()> {
+        const SAMPLE_RATE: u32 = 8000;
+
+        let samples_at = |cycle: usize| {
+            let seconds = (cycle - 1) as f64 * cycle_seconds;
+            (seconds * SAMPLE_RATE as f64).round() as usize
+        };
+
+        let length = self
+            .notes
+            .iter()
+            .map(|note| samples_at(note.cycle + note.duration))
+            .max()
+            .unwrap_or(0);
+
+        // Unsigned 8 bit samples are silent in the middle
+        let mut samples = vec![128u8; length];
+
+        for (index, note) in self.notes.iter().enumerate() {
+            let start = samples_at(note.cycle);
+            let mut end = samples_at(note.cycle + note.duration);
+
+            if let Some(next) = self.notes.get(index + 1) {
+                end = end.min(samples_at(next.cycle));
+            }
+
+            if note.frequency <= 0 {
+                continue;
+            }
+
+            let period = SAMPLE_RATE as f64 / note.frequency as f64;
+
+            for (offset, sample) in samples[start..end].iter_mut().enumerate() {
+                let high = (offset as f64 % period) < period / 2.0;
+                *sample = if high { 192 } else { 64 };
+            }
+        }
+
+        let data = samples.len() as u32;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        // The format: PCM, one channel, the sample rate, the byte rate, the
+        // block alignment, and the bits per sample
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&SAMPLE_RATE.to_le_bytes())?;
+        writer.write_all(&SAMPLE_RATE.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?;
+        writer.write_all(&8u16.to_le_bytes())?;
+
+        writer.write_all(b"data")?;
+        writer.write_all(&data.to_le_bytes())?;
+        writer.write_all(&samples)
+    }
+}
+
+/// Plays whatever tone the machine asked for during each cycle, like
+/// `latch()`
+impl CycleObserver for Beeper {
+    fn observe(&mut self, cycle: &Cycle) {
+        if let Some(Signal::Tone {
+            frequency,
+            duration,
+        }) = cycle.signal
+        {
+            self.notes.push(Note {
+                cycle: cycle.number,
+                frequency,
+                duration,
+            });
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::{Debug, Display, Formatter};
+use core::ops::Range;
+
+use super::Device;
+
+/// Connects a machine to its devices. Every device owns a range of addresses
+/// and no two ranges overlap. Reads of an address nobody owns are `0` and
+/// writes to one go nowhere, like an empty slot on a real bus.
+#[derive(Default)]
+pub struct Bus {
+    mappings: Vec<Mapping>,
+}
+
+struct Mapping {
+    addresses: Range<usize>,
+    device: Box<dyn Device>,
+}
+
+impl Bus {
+    pub fn new() -> Self {
+        Bus::default()
+    }
+
+    /// Attaches a device to the `length` addresses starting at `start`
+    pub fn attach(
+        &mut self,
+        start: usize,
+        length: usize,
+        device: impl Device + 'static,
+    ) -> Result<(), BusError> {
+        self.attach_boxed(start, length, Box::new(device))
+    }
+
+    /// Attaches a device that's already boxed, like `attach()`
+    pub(super) fn attach_boxed(
+        &mut self,
+        start: usize,
+        length: usize,
+        device: Box<dyn Device>,
+    ) -> Result<(), BusError> {
+        let addresses = start..start.saturating_add(length);
+
+        if addresses.is_empty() {
+            return Err(BusError::Empty { start });
+        }
+
+        let overlap = self.mappings.iter().find(|mapping| {
+            mapping.addresses.start < addresses.end && addresses.start < mapping.addresses.end
+        });
+
+        if let Some(mapping) = overlap {
+            return Err(BusError::Overlap {
+                requested: addresses,
+                taken: mapping.addresses.clone(),
+            });
+        }
+
+        self.mappings.push(Mapping { addresses, device });
+
+        Ok(())
+    }
+
+    /// Returns the address ranges of every attached device, in the order they
+    /// were attached
+    pub fn ranges(&self) -> Vec<Range<usize>> {
+        self.mappings
+            .iter()
+            .map(|mapping| mapping.addresses.clone())
+            .collect()
+    }
+
+    /// Reads `address` from whichever device owns it
+    pub fn read(&mut self, address: usize) -> isize {
+        match self.find(address) {
+            Some((device, offset)) => device.read(offset),
+            None => 0,
+        }
+    }
+
+    /// Writes `value` to whichever device owns `address`
+    pub fn write(&mut self, address: usize, value: isize) {
+        if let Some((device, offset)) = self.find(address) {
+            device.write(offset, value);
+        }
+    }
+
+    /// Ticks every device, in the order they were attached
+    pub fn tick(&mut self) {
+        for mapping in &mut self.mappings {
+            mapping.device.tick();
+        }
+    }
+
+    fn find(&mut self, address: usize) -> Option<(&mut (dyn Device + 'static), usize)> {
+        self.mappings
+            .iter_mut()
+            .find(|mapping| mapping.addresses.contains(&address))
+            .map(|mapping| {
+                let offset = address - mapping.addresses.start;
+                (mapping.device.as_mut(), offset)
+            })
+    }
+}
+
+/// Devices can't be printed, so show where they are instead
+impl Debug for Bus {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter
+            .debug_struct("Bus")
+            .field("ranges", &self.ranges())
+            .finish()
+    }
+}
+
+/// Why a device couldn't be attached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BusError {
+    /// The device asked for no addresses at all
+    Empty { start: usize },
+
+    /// Some of the addresses already belong to another device
+    Overlap {
+        requested: Range<usize>,
+        taken: Range<usize>,
+    },
+}
+
+impl Display for BusError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BusError::Empty { start } => {
+                write!(formatter, "no addresses requested at {}", start)
+            }
+            BusError::Overlap { requested, taken } => write!(
+                formatter,
+                "addresses {:?} overlap {:?}, which are already attached",
+                requested, taken
+            ),
+        }
+    }
+}
+
+impl core::error::Error for BusError {}
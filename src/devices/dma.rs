@@ -0,0 +1,177 @@
+use super::Device;
+use crate::interrupts::InterruptLine;
+
+/// Where the next transfer starts reading, as an offset into the source
+pub const SOURCE: usize = 0;
+
+/// Where the next transfer starts writing, as an offset into the destination
+pub const DESTINATION: usize = 1;
+
+/// How many words the next transfer copies
+pub const LENGTH: usize = 2;
+
+/// Writing starts a transfer. Reading returns how many words are left to copy.
+pub const CONTROL: usize = 3;
+
+/// Reads `1` once a transfer has finished, and `0` again after that
+pub const DONE: usize = 4;
+
+/// How many addresses the engine takes on the bus
+pub const REGISTERS: usize = 5;
+
+/// A DMA engine (direct memory access), copying blocks of words from one
+/// device to another while the program keeps running, e.g. an image in `Ram`
+/// to an `LedMatrix`. It takes `REGISTERS` addresses on the bus, `SOURCE`
+/// through `DONE`. The devices it copies between are its own, so attach them
+/// to the bus too, as an `Rc<RefCell<_>>`, to fill and look at them.
+///
+/// A program sets the source, destination, and length with `stx`, then
+/// writes `CONTROL` to start. Every cycle after that copies `rate()` words,
+/// and once the last is copied the engine raises its interrupt: `DONE` reads
+/// `1` for the program to poll, `take_interrupt()` says so to whoever drives
+/// the machine, and the line given to `with_interrupt()`, if any, is raised.
+#[derive(Debug, Clone)]
+pub struct Dma<S, D> {
+    source: S,
+    destination: D,
+
+    /// The block programmed for the next transfer
+    next: Block,
+
+    /// The block being copied, what's left of it
+    transfer: Block,
+
+    /// How many words a cycle copies
+    rate: usize,
+
+    /// Whether a transfer finished since the interrupt was last taken
+    interrupt: bool,
+
+    /// The line to raise when a transfer finishes, if any
+    line: Option<InterruptLine>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Block {
+    source: usize,
+    destination: usize,
+    length: usize,
+}
+
+impl<S: Device, D: Device> Dma<S, D> {
+    /// Creates an idle engine copying from `source` to `destination`, a word
+    /// every cycle
+    pub fn new(source: S, destination: D) -> Self {
+        Dma {
+            source,
+            destination,
+            next: Block::default(),
+            transfer: Block::default(),
+            rate: 1,
+            interrupt: false,
+            line: None,
+        }
+    }
+
+    /// Raises the interrupt line whenever a transfer finishes, e.g. one from
+    /// `InterruptController::line()`, so its handler runs
+    pub fn with_interrupt(mut self, line: InterruptLine) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Copies this many words every cycle rather than one. There's always at
+    /// least one.
+    pub fn with_rate(mut self, words: usize) -> Self {
+        self.rate = words.max(1);
+        self
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// Returns `true` while a transfer is under way
+    pub fn is_busy(&self) -> bool {
+        self.transfer.length > 0
+    }
+
+    /// Returns `true` if a transfer finished since the last time this was
+    /// asked, and lowers the interrupt
+    pub fn take_interrupt(&mut self) -> bool {
+        core::mem::take(&mut self.interrupt)
+    }
+
+    /// Raises the interrupt once a transfer is done
+    fn finish(&mut self) {
+        self.interrupt = true;
+
+        if let Some(line) = &self.line {
+            line.raise();
+        }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    pub fn destination(&self) -> &D {
+        &self.destination
+    }
+}
+
+impl<S: Device, D: Device> Device for Dma<S, D> {
+    /// Copies the next words of the transfer, if there is one
+    fn tick(&mut self) {
+        if !self.is_busy() {
+            return;
+        }
+
+        for _ in 0..self.rate.min(self.transfer.length) {
+            let value = self.source.read(self.transfer.source);
+            self.destination.write(self.transfer.destination, value);
+
+            self.transfer.source += 1;
+            self.transfer.destination += 1;
+            self.transfer.length -= 1;
+        }
+
+        if !self.is_busy() {
+            self.finish();
+        }
+    }
+
+    fn read(&mut self, offset: usize) -> isize {
+        match offset {
+            SOURCE => self.next.source as isize,
+            DESTINATION => self.next.destination as isize,
+            LENGTH => self.next.length as isize,
+            CONTROL => self.transfer.length as isize,
+            DONE => self.take_interrupt() as isize,
+            _ => 0,
+        }
+    }
+
+    /// Negative addresses and lengths are taken as `0`. Starting a transfer
+    /// while another is under way abandons the first.
+    fn write(&mut self, offset: usize, value: isize) {
+        let value = value.max(0) as usize;
+
+        match offset {
+            SOURCE => self.next.source = value,
+            DESTINATION => self.next.destination = value,
+            LENGTH => self.next.length = value,
+            CONTROL => {
+                self.transfer = self.next;
+
+                self.interrupt = false;
+
+                // Copying nothing is done as soon as it starts
+                if !self.is_busy() {
+                    self.finish();
+                }
+            }
+            _ => (),
+        }
+    }
+}
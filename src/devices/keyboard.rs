@@ -0,0 +1,124 @@
+use alloc::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use super::Device;
+
+/// Feeds key presses to a program. The `in` instruction loads the next key's
+/// code point into the register, or `0` when nobody has pressed anything, so
+/// a program can poll for input every so often without stalling.
+///
+/// Keys come from a queue filled up front with `press()`, from a channel that
+/// another thread sends to while the program runs (with the `std` feature),
+/// or, with the `crossterm` feature, from the terminal. They're taken in that
+/// order.
+#[derive(Debug, Default)]
+pub struct Keyboard {
+    pressed: VecDeque<char>,
+
+    #[cfg(feature = "std")]
+    channel: Option<Receiver<char>>,
+
+    #[cfg(feature = "crossterm")]
+    terminal: bool,
+}
+
+impl Keyboard {
+    /// Creates a keyboard nobody is pressing
+    pub fn new() -> Self {
+        Keyboard::default()
+    }
+
+    /// Creates a keyboard with the given keys already queued up
+    pub fn with_keys(keys: impl IntoIterator<Item = char>) -> Self {
+        let mut keyboard = Keyboard::new();
+        keyboard.pressed.extend(keys);
+        keyboard
+    }
+
+    /// Creates a keyboard fed by a channel. Every key sent down the returned
+    /// sender is pressed, in order.
+    #[cfg(feature = "std")]
+    pub fn channel() -> (Sender<char>, Self) {
+        let (sender, receiver) = mpsc::channel();
+
+        let keyboard = Keyboard {
+            channel: Some(receiver),
+            ..Keyboard::default()
+        };
+
+        (sender, keyboard)
+    }
+
+    /// Creates a keyboard fed by key presses in the terminal. It doesn't
+    /// touch the terminal's mode, so put it in raw mode first for keys to
+    /// arrive as they're pressed rather than a line at a time.
+    #[cfg(feature = "crossterm")]
+    pub fn terminal() -> Self {
+        Keyboard {
+            terminal: true,
+            ..Keyboard::default()
+        }
+    }
+
+    /// Queues up a key press
+    pub fn press(&mut self, key: char) {
+        self.pressed.push_back(key);
+    }
+
+    /// Takes the next key that was pressed, if any
+    pub fn next_key(&mut self) -> Option<char> {
+        if let Some(key) = self.pressed.pop_front() {
+            return Some(key);
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(key) = self.channel.as_ref().and_then(|r| r.try_recv().ok()) {
+            return Some(key);
+        }
+
+        #[cfg(feature = "crossterm")]
+        if self.terminal {
+            return terminal_key();
+        }
+
+        None
+    }
+}
+
+/// Takes a key press from the terminal without waiting for one. Anything that
+/// isn't a key being pressed (e.g. releases or resizes) is skipped over.
+#[cfg(feature = "crossterm")]
+fn terminal_key() -> Option<char> {
+    use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+    use std::time::Duration;
+
+    while event::poll(Duration::ZERO).ok()? {
+        if let Event::Key(key) = event::read().ok()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char(c) => return Some(c),
+                KeyCode::Enter => return Some('\n'),
+                KeyCode::Tab => return Some('\t'),
+                KeyCode::Backspace => return Some('\u{8}'),
+                KeyCode::Esc => return Some('\u{1b}'),
+                _ => continue,
+            }
+        }
+    }
+
+    None
+}
+
+/// The keyboard also works on the bus: every read takes the next key, just
+/// like `in` does
+impl Device for Keyboard {
+    fn read(&mut self, _offset: usize) -> isize {
+        self.next_key().map_or(0, |key| key as isize)
+    }
+
+    fn write(&mut self, _offset: usize, _value: isize) {}
+}
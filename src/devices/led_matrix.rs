@@ -0,0 +1,147 @@
+use alloc::{vec, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+use super::Device;
+use crate::images::Raster;
+use crate::machines::RegisterSource;
+
+/// A grid of LEDs of any size, each with its own brightness, like the ones on
+/// hardware badges. Level `0` is off and `levels - 1` is full brightness.
+///
+/// It can be driven two ways. Like a `Screen`, a beam can scan the LEDs one
+/// per cycle, setting each to the register's value. Or a program can address
+/// LEDs directly: address `row * columns + column` holds that LED's level.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedMatrix {
+    rows: usize,
+    columns: usize,
+    levels: u8,
+    leds: Vec<u8>,
+}
+
+impl LedMatrix {
+    /// Creates a matrix with every LED off. There are always at least two
+    /// levels: off and on.
+    pub fn new(rows: usize, columns: usize, levels: u8) -> Self {
+        LedMatrix {
+            rows,
+            columns,
+            levels: levels.max(2),
+            leds: vec![0; rows * columns],
+        }
+    }
+
+    /// Returns the number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of LEDs in each row
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of brightness levels, including off
+    pub fn levels(&self) -> u8 {
+        self.levels
+    }
+
+    /// Returns the brightness of the LED in `column` of `row`. LEDs off the
+    /// matrix are off.
+    pub fn brightness(&self, row: usize, column: usize) -> u8 {
+        match row < self.rows && column < self.columns {
+            true => self.leds[row * self.columns + column],
+            false => 0,
+        }
+    }
+
+    /// Returns the brightness of the LED at `address`. Addresses off the
+    /// matrix read as off.
+    pub fn read(&self, address: usize) -> isize {
+        self.leds.get(address).copied().unwrap_or(0) as isize
+    }
+
+    /// Sets the LED at `address` to `value`, clamped to the available levels.
+    /// Writes off the matrix are ignored.
+    pub fn write(&mut self, address: usize, value: isize) {
+        let level = value.clamp(0, self.levels as isize - 1) as u8;
+
+        if let Some(led) = self.leds.get_mut(address) {
+            *led = level;
+        }
+    }
+
+    /// Sets the LED under the beam to the source's register. The beam visits
+    /// one LED per cycle, left to right and top to bottom, and starts over
+    /// once it reaches the end.
+    pub fn latch(&mut self, source: &impl RegisterSource) {
+        if self.leds.is_empty() {
+            return;
+        }
+
+        let address = (source.ticks() - 1) % self.leds.len();
+        self.write(address, source.read_register());
+    }
+
+    /// Cycles the source until it finishes, latching the register on every
+    /// cycle
+    pub fn run(&mut self, source: &mut impl RegisterSource) {
+        while source.is_executing() {
+            self.latch(source);
+            source.cycle();
+        }
+    }
+}
+
+/// Each LED is one address on the bus, laid out the same as `read()` and
+/// `write()`
+impl Device for LedMatrix {
+    fn read(&mut self, offset: usize) -> isize {
+        LedMatrix::read(self, offset)
+    }
+
+    fn write(&mut self, offset: usize, value: isize) {
+        LedMatrix::write(self, offset, value)
+    }
+}
+
+impl Raster for LedMatrix {
+    fn width(&self) -> usize {
+        self.columns
+    }
+
+    fn height(&self) -> usize {
+        self.rows
+    }
+
+    /// Spreads the levels evenly from off to fully lit
+    fn intensity(&self, x: usize, y: usize) -> u8 {
+        let level = self.brightness(y, x) as usize;
+        (level * u8::MAX as usize / (self.levels - 1) as usize) as u8
+    }
+}
+
+/// Draws each LED as a character, from blank (off) to `@` (full brightness)
+impl Display for LedMatrix {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        const SHADES: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+        let brightest = (self.levels - 1) as usize;
+
+        for row in self.leds.chunks(self.columns.max(1)) {
+            for level in row {
+                // Anything on gets at least the faintest shade
+                let shade = match *level {
+                    0 => 0,
+                    level => (level as usize * (SHADES.len() - 1)).div_ceil(brightest),
+                };
+
+                write!(formatter, "{}", SHADES[shade])?;
+            }
+
+            writeln!(formatter)?;
+        }
+
+        Ok(())
+    }
+}
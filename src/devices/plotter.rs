@@ -0,0 +1,145 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+use crate::images::Raster;
+use crate::machines::RegisterSource;
+
+/// A pen plotter that draws lines on a canvas, following the register.
+///
+/// The register is read as a position on the canvas, counted like the
+/// screen's pixels: left to right and then top to bottom, so position `p` is
+/// column `p % width` of row `p / width`. Whenever the register moves, the pen
+/// draws a straight line from where it was to where it is now. Positions off
+/// the canvas (including negative ones) lift the pen, so a program can move it
+/// without drawing by going off the canvas and back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plotter {
+    width: usize,
+    height: usize,
+    canvas: Vec<bool>,
+
+    /// Where the pen is, if it's on the canvas
+    pen: Option<(usize, usize)>,
+}
+
+impl Plotter {
+    /// Creates a plotter with a blank canvas and the pen lifted
+    pub fn new(width: usize, height: usize) -> Self {
+        Plotter {
+            width,
+            height,
+            canvas: vec![false; width * height],
+            pen: None,
+        }
+    }
+
+    /// Moves the pen to `position`, drawing a line from where it was if it
+    /// was on the canvas too
+    pub fn move_to(&mut self, position: isize) {
+        let area = (self.width * self.height) as isize;
+
+        let target = match self.width > 0 && (0..area).contains(&position) {
+            true => {
+                let position = position as usize;
+                Some((position % self.width, position / self.width))
+            }
+            false => None,
+        };
+
+        if let (Some(from), Some(to)) = (self.pen, target) {
+            self.line(from, to);
+        } else if let Some((x, y)) = target {
+            self.canvas[y * self.width + x] = true;
+        }
+
+        self.pen = target;
+    }
+
+    /// Moves the pen to wherever the source's register points
+    pub fn latch(&mut self, source: &impl RegisterSource) {
+        let position = source.read_register();
+
+        if self.pen.map(|(x, y)| (y * self.width + x) as isize) != Some(position) {
+            self.move_to(position);
+        }
+    }
+
+    /// Cycles the source until it finishes, following the register on every
+    /// cycle
+    pub fn run(&mut self, source: &mut impl RegisterSource) {
+        while source.is_executing() {
+            self.latch(source);
+            source.cycle();
+        }
+
+        self.latch(source);
+    }
+
+    /// Returns `true` if the pen has drawn over the point in column `x` of row
+    /// `y`
+    pub fn is_drawn(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.canvas[y * self.width + x]
+    }
+
+    /// Draws a line between two points on the canvas with Bresenham's
+    /// algorithm
+    fn line(&mut self, from: (usize, usize), to: (usize, usize)) {
+        let (mut x, mut y) = (from.0 as isize, from.1 as isize);
+        let (x1, y1) = (to.0 as isize, to.1 as isize);
+
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let step_x = if x < x1 { 1 } else { -1 };
+        let step_y = if y < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            self.canvas[y as usize * self.width + x as usize] = true;
+
+            if (x, y) == (x1, y1) {
+                break;
+            }
+
+            let doubled = 2 * error;
+
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+}
+
+impl Raster for Plotter {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn intensity(&self, x: usize, y: usize) -> u8 {
+        match self.is_drawn(x, y) {
+            true => u8::MAX,
+            false => 0,
+        }
+    }
+}
+
+/// Draws the canvas like a screen: `#` where the pen went and `.` elsewhere
+impl Display for Plotter {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        for row in self.canvas.chunks(self.width.max(1)) {
+            let row: String = row.iter().map(|d| if *d { '#' } else { '.' }).collect();
+            writeln!(formatter, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use alloc::{vec, vec::Vec};
+
+use super::Device;
+
+/// Plain memory: every address holds the last value written to it, starting
+/// at `0`. Programs have nowhere else to keep values, having just the one
+/// register, so this is where e.g. an image waits before it's copied to a
+/// display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ram {
+    words: Vec<isize>,
+}
+
+impl Ram {
+    /// Creates memory of `length` words, all `0`
+    pub fn new(length: usize) -> Self {
+        Ram {
+            words: vec![0; length],
+        }
+    }
+
+    /// Returns every word, the one at address `0` first
+    pub fn words(&self) -> &[isize] {
+        &self.words
+    }
+
+    /// Returns every word for modification, e.g. to load an image before the
+    /// program runs
+    pub fn words_mut(&mut self) -> &mut [isize] {
+        &mut self.words
+    }
+}
+
+/// Offsets past the end read as `0` and ignore writes
+impl Device for Ram {
+    fn read(&mut self, offset: usize) -> isize {
+        self.words.get(offset).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, offset: usize, value: isize) {
+        if let Some(word) = self.words.get_mut(offset) {
+            *word = value;
+        }
+    }
+}
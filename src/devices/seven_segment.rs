@@ -0,0 +1,155 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+use super::Device;
+use crate::machines::RegisterSource;
+
+/// Segment bits, from the top clockwise and then the middle: `a` through `g`
+const A: u8 = 0b0000001;
+const B: u8 = 0b0000010;
+const C: u8 = 0b0000100;
+const D: u8 = 0b0001000;
+const E: u8 = 0b0010000;
+const F: u8 = 0b0100000;
+const G: u8 = 0b1000000;
+
+/// The segments lit for each decimal digit
+const DIGITS: [u8; 10] = [
+    A | B | C | D | E | F,     // 0
+    B | C,                     // 1
+    A | B | D | E | G,         // 2
+    A | B | C | D | G,         // 3
+    B | C | F | G,             // 4
+    A | C | D | F | G,         // 5
+    A | C | D | E | F | G,     // 6
+    A | B | C,                 // 7
+    A | B | C | D | E | F | G, // 8
+    A | B | C | D | F | G,     // 9
+];
+
+/// A row of seven-segment displays showing the register as a decimal number.
+/// Unlike a `Screen` it doesn't keep any history: it shows whatever value was
+/// latched last, so it's driven by whoever cycles the machine.
+///
+/// Negative numbers get a minus sign in the leftmost digit. Numbers that don't
+/// fit light just the middle segment of every digit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SevenSegment {
+    digits: usize,
+    value: isize,
+}
+
+impl SevenSegment {
+    /// Creates a display with the given number of digits, showing `0`
+    pub fn new(digits: usize) -> Self {
+        SevenSegment { digits, value: 0 }
+    }
+
+    /// Shows the given value
+    pub fn show(&mut self, value: isize) {
+        self.value = value;
+    }
+
+    /// Shows the source's register as it is right now
+    pub fn latch(&mut self, source: &impl RegisterSource) {
+        self.show(source.read_register());
+    }
+
+    /// Cycles the source until it finishes, latching the register on every
+    /// cycle, so the display ends up showing the final value
+    pub fn run(&mut self, source: &mut impl RegisterSource) {
+        self.latch(source);
+
+        while source.is_executing() {
+            source.cycle();
+            self.latch(source);
+        }
+    }
+
+    /// Returns the value being shown
+    pub fn value(&self) -> isize {
+        self.value
+    }
+
+    /// Returns the segments lit on each digit, leftmost first, as bits `a`
+    /// (`0b1`) through `g` (`0b1000000`)
+    pub fn segments(&self) -> Vec<u8> {
+        let mut segments = vec![0; self.digits];
+        let mut magnitude = self.value.unsigned_abs();
+
+        // Fill in digits from the right, leaving room for a minus sign
+        let room = match self.value < 0 {
+            true => self.digits.saturating_sub(1),
+            false => self.digits,
+        };
+
+        for (place, digit) in segments.iter_mut().rev().take(room).enumerate() {
+            if magnitude == 0 && place > 0 {
+                break;
+            }
+
+            *digit = DIGITS[magnitude % 10];
+            magnitude /= 10;
+        }
+
+        // Whatever is left over didn't fit
+        if magnitude != 0 {
+            return vec![G; self.digits];
+        }
+
+        if self.value < 0 {
+            segments[0] = G;
+        }
+
+        segments
+    }
+}
+
+/// A single address on the bus: writing shows a value and reading returns
+/// the one being shown
+impl Device for SevenSegment {
+    fn read(&mut self, _offset: usize) -> isize {
+        self.value
+    }
+
+    fn write(&mut self, _offset: usize, value: isize) {
+        self.show(value)
+    }
+}
+
+/// Draws the digits three rows tall in ASCII:
+///
+/// ```text
+///  _     _
+/// | |  | _|
+/// |_|  ||_
+/// ```
+impl Display for SevenSegment {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        let segments = self.segments();
+
+        let lit = |digit: u8, segment: u8, c: char| match digit & segment {
+            0 => ' ',
+            _ => c,
+        };
+
+        let top: String = segments
+            .iter()
+            .flat_map(|d| [' ', lit(*d, A, '_'), ' '])
+            .collect();
+
+        let middle: String = segments
+            .iter()
+            .flat_map(|d| [lit(*d, F, '|'), lit(*d, G, '_'), lit(*d, B, '|')])
+            .collect();
+
+        let bottom: String = segments
+            .iter()
+            .flat_map(|d| [lit(*d, E, '|'), lit(*d, D, '_'), lit(*d, C, '|')])
+            .collect();
+
+        writeln!(formatter, "{}", top)?;
+        writeln!(formatter, "{}", middle)?;
+        writeln!(formatter, "{}", bottom)
+    }
+}
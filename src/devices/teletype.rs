@@ -0,0 +1,95 @@
+use alloc::string::{String, ToString};
+
+use super::Device;
+use crate::machines::{Signal, VirtualMachine};
+use crate::observers::{Cycle, CycleObserver};
+
+/// How a teletype prints the values it receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TeletypeMode {
+    /// Each value is printed as a decimal number on its own line
+    #[default]
+    Numbers,
+
+    /// Each value is printed as the character with that code point. Values
+    /// that aren't one print as `�`.
+    Ascii,
+}
+
+/// A printer for programs. Every time the program executes `out`, the
+/// register's value is appended to the transcript, independent of anything on
+/// the screen.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Teletype {
+    mode: TeletypeMode,
+    transcript: String,
+}
+
+impl Teletype {
+    pub fn new(mode: TeletypeMode) -> Self {
+        Teletype {
+            mode,
+            transcript: String::new(),
+        }
+    }
+
+    /// Prints a single value
+    pub fn print(&mut self, value: isize) {
+        match self.mode {
+            TeletypeMode::Numbers => {
+                self.transcript.push_str(&value.to_string());
+                self.transcript.push('\n');
+            }
+            TeletypeMode::Ascii => {
+                let c = u32::try_from(value)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+
+                self.transcript.push(c);
+            }
+        }
+    }
+
+    /// Prints whatever the machine sent out during its last cycle, if
+    /// anything
+    pub fn latch(&mut self, machine: &VirtualMachine) {
+        if let Some(Signal::Out(value)) = machine.signal() {
+            self.print(value);
+        }
+    }
+
+    /// Cycles the machine until it finishes, printing everything it sends out
+    pub fn run(&mut self, machine: &mut VirtualMachine) {
+        while machine.is_executing() {
+            machine.cycle();
+            self.latch(machine);
+        }
+    }
+
+    /// Returns everything printed so far
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+}
+
+/// A single address on the bus. Writing prints a value; there's nothing to
+/// read back.
+impl Device for Teletype {
+    fn read(&mut self, _offset: usize) -> isize {
+        0
+    }
+
+    fn write(&mut self, _offset: usize, value: isize) {
+        self.print(value)
+    }
+}
+
+/// Prints whatever the machine sent out during each cycle, like `latch()`
+impl CycleObserver for Teletype {
+    fn observe(&mut self, cycle: &Cycle) {
+        if let Some(Signal::Out(value)) = cycle.signal {
+            self.print(value);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use core::fmt::{Display, Formatter};
+
+use crate::assembly::AsmError;
+use crate::binary::DecodeError;
+use crate::devices::BusError;
+use crate::instructions::ParseError;
+use crate::linking::LinkError;
+use crate::machines::VmError;
+use crate::screens::{DecodeScreenError, OcrError, OverflowError, ParseScreenError, ScreenError};
+
+/// Everything that can go wrong in the crate, grouped by where it went wrong.
+/// An application embedding the machine can return this from anything built
+/// on it, using `?` on the narrower errors, and still match on the cause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A program in the puzzle's format or the binary one didn't read
+    Parse(ParseError),
+
+    /// The machine couldn't cycle or attach a device
+    Vm(VmError),
+
+    /// A screen couldn't be drawn, read, or decoded
+    Screen(ScreenError),
+
+    /// Hand-written assembly didn't assemble
+    Asm(AsmError),
+
+    /// Assembled objects didn't link
+    Link(LinkError),
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::Parse(error)
+    }
+}
+
+impl From<VmError> for Error {
+    fn from(error: VmError) -> Self {
+        Error::Vm(error)
+    }
+}
+
+impl From<ScreenError> for Error {
+    fn from(error: ScreenError) -> Self {
+        Error::Screen(error)
+    }
+}
+
+impl From<AsmError> for Error {
+    fn from(error: AsmError) -> Self {
+        Error::Asm(error)
+    }
+}
+
+impl From<LinkError> for Error {
+    fn from(error: LinkError) -> Self {
+        Error::Link(error)
+    }
+}
+
+/// The narrower errors convert straight to their group, so `?` works on them
+/// without an intermediate `map_err`
+impl From<DecodeError> for Error {
+    fn from(error: DecodeError) -> Self {
+        Error::Parse(error.into())
+    }
+}
+
+impl From<BusError> for Error {
+    fn from(error: BusError) -> Self {
+        Error::Vm(error.into())
+    }
+}
+
+impl From<OverflowError> for Error {
+    fn from(error: OverflowError) -> Self {
+        Error::Screen(error.into())
+    }
+}
+
+impl From<ParseScreenError> for Error {
+    fn from(error: ParseScreenError) -> Self {
+        Error::Screen(error.into())
+    }
+}
+
+impl From<DecodeScreenError> for Error {
+    fn from(error: DecodeScreenError) -> Self {
+        Error::Screen(error.into())
+    }
+}
+
+impl From<OcrError> for Error {
+    fn from(error: OcrError) -> Self {
+        Error::Screen(error.into())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Parse(error) => write!(formatter, "can't parse: {}", error),
+            Error::Vm(error) => write!(formatter, "{}", error),
+            Error::Screen(error) => write!(formatter, "{}", error),
+            Error::Asm(error) => write!(formatter, "can't assemble: {}", error),
+            Error::Link(error) => write!(formatter, "can't link: {}", error),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Parse(error) => Some(error),
+            Error::Vm(error) => Some(error),
+            Error::Screen(error) => Some(error),
+            Error::Asm(error) => Some(error),
+            Error::Link(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::instructions::Instruction;
+use crate::machines::VirtualMachine;
+use crate::observers::{Cycle, CycleObserver, Fanout};
+use crate::screens::{self, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Narrates every cycle in plain language, for learning how the timing works:
+/// which instruction is running and for how much longer, where the sprite is,
+/// and why the beam's pixel is lit or not. It's an observer, so attach it to
+/// a `Fanout` or use `explain()`. Like a screen, it assumes the puzzle's size
+/// and sprite unless told otherwise.
+#[derive(Debug, Clone)]
+pub struct Explainer {
+    program: Vec<Instruction>,
+    width: usize,
+    height: usize,
+    sprite_width: usize,
+
+    /// The instruction the last cycle belonged to, and how many of its
+    /// cycles have gone by
+    current: Option<(usize, usize)>,
+    previous_register: Option<isize>,
+    lines: Vec<String>,
+}
+
+impl Explainer {
+    /// Explains a run of the given program. It's only told which instruction
+    /// each cycle belongs to, so this is how it knows what they are.
+    pub fn new(program: &[Instruction]) -> Self {
+        Explainer {
+            program: program.to_vec(),
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            sprite_width: 3,
+            current: None,
+            previous_register: None,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Explains the beam on a screen of a different size
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Explains a sprite of a different width
+    pub fn with_sprite_width(mut self, width: usize) -> Self {
+        self.sprite_width = width;
+        self
+    }
+
+    /// Returns a line for every cycle observed so far
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines
+    }
+
+    /// Describes the cycle, keeping track of how far into its instruction it
+    /// is for the next one
+    fn narrate(&mut self, cycle: &Cycle) -> String {
+        let mut line = format!("cycle {}: ", cycle.number);
+
+        match cycle.instruction.and_then(|index| {
+            let instruction = self.program.get(index)?;
+            Some((index, *instruction))
+        }) {
+            Some((index, instruction)) => {
+                let elapsed = match self.current {
+                    Some((current, elapsed)) if current == index => elapsed + 1,
+                    _ => 1,
+                };
+                self.current = Some((index, elapsed));
+
+                self.narrate_instruction(&mut line, instruction, elapsed, cycle.register);
+            }
+            None => line.push_str("nothing is running"),
+        }
+
+        if let Some(signal) = cycle.signal {
+            let _ = write!(line, "; it signals `{}`", signal);
+        }
+
+        self.narrate_beam(&mut line, cycle);
+        self.previous_register = Some(cycle.register);
+
+        line
+    }
+
+    fn narrate_instruction(
+        &self,
+        line: &mut String,
+        instruction: Instruction,
+        elapsed: usize,
+        register: isize,
+    ) {
+        let remaining = instruction.cycles().saturating_sub(elapsed);
+
+        let _ = match (elapsed, remaining) {
+            (1, 0) => write!(line, "{} takes its only cycle", instruction),
+            (1, 1) => write!(line, "{} begins; it needs one more cycle", instruction),
+            (1, _) => write!(
+                line,
+                "{} begins; it needs {} more cycles",
+                instruction, remaining
+            ),
+            (_, 0) => write!(line, "{} finishes", instruction),
+            (_, 1) => write!(line, "{} continues; one more cycle to go", instruction),
+            (_, _) => write!(
+                line,
+                "{} continues; {} more cycles to go",
+                instruction, remaining
+            ),
+        };
+
+        if remaining > 0 {
+            return;
+        }
+
+        let _ = match instruction {
+            Instruction::Addx(number) => write!(
+                line,
+                "; the register becomes {} after this cycle",
+                register.wrapping_add(number)
+            ),
+            Instruction::Ldx(address) => write!(
+                line,
+                "; the register is loaded from address {} after this cycle",
+                address
+            ),
+            Instruction::In => write!(line, "; the register is read from input after this cycle"),
+            Instruction::Custom(..) => write!(line, "; a custom op may change the register"),
+            Instruction::Noop | Instruction::Out | Instruction::Tone(_) | Instruction::Stx(_) => {
+                Ok(())
+            }
+        };
+    }
+
+    fn narrate_beam(&self, line: &mut String, cycle: &Cycle) {
+        let sprite = screens::sprite_columns(cycle.register, self.sprite_width);
+        let moved = self
+            .previous_register
+            .is_some_and(|previous| previous != cycle.register);
+
+        let _ = write!(
+            line,
+            "; sprite {} columns {} to {}",
+            match moved {
+                true => "moves to",
+                false => "stays at",
+            },
+            sprite.start(),
+            sprite.end()
+        );
+
+        let pixels = self.width * self.height;
+        if pixels == 0 {
+            return;
+        }
+
+        let index = (cycle.number - 1) % pixels;
+        let (x, y) = (index % self.width, index / self.width);
+        let column = x as isize;
+
+        let _ = match column {
+            _ if sprite.contains(&column) => write!(
+                line,
+                "; pixel ({},{}) lit because the beam overlaps the sprite",
+                x, y
+            ),
+            _ if column < *sprite.start() => write!(
+                line,
+                "; pixel ({},{}) dark because the beam is left of the sprite",
+                x, y
+            ),
+            _ => write!(
+                line,
+                "; pixel ({},{}) dark because the beam is right of the sprite",
+                x, y
+            ),
+        };
+    }
+}
+
+impl CycleObserver for Explainer {
+    fn observe(&mut self, cycle: &Cycle) {
+        let line = self.narrate(cycle);
+        self.lines.push(line);
+    }
+}
+
+/// Runs the program on a machine with nothing attached and narrates every
+/// cycle, on the puzzle's screen
+pub fn explain(program: &[Instruction]) -> Vec<String> {
+    let mut explainer = Explainer::new(program);
+    let machine = VirtualMachine::new(VecDeque::from(program.to_vec()));
+
+    Fanout::new(machine).with_observer(&mut explainer).run();
+
+    explainer.into_lines()
+}
@@ -0,0 +1,331 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use crate::observers::Cycle;
+use crate::screens::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// A little expression about a cycle, like `x * tick` or `x % 40 == col`,
+/// for conditions and displays that shouldn't need Rust closures: breakpoint
+/// conditions and watches in the debugger, and watches in traces.
+///
+/// Everything is an integer. Comparisons and `&&`, `||`, and `!` make `1`
+/// for true and `0` for false, and take anything but `0` as true. Dividing
+/// by zero makes `0`, and arithmetic wraps rather than overflowing. From
+/// lowest to highest precedence the operators are `||`, `&&`, `==` and `!=`,
+/// `<`, `<=`, `>`, and `>=`, `+` and `-`, then `*`, `/`, and `%`, and the
+/// unary `-` and `!`. Parentheses group.
+///
+/// The names for the cycle are:
+///
+/// - `x`: the register during the cycle
+/// - `tick` (or `cycle`): the cycle's number, from `1`
+/// - `col` and `row`: where the beam is on the specification's screen
+/// - `pc`: the position in the program of the instruction occupying the
+///   cycle, or `-1` if there isn't one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    source: String,
+    tree: Node,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Number(isize),
+    Variable(Variable),
+    Negate(Box<Node>),
+    Not(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    Register,
+    Tick,
+    Column,
+    Row,
+    Pc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+/// The operators that can join two operands, by precedence, lowest first
+const PRECEDENCE: [&[(&str, Operator)]; 6] = [
+    &[("||", Operator::Or)],
+    &[("&&", Operator::And)],
+    &[("==", Operator::Equal), ("!=", Operator::NotEqual)],
+    &[
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ],
+    &[("+", Operator::Add), ("-", Operator::Subtract)],
+    &[
+        ("*", Operator::Multiply),
+        ("/", Operator::Divide),
+        ("%", Operator::Remainder),
+    ],
+];
+
+/// An expression didn't parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpressionError {
+    /// Where the problem is, counting characters from `1`
+    pub column: usize,
+
+    pub message: String,
+}
+
+impl Expression {
+    /// Works the expression out for the cycle
+    pub fn evaluate(&self, cycle: &Cycle) -> isize {
+        self.tree.evaluate(cycle)
+    }
+
+    /// Returns `true` if the expression works out as anything but `0` for the
+    /// cycle
+    pub fn holds(&self, cycle: &Cycle) -> bool {
+        self.evaluate(cycle) != 0
+    }
+}
+
+impl Node {
+    fn evaluate(&self, cycle: &Cycle) -> isize {
+        match self {
+            Node::Number(number) => *number,
+            Node::Variable(variable) => variable.value(cycle),
+            Node::Negate(operand) => operand.evaluate(cycle).wrapping_neg(),
+            Node::Not(operand) => (operand.evaluate(cycle) == 0) as isize,
+            Node::Binary(Operator::Or, left, right) => {
+                (left.evaluate(cycle) != 0 || right.evaluate(cycle) != 0) as isize
+            }
+            Node::Binary(Operator::And, left, right) => {
+                (left.evaluate(cycle) != 0 && right.evaluate(cycle) != 0) as isize
+            }
+            Node::Binary(operator, left, right) => {
+                operator.apply(left.evaluate(cycle), right.evaluate(cycle))
+            }
+        }
+    }
+}
+
+impl Variable {
+    fn named(name: &str) -> Option<Self> {
+        match name {
+            "x" => Some(Variable::Register),
+            "tick" | "cycle" => Some(Variable::Tick),
+            "col" => Some(Variable::Column),
+            "row" => Some(Variable::Row),
+            "pc" => Some(Variable::Pc),
+            _ => None,
+        }
+    }
+
+    fn value(self, cycle: &Cycle) -> isize {
+        let beam = cycle.number.saturating_sub(1);
+
+        match self {
+            Variable::Register => cycle.register,
+            Variable::Tick => cycle.number as isize,
+            Variable::Column => (beam % SCREEN_WIDTH) as isize,
+            Variable::Row => (beam / SCREEN_WIDTH % SCREEN_HEIGHT) as isize,
+            Variable::Pc => cycle.instruction.map_or(-1, |index| index as isize),
+        }
+    }
+}
+
+impl Operator {
+    fn apply(self, left: isize, right: isize) -> isize {
+        match self {
+            Operator::Or => (left != 0 || right != 0) as isize,
+            Operator::And => (left != 0 && right != 0) as isize,
+            Operator::Equal => (left == right) as isize,
+            Operator::NotEqual => (left != right) as isize,
+            Operator::Less => (left < right) as isize,
+            Operator::LessOrEqual => (left <= right) as isize,
+            Operator::Greater => (left > right) as isize,
+            Operator::GreaterOrEqual => (left >= right) as isize,
+            Operator::Add => left.wrapping_add(right),
+            Operator::Subtract => left.wrapping_sub(right),
+            Operator::Multiply => left.wrapping_mul(right),
+            Operator::Divide => left.checked_div(right).unwrap_or(0),
+            Operator::Remainder => left.checked_rem(right).unwrap_or(0),
+        }
+    }
+}
+
+/// Reads an expression by recursive descent, one precedence level at a time
+struct Parser<'a> {
+    source: &'a str,
+
+    /// The byte offset of the next character to read
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn binary(&mut self, level: usize) -> Result<Node, ExpressionError> {
+        let Some(operators) = PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+
+        let mut left = self.binary(level + 1)?;
+
+        'operands: loop {
+            for (symbol, operator) in operators.iter() {
+                if self.eat(symbol) {
+                    let right = self.binary(level + 1)?;
+                    left = Node::Binary(*operator, Box::new(left), Box::new(right));
+                    continue 'operands;
+                }
+            }
+
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.eat("-") {
+            return Ok(Node::Negate(Box::new(self.unary()?)));
+        }
+
+        if self.eat("!") {
+            return Ok(Node::Not(Box::new(self.unary()?)));
+        }
+
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, ExpressionError> {
+        self.skip_space();
+
+        if self.eat("(") {
+            let inner = self.binary(0)?;
+
+            if !self.eat(")") {
+                return Err(self.error("expected `)`"));
+            }
+
+            return Ok(inner);
+        }
+
+        let start = self.position;
+        let word: String = self
+            .rest()
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+
+        if word.is_empty() {
+            return Err(self.error("expected a number, a name, or `(`"));
+        }
+
+        self.position += word.len();
+
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            return word.parse().map(Node::Number).map_err(|_| ExpressionError {
+                column: self.column(start),
+                message: format!("`{}` isn't a number", word),
+            });
+        }
+
+        Variable::named(&word)
+            .map(Node::Variable)
+            .ok_or_else(|| ExpressionError {
+                column: self.column(start),
+                message: format!(
+                    "unknown name `{}` (expected x, tick, cycle, col, row, or pc)",
+                    word
+                ),
+            })
+    }
+
+    /// Skips spaces and then the symbol, if it's next. Longer symbols are
+    /// tried first, so `<` never takes the start of `<=`.
+    fn eat(&mut self, symbol: &str) -> bool {
+        self.skip_space();
+
+        if self.rest().starts_with(symbol) {
+            self.position += symbol.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_space(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn rest(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn column(&self, position: usize) -> usize {
+        self.source[..position].chars().count() + 1
+    }
+
+    fn error(&self, message: &str) -> ExpressionError {
+        ExpressionError {
+            column: self.column(self.position),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ExpressionError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+        };
+
+        let tree = parser.binary(0)?;
+        parser.skip_space();
+
+        if !parser.rest().is_empty() {
+            return Err(parser.error("expected an operator"));
+        }
+
+        Ok(Expression {
+            source: source.trim().to_string(),
+            tree,
+        })
+    }
+}
+
+/// Shows the expression as it was written
+impl Display for Expression {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str(&self.source)
+    }
+}
+
+impl Display for ExpressionError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl Error for ExpressionError {}
@@ -0,0 +1,144 @@
+use std::ffi::{c_char, CStr};
+use std::ptr;
+
+use crate::assembly;
+use crate::machines::{Detached, VirtualMachine};
+use crate::observers::Fanout;
+use crate::screens::Screen;
+
+/// A machine and the screen it draws on, for C to hold through a pointer.
+/// It's opaque on the other side: see `include/legolos.h`.
+pub struct LegolosMachine {
+    machine: VirtualMachine,
+    screen: Screen<Detached>,
+}
+
+/// Assembles the program and returns a machine ready to run it, or null if
+/// the program isn't UTF-8 or doesn't assemble. Free it with
+/// `legolos_machine_free()`.
+///
+/// # Safety
+///
+/// `program` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_new(program: *const c_char) -> *mut LegolosMachine {
+    if program.is_null() {
+        return ptr::null_mut();
+    }
+
+    let Ok(source) = CStr::from_ptr(program).to_str() else {
+        return ptr::null_mut();
+    };
+
+    let Ok(program) = assembly::assemble(source) else {
+        return ptr::null_mut();
+    };
+
+    let machine = LegolosMachine {
+        machine: VirtualMachine::new(program),
+        screen: Screen::new(Detached),
+    };
+
+    Box::into_raw(Box::new(machine))
+}
+
+/// Frees a machine. Null is ignored.
+///
+/// # Safety
+///
+/// `machine` must be null or come from `legolos_machine_new()`, and not have
+/// been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_free(machine: *mut LegolosMachine) {
+    if !machine.is_null() {
+        drop(Box::from_raw(machine));
+    }
+}
+
+/// Runs a single cycle, drawing on the screen. Returns `false` if the program
+/// had already finished.
+///
+/// # Safety
+///
+/// `machine` must be a live machine from `legolos_machine_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_cycle(machine: *mut LegolosMachine) -> bool {
+    let machine = &mut *machine;
+
+    Fanout::new(&mut machine.machine)
+        .with_observer(&mut machine.screen)
+        .cycle()
+        .is_some()
+}
+
+/// Runs the program until it finishes and returns how many cycles that took
+///
+/// # Safety
+///
+/// `machine` must be a live machine from `legolos_machine_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_run(machine: *mut LegolosMachine) -> usize {
+    let machine = &mut *machine;
+
+    Fanout::new(&mut machine.machine)
+        .with_observer(&mut machine.screen)
+        .run()
+}
+
+/// Returns the register's value during the cycle about to run
+///
+/// # Safety
+///
+/// `machine` must be a live machine from `legolos_machine_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_register(machine: *const LegolosMachine) -> isize {
+    (*machine).machine.read_register()
+}
+
+/// Returns the number of the cycle about to run. The first is `1`.
+///
+/// # Safety
+///
+/// `machine` must be a live machine from `legolos_machine_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_ticks(machine: *const LegolosMachine) -> usize {
+    (*machine).machine.get_ticks()
+}
+
+/// Returns `false` once the program has finished
+///
+/// # Safety
+///
+/// `machine` must be a live machine from `legolos_machine_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_is_executing(machine: *const LegolosMachine) -> bool {
+    (*machine).machine.is_executing()
+}
+
+/// Writes the screen drawn so far into the buffer as text, one row per line,
+/// and NUL-terminates it. Like `snprintf()`, it writes at most `capacity`
+/// bytes (NUL included) and returns the length of the whole screen (NUL
+/// excluded), so a return of `capacity` or more means it was cut short. Pass
+/// a null buffer to only ask for the length.
+///
+/// # Safety
+///
+/// `machine` must be a live machine from `legolos_machine_new()`, and
+/// `buffer` null or valid for writing `capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn legolos_machine_screen(
+    machine: *const LegolosMachine,
+    buffer: *mut c_char,
+    capacity: usize,
+) -> usize {
+    let screen = (*machine).screen.to_string();
+
+    if !buffer.is_null() && capacity > 0 {
+        let length = screen.len().min(capacity - 1);
+
+        ptr::copy_nonoverlapping(screen.as_ptr(), buffer.cast(), length);
+        *buffer.add(length) = 0;
+    }
+
+    screen.len()
+}
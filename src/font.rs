@@ -0,0 +1,109 @@
+use alloc::vec::Vec;
+
+use crate::screens::Screen;
+
+/// Every glyph is four pixels wide
+pub const GLYPH_WIDTH: usize = 4;
+
+/// Every glyph is six pixels tall, i.e. as tall as the screen
+pub const GLYPH_HEIGHT: usize = 6;
+
+/// Glyphs are drawn one column apart, so each takes up five columns and the
+/// 40 column screen fits eight of them
+pub const GLYPH_ADVANCE: usize = GLYPH_WIDTH + 1;
+
+/// A capital letter in the 4×6 font the puzzles draw with. Each row is four
+/// bits where the most significant one is the leftmost pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    letter: char,
+    rows: [u8; GLYPH_HEIGHT],
+}
+
+impl Glyph {
+    /// Returns the letter this glyph draws
+    pub fn letter(&self) -> char {
+        self.letter
+    }
+
+    /// Returns the glyph's rows, top to bottom. The most significant of the
+    /// low four bits is the leftmost pixel.
+    pub fn rows(&self) -> [u8; GLYPH_HEIGHT] {
+        self.rows
+    }
+
+    /// Returns `true` if the pixel in column `x` of row `y` is lit
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < GLYPH_WIDTH && y < GLYPH_HEIGHT && self.rows[y] & (0b1000 >> x) != 0
+    }
+}
+
+/// Returns the glyph drawing `letter`, if the font has one. Only capitals are
+/// included and not even all of them: just those known to appear in puzzles.
+/// A space is a blank glyph.
+pub fn glyph(letter: char) -> Option<&'static Glyph> {
+    GLYPHS.iter().find(|glyph| glyph.letter == letter)
+}
+
+/// Returns the glyph whose pixels are exactly `rows`, if any
+pub fn recognize(rows: [u8; GLYPH_HEIGHT]) -> Option<&'static Glyph> {
+    GLYPHS.iter().find(|glyph| glyph.rows == rows)
+}
+
+/// Returns every glyph in the font
+pub fn glyphs() -> &'static [Glyph] {
+    &GLYPHS
+}
+
+/// Draws `text` onto the screen with the top-left corner of its first glyph at
+/// column `left` of row `top`. Glyphs are spaced `GLYPH_ADVANCE` apart and
+/// anything falling off the screen is clipped. Only lit pixels are drawn, so
+/// whatever was on the screen shows through the gaps.
+///
+/// Fails on the first character the font has no glyph for, without drawing
+/// anything.
+pub fn draw_text<M>(
+    screen: &mut Screen<M>,
+    left: usize,
+    top: usize,
+    text: &str,
+) -> Result<(), char> {
+    let glyphs = text
+        .chars()
+        .map(|letter| glyph(letter).ok_or(letter))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (position, glyph) in glyphs.iter().enumerate() {
+        for y in 0..GLYPH_HEIGHT {
+            for x in 0..GLYPH_WIDTH {
+                if glyph.is_lit(x, y) {
+                    screen.set_pixel(left + position * GLYPH_ADVANCE + x, top + y, true);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[rustfmt::skip]
+const GLYPHS: [Glyph; 18] = [
+    Glyph { letter: 'A', rows: [0b0110, 0b1001, 0b1001, 0b1111, 0b1001, 0b1001] },
+    Glyph { letter: 'B', rows: [0b1110, 0b1001, 0b1110, 0b1001, 0b1001, 0b1110] },
+    Glyph { letter: 'C', rows: [0b0110, 0b1001, 0b1000, 0b1000, 0b1001, 0b0110] },
+    Glyph { letter: 'E', rows: [0b1111, 0b1000, 0b1110, 0b1000, 0b1000, 0b1111] },
+    Glyph { letter: 'F', rows: [0b1111, 0b1000, 0b1110, 0b1000, 0b1000, 0b1000] },
+    Glyph { letter: 'G', rows: [0b0110, 0b1001, 0b1000, 0b1011, 0b1001, 0b0111] },
+    Glyph { letter: 'H', rows: [0b1001, 0b1001, 0b1111, 0b1001, 0b1001, 0b1001] },
+    Glyph { letter: 'I', rows: [0b0111, 0b0010, 0b0010, 0b0010, 0b0010, 0b0111] },
+    Glyph { letter: 'J', rows: [0b0011, 0b0001, 0b0001, 0b0001, 0b1001, 0b0110] },
+    Glyph { letter: 'K', rows: [0b1001, 0b1010, 0b1100, 0b1010, 0b1010, 0b1001] },
+    Glyph { letter: 'L', rows: [0b1000, 0b1000, 0b1000, 0b1000, 0b1000, 0b1111] },
+    Glyph { letter: 'O', rows: [0b0110, 0b1001, 0b1001, 0b1001, 0b1001, 0b0110] },
+    Glyph { letter: 'P', rows: [0b1110, 0b1001, 0b1001, 0b1110, 0b1000, 0b1000] },
+    Glyph { letter: 'R', rows: [0b1110, 0b1001, 0b1001, 0b1110, 0b1010, 0b1001] },
+    Glyph { letter: 'S', rows: [0b0111, 0b1000, 0b1000, 0b0110, 0b0001, 0b1110] },
+    Glyph { letter: 'U', rows: [0b1001, 0b1001, 0b1001, 0b1001, 0b1001, 0b0110] },
+    Glyph { letter: 'Z', rows: [0b1111, 0b0001, 0b0010, 0b0100, 0b1000, 0b1111] },
+    Glyph { letter: ' ', rows: [0; GLYPH_HEIGHT] },
+];
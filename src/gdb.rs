@@ -0,0 +1,435 @@
+use std::fmt::Write as _;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, ToSocketAddrs};
+
+use crate::debugger::{Debugger, Stop};
+
+/// The target description sent to the debugger, which names the machine's
+/// registers: the register itself, the position of the instruction about to
+/// run (standing in for a program counter), and the cycle counter
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.legolos.core">
+    <reg name="x" bitsize="64" type="int64" regnum="0"/>
+    <reg name="pc" bitsize="64" type="code_ptr" regnum="1"/>
+    <reg name="cycle" bitsize="64" type="uint64" regnum="2"/>
+  </feature>
+</target>
+"#;
+
+/// How many registers the target description names
+const REGISTERS: usize = 3;
+
+/// The stop reply for a trap, which is how every stop is reported
+const TRAPPED: &str = "S05";
+
+/// A minimal server for GDB's remote serial protocol, so `gdb`, `lldb`, and
+/// front-ends built on them can drive a `Debugger`. It supports reading
+/// registers, stepping a cycle at a time (forwards and backwards),
+/// continuing, and breakpoints on instructions, whose addresses are their
+/// positions in the program.
+///
+/// The machine has no memory to read, so memory requests fail. So do writes
+/// to registers: the debugger replays the program to step backwards, which
+/// only works if nothing but the program changes the machine.
+pub struct GdbStub<S> {
+    stream: S,
+    debugger: Debugger,
+
+    /// Whether packets are still acknowledged. Debuggers usually turn this
+    /// off straight away on a reliable connection like TCP.
+    acknowledging: bool,
+}
+
+/// What came in over the connection
+enum Packet {
+    /// A command, without its framing or checksum
+    Command(String),
+
+    /// The debugger asked to interrupt the running program (e.g. `Ctrl-C`)
+    Interrupt,
+}
+
+impl<S: Read + Write> GdbStub<S> {
+    /// Creates a server talking to a debugger over the given connection
+    pub fn new(stream: S, debugger: Debugger) -> Self {
+        GdbStub {
+            stream,
+            debugger,
+            acknowledging: true,
+        }
+    }
+
+    /// Serves commands until the debugger detaches, kills the program, or
+    /// hangs up, then returns the debugger as it was left
+    pub fn serve(mut self) -> io::Result<Debugger> {
+        loop {
+            let command = match self.read_packet() {
+                Ok(Packet::Command(command)) => command,
+                Ok(Packet::Interrupt) => {
+                    // Nothing runs in the background to interrupt. Say it
+                    // stopped anyway, which is what the debugger waits for.
+                    self.send(TRAPPED)?;
+                    continue;
+                }
+                Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+                Err(error) => return Err(error),
+            };
+
+            match command.as_str() {
+                "D" | "D;1" => {
+                    self.send("OK")?;
+                    break;
+                }
+                "k" | "vKill;1" => break,
+                _ => {
+                    let reply = self.reply(&command);
+                    self.send(&reply)?;
+                }
+            }
+        }
+
+        Ok(self.debugger)
+    }
+
+    /// Returns the reply to a command. An empty reply tells the debugger
+    /// the command isn't supported.
+    fn reply(&mut self, command: &str) -> String {
+        if let Some(annex) = command.strip_prefix("qXfer:features:read:target.xml:") {
+            return match parse_range(annex) {
+                Some((offset, length)) => read_part(TARGET_XML, offset, length),
+                None => "E01".to_string(),
+            };
+        }
+
+        // Whatever the debugger says it supports, acknowledgements can only
+        // be turned off once it asks to
+        if command.starts_with("qSupported") {
+            return "PacketSize=1000;qXfer:features:read+;QStartNoAckMode+;\
+                    ReverseStep+;ReverseContinue+"
+                .to_string();
+        }
+
+        if command == "QStartNoAckMode" {
+            self.acknowledging = false;
+            return "OK".to_string();
+        }
+
+        match command.as_bytes().first() {
+            Some(b'?') => TRAPPED.to_string(),
+            Some(b'g') => (0..REGISTERS).map(|n| self.register(n)).collect(),
+            Some(b'p') => match usize::from_str_radix(&command[1..], 16) {
+                Ok(n) if n < REGISTERS => self.register(n),
+                _ => "E01".to_string(),
+            },
+            Some(b'G' | b'P' | b'm' | b'M' | b'X') => "E01".to_string(),
+            Some(b's') => match self.debugger.step() {
+                Some(_) => TRAPPED.to_string(),
+                None => self.exited(),
+            },
+            Some(b'c') => match self.debugger.resume() {
+                Stop::Breakpoint(_) | Stop::Alarm(_) => TRAPPED.to_string(),
+                Stop::Finished => self.exited(),
+            },
+            Some(b'Z' | b'z') => self.breakpoint(command),
+            Some(b'H') => "OK".to_string(),
+            _ => match command {
+                "bs" => match self.debugger.reverse_step() {
+                    true => TRAPPED.to_string(),
+                    false => "T05replaylog:begin;".to_string(),
+                },
+                "bc" => match self.debugger.reverse_resume() {
+                    Some(_) => TRAPPED.to_string(),
+                    None => "T05replaylog:begin;".to_string(),
+                },
+                "qAttached" => "1".to_string(),
+                "qC" => "QC1".to_string(),
+                "qfThreadInfo" => "m1".to_string(),
+                "qsThreadInfo" => "l".to_string(),
+                _ => String::new(),
+            },
+        }
+    }
+
+    /// Returns the value of register `n` as the protocol expects it: the
+    /// hexadecimal of its little-endian bytes
+    fn register(&self, n: usize) -> String {
+        let machine = self.debugger.machine();
+
+        let value = match n {
+            0 => machine.read_register() as u64,
+            1 => machine
+                .instruction_index()
+                .unwrap_or(self.debugger.program().len()) as u64,
+            _ => machine.get_ticks() as u64,
+        };
+
+        hex(&value.to_le_bytes())
+    }
+
+    /// Sets (`Z`) or clears (`z`) a breakpoint. Only software and hardware
+    /// breakpoints on execution make sense, since there's no memory to watch.
+    fn breakpoint(&mut self, command: &str) -> String {
+        let setting = command.starts_with('Z');
+        let mut fields = command[1..].split(',');
+
+        let kind = fields.next();
+        let address = fields
+            .next()
+            .and_then(|a| usize::from_str_radix(a, 16).ok());
+
+        match (kind, address) {
+            (Some("0" | "1"), Some(index)) => {
+                if self.debugger.breakpoints().contains(&index) != setting {
+                    self.debugger.toggle_breakpoint(index);
+                }
+
+                "OK".to_string()
+            }
+            (Some("0" | "1"), None) => "E01".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Reports that the program finished, with the final register (clamped
+    /// to a byte) for an exit status
+    fn exited(&self) -> String {
+        let status = self.debugger.machine().read_register().clamp(0, 255);
+        format!("W{:02x}", status)
+    }
+
+    /// Reads bytes until a whole packet arrives, acknowledging it (or asking
+    /// for it again if its checksum is off) unless that's been turned off.
+    /// Acknowledgements from the debugger are skipped.
+    fn read_packet(&mut self) -> io::Result<Packet> {
+        loop {
+            match self.read_byte()? {
+                b'$' => (),
+                0x03 => return Ok(Packet::Interrupt),
+                _ => continue,
+            }
+
+            let mut data = Vec::new();
+
+            loop {
+                match self.read_byte()? {
+                    b'#' => break,
+                    byte => data.push(byte),
+                }
+            }
+
+            let checksum = [self.read_byte()?, self.read_byte()?];
+            let expected = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+            if self.acknowledging {
+                if expected != Some(checksum_of(&data)) {
+                    self.stream.write_all(b"-")?;
+                    continue;
+                }
+
+                self.stream.write_all(b"+")?;
+            }
+
+            return Ok(Packet::Command(String::from_utf8_lossy(&data).into_owned()));
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<u8> {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    /// Frames and sends a reply. The debugger's acknowledgement, if any, is
+    /// skipped when reading its next packet.
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", data, checksum_of(data.as_bytes()));
+
+        self.stream.write_all(packet.as_bytes())?;
+        self.stream.flush()
+    }
+}
+
+/// Waits for a single debugger to connect at the address and serves it until
+/// it's done, then returns the debugger as it was left
+pub fn listen(address: impl ToSocketAddrs, debugger: Debugger) -> io::Result<Debugger> {
+    let listener = TcpListener::bind(address)?;
+    let (stream, _) = listener.accept()?;
+
+    stream.set_nodelay(true)?;
+    GdbStub::new(stream, debugger).serve()
+}
+
+/// The sum of the bytes, modulo 256
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{:02x}", byte);
+        hex
+    })
+}
+
+/// Parses the `offset,length` (both in hexadecimal) of a `qXfer` request
+fn parse_range(annex: &str) -> Option<(usize, usize)> {
+    let (offset, length) = annex.split_once(',')?;
+
+    Some((
+        usize::from_str_radix(offset, 16).ok()?,
+        usize::from_str_radix(length, 16).ok()?,
+    ))
+}
+
+/// Returns the requested part of a document for a `qXfer` reply: `l` if it's
+/// the last part, `m` if there's more to come
+fn read_part(document: &str, offset: usize, length: usize) -> String {
+    let start = offset.min(document.len());
+    let end = offset.saturating_add(length).min(document.len());
+
+    let marker = match end == document.len() {
+        true => 'l',
+        false => 'm',
+    };
+
+    format!("{}{}", marker, &document[start..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::instructions::Instruction;
+
+    #[test]
+    fn runs_to_breakpoints_and_reads_registers() {
+        let (replies, debugger) = serve(&[
+            "qSupported:swbreak+",
+            "QStartNoAckMode",
+            "Z0,1,1",
+            "c",
+            "g",
+            "p1",
+            "p3",
+            "m0,4",
+            "vMustReplyEmpty",
+            "bs",
+            "p0",
+            "c",
+            "c",
+            "D",
+        ]);
+
+        assert_eq!(
+            replies,
+            [
+                "+$PacketSize=1000;qXfer:features:read+;QStartNoAckMode+;\
+                 ReverseStep+;ReverseContinue+#",
+                "+$OK#",
+                "$OK#",
+                "$S05#",
+                "$040000000000000001000000000000000300000000000000#",
+                "$0100000000000000#",
+                "$E01#",
+                "$E01#",
+                "$#",
+                "$S05#",
+                "$0100000000000000#",
+                "$S05#",
+                "$W03#",
+                "$OK#",
+            ]
+            .concat()
+        );
+        assert_eq!(debugger.machine().read_register(), 3);
+    }
+
+    #[test]
+    fn asks_again_for_packets_whose_checksums_are_off() {
+        let mut stub = GdbStub::new(
+            Connection {
+                input: b"$?#00$?#3f",
+                output: Rc::new(RefCell::new(Vec::new())),
+            },
+            Debugger::new([Instruction::Noop]),
+        );
+
+        assert!(matches!(stub.read_packet(), Ok(Packet::Command(command)) if command == "?"));
+        assert_eq!(stub.stream.output.borrow().as_slice(), b"-+");
+    }
+
+    #[test]
+    fn sends_the_target_description_in_parts() {
+        let from = |offset, length| read_part(TARGET_XML, offset, length);
+
+        assert_eq!(from(0, 5), "m<?xml");
+        assert_eq!(from(TARGET_XML.len() - 3, 10), "lt>\n");
+        assert_eq!(from(usize::MAX, usize::MAX), "l");
+        assert_eq!(parse_range("1f,400"), Some((0x1f, 0x400)));
+        assert_eq!(parse_range("1f"), None);
+    }
+
+    /// Serves the commands, packaged as packets, to a debugger running a
+    /// short program. Returns the replies, checksums dropped to keep them
+    /// readable, along with the debugger as it was left.
+    fn serve(commands: &[&str]) -> (String, Debugger) {
+        let packets: String = commands
+            .iter()
+            .map(|command| format!("${}#{:02x}", command, checksum_of(command.as_bytes())))
+            .collect();
+        let replies = Rc::new(RefCell::new(Vec::new()));
+
+        let connection = Connection {
+            input: packets.as_bytes(),
+            output: Rc::clone(&replies),
+        };
+        let program = [
+            Instruction::Addx(3),
+            Instruction::Noop,
+            Instruction::Addx(-1),
+        ];
+        let debugger = GdbStub::new(connection, Debugger::new(program))
+            .serve()
+            .unwrap();
+
+        let replies = String::from_utf8(replies.borrow().clone()).unwrap();
+        let mut readable = String::new();
+        let mut rest = replies.as_str();
+
+        while let Some((before, after)) = rest.split_once('#') {
+            readable.push_str(before);
+            readable.push('#');
+            rest = &after[2..];
+        }
+
+        (readable, debugger)
+    }
+
+    /// One end of a connection in memory
+    struct Connection<'a> {
+        input: &'a [u8],
+        output: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Read for Connection<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buffer)
+        }
+    }
+
+    impl Write for Connection<'_> {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            self.output.borrow_mut().write(buffer)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
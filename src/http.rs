@@ -0,0 +1,332 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Read, Take, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use crate::caches::{RenderCache, Rendered};
+use crate::images;
+use crate::programs::Program;
+
+/// The largest program accepted, in bytes. The sample is under 1 KiB.
+const MAX_BODY: usize = 1 << 20;
+
+/// The most a request's line and headers may take up together, in bytes.
+/// Clients send well under 1 KiB.
+const MAX_HEAD: u64 = 16 << 10;
+
+/// How long a client may keep the server waiting for the rest of a request
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How far PNGs are scaled up unless the request says otherwise
+const DEFAULT_SCALE: usize = 4;
+
+/// Listens at the address and answers every request on a thread of its own,
+/// for as long as the process runs. Returns the address it's listening at,
+/// which is useful after binding port `0`.
+///
+/// The body of a request is a program in any format `Program::from_bytes()`
+/// reads. `POST /render` runs it and answers with the screen it draws, as
+/// text, or as a PNG given `?format=png` or `Accept: image/png` (scaled up
+/// with `?scale=`). `POST /timeline` answers with the register during every
+/// cycle, as CSV. Programs it has seen lately are answered from a
+/// `RenderCache` without running them again.
+pub fn serve(address: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?;
+
+    let cache = Arc::new(Mutex::new(RenderCache::default()));
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let cache = Arc::clone(&cache);
+
+            thread::spawn(move || {
+                // A client that hangs up early is its own problem
+                let _ = respond(stream, &cache);
+            });
+        }
+    });
+
+    Ok(address)
+}
+
+/// What's worth knowing about a request
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    accept: String,
+    body: Vec<u8>,
+}
+
+/// An answer to a request
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: &'static str, body: impl Into<String>) -> Self {
+        Response {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.into().into_bytes(),
+        }
+    }
+}
+
+fn respond(mut stream: TcpStream, cache: &Mutex<RenderCache>) -> io::Result<()> {
+    let response = match read_request(&stream)? {
+        Ok(request) => route(&request, cache),
+        Err(response) => response,
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+
+    stream.write_all(&response.body)
+}
+
+/// Reads the request, or the response refusing it
+fn read_request(stream: &TcpStream) -> io::Result<Result<Request, Response>> {
+    // A client that goes quiet would otherwise keep its thread forever
+    stream.set_read_timeout(Some(TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream);
+    let mut head = (&mut reader).take(MAX_HEAD);
+
+    let mut line = String::new();
+    if let Err(response) = read_line(&mut head, &mut line)? {
+        return Ok(Err(response));
+    }
+
+    let mut words = line.split_whitespace();
+    let (Some(method), Some(target)) = (words.next(), words.next()) else {
+        return Ok(Err(Response::text("400 Bad Request", "bad request\n")));
+    };
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        accept: String::new(),
+        body: Vec::new(),
+    };
+
+    let mut length = 0;
+    let mut expecting = false;
+
+    loop {
+        if let Err(response) = read_line(&mut head, &mut line)? {
+            return Ok(Err(response));
+        }
+
+        if line.trim_end().is_empty() {
+            break;
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => length = value.trim().parse().unwrap_or(0),
+            "accept" => request.accept = value.trim().to_string(),
+            "expect" => expecting = value.trim().eq_ignore_ascii_case("100-continue"),
+            _ => (),
+        }
+    }
+
+    if length > MAX_BODY {
+        return Ok(Err(Response::text(
+            "413 Payload Too Large",
+            format!("programs are limited to {} bytes\n", MAX_BODY),
+        )));
+    }
+
+    // curl, for one, waits to be told to go ahead with a bigger program
+    if expecting {
+        let mut stream = stream;
+        stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+    }
+
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+
+    Ok(Ok(request))
+}
+
+/// Reads the next line of a request's line and headers into `line`, or the
+/// response refusing them if they're longer than `MAX_HEAD` or stop short
+fn read_line(head: &mut Take<impl BufRead>, line: &mut String) -> io::Result<Result<(), Response>> {
+    line.clear();
+    head.read_line(line)?;
+
+    Ok(match (line.ends_with('\n'), head.limit()) {
+        (true, _) => Ok(()),
+        (false, 0) => Err(Response::text(
+            "431 Request Header Fields Too Large",
+            format!("headers are limited to {} bytes\n", MAX_HEAD),
+        )),
+        (false, _) => Err(Response::text("400 Bad Request", "bad request\n")),
+    })
+}
+
+fn route(request: &Request, cache: &Mutex<RenderCache>) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/render") => with_program(request, cache, render),
+        ("POST", "/timeline") => with_program(request, cache, timeline),
+        (_, "/render" | "/timeline") => {
+            Response::text("405 Method Not Allowed", "POST a program\n")
+        }
+        _ => Response::text("404 Not Found", "not found\n"),
+    }
+}
+
+/// Reads the request's program and answers with whatever `answer` makes of
+/// it, or says why it didn't read
+fn with_program(
+    request: &Request,
+    cache: &Mutex<RenderCache>,
+    answer: fn(&Request, &Rendered) -> Response,
+) -> Response {
+    let program = match Program::from_bytes(&request.body) {
+        Ok(program) => program,
+        Err(error) => return Response::text("400 Bad Request", format!("{}\n", error)),
+    };
+
+    // The cache isn't locked while the program runs, so a long one doesn't
+    // hold up every other request. Two requests for the same new program
+    // both run it, and the second's is kept.
+    let cached = lock(cache).get(&program);
+
+    let rendered = cached.unwrap_or_else(|| {
+        let rendered = Arc::new(Rendered::new(&program));
+        lock(cache).insert(&program, Arc::clone(&rendered));
+        rendered
+    });
+
+    answer(request, &rendered)
+}
+
+fn lock(cache: &Mutex<RenderCache>) -> MutexGuard<'_, RenderCache> {
+    // A thread that panicked left the cache no worse than it found it
+    cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn render(request: &Request, rendered: &Rendered) -> Response {
+    let screen = &rendered.screen;
+
+    let png =
+        parameter(&request.query, "format") == Some("png") || request.accept.contains("image/png");
+
+    match png {
+        true => {
+            let scale = parameter(&request.query, "scale")
+                .and_then(|scale| scale.parse().ok())
+                .filter(|scale| (1..=64).contains(scale))
+                .unwrap_or(DEFAULT_SCALE);
+
+            Response {
+                status: "200 OK",
+                content_type: "image/png",
+                body: images::png(screen, scale),
+            }
+        }
+        false => Response::text("200 OK", screen.to_string()),
+    }
+}
+
+fn timeline(_request: &Request, rendered: &Rendered) -> Response {
+    let mut csv = String::from("cycle,register\n");
+
+    for (index, register) in rendered.timeline.iter().enumerate() {
+        let _ = writeln!(csv, "{},{}", index + 1, register);
+    }
+
+    Response {
+        status: "200 OK",
+        content_type: "text/csv",
+        body: csv.into_bytes(),
+    }
+}
+
+/// Returns the value of a parameter in a query string like `a=1&b=2`
+fn parameter<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_posted_programs() {
+        let address = serve("127.0.0.1:0").unwrap();
+        let program = "noop\n".repeat(240);
+
+        let response = request(
+            address,
+            &format!(
+                "POST /timeline HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+                program.len(),
+                program
+            ),
+        );
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+        assert!(response.contains("\r\n\r\ncycle,register\n1,1\n2,1\n"));
+        assert!(response.ends_with("\n240,1\n"));
+
+        let response = request(address, "POST /render HTTP/1.1\r\n");
+        assert!(response.starts_with("HTTP/1.1 400 "), "{}", response);
+    }
+
+    #[test]
+    fn heads_are_only_read_so_far() {
+        let long = "a".repeat(MAX_HEAD as usize);
+
+        for head in [
+            format!("POST /render?{} HTTP/1.1\r\n", long),
+            format!("POST /render HTTP/1.1\r\nX-Padding: a\r\n{}", long),
+        ] {
+            let mut head = head.as_bytes().take(MAX_HEAD);
+            let mut line = String::new();
+
+            let refusal = loop {
+                if let Err(response) = read_line(&mut head, &mut line).unwrap() {
+                    break response;
+                }
+            };
+
+            assert_eq!(refusal.status, "431 Request Header Fields Too Large");
+        }
+    }
+
+    /// Sends the request, then returns the whole response to it
+    fn request(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+}
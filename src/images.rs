@@ -0,0 +1,180 @@
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::screens::Screen;
+
+// The CRT's glow and curvature need floating-point functions from `std`
+#[cfg(feature = "std")]
+mod crt;
+mod gif;
+
+#[cfg(feature = "std")]
+pub use crt::Crt;
+pub use gif::gif;
+
+/// Anything that can be exported as an image: a grid of pixels, each with an
+/// intensity from `0` (dark) to `255` (fully lit)
+pub trait Raster {
+    fn width(&self) -> usize;
+
+    fn height(&self) -> usize;
+
+    /// Returns the intensity of the pixel in column `x` of row `y`
+    fn intensity(&self, x: usize, y: usize) -> u8;
+}
+
+impl<M> Raster for Screen<M> {
+    fn width(&self) -> usize {
+        Screen::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Screen::height(self)
+    }
+
+    fn intensity(&self, x: usize, y: usize) -> u8 {
+        match self.is_lit(x, y) {
+            true => u8::MAX,
+            false => 0,
+        }
+    }
+}
+
+/// Encodes the raster as a grayscale PNG where every pixel becomes a
+/// `scale`×`scale` square. Lit pixels are white on black, like the CRT.
+///
+/// It's a minimal encoder: the image data is stored rather than compressed,
+/// which is plenty for screens this small.
+pub fn png(raster: &impl Raster, scale: usize) -> Vec<u8> {
+    let scale = scale.max(1);
+    let width = raster.width() * scale;
+    let height = raster.height() * scale;
+
+    // Every scanline starts with its filter type, which is always "none"
+    let mut scanlines = Vec::with_capacity((width + 1) * height);
+
+    for y in 0..height {
+        scanlines.push(0);
+        scanlines.extend((0..width).map(|x| raster.intensity(x / scale, y / scale)));
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+
+    // Eight bits per pixel, grayscale, and the only compression, filter, and
+    // interlace methods there are (or none of them)
+    header.extend([8, 0, 0, 0, 0]);
+
+    let mut image = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut image, b"IHDR", &header);
+    write_chunk(&mut image, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut image, b"IEND", &[]);
+
+    image
+}
+
+/// Encodes the raster as an SVG where every pixel is a `scale`×`scale`
+/// square. Lit pixels are white on a black background, like the CRT.
+pub fn svg(raster: &impl Raster, scale: usize) -> String {
+    let scale = scale.max(1);
+    let width = raster.width() * scale;
+    let height = raster.height() * scale;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    );
+
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"black\"/>\n",
+        width, height
+    ));
+
+    for y in 0..raster.height() {
+        for x in 0..raster.width() {
+            let intensity = raster.intensity(x, y);
+
+            if intensity == 0 {
+                continue;
+            }
+
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"rgb({i},{i},{i})\"/>\n",
+                x * scale,
+                y * scale,
+                s = scale,
+                i = intensity
+            ));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Appends a PNG chunk: its length, type, data, and a CRC of the latter two
+fn write_chunk(image: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    image.extend((data.len() as u32).to_be_bytes());
+    image.extend(kind);
+    image.extend(data);
+
+    let checksum = crc32(kind.iter().chain(data));
+    image.extend(checksum.to_be_bytes());
+}
+
+/// Wraps the data in a zlib stream without compressing it, using deflate's
+/// "stored" blocks of at most 65535 bytes each
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    // The smallest window and no preset dictionary, which makes the header's
+    // check bits work out
+    let mut stream = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+
+    // Even nothing at all needs one (empty) final block
+    if blocks.peek().is_none() {
+        stream.extend([1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let length = block.len() as u16;
+
+        stream.push(last as u8);
+        stream.extend(length.to_le_bytes());
+        stream.extend((!length).to_le_bytes());
+        stream.extend(block);
+    }
+
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+/// The CRC-32 used by PNG (and zip, and many others)
+pub(crate) fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = u32::MAX;
+
+    for byte in bytes {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+/// The checksum ending a zlib stream
+fn adler32(bytes: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+
+    let (a, b) = bytes.iter().fold((1, 0), |(a, b), byte| {
+        let a = (a + *byte as u32) % MODULUS;
+        (a, (b + a) % MODULUS)
+    });
+
+    (b << 16) | a
+}
@@ -0,0 +1,136 @@
+use super::Raster;
+
+/// A raster as it would look on an old CRT. It scales up another raster so
+/// every pixel covers a `scale`×`scale` block, then optionally:
+///
+/// - darkens the gap between each row of pixels, like a CRT's scanlines,
+/// - lets lit pixels glow onto their neighbours, like phosphor bloom, and
+/// - bows the picture out in the middle, like the curved glass of the tube.
+///
+/// It's a `Raster` itself, so any export works with it. Export it at a scale
+/// of `1`, since it's already scaled up.
+pub struct Crt<'a, R> {
+    raster: &'a R,
+    scale: usize,
+    scanlines: bool,
+    bloom: bool,
+    curvature: f64,
+}
+
+impl<'a, R: Raster> Crt<'a, R> {
+    /// Scales up the raster without any effects yet
+    pub fn new(raster: &'a R, scale: usize) -> Self {
+        Crt {
+            raster,
+            scale: scale.max(1),
+            scanlines: false,
+            bloom: false,
+            curvature: 0.0,
+        }
+    }
+
+    /// Sets whether scanlines are drawn. The last row of every pixel is
+    /// drawn at half intensity, so at a scale of `1` every row is.
+    pub fn with_scanlines(mut self, enabled: bool) -> Self {
+        self.scanlines = enabled;
+        self
+    }
+
+    /// Sets whether lit pixels bloom onto their neighbours
+    pub fn with_bloom(mut self, enabled: bool) -> Self {
+        self.bloom = enabled;
+        self
+    }
+
+    /// Sets how much the picture bows out. `0` is flat and around `0.1`
+    /// looks like a typical tube. The corners curl away into black.
+    pub fn with_curvature(mut self, amount: f64) -> Self {
+        self.curvature = amount.max(0.0);
+        self
+    }
+
+    /// Works out which point of the flat picture appears at the centre of
+    /// output pixel (`x`, `y`), in output pixels, or `None` if it's past the
+    /// edge of the tube
+    fn source_point(&self, x: usize, y: usize) -> Option<(f64, f64)> {
+        let width = self.width() as f64;
+        let height = self.height() as f64;
+
+        // Map to -1..1 with the origin in the middle of the picture
+        let u = (x as f64 + 0.5) / width * 2.0 - 1.0;
+        let v = (y as f64 + 0.5) / height * 2.0 - 1.0;
+
+        let u = u * (1.0 + self.curvature * v * v);
+        let v = v * (1.0 + self.curvature * u * u);
+
+        if u.abs() > 1.0 || v.abs() > 1.0 {
+            return None;
+        }
+
+        Some(((u + 1.0) / 2.0 * width, (v + 1.0) / 2.0 * height))
+    }
+
+    /// Sums the glow reaching the point from the pixels around the one it's
+    /// in. The glow fades with the distance from each pixel's edge.
+    fn glow(&self, x: f64, y: f64, column: usize, row: usize) -> f64 {
+        let scale = self.scale as f64;
+        let mut glow = 0.0;
+
+        for dy in -1..=1isize {
+            for dx in -1..=1isize {
+                let (Some(nx), Some(ny)) =
+                    (column.checked_add_signed(dx), row.checked_add_signed(dy))
+                else {
+                    continue;
+                };
+
+                if (dx, dy) == (0, 0) || nx >= self.raster.width() || ny >= self.raster.height() {
+                    continue;
+                }
+
+                // How far the point is from the neighbour's block, in pixels
+                // of the original raster
+                let left = nx as f64 * scale;
+                let top = ny as f64 * scale;
+                let distance_x = (left - x).max(x - (left + scale)).max(0.0) / scale;
+                let distance_y = (top - y).max(y - (top + scale)).max(0.0) / scale;
+                let distance = distance_x * distance_x + distance_y * distance_y;
+
+                glow += self.raster.intensity(nx, ny) as f64 * 0.35 * (-4.0 * distance).exp();
+            }
+        }
+
+        glow
+    }
+}
+
+impl<R: Raster> Raster for Crt<'_, R> {
+    fn width(&self) -> usize {
+        self.raster.width() * self.scale
+    }
+
+    fn height(&self) -> usize {
+        self.raster.height() * self.scale
+    }
+
+    fn intensity(&self, x: usize, y: usize) -> u8 {
+        let Some((source_x, source_y)) = self.source_point(x, y) else {
+            return 0;
+        };
+
+        let column = (source_x as usize / self.scale).min(self.raster.width().saturating_sub(1));
+        let row = (source_y as usize / self.scale).min(self.raster.height().saturating_sub(1));
+
+        let mut intensity = self.raster.intensity(column, row) as f64;
+
+        if self.bloom {
+            intensity += self.glow(source_x, source_y, column, row);
+        }
+
+        if self.scanlines && source_y as usize % self.scale == self.scale - 1 {
+            intensity /= 2.0;
+        }
+
+        intensity.round().min(u8::MAX as f64) as u8
+    }
+}
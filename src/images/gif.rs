@@ -0,0 +1,104 @@
+use alloc::vec::Vec;
+
+use super::Raster;
+
+/// Encodes the raster as a grayscale GIF where every pixel becomes a
+/// `scale`×`scale` square. Lit pixels are white on black, like the CRT.
+///
+/// Like `png()`, it's a minimal encoder. GIF has to be LZW compressed, but
+/// resetting the dictionary before it ever grows keeps every code the same
+/// size, so each pixel simply becomes a code of its own.
+pub fn gif(raster: &impl Raster, scale: usize) -> Vec<u8> {
+    let scale = scale.max(1);
+    let width = (raster.width() * scale) as u16;
+    let height = (raster.height() * scale) as u16;
+
+    let mut image = b"GIF89a".to_vec();
+
+    // The logical screen: its size, a global palette of 256 colors, and no
+    // background color or aspect ratio to speak of
+    image.extend(width.to_le_bytes());
+    image.extend(height.to_le_bytes());
+    image.extend([0xf7, 0, 0]);
+
+    // Every shade of gray, in order, so a pixel's intensity is its color
+    for shade in 0..=u8::MAX {
+        image.extend([shade, shade, shade]);
+    }
+
+    // A single image covering the whole screen
+    image.push(b',');
+    image.extend([0, 0, 0, 0]);
+    image.extend(width.to_le_bytes());
+    image.extend(height.to_le_bytes());
+    image.push(0);
+
+    let pixels = (0..height as usize)
+        .flat_map(|y| (0..width as usize).map(move |x| raster.intensity(x / scale, y / scale)));
+
+    image.push(MINIMUM_CODE_SIZE);
+
+    for block in lzw(pixels).chunks(u8::MAX as usize) {
+        image.push(block.len() as u8);
+        image.extend(block);
+    }
+
+    image.push(0);
+    image.push(b';');
+    image
+}
+
+/// Eight bits for the colors, making codes nine bits with the two control
+/// codes after them
+const MINIMUM_CODE_SIZE: u8 = 8;
+
+const CLEAR: u16 = 1 << MINIMUM_CODE_SIZE;
+const END: u16 = CLEAR + 1;
+
+/// The decoder adds a dictionary entry for every code after the first. Codes
+/// would grow to ten bits once 256 were added, so clear it well before then.
+const CODES_PER_CLEAR: usize = 250;
+
+/// Writes the pixels as nine bit codes, packed least significant bit first
+fn lzw(pixels: impl Iterator<Item = u8>) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+
+    for (index, pixel) in pixels.enumerate() {
+        if index % CODES_PER_CLEAR == 0 {
+            bits.write(CLEAR);
+        }
+
+        bits.write(pixel as u16);
+    }
+
+    bits.write(END);
+    bits.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    length: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16) {
+        self.buffer |= (code as u32) << self.length;
+        self.length += MINIMUM_CODE_SIZE as u32 + 1;
+
+        while self.length >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.length -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.length > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+
+        self.bytes
+    }
+}
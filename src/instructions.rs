@@ -1,43 +1,309 @@
-use std::collections::VecDeque;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use core::char::REPLACEMENT_CHARACTER;
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use crate::binary::DecodeError;
 
 /// The problem's input is well formatted. Every line contains one instruction.
+///
+/// # Panics
+///
+/// If a line isn't an instruction. See `try_parse_instructions()` for input
+/// that might not be well formatted.
 pub fn parse_instructions(input: &str) -> VecDeque<Instruction> {
+    try_parse_instructions(input).unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Parses the problem's input like `parse_instructions()`, but reports the
+/// first line that isn't an instruction rather than panicking
+pub fn try_parse_instructions(input: &str) -> Result<VecDeque<Instruction>, ParseError> {
     let mut instructions = VecDeque::new();
+    let mut offset = 0;
 
-    for line in input.split('\n') {
-        instructions.push_back(Instruction::from(line));
+    for (index, line) in input.split('\n').enumerate() {
+        let instruction = parse_instruction(line).ok_or_else(|| ParseError::Instruction {
+            line: index + 1,
+            offset,
+            text: line.to_string(),
+        })?;
+
+        instructions.push_back(instruction);
+        offset += line.len() + 1;
     }
 
-    instructions
+    Ok(instructions)
 }
 
-/// This machine has a myriad of options: add with one operand or do nothing.
+/// The UTF-8 byte order mark some editors start files with
+pub(crate) const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Parses the problem's input from a file of unknown origin. It's decoded
+/// leniently: a UTF-8 byte order mark is skipped, lines may end in `\r\n` as
+/// well as `\n`, the last line may or may not end at all, and bytes that
+/// aren't UTF-8 are replaced rather than rejected. Lines that still aren't
+/// instructions are reported by number and by their byte offset into
+/// `bytes`, so they can be found even if they didn't decode.
+pub fn parse_instructions_from_bytes(bytes: &[u8]) -> Result<VecDeque<Instruction>, ParseError> {
+    let mut instructions = VecDeque::new();
+
+    let body = bytes.strip_prefix(BOM).unwrap_or(bytes);
+    let mut offset = bytes.len() - body.len();
+    let body = body.strip_suffix(b"\n").unwrap_or(body);
+
+    if body.is_empty() {
+        return Ok(instructions);
+    }
+
+    for (index, line) in body.split(|&byte| byte == b'\n').enumerate() {
+        let code = line.strip_suffix(b"\r").unwrap_or(line);
+        let text = String::from_utf8_lossy(code);
+
+        let instruction = parse_instruction(&text).ok_or_else(|| ParseError::Instruction {
+            line: index + 1,
+            offset,
+            text: text.to_string(),
+        })?;
+
+        instructions.push_back(instruction);
+        offset += line.len() + 1;
+    }
+
+    Ok(instructions)
+}
+
+/// This machine has a myriad of options: add with one operand, do nothing, or
+/// send the register to whatever is listening (e.g. a teletype or, as the
+/// frequency of a tone lasting the operand's number of cycles, a speaker). It
+/// can also load the register from, or store it to, an address on the
+/// machine's bus, or load it with the next key pressed on a keyboard.
+/// Anything else is a custom instruction, identified by its opcode and given
+/// meaning by whatever `CustomOp` the machine has for it.
 ///
 /// NB this is implicitly coupled to the machine's implementation of scheduling.
-/// A `Noop` takes a single CPU cycle to complete, but `Addx` takes two. Neither
-/// is captured here.
-#[derive(Debug, Clone, Copy)]
+/// `Addx` takes two CPU cycles to complete and everything else takes one. The
+/// machine doesn't consult `cycles()`, so the two must be kept in step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Instruction {
     Noop,
     Addx(isize),
+    Out,
+    Tone(usize),
+    Ldx(usize),
+    Stx(usize),
+    In,
+    Custom(u8, isize),
+}
+
+impl Instruction {
+    /// Returns the number of CPU cycles the instruction takes to complete
+    pub const fn cycles(&self) -> usize {
+        match self {
+            Instruction::Noop => 1,
+            Instruction::Addx(_) => 2,
+            Instruction::Out => 1,
+            Instruction::Tone(_) => 1,
+            Instruction::Ldx(_) => 1,
+            Instruction::Stx(_) => 1,
+            Instruction::In => 1,
+            Instruction::Custom(..) => 1,
+        }
+    }
+
+    /// Returns the name the instruction goes by in a program, without its
+    /// operand
+    pub const fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Noop => "noop",
+            Instruction::Addx(_) => "addx",
+            Instruction::Out => "out",
+            Instruction::Tone(_) => "tone",
+            Instruction::Ldx(_) => "ldx",
+            Instruction::Stx(_) => "stx",
+            Instruction::In => "in",
+            Instruction::Custom(..) => "op",
+        }
+    }
+}
+
+/// Parses a single line of the problem's input, the way `From<&str>` always
+/// has, or returns `None` if it isn't an instruction
+fn parse_instruction(s: &str) -> Option<Instruction> {
+    // The noop instruction is the simplest. Parse it first without bother.
+    if s.starts_with("noop") {
+        return Some(Instruction::Noop);
+    }
+
+    // So are output and input, which have no operand either. They're the
+    // whole line, though, so e.g. `inc 5` isn't taken for `in`.
+    match s.trim() {
+        "out" => return Some(Instruction::Out),
+        "in" => return Some(Instruction::In),
+        _ => (),
+    }
+
+    // Tones start with `tone` followed by a space followed by the duration
+    // (operand), just like adding below
+    if let Some(duration) = s.strip_prefix("tone ") {
+        return Some(Instruction::Tone(duration.parse().ok()?));
+    }
+
+    // Loads and stores are the same again, with an address for an operand
+    if let Some(address) = s.strip_prefix("ldx ") {
+        return Some(Instruction::Ldx(address.parse().ok()?));
+    }
+
+    if let Some(address) = s.strip_prefix("stx ") {
+        return Some(Instruction::Stx(address.parse().ok()?));
+    }
+
+    // Custom instructions put their opcode straight after `op`, e.g. `op3 7`
+    if let Some(rest) = s.strip_prefix("op") {
+        let (opcode, operand) = rest.split_once(' ')?;
+        return Some(Instruction::Custom(
+            opcode.parse().ok()?,
+            operand.parse().ok()?,
+        ));
+    }
+
+    // Add instructions always start with `addx` followed by a space
+    // followed by the value (operand). Skip past that index and ignore the
+    // first portion.
+    if s.starts_with("addx ") {
+        let number = s.get(5..)?.parse::<isize>().ok()?;
+        return Some(Instruction::Addx(number));
+    }
+
+    None
 }
 
+/// Parses a single line of the problem's input
+///
+/// # Panics
+///
+/// If the line isn't an instruction. Use `str::parse()` for lines that might
+/// not be.
 impl From<&str> for Instruction {
     fn from(s: &str) -> Self {
-        // The noop instruction is the simplest. Parse it first without bother.
-        if s.starts_with("noop") {
-            return Instruction::Noop;
+        s.parse().unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+/// Parses a single line of the problem's input, which is always line `1` in
+/// the error
+impl FromStr for Instruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_instruction(s).ok_or_else(|| ParseError::Instruction {
+            line: 1,
+            offset: 0,
+            text: s.to_string(),
+        })
+    }
+}
+
+/// Writes the instruction the way it appears in a program, so a displayed
+/// instruction parses back into the same one
+impl Display for Instruction {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Instruction::Noop => write!(formatter, "noop"),
+            Instruction::Addx(number) => write!(formatter, "addx {}", number),
+            Instruction::Out => write!(formatter, "out"),
+            Instruction::Tone(duration) => write!(formatter, "tone {}", duration),
+            Instruction::Ldx(address) => write!(formatter, "ldx {}", address),
+            Instruction::Stx(address) => write!(formatter, "stx {}", address),
+            Instruction::In => write!(formatter, "in"),
+            Instruction::Custom(opcode, operand) => write!(formatter, "op{} {}", opcode, operand),
+        }
+    }
+}
+
+/// Why a program in one of the machine's own formats couldn't be read. For
+/// hand-written assembly, see `AsmError`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A line of the problem's input wasn't an instruction. `offset` is where
+    /// the line starts, in bytes, and `text` is the line as it decoded.
+    Instruction {
+        line: usize,
+        offset: usize,
+        text: String,
+    },
+
+    /// The binary format didn't decode
+    Binary(DecodeError),
+}
+
+impl From<DecodeError> for ParseError {
+    fn from(error: DecodeError) -> Self {
+        ParseError::Binary(error)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            // The text alone won't find bytes that didn't decode
+            ParseError::Instruction { line, offset, text }
+                if text.contains(REPLACEMENT_CHARACTER) =>
+            {
+                write!(
+                    formatter,
+                    "line {} (byte {}): `{}` isn't valid UTF-8 or an instruction",
+                    line, offset, text
+                )
+            }
+            ParseError::Instruction { line, text, .. } => {
+                write!(formatter, "line {}: `{}` isn't an instruction", line, text)
+            }
+            ParseError::Binary(error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Instruction { .. } => None,
+            ParseError::Binary(error) => Some(error),
         }
+    }
+}
 
-        // Add instructions always start with `addx` followed by a space
-        // followed by the value (operand). Split at that index and ignore the
-        // first portion.
-        let (_addx, number) = s.split_at(5);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        let number = number
-            .parse::<isize>()
-            .unwrap_or_else(|_| panic!("Can't parse isize from {}", number));
+    #[test]
+    fn tones_need_a_space() {
+        assert_eq!("tone 5".parse(), Ok(Instruction::Tone(5)));
 
-        Instruction::Addx(number)
+        for line in ["tone_5", "tone+5", "tones5", "tone5"] {
+            assert!(line.parse::<Instruction>().is_err(), "{}", line);
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn loads_and_stores_need_a_space() {
+        assert_eq!("ldx 5".parse(), Ok(Instruction::Ldx(5)));
+        assert_eq!("stx 5".parse(), Ok(Instruction::Stx(5)));
+
+        for line in ["ldx_5", "ldx+5", "stx_5", "ldx5"] {
+            assert!(line.parse::<Instruction>().is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn in_and_out_are_whole_lines() {
+        assert_eq!("in".parse(), Ok(Instruction::In));
+        assert_eq!("out".parse(), Ok(Instruction::Out));
+
+        for line in ["outside", "inc 5", "int", "in 7"] {
+            assert!(line.parse::<Instruction>().is_err(), "{}", line);
+        }
+    }
+}
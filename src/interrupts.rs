@@ -0,0 +1,166 @@
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use crate::instructions::Instruction;
+
+/// A wire a device pulls to ask for the machine's attention. Clones are the
+/// same wire, so the controller keeps one and devices get the others, e.g.
+/// `Dma::with_interrupt()`.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptLine(Rc<Cell<bool>>);
+
+impl InterruptLine {
+    /// Asks for the line's handler to run. It stays raised until the machine
+    /// takes it.
+    pub fn raise(&self) {
+        self.0.set(true);
+    }
+
+    pub fn is_raised(&self) -> bool {
+        self.0.get()
+    }
+
+    /// Gives up asking, if the machine hasn't taken it yet
+    pub fn lower(&self) {
+        self.0.set(false);
+    }
+}
+
+/// Decides which interrupt the machine services next. Every line has a
+/// handler, a short program of its own, and a priority: higher numbers are
+/// more urgent.
+///
+/// Once the instruction occupying a cycle has finished, a machine with a
+/// raised line whose priority is higher than that of whatever it's running
+/// sets it aside and runs the line's handler instead. The program itself is
+/// less urgent than any line. When the handler finishes, the machine picks up
+/// where it left off, taking no cycle to do so. A handler can be interrupted
+/// in turn by a line more urgent than its own, so handlers nest; a line just
+/// as urgent waits for it to finish. Masked lines wait too, until unmasked.
+///
+/// The register isn't saved: handlers see, and leave, whatever is in it,
+/// which is how they hand values to the program.
+#[derive(Debug, Clone, Default)]
+pub struct InterruptController {
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    wire: InterruptLine,
+    priority: u8,
+    masked: bool,
+    handler: Arc<[Instruction]>,
+}
+
+/// An interrupt the machine is servicing, and what it set aside to do so
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Servicing {
+    pub(crate) line: usize,
+    pub(crate) priority: u8,
+
+    /// The program or handler that was interrupted, and the position of its
+    /// next instruction
+    pub(crate) program: Arc<[Instruction]>,
+    pub(crate) scheduled: usize,
+}
+
+impl InterruptController {
+    pub fn new() -> Self {
+        InterruptController::default()
+    }
+
+    /// Adds a line with the given priority and handler, unmasked, and returns
+    /// its number. Lines are numbered from `0`, in the order they're added.
+    pub fn add_line(&mut self, priority: u8, handler: impl Into<Arc<[Instruction]>>) -> usize {
+        self.lines.push(Line {
+            wire: InterruptLine::default(),
+            priority,
+            masked: false,
+            handler: handler.into(),
+        });
+
+        self.lines.len() - 1
+    }
+
+    /// Returns the line's wire, to hand to a device that raises it
+    pub fn line(&self, line: usize) -> Option<InterruptLine> {
+        self.lines.get(line).map(|line| line.wire.clone())
+    }
+
+    /// Returns the number of lines
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Raises the line, as if a device had. Lines that don't exist are
+    /// ignored.
+    pub fn raise(&self, line: usize) {
+        if let Some(line) = self.lines.get(line) {
+            line.wire.raise();
+        }
+    }
+
+    /// Lowers the line, if the machine hasn't taken it yet
+    pub fn lower(&self, line: usize) {
+        if let Some(line) = self.lines.get(line) {
+            line.wire.lower();
+        }
+    }
+
+    /// Holds the line's interrupts back until it's unmasked
+    pub fn mask(&mut self, line: usize) {
+        if let Some(line) = self.lines.get_mut(line) {
+            line.masked = true;
+        }
+    }
+
+    pub fn unmask(&mut self, line: usize) {
+        if let Some(line) = self.lines.get_mut(line) {
+            line.masked = false;
+        }
+    }
+
+    pub fn is_masked(&self, line: usize) -> bool {
+        self.lines.get(line).is_some_and(|line| line.masked)
+    }
+
+    /// Returns the line's handler, if there is such a line
+    pub fn handler(&self, line: usize) -> Option<&[Instruction]> {
+        self.lines.get(line).map(|line| &*line.handler)
+    }
+
+    /// Returns the line's priority, if there is such a line
+    pub fn priority(&self, line: usize) -> Option<u8> {
+        self.lines.get(line).map(|line| line.priority)
+    }
+
+    /// Returns the numbers of the lines that are raised, masked or not
+    pub fn pending(&self) -> Vec<usize> {
+        (0..self.lines.len())
+            .filter(|line| self.lines[*line].wire.is_raised())
+            .collect()
+    }
+
+    /// Lowers and returns the most urgent raised, unmasked line more urgent
+    /// than `running` (`None` for the program), with its priority and
+    /// handler. Of equally urgent lines, the lowest numbered goes first.
+    pub(crate) fn take(&self, running: Option<u8>) -> Option<(usize, u8, Arc<[Instruction]>)> {
+        let (number, line) = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.wire.is_raised() && !line.masked)
+            .filter(|(_, line)| running.is_none_or(|running| line.priority > running))
+            .min_by_key(|(number, line)| (core::cmp::Reverse(line.priority), *number))?;
+
+        line.wire.lower();
+        Some((number, line.priority, Arc::clone(&line.handler)))
+    }
+}
@@ -0,0 +1,150 @@
+use crate::binary;
+use crate::instructions::Instruction;
+
+/// Everything there is to know about one of the machine's instructions
+/// without running it, for editors, documentation, and anything else that
+/// wants to complete or check a program as it's written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opcode {
+    /// The name the instruction goes by in a program. Custom instructions
+    /// are written with their own opcode straight after it, e.g. `op3`.
+    pub mnemonic: &'static str,
+
+    /// The byte the instruction starts with in the binary format
+    pub code: u8,
+
+    /// What the instruction's operand is, if it takes one
+    pub operand: Option<Operand>,
+
+    /// How many CPU cycles the instruction takes to complete
+    pub cycles: usize,
+
+    /// What the instruction does besides taking up cycles
+    pub effects: &'static [Effect],
+
+    /// One line on what the instruction is for
+    pub summary: &'static str,
+}
+
+/// The kinds of operand an instruction can take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    /// Any integer, e.g. what `addx` adds
+    Number,
+
+    /// A number of cycles. It can't be negative.
+    Duration,
+
+    /// An address on the machine's bus. It can't be negative.
+    Address,
+}
+
+/// Something an instruction does that can be seen from outside of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// It changes the register once it completes
+    WritesRegister,
+
+    /// It sends a signal, e.g. to a teletype or a speaker
+    Signals,
+
+    /// It reads from a device on the bus
+    ReadsBus,
+
+    /// It writes to a device on the bus
+    WritesBus,
+
+    /// It waits on the keyboard for the next key pressed
+    ReadsKeyboard,
+
+    /// It does whatever the machine's `CustomOp` for it does, if it has one
+    Custom,
+}
+
+impl Operand {
+    /// Returns `true` if the operand may be negative
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Operand::Number)
+    }
+}
+
+/// Describes the instruction from the instruction itself, so the table can't
+/// disagree with the machine about its mnemonic, encoding, or timing
+const fn describe(
+    instruction: Instruction,
+    operand: Option<Operand>,
+    effects: &'static [Effect],
+    summary: &'static str,
+) -> Opcode {
+    Opcode {
+        mnemonic: instruction.mnemonic(),
+        code: binary::opcode(&instruction),
+        operand,
+        cycles: instruction.cycles(),
+        effects,
+        summary,
+    }
+}
+
+static OPCODES: [Opcode; 8] = [
+    describe(Instruction::Noop, None, &[], "does nothing for a cycle"),
+    describe(
+        Instruction::Addx(0),
+        Some(Operand::Number),
+        &[Effect::WritesRegister],
+        "adds the operand to the register",
+    ),
+    describe(
+        Instruction::Out,
+        None,
+        &[Effect::Signals],
+        "sends the register to whatever is listening",
+    ),
+    describe(
+        Instruction::Tone(0),
+        Some(Operand::Duration),
+        &[Effect::Signals],
+        "plays a tone at the register's frequency for the operand's cycles",
+    ),
+    describe(
+        Instruction::Ldx(0),
+        Some(Operand::Address),
+        &[Effect::WritesRegister, Effect::ReadsBus],
+        "loads the register from the address",
+    ),
+    describe(
+        Instruction::Stx(0),
+        Some(Operand::Address),
+        &[Effect::WritesBus],
+        "stores the register to the address",
+    ),
+    describe(
+        Instruction::In,
+        None,
+        &[Effect::WritesRegister, Effect::ReadsKeyboard],
+        "loads the register with the next key pressed, or 0 if there is none",
+    ),
+    describe(
+        Instruction::Custom(0, 0),
+        Some(Operand::Number),
+        &[Effect::WritesRegister, Effect::Custom],
+        "runs the machine's custom op for the opcode, if it has one",
+    ),
+];
+
+/// Returns every instruction the machine supports, in the order of their
+/// codes in the binary format
+pub fn opcodes() -> &'static [Opcode] {
+    &OPCODES
+}
+
+/// Returns the instruction going by the mnemonic, if there is one. Custom
+/// instructions go by `op` whatever their opcode.
+pub fn opcode(mnemonic: &str) -> Option<&'static Opcode> {
+    OPCODES.iter().find(|opcode| opcode.mnemonic == mnemonic)
+}
+
+/// Returns what's known about the instruction's kind, whatever its operand
+pub fn lookup(instruction: &Instruction) -> &'static Opcode {
+    &OPCODES[binary::opcode(instruction) as usize]
+}
@@ -0,0 +1,179 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::mem;
+
+use cranelift_codegen::ir::{AbiParam, InstBuilder, MemFlagsData};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Linkage, Module};
+
+use crate::programs::Program;
+
+/// How many cycles' registers are stored relative to the same address before
+/// moving on, keeping every offset well within what an instruction encodes
+const STRIDE: usize = 1 << 16;
+
+/// A program compiled to native code for this machine's CPU with Cranelift,
+/// for enormous programs or ones evaluated millions of times over. The code
+/// is one straight run with the register kept in a CPU register, storing it
+/// for every cycle as it goes: no loop, no dispatch, nothing to decode.
+///
+/// Like a `CompiledProgram`, which it's built from, it runs the program as a
+/// machine with nothing attached would.
+pub struct JitProgram {
+    /// Owns the code, which is freed along with it
+    module: Option<JITModule>,
+
+    /// Writes the register during every cycle to the buffer it's given
+    function: unsafe extern "C" fn(*mut isize),
+
+    cycles: usize,
+}
+
+/// Why a program couldn't be compiled, e.g. Cranelift doesn't target this CPU
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JitError(pub String);
+
+impl JitProgram {
+    /// Compiles the program to native code
+    pub fn compile(program: &Program) -> Result<Self, JitError> {
+        let compiled = program.compile();
+
+        let mut flags = settings::builder();
+        flags.set("opt_level", "speed").map_err(error)?;
+
+        let isa = cranelift_native::builder()
+            .map_err(error)?
+            .finish(settings::Flags::new(flags))
+            .map_err(error)?;
+
+        let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+        let pointer = module.target_config().pointer_type();
+        let size = pointer.bytes() as usize;
+
+        let mut context = module.make_context();
+        context.func.signature.params.push(AbiParam::new(pointer));
+
+        let id = module
+            .declare_function("timeline", Linkage::Local, &context.func.signature)
+            .map_err(error)?;
+
+        let mut builder_context = FunctionBuilderContext::new();
+        let mut builder = FunctionBuilder::new(&mut context.func, &mut builder_context);
+
+        let block = builder.create_block();
+        builder.append_block_params_for_function_params(block);
+        builder.switch_to_block(block);
+        builder.seal_block(block);
+
+        let mut timeline = builder.block_params(block)[0];
+        let mut register = builder.ins().iconst(pointer, 1);
+
+        for (index, &(keep, add)) in compiled.cycles().iter().enumerate() {
+            if index > 0 && index % STRIDE == 0 {
+                timeline = builder.ins().iadd_imm_s(timeline, (STRIDE * size) as i64);
+            }
+
+            let offset = (index % STRIDE * size) as i32;
+            builder
+                .ins()
+                .store(MemFlagsData::trusted(), register, timeline, offset);
+
+            register = match (keep, add) {
+                (0, add) => builder.ins().iconst(pointer, add as i64),
+                (_, 0) => register,
+                (_, add) => builder.ins().iadd_imm_s(register, add as i64),
+            };
+        }
+
+        builder.ins().return_(&[]);
+        builder.finalize(module.target_config());
+
+        module.define_function(id, &mut context).map_err(error)?;
+        module.clear_context(&mut context);
+        module.finalize_definitions().map_err(error)?;
+
+        let code = module.get_finalized_function(id);
+
+        // The function was declared with the signature it's given here: a
+        // pointer to as many registers as there are cycles
+        let function =
+            unsafe { mem::transmute::<*const u8, unsafe extern "C" fn(*mut isize)>(code) };
+
+        Ok(JitProgram {
+            module: Some(module),
+            function,
+            cycles: compiled.len(),
+        })
+    }
+
+    /// Returns the number of cycles the program takes
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Runs the program and returns the register's value during every cycle,
+    /// just like `timelines::register_timeline()` of a machine running it
+    pub fn timeline(&self) -> Vec<isize> {
+        let mut timeline = vec![0; self.cycles];
+        self.write_timeline(&mut timeline);
+        timeline
+    }
+
+    /// Runs the program like `timeline()`, writing the register during every
+    /// cycle to the start of `timeline`, so a search loop can reuse a buffer.
+    /// Panics if it's shorter than `cycles()`.
+    pub fn write_timeline(&self, timeline: &mut [isize]) {
+        assert!(
+            timeline.len() >= self.cycles,
+            "the program takes {} cycles, but the timeline only has room for {}",
+            self.cycles,
+            timeline.len()
+        );
+
+        // The code writes exactly `cycles` registers, which there's room for
+        unsafe { (self.function)(timeline.as_mut_ptr()) }
+    }
+}
+
+impl Drop for JitProgram {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            // Nothing can call the code once `function` goes with `self`
+            unsafe { module.free_memory() }
+        }
+    }
+}
+
+fn error(error: impl Display) -> JitError {
+    JitError(error.to_string())
+}
+
+impl Display for JitError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "couldn't compile to native code: {}", self.0)
+    }
+}
+
+impl Error for JitError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::Instruction;
+
+    #[test]
+    fn native_code_wraps_like_the_machine() {
+        let program = Program::from(vec![
+            Instruction::Addx(isize::MAX),
+            Instruction::Addx(isize::MAX),
+            Instruction::Noop,
+        ]);
+
+        assert_eq!(
+            JitProgram::compile(&program).unwrap().timeline(),
+            program.compile().run_compiled()
+        );
+    }
+}
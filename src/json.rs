@@ -0,0 +1,443 @@
+use std::fmt::{Display, Formatter};
+
+/// How deep arrays and objects may nest in a document `Json::parse()` reads.
+/// Each level is a call deeper, so a peer sending `[[[[…` mustn't be able to
+/// run the stack out; nothing the protocols send comes close.
+pub const MAX_DEPTH: usize = 128;
+
+/// A JSON value, just enough of one for the protocols spoken here. Objects
+/// keep their keys in order, which keeps what's written predictable.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Builds an object from its keys and values
+    pub fn object<'a>(entries: impl IntoIterator<Item = (&'a str, Json)>) -> Self {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+
+        Json::Object(entries)
+    }
+
+    /// Returns the value of an object's key, if it's an object with one
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    /// Returns the number, if it's a whole one that isn't negative
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Json::Number(number) if *number >= 0.0 && number.fract() == 0.0 => {
+                Some(*number as usize)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Parses a whole document. Anything but whitespace after the value is an
+    /// error, and so are arrays and objects nested more than `MAX_DEPTH`
+    /// deep.
+    pub fn parse(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            offset: 0,
+            depth: 0,
+        };
+
+        let value = parser.value()?;
+        parser.skip_whitespace();
+
+        match parser.offset == text.len() {
+            true => Ok(value),
+            false => Err(parser.error()),
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(boolean: bool) -> Self {
+        Json::Bool(boolean)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(string: &str) -> Self {
+        Json::String(string.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(string: String) -> Self {
+        Json::String(string)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(number: usize) -> Self {
+        Json::Number(number as f64)
+    }
+}
+
+impl From<isize> for Json {
+    fn from(number: isize) -> Self {
+        Json::Number(number as f64)
+    }
+}
+
+impl From<Vec<Json>> for Json {
+    fn from(values: Vec<Json>) -> Self {
+        Json::Array(values)
+    }
+}
+
+/// Writes the value compactly, on a single line
+impl Display for Json {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(formatter, "null"),
+            Json::Bool(boolean) => write!(formatter, "{}", boolean),
+            Json::Number(number) => write!(formatter, "{}", number),
+            Json::String(string) => write_string(formatter, string),
+            Json::Array(values) => {
+                write!(formatter, "[")?;
+
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(formatter, ",")?;
+                    }
+
+                    write!(formatter, "{}", value)?;
+                }
+
+                write!(formatter, "]")
+            }
+            Json::Object(entries) => {
+                write!(formatter, "{{")?;
+
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(formatter, ",")?;
+                    }
+
+                    write_string(formatter, key)?;
+                    write!(formatter, ":{}", value)?;
+                }
+
+                write!(formatter, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(formatter: &mut Formatter<'_>, string: &str) -> std::fmt::Result {
+    write!(formatter, "\"")?;
+
+    for c in string.chars() {
+        match c {
+            '"' => write!(formatter, "\\\"")?,
+            '\\' => write!(formatter, "\\\\")?,
+            '\n' => write!(formatter, "\\n")?,
+            '\r' => write!(formatter, "\\r")?,
+            '\t' => write!(formatter, "\\t")?,
+            c if c.is_control() => write!(formatter, "\\u{:04x}", c as u32)?,
+            c => write!(formatter, "{}", c)?,
+        }
+    }
+
+    write!(formatter, "\"")
+}
+
+/// Where a document stopped making sense
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonError {
+    pub offset: usize,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "invalid JSON at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+struct Parser<'a> {
+    text: &'a [u8],
+    offset: usize,
+
+    /// How many arrays and objects the parser is inside
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some(b'{' | b'[') if self.depth == MAX_DEPTH => Err(self.error()),
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Parses an array or object a level deeper than the parser is
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, JsonError>,
+    ) -> Result<Json, JsonError> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        let mut entries = Vec::new();
+        self.offset += 1;
+
+        self.skip_whitespace();
+
+        if self.eat(b'}') {
+            return Ok(Json::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            if self.peek() != Some(b'"') {
+                return Err(self.error());
+            }
+
+            let key = self.string()?;
+            self.skip_whitespace();
+
+            if !self.eat(b':') {
+                return Err(self.error());
+            }
+
+            entries.push((key, self.value()?));
+            self.skip_whitespace();
+
+            if self.eat(b'}') {
+                return Ok(Json::Object(entries));
+            }
+
+            if !self.eat(b',') {
+                return Err(self.error());
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        let mut values = Vec::new();
+        self.offset += 1;
+
+        self.skip_whitespace();
+
+        if self.eat(b']') {
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+            self.skip_whitespace();
+
+            if self.eat(b']') {
+                return Ok(Json::Array(values));
+            }
+
+            if !self.eat(b',') {
+                return Err(self.error());
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        let mut bytes = Vec::new();
+        self.offset += 1;
+
+        loop {
+            let byte = self.next().ok_or_else(|| self.error())?;
+
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.next().ok_or_else(|| self.error())? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error()),
+                    };
+
+                    let mut buffer = [0; 4];
+                    bytes.extend(escaped.encode_utf8(&mut buffer).as_bytes());
+                }
+                byte => bytes.push(byte),
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| self.error())
+    }
+
+    /// Decodes the four hex digits after `\u`, and the low half that must
+    /// follow if they're the high half of a surrogate pair
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex_digits()?;
+
+        let code = match high {
+            0xd800..=0xdbff => {
+                if !(self.eat(b'\\') && self.eat(b'u')) {
+                    return Err(self.error());
+                }
+
+                let low = self.hex_digits()?;
+
+                if !(0xdc00..=0xdfff).contains(&low) {
+                    return Err(self.error());
+                }
+
+                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+            }
+            code => code,
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error())
+    }
+
+    fn hex_digits(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .text
+            .get(self.offset..self.offset + 4)
+            .ok_or_else(|| self.error())?;
+
+        let code = std::str::from_utf8(digits)
+            .ok()
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error())?;
+
+        self.offset += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.offset;
+
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.offset += 1;
+        }
+
+        std::str::from_utf8(&self.text[start..self.offset])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(Json::Number)
+            .ok_or(JsonError { offset: start })
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, JsonError> {
+        match self.text[self.offset..].starts_with(keyword.as_bytes()) {
+            true => {
+                self.offset += keyword.len();
+                Ok(value)
+            }
+            false => Err(self.error()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.offset).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.offset += 1;
+        Some(byte)
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        match self.peek() == Some(byte) {
+            true => {
+                self.offset += 1;
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn error(&self) -> JsonError {
+        JsonError {
+            offset: self.offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_nest_only_so_deep() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(
+            Json::parse(&nested(MAX_DEPTH + 1)),
+            Err(JsonError { offset: MAX_DEPTH })
+        );
+        assert!(Json::parse(&"{\"a\":".repeat(1 << 20)).is_err());
+    }
+}
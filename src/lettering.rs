@@ -0,0 +1,166 @@
+use alloc::collections::VecDeque;
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use crate::font::{self, GLYPH_ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::instructions::Instruction;
+
+/// The screen these programs draw on. Defined by the specification.
+const WIDTH: usize = 40;
+const CYCLES: usize = WIDTH * GLYPH_HEIGHT;
+
+/// The register values worth considering. The sprite is three pixels wide, so
+/// anything further left than `-1` or further right than `40` lights nothing.
+/// One value past each end is enough to have a "dark everywhere" option.
+const LOWEST: isize = -2;
+const HIGHEST: isize = WIDTH as isize + 1;
+const VALUES: usize = (HIGHEST - LOWEST + 1) as usize;
+
+/// Generates a program that draws `text` when run on a screen, i.e. the
+/// inverse of `Screen::decode_text()`. Letters are drawn with the same font,
+/// left to right, so at most eight fit.
+///
+/// Not every text can be drawn. The register starts at `1` and no instruction
+/// can change it before the end of the second cycle, so the two top-left
+/// pixels are always lit. The first letter must light them too. The puzzles'
+/// answers always do, for the same reason.
+pub fn program_for_text(text: &str) -> Result<VecDeque<Instruction>, LetteringError> {
+    let pixels = rasterize(text)?;
+
+    // Work backwards from the last cycle to find, for every cycle and register
+    // value, whether the rest of the screen can still be drawn from there.
+    // `reachable[cycle][value]` is `true` when it can.
+    let mut reachable = vec![[false; VALUES]; CYCLES + 1];
+    reachable[CYCLES] = [true; VALUES];
+
+    for cycle in (0..CYCLES).rev() {
+        let anything_after_addx = cycle + 2 <= CYCLES && reachable[cycle + 2].contains(&true);
+
+        for value in LOWEST..=HIGHEST {
+            reachable[cycle][index(value)] = fits(&pixels, cycle, value)
+                && (reachable[cycle + 1][index(value)]
+                    || (anything_after_addx && fits(&pixels, cycle + 1, value)));
+        }
+    }
+
+    if !reachable[0][index(1)] {
+        return Err(LetteringError::Unreachable(text.to_string()));
+    }
+
+    // Then walk forwards taking any step that keeps the rest drawable. A
+    // `noop` is preferred since it holds the register where it is. Otherwise
+    // an `addx` holds it for two cycles and then moves it wherever the rest of
+    // the screen can be drawn from.
+    let mut program = VecDeque::new();
+    let mut cycle = 0;
+    let mut value = 1;
+
+    while cycle < CYCLES {
+        if reachable[cycle + 1][index(value)] {
+            program.push_back(Instruction::Noop);
+            cycle += 1;
+            continue;
+        }
+
+        let next = (LOWEST..=HIGHEST)
+            .find(|next| reachable[cycle + 2][index(*next)])
+            .expect("the search said an addx works here");
+
+        program.push_back(Instruction::Addx(next - value));
+        value = next;
+        cycle += 2;
+    }
+
+    Ok(program)
+}
+
+/// Lays the text out in a framebuffer, one `bool` per pixel
+fn rasterize(text: &str) -> Result<Vec<bool>, LetteringError> {
+    let length = text.chars().count();
+
+    if length > WIDTH / GLYPH_ADVANCE {
+        return Err(LetteringError::TooLong(length));
+    }
+
+    let mut pixels = vec![false; CYCLES];
+
+    for (position, letter) in text.chars().enumerate() {
+        let glyph = font::glyph(letter).ok_or(LetteringError::UnknownLetter(letter))?;
+
+        for y in 0..GLYPH_HEIGHT {
+            for x in 0..GLYPH_WIDTH {
+                pixels[y * WIDTH + position * GLYPH_ADVANCE + x] = glyph.is_lit(x, y);
+            }
+        }
+    }
+
+    if !pixels[0] || !pixels[1] {
+        let first = text.chars().next().unwrap_or(' ');
+        return Err(LetteringError::DarkCorner(first));
+    }
+
+    Ok(pixels)
+}
+
+/// Returns `true` if the register holding `value` during `cycle` draws the
+/// right pixel: lit exactly when the sprite overlaps the beam
+fn fits(pixels: &[bool], cycle: usize, value: isize) -> bool {
+    let column = (cycle % WIDTH) as isize;
+    let overlaps = (value - column).abs() <= 1;
+
+    overlaps == pixels[cycle]
+}
+
+/// Maps a register value to its index in the search table
+fn index(value: isize) -> usize {
+    (value - LOWEST) as usize
+}
+
+/// The ways a text can fail to become a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LetteringError {
+    /// The text has more letters than fit on the screen
+    TooLong(usize),
+
+    /// The font has no glyph for this character
+    UnknownLetter(char),
+
+    /// The first letter doesn't light the two top-left pixels, which every
+    /// program does on its first two cycles
+    DarkCorner(char),
+
+    /// No sequence of instructions draws the text. The two cycle `addx`
+    /// makes some pixel patterns impossible.
+    Unreachable(String),
+}
+
+impl Display for LetteringError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LetteringError::TooLong(length) => write!(
+                formatter,
+                "{} letters don't fit on the screen (at most {} do)",
+                length,
+                WIDTH / GLYPH_ADVANCE
+            ),
+            LetteringError::UnknownLetter(letter) => {
+                write!(formatter, "the font has no glyph for {:?}", letter)
+            }
+            LetteringError::DarkCorner(letter) => write!(
+                formatter,
+                "{:?} can't come first since it doesn't light the two top-left pixels",
+                letter
+            ),
+            LetteringError::Unreachable(text) => {
+                write!(formatter, "no program draws {:?}", text)
+            }
+        }
+    }
+}
+
+impl Error for LetteringError {}
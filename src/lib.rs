@@ -0,0 +1,78 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod alarms;
+pub mod analyses;
+pub mod assembly;
+mod base64;
+pub mod batches;
+pub mod binary;
+pub mod caches;
+#[cfg(feature = "std")]
+pub mod config;
+pub mod containers;
+pub mod course;
+#[cfg(feature = "std")]
+pub mod dap;
+pub mod debugger;
+pub mod demos;
+pub mod devices;
+pub mod errors;
+pub mod explanations;
+pub mod expressions;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fnv;
+pub mod font;
+#[cfg(feature = "std")]
+pub mod gdb;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod images;
+pub mod instructions;
+pub mod interrupts;
+pub mod isa;
+#[cfg(feature = "cranelift")]
+pub mod jit;
+#[cfg(feature = "std")]
+mod json;
+pub mod lettering;
+pub mod linking;
+#[cfg(feature = "std")]
+pub mod lsp;
+pub mod machines;
+#[cfg(feature = "std")]
+pub mod metrics;
+pub mod mutations;
+pub mod observers;
+#[cfg(feature = "plugins")]
+pub mod plugins;
+pub mod pools;
+pub mod programs;
+#[cfg(feature = "test-util")]
+pub mod properties;
+#[cfg(feature = "pyo3")]
+mod python;
+pub mod recorders;
+#[cfg(feature = "std")]
+pub mod remote;
+pub mod screens;
+#[cfg(feature = "rhai")]
+pub mod scripts;
+#[cfg(feature = "websocket")]
+mod sha1;
+#[cfg(feature = "std")]
+pub mod sharing;
+pub mod statistics;
+#[cfg(feature = "websocket")]
+pub mod streaming;
+pub mod testing;
+pub mod timelines;
+#[cfg(feature = "std")]
+pub mod traces;
+mod varint;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use errors::Error;
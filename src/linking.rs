@@ -0,0 +1,487 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use crate::assembly::{self, AsmError, AsmProblem};
+use crate::instructions::Instruction;
+use crate::programs::Program;
+
+/// The segment instructions go in until a `.segment` directive says otherwise
+pub const DEFAULT_SEGMENT: &str = "text";
+
+/// An assembled piece of a program that can't run on its own yet: operands may
+/// name symbols defined in other objects. It's the same assembly as
+/// `assembly::assemble()` takes, plus:
+///
+/// - `name:` labels the next instruction. Linked, the label's value is the
+///   instruction's position in the whole program.
+/// - `.equ name value` defines a constant, e.g. a device's address.
+/// - `.segment name` puts the instructions that follow in a named segment. The
+///   linker gathers each segment from every object before the next segment.
+/// - An operand can be a symbol (a label or constant) instead of a number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    name: String,
+    segments: Vec<Segment>,
+    constants: Vec<(String, isize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    name: String,
+    instructions: Vec<Instruction>,
+
+    /// The line every instruction came from
+    lines: Vec<usize>,
+
+    /// Labels and the position in the segment they label
+    labels: Vec<(String, usize)>,
+
+    /// Operands still waiting for a symbol's value
+    references: Vec<Reference>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Reference {
+    /// The position of the instruction in its segment, or, once linked, in
+    /// the program
+    index: usize,
+    symbol: String,
+    line: usize,
+}
+
+impl Object {
+    /// Assembles an object. The name is only used to say where link errors
+    /// are, e.g. the file it came from.
+    pub fn assemble(name: impl Into<String>, source: &str) -> Result<Self, AsmError> {
+        let mut object = Object {
+            name: name.into(),
+            segments: Vec::new(),
+            constants: Vec::new(),
+        };
+        let mut segment = DEFAULT_SEGMENT;
+
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |problem| AsmError {
+                line: line_number,
+                problem,
+            };
+
+            let code = match line.split_once(';') {
+                Some((code, _comment)) => code,
+                None => line,
+            };
+
+            let mut words: Vec<&str> = code.split_whitespace().collect();
+
+            if let Some(label) = words.first().and_then(|word| word.strip_suffix(':')) {
+                if !is_symbol(label) {
+                    return Err(error(AsmProblem::BadOperand(label.to_string())));
+                }
+
+                let segment = object.segment(segment);
+                let offset = segment.instructions.len();
+                segment.labels.push((label.to_string(), offset));
+                words.remove(0);
+            }
+
+            match words.as_slice() {
+                [] => (),
+                [".segment", name] => segment = name,
+                [".equ", name, value] if is_symbol(name) => {
+                    let value = value
+                        .parse()
+                        .map_err(|_| error(AsmProblem::BadOperand(value.to_string())))?;
+
+                    object.constants.push((name.to_string(), value));
+                }
+                [".segment" | ".equ", ..] => {
+                    return Err(error(AsmProblem::BadOperand(words[1..].join(" "))))
+                }
+                [directive, ..] if directive.starts_with('.') => {
+                    return Err(error(AsmProblem::UnknownDirective(directive.to_string())))
+                }
+                [_, _, extra, ..] => {
+                    return Err(error(AsmProblem::ExtraOperand(extra.to_string())))
+                }
+                [mnemonic, operand] if is_symbol(operand) => {
+                    // Assemble with a stand-in to check the instruction takes
+                    // an operand at all, then fill it in when linking. If it
+                    // doesn't, the symbol is the operand to blame.
+                    let instruction = assembly::assemble_instruction(mnemonic, Some("0"))
+                        .map_err(|problem| match problem {
+                            AsmProblem::ExtraOperand(_) => {
+                                AsmProblem::ExtraOperand(operand.to_string())
+                            }
+                            problem => problem,
+                        })
+                        .map_err(error)?;
+                    let segment = object.segment(segment);
+
+                    segment.references.push(Reference {
+                        index: segment.instructions.len(),
+                        symbol: operand.to_string(),
+                        line: line_number,
+                    });
+                    segment.instructions.push(instruction);
+                    segment.lines.push(line_number);
+                }
+                [mnemonic, operand @ ..] => {
+                    let instruction =
+                        assembly::assemble_instruction(mnemonic, operand.first().copied())
+                            .map_err(error)?;
+
+                    let segment = object.segment(segment);
+                    segment.instructions.push(instruction);
+                    segment.lines.push(line_number);
+                }
+            }
+        }
+
+        Ok(object)
+    }
+
+    /// Returns the name the object was assembled with
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the segment with the given name, adding it if it's new. It's
+    /// only added once something goes in it, so an object that starts with
+    /// `.segment` doesn't also put an empty default segment first.
+    fn segment(&mut self, name: &str) -> &mut Segment {
+        if let Some(index) = self
+            .segments
+            .iter()
+            .position(|segment| segment.name == name)
+        {
+            return &mut self.segments[index];
+        }
+
+        self.segments.push(Segment {
+            name: name.to_string(),
+            instructions: Vec::new(),
+            lines: Vec::new(),
+            labels: Vec::new(),
+            references: Vec::new(),
+        });
+
+        self.segments.last_mut().expect("it was just pushed")
+    }
+}
+
+/// Symbols are names: a letter or underscore, then letters, digits,
+/// underscores, or dots
+fn is_symbol(word: &str) -> bool {
+    let mut chars = word.chars();
+
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Combines objects into one program. Segments are laid out in the order
+/// they first appear in any object, and within each segment, objects are in
+/// the order they were added. So a library's `init` segment can run before
+/// every demo's `text`, however the objects are listed.
+#[derive(Debug, Clone, Default)]
+pub struct Linker {
+    objects: Vec<Object>,
+}
+
+/// A linked program along with where every label ended up, e.g. to set
+/// breakpoints by name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linked {
+    pub program: Program,
+    pub labels: BTreeMap<String, usize>,
+
+    /// Where every instruction came from: the object, by the order it was
+    /// added in, and the line in it
+    pub lines: Vec<(usize, usize)>,
+}
+
+impl Linker {
+    pub fn new() -> Self {
+        Linker::default()
+    }
+
+    /// Adds an object after those already added
+    pub fn with_object(mut self, object: Object) -> Self {
+        self.objects.push(object);
+        self
+    }
+
+    /// Lays out the segments and fills in every operand that names a symbol
+    pub fn link(&self) -> Result<Linked, LinkError> {
+        let mut order: Vec<&str> = Vec::new();
+
+        for object in &self.objects {
+            for segment in &object.segments {
+                if !order.contains(&segment.name.as_str()) {
+                    order.push(&segment.name);
+                }
+            }
+        }
+
+        let mut instructions = Vec::new();
+        let mut lines = Vec::new();
+        let mut labels = BTreeMap::new();
+        let mut references = Vec::new();
+
+        // Every symbol, its value, and the object that defined it
+        let mut definitions: Vec<(&str, isize, &str)> = Vec::new();
+
+        for name in &order {
+            for (position, object) in self.objects.iter().enumerate() {
+                let Some(segment) = object.segments.iter().find(|s| s.name == *name) else {
+                    continue;
+                };
+
+                let base = instructions.len();
+                instructions.extend_from_slice(&segment.instructions);
+                lines.extend(segment.lines.iter().map(|&line| (position, line)));
+
+                for (label, offset) in &segment.labels {
+                    definitions.push((label, (base + offset) as isize, &object.name));
+                    labels.insert(label.clone(), base + offset);
+                }
+
+                for reference in &segment.references {
+                    references.push((base + reference.index, reference, object.name.as_str()));
+                }
+            }
+        }
+
+        for object in &self.objects {
+            for (name, value) in &object.constants {
+                definitions.push((name, *value, &object.name));
+            }
+        }
+
+        let mut symbols = BTreeMap::new();
+
+        for (name, value, object) in definitions {
+            if let Some((_, first)) = symbols.insert(name, (value, object)) {
+                return Err(LinkError::Duplicate {
+                    symbol: name.to_string(),
+                    first: first.to_string(),
+                    second: object.to_string(),
+                });
+            }
+        }
+
+        for (index, reference, object) in references {
+            let at = || (object.to_string(), reference.line, reference.symbol.clone());
+
+            let Some(&(value, _)) = symbols.get(reference.symbol.as_str()) else {
+                let (object, line, symbol) = at();
+                return Err(LinkError::Undefined {
+                    symbol,
+                    object,
+                    line,
+                });
+            };
+
+            instructions[index] = with_operand(instructions[index], value).ok_or_else(|| {
+                let (object, line, symbol) = at();
+                LinkError::OutOfRange {
+                    symbol,
+                    value,
+                    object,
+                    line,
+                }
+            })?;
+        }
+
+        Ok(Linked {
+            program: Program::new(instructions),
+            labels,
+            lines,
+        })
+    }
+}
+
+/// Returns the instruction with its operand replaced, unless the value doesn't
+/// fit, e.g. a negative address
+fn with_operand(instruction: Instruction, value: isize) -> Option<Instruction> {
+    let unsigned = usize::try_from(value).ok();
+
+    match instruction {
+        Instruction::Addx(_) => Some(Instruction::Addx(value)),
+        Instruction::Tone(_) => unsigned.map(Instruction::Tone),
+        Instruction::Ldx(_) => unsigned.map(Instruction::Ldx),
+        Instruction::Stx(_) => unsigned.map(Instruction::Stx),
+        Instruction::Custom(opcode, _) => Some(Instruction::Custom(opcode, value)),
+        Instruction::Noop | Instruction::Out | Instruction::In => None,
+    }
+}
+
+/// Why objects couldn't be linked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    /// Two objects (or one, twice) define the same symbol
+    Duplicate {
+        symbol: String,
+        first: String,
+        second: String,
+    },
+
+    /// An operand names a symbol nobody defines
+    Undefined {
+        symbol: String,
+        object: String,
+        line: usize,
+    },
+
+    /// A symbol's value doesn't fit the operand, e.g. a negative address
+    OutOfRange {
+        symbol: String,
+        value: isize,
+        object: String,
+        line: usize,
+    },
+}
+
+impl Display for LinkError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LinkError::Duplicate {
+                symbol,
+                first,
+                second,
+            } => write!(
+                formatter,
+                "`{}` is defined in {} and again in {}",
+                symbol, first, second
+            ),
+            LinkError::Undefined {
+                symbol,
+                object,
+                line,
+            } => write!(formatter, "{}:{}: `{}` isn't defined", object, line, symbol),
+            LinkError::OutOfRange {
+                symbol,
+                value,
+                object,
+                line,
+            } => write!(
+                formatter,
+                "{}:{}: `{}` is {}, which the operand can't be",
+                object, line, symbol, value
+            ),
+        }
+    }
+}
+
+impl Error for LinkError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn lays_out_segments_from_every_object() {
+        let library = Object::assemble(
+            "library",
+            ".equ SPEAKER 7\n.segment init\nsetup: addx 1\n.segment text\nbeep: tone SPEAKER\n",
+        )
+        .unwrap();
+        let demo = Object::assemble(
+            "demo",
+            ".segment init\n  noop ; ready\n.segment text\nloop: addx loop\nstx beep\n",
+        )
+        .unwrap();
+
+        let linked = Linker::new()
+            .with_object(library)
+            .with_object(demo)
+            .link()
+            .unwrap();
+
+        assert_eq!(
+            linked.program,
+            Program::from(vec![
+                Instruction::Addx(1),
+                Instruction::Noop,
+                Instruction::Tone(7),
+                Instruction::Addx(3),
+                Instruction::Stx(2),
+            ])
+        );
+        assert_eq!(
+            linked.labels,
+            BTreeMap::from([
+                ("beep".to_string(), 2),
+                ("loop".to_string(), 3),
+                ("setup".to_string(), 0),
+            ])
+        );
+        assert_eq!(linked.lines, vec![(0, 3), (1, 2), (0, 5), (1, 4), (1, 5)]);
+    }
+
+    #[test]
+    fn refuses_symbols_undefined_twice_defined_or_out_of_range() {
+        let link = |sources: &[&str]| {
+            sources
+                .iter()
+                .enumerate()
+                .fold(Linker::new(), |linker, (index, source)| {
+                    let name = ["first", "second"][index];
+                    linker.with_object(Object::assemble(name, source).unwrap())
+                })
+                .link()
+        };
+
+        assert_eq!(
+            link(&["noop\naddx missing"]),
+            Err(LinkError::Undefined {
+                symbol: "missing".to_string(),
+                object: "first".to_string(),
+                line: 2,
+            })
+        );
+        assert_eq!(
+            link(&["here: noop", ".equ here 3"]),
+            Err(LinkError::Duplicate {
+                symbol: "here".to_string(),
+                first: "first".to_string(),
+                second: "second".to_string(),
+            })
+        );
+        assert_eq!(
+            link(&[".equ BELOW -1\nldx BELOW"]),
+            Err(LinkError::OutOfRange {
+                symbol: "BELOW".to_string(),
+                value: -1,
+                object: "first".to_string(),
+                line: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn refuses_directives_and_labels_it_cant_read() {
+        let problem = |source| Object::assemble("object", source).unwrap_err().problem;
+
+        assert_eq!(
+            problem(".align 4"),
+            AsmProblem::UnknownDirective(".align".to_string())
+        );
+        assert_eq!(
+            problem(".equ 4 4"),
+            AsmProblem::BadOperand("4 4".to_string())
+        );
+        assert_eq!(
+            problem("4ever: noop"),
+            AsmProblem::BadOperand("4ever".to_string())
+        );
+        assert_eq!(
+            problem("noop later"),
+            AsmProblem::ExtraOperand("later".to_string())
+        );
+    }
+}
@@ -0,0 +1,780 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, ErrorKind, Write};
+
+use crate::analyses::{self, Termination, Trigger};
+use crate::assembly;
+use crate::instructions::Instruction;
+use crate::interrupts::InterruptController;
+use crate::isa;
+use crate::json::Json;
+use crate::linking::{LinkError, Linked, Linker, Object};
+use crate::screens::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// The JSON-RPC error for a request the server doesn't know
+const METHOD_NOT_FOUND: isize = -32601;
+
+/// A server for the Language Server Protocol, so editors can check assembly
+/// as it's written, in the dialect `linking::Object` reads. It reports
+/// problems the assembler and linker find, warns about instructions that
+/// never run because they'd start after the screen is drawn, shows what an
+/// instruction costs
+/// and the register it starts with when hovering over it, jumps to where
+/// labels and constants are defined, and completes mnemonics and symbols. Given
+/// interrupt handlers in its `initializationOptions`, it warns about programs
+/// that may never finish because the handlers keep setting each other off.
+///
+/// Messages are framed like the Debug Adapter Protocol's, with a
+/// `Content-Length` header, usually over the standard input and output of a
+/// process the editor starts.
+pub struct LspServer<R, W> {
+    input: R,
+    output: W,
+
+    /// The text of every open document, by URI
+    documents: BTreeMap<String, String>,
+
+    /// The interrupt handlers programs are checked against, and what sets
+    /// their lines off, as the editor configured them
+    interrupts: InterruptController,
+    triggers: Vec<Trigger>,
+
+    /// What was wrong with the handlers the editor configured
+    configuration: Vec<String>,
+}
+
+/// A label or constant a document defines
+struct Symbol {
+    name: String,
+
+    /// Counting from `0`, like the protocol, and `column` in UTF-16 code
+    /// units like it too
+    line: usize,
+    column: usize,
+
+    /// A constant's value. Labels' values are only known once linked.
+    value: Option<isize>,
+}
+
+/// Something wrong with a line of a document
+struct Problem {
+    /// Counting from `0`, like the protocol
+    line: usize,
+
+    /// Whether the document still builds despite it
+    warning: bool,
+
+    message: String,
+}
+
+/// What's known about a document
+struct Analysis {
+    problems: Vec<Problem>,
+
+    linked: Option<Linked>,
+    symbols: Vec<Symbol>,
+}
+
+impl<R: BufRead, W: Write> LspServer<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        LspServer {
+            input,
+            output,
+            documents: BTreeMap::new(),
+            interrupts: InterruptController::new(),
+            triggers: Vec::new(),
+            configuration: Vec::new(),
+        }
+    }
+
+    /// Serves requests until the editor says to exit or closes the input
+    pub fn serve(mut self) -> io::Result<()> {
+        while let Some(message) = self.read_message()? {
+            let Ok(message) = Json::parse(&message) else {
+                continue;
+            };
+
+            if !self.handle(&message)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles a request or notification, responding to it and publishing
+    /// whatever it changed. Returns `false` once the editor is done.
+    fn handle(&mut self, message: &Json) -> io::Result<bool> {
+        let method = message.get("method").and_then(Json::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Json::Null);
+        let id = message.get("id").cloned();
+
+        let result = match method {
+            "initialize" => {
+                if let Some(options) = params.get("initializationOptions") {
+                    self.configure(options);
+                }
+
+                let capabilities = Json::object([
+                    // The whole document is sent on every change
+                    ("textDocumentSync", 1usize.into()),
+                    ("hoverProvider", true.into()),
+                    ("definitionProvider", true.into()),
+                    ("completionProvider", Json::object([])),
+                ]);
+
+                Json::object([
+                    ("capabilities", capabilities),
+                    ("serverInfo", Json::object([("name", "legolos".into())])),
+                ])
+            }
+            "shutdown" => Json::Null,
+            "exit" => return Ok(false),
+            "textDocument/didOpen" | "textDocument/didChange" => {
+                let uri = uri(&params);
+                let text = params
+                    .get("textDocument")
+                    .and_then(|document| document.get("text"))
+                    .or_else(|| {
+                        params
+                            .get("contentChanges")
+                            .and_then(Json::as_array)
+                            .and_then(|changes| changes.last())
+                            .and_then(|change| change.get("text"))
+                    })
+                    .and_then(Json::as_str);
+
+                if let Some(text) = text {
+                    self.documents.insert(uri.clone(), text.to_string());
+                    self.publish(&uri)?;
+                }
+
+                return Ok(true);
+            }
+            "textDocument/didClose" => {
+                let uri = uri(&params);
+                self.documents.remove(&uri);
+                self.publish(&uri)?;
+
+                return Ok(true);
+            }
+            "textDocument/hover" => self.hover(&params),
+            "textDocument/definition" => self.definition(&params),
+            "textDocument/completion" => self.completion(&params),
+            _ => {
+                if let Some(id) = id {
+                    let error = Json::object([
+                        ("code", METHOD_NOT_FOUND.into()),
+                        ("message", format!("`{}` isn't supported", method).into()),
+                    ]);
+
+                    self.send(vec![("id", id), ("error", error)])?;
+                }
+
+                return Ok(true);
+            }
+        };
+
+        // Notifications, e.g. `initialized`, get no response
+        if let Some(id) = id {
+            self.send(vec![("id", id), ("result", result)])?;
+        }
+
+        Ok(true)
+    }
+
+    /// Reads the interrupt handlers from the editor's `initializationOptions`,
+    /// e.g. `{"interrupts":[{"priority":1,"handler":"stx 3","triggers":[3]}]}`.
+    /// Lines are numbered from `0` in order, and storing at any of a line's
+    /// triggers sets it off, like the `check` command's `--trigger`. A
+    /// handler that doesn't assemble does nothing, and every document says
+    /// so.
+    fn configure(&mut self, options: &Json) {
+        let interrupts = options
+            .get("interrupts")
+            .and_then(Json::as_array)
+            .unwrap_or(&[]);
+
+        for (line, interrupt) in interrupts.iter().enumerate() {
+            let priority = interrupt
+                .get("priority")
+                .and_then(Json::as_usize)
+                .and_then(|priority| u8::try_from(priority).ok())
+                .unwrap_or(0);
+            let source = interrupt
+                .get("handler")
+                .and_then(Json::as_str)
+                .unwrap_or("");
+
+            let handler: Vec<_> = match assembly::assemble(source) {
+                Ok(handler) => handler.into(),
+                Err(error) => {
+                    self.configuration.push(format!(
+                        "interrupt {}'s handler doesn't assemble: {}",
+                        line, error
+                    ));
+                    Vec::new()
+                }
+            };
+
+            self.interrupts.add_line(priority, handler);
+
+            let addresses = interrupt
+                .get("triggers")
+                .and_then(Json::as_array)
+                .unwrap_or(&[]);
+
+            for address in addresses.iter().filter_map(Json::as_usize) {
+                self.triggers.push(Trigger { address, line });
+            }
+        }
+    }
+
+    /// Warns about handlers that may keep setting each other off once the
+    /// program starts them, on the first store that does
+    fn termination(&self, analysis: &Analysis) -> Option<Problem> {
+        let linked = analysis.linked.as_ref()?;
+        let instructions = linked.program.instructions();
+
+        let termination = analyses::termination(instructions, &self.interrupts, &self.triggers);
+
+        let Termination::MayLoop { lines } = &termination else {
+            return None;
+        };
+
+        let index = instructions.iter().position(|instruction| {
+            self.triggers.iter().any(|trigger| {
+                *instruction == Instruction::Stx(trigger.address) && lines.contains(&trigger.line)
+            })
+        });
+
+        Some(Problem {
+            line: index.map_or(0, |index| linked.lines[index].1 - 1),
+            warning: true,
+            message: termination.to_string(),
+        })
+    }
+
+    /// Sends the document's problems, or none once it's closed
+    fn publish(&mut self, uri: &str) -> io::Result<()> {
+        let diagnostics = match self.documents.get(uri) {
+            Some(text) => {
+                let lines: Vec<&str> = text.lines().collect();
+                let analysis = analyze(uri, text);
+
+                let configuration = self.configuration.iter().map(|message| Problem {
+                    line: 0,
+                    warning: true,
+                    message: message.clone(),
+                });
+                let termination = self.termination(&analysis);
+
+                analysis
+                    .problems
+                    .into_iter()
+                    .chain(configuration)
+                    .chain(termination)
+                    .map(|problem| {
+                        let length = lines
+                            .get(problem.line)
+                            .map_or(0, |line| line.encode_utf16().count());
+
+                        // Errors are 1 and warnings 2
+                        let severity = match problem.warning {
+                            true => 2usize,
+                            false => 1usize,
+                        };
+
+                        Json::object([
+                            ("range", range(problem.line, 0, length)),
+                            ("severity", severity.into()),
+                            ("source", "legolos".into()),
+                            ("message", problem.message.into()),
+                        ])
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let params = Json::object([("uri", uri.into()), ("diagnostics", diagnostics.into())]);
+
+        self.send(vec![
+            ("method", "textDocument/publishDiagnostics".into()),
+            ("params", params),
+        ])
+    }
+
+    /// Describes the symbol under the cursor, or else the instruction on its
+    /// line
+    fn hover(&self, params: &Json) -> Json {
+        let Some((uri, text, line, column)) = self.position(params) else {
+            return Json::Null;
+        };
+
+        let analysis = analyze(&uri, text);
+        let word = word_at(text, line, column);
+
+        let contents = match analysis
+            .symbols
+            .iter()
+            .find(|symbol| Some(&*symbol.name) == word)
+        {
+            Some(symbol) => describe_symbol(symbol, &analysis),
+            None => match describe_line(line, &analysis) {
+                Some(contents) => contents,
+                None => return Json::Null,
+            },
+        };
+
+        Json::object([(
+            "contents",
+            Json::object([("kind", "markdown".into()), ("value", contents.into())]),
+        )])
+    }
+
+    /// Finds where the symbol under the cursor is defined
+    fn definition(&self, params: &Json) -> Json {
+        let Some((uri, text, line, column)) = self.position(params) else {
+            return Json::Null;
+        };
+
+        let Some(word) = word_at(text, line, column) else {
+            return Json::Null;
+        };
+
+        match analyze(&uri, text)
+            .symbols
+            .iter()
+            .find(|symbol| symbol.name == word)
+        {
+            Some(symbol) => Json::object([
+                ("uri", uri.as_str().into()),
+                (
+                    "range",
+                    range(
+                        symbol.line,
+                        symbol.column,
+                        symbol.column + symbol.name.encode_utf16().count(),
+                    ),
+                ),
+            ]),
+            None => Json::Null,
+        }
+    }
+
+    /// Offers every mnemonic, then every symbol the document defines
+    fn completion(&self, params: &Json) -> Json {
+        let mut items: Vec<Json> = isa::opcodes()
+            .iter()
+            .map(|opcode| {
+                Json::object([
+                    ("label", opcode.mnemonic.into()),
+                    // A keyword
+                    ("kind", 14usize.into()),
+                    ("detail", cost(opcode).into()),
+                    ("documentation", opcode.summary.into()),
+                ])
+            })
+            .collect();
+
+        if let Some(text) = self.documents.get(&uri(params)) {
+            for symbol in analyze("", text).symbols {
+                let (kind, detail) = match symbol.value {
+                    // A constant
+                    Some(value) => (21usize, format!("= {}", value)),
+                    // A reference
+                    None => (18usize, "label".to_string()),
+                };
+
+                items.push(Json::object([
+                    ("label", symbol.name.into()),
+                    ("kind", kind.into()),
+                    ("detail", detail.into()),
+                ]));
+            }
+        }
+
+        items.into()
+    }
+
+    /// Returns the document and the position the request is about
+    fn position(&self, params: &Json) -> Option<(String, &str, usize, usize)> {
+        let uri = uri(params);
+        let text = self.documents.get(&uri)?;
+        let position = params.get("position")?;
+        let line = position.get("line").and_then(Json::as_usize)?;
+        let column = position.get("character").and_then(Json::as_usize)?;
+
+        Some((uri, text, line, column))
+    }
+
+    /// Writes a JSON-RPC message with its header
+    fn send(&mut self, fields: Vec<(&str, Json)>) -> io::Result<()> {
+        let message =
+            Json::object([("jsonrpc", "2.0".into())].into_iter().chain(fields)).to_string();
+
+        write!(
+            self.output,
+            "Content-Length: {}\r\n\r\n{}",
+            message.len(),
+            message
+        )?;
+        self.output.flush()
+    }
+
+    /// Reads the next message's content, or `None` once the input is closed
+    fn read_message(&mut self) -> io::Result<Option<String>> {
+        let mut length = None;
+
+        loop {
+            let mut header = String::new();
+
+            if self.input.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+
+            let header = header.trim_end();
+
+            if header.is_empty() {
+                break;
+            }
+
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+
+        let Some(length) = length else {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "a message had no Content-Length",
+            ));
+        };
+
+        let mut content = vec![0; length];
+        self.input.read_exact(&mut content)?;
+
+        Ok(Some(String::from_utf8_lossy(&content).into_owned()))
+    }
+}
+
+/// Returns the URI of the document the parameters are about
+fn uri(params: &Json) -> String {
+    params
+        .get("textDocument")
+        .and_then(|document| document.get("uri"))
+        .and_then(Json::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn range(line: usize, start: usize, end: usize) -> Json {
+    let position =
+        |character: usize| Json::object([("line", line.into()), ("character", character.into())]);
+
+    Json::object([("start", position(start)), ("end", position(end))])
+}
+
+/// Assembles and links the document on its own, and finds its symbols
+fn analyze(uri: &str, text: &str) -> Analysis {
+    let symbols = symbols(text);
+    let mut problems = Vec::new();
+    let mut linked = None;
+
+    match Object::assemble(uri, text) {
+        Err(error) => problems.push(Problem {
+            line: error.line - 1,
+            warning: false,
+            message: error.problem.to_string(),
+        }),
+        Ok(object) => match Linker::new().with_object(object).link() {
+            Ok(program) => {
+                problems.extend(unreachable(&program));
+                linked = Some(program);
+            }
+            Err(error) => {
+                let line = match &error {
+                    LinkError::Undefined { line, .. } | LinkError::OutOfRange { line, .. } => {
+                        line - 1
+                    }
+                    // The second definition is the one that's out of place
+                    LinkError::Duplicate { symbol, .. } => symbols
+                        .iter()
+                        .rev()
+                        .find(|candidate| candidate.name == *symbol)
+                        .map_or(0, |symbol| symbol.line),
+                };
+
+                // The linker's messages lead with the object and line, which
+                // the editor already shows
+                let message = match error {
+                    LinkError::Undefined { symbol, .. } => format!("`{}` isn't defined", symbol),
+                    LinkError::OutOfRange { symbol, value, .. } => {
+                        format!("`{}` is {}, which the operand can't be", symbol, value)
+                    }
+                    LinkError::Duplicate { symbol, .. } => {
+                        format!("`{}` is defined more than once", symbol)
+                    }
+                };
+
+                problems.push(Problem {
+                    line,
+                    warning: false,
+                    message,
+                });
+            }
+        },
+    }
+
+    Analysis {
+        problems,
+        linked,
+        symbols,
+    }
+}
+
+/// Warns about every instruction that never runs when the program is drawn,
+/// because it would start after the last of the screen's pixels
+fn unreachable(linked: &Linked) -> Vec<Problem> {
+    let pixels = SCREEN_WIDTH * SCREEN_HEIGHT;
+    let instructions = linked.program.instructions();
+    let entering = analyses::entering(instructions);
+
+    analyses::unreachable(instructions, pixels)
+        .map(|index| Problem {
+            line: linked.lines[index].1 - 1,
+            warning: true,
+            message: format!(
+                "never runs when drawn: it starts on cycle {}, after the screen's {} pixels",
+                entering[index].cycle, pixels
+            ),
+        })
+        .collect()
+}
+
+/// Finds every label and constant the text defines, whether or not it
+/// assembles
+fn symbols(text: &str) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+
+    for (line, source) in text.lines().enumerate() {
+        let code = source.split(';').next().unwrap_or_default();
+        let mut words = code.split_whitespace();
+        // Names are looked for from `from` along, e.g. past `.equ` so a
+        // name that's one of its letters isn't found inside it
+        let column = |name: &str, from: usize| {
+            utf16_column(code, from + code[from..].find(name).unwrap_or(0))
+        };
+
+        match words.next() {
+            Some(".equ") => {
+                if let Some(name) = words.next() {
+                    let directive = code.find(".equ").unwrap_or(0) + ".equ".len();
+
+                    symbols.push(Symbol {
+                        name: name.to_string(),
+                        line,
+                        column: column(name, directive),
+                        value: words.next().and_then(|value| value.parse().ok()),
+                    });
+                }
+            }
+            Some(word) => {
+                if let Some(name) = word.strip_suffix(':') {
+                    symbols.push(Symbol {
+                        name: name.to_string(),
+                        line,
+                        column: column(name, 0),
+                        value: None,
+                    });
+                }
+            }
+            None => (),
+        }
+    }
+
+    symbols
+}
+
+/// Returns the symbol-like word under the cursor, `column` UTF-16 code units
+/// along the line, as the protocol counts them
+fn word_at(text: &str, line: usize, column: usize) -> Option<&str> {
+    let source = text.lines().nth(line)?;
+    let is_part = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '.';
+
+    let column = byte_column(source, column);
+
+    let start = source[..column]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| !is_part(*c))
+        .map_or(0, |(index, c)| index + c.len_utf8());
+    let end = source[column..]
+        .find(|c: char| !is_part(c))
+        .map_or(source.len(), |index| column + index);
+
+    match &source[start..end] {
+        "" => None,
+        word => Some(word),
+    }
+}
+
+/// Returns how many UTF-16 code units into the line the byte offset is
+fn utf16_column(line: &str, offset: usize) -> usize {
+    line[..offset].encode_utf16().count()
+}
+
+/// The inverse of `utf16_column()`: returns the byte offset of the character
+/// the UTF-16 column falls on, or the end of the line if it's past it
+fn byte_column(line: &str, column: usize) -> usize {
+    let mut units = 0;
+
+    for (offset, c) in line.char_indices() {
+        units += c.len_utf16();
+
+        if units > column {
+            return offset;
+        }
+    }
+
+    line.len()
+}
+
+/// Says what an instruction costs, e.g. `2 cycles`
+fn cost(opcode: &isa::Opcode) -> String {
+    match opcode.cycles {
+        1 => "1 cycle".to_string(),
+        cycles => format!("{} cycles", cycles),
+    }
+}
+
+fn describe_symbol(symbol: &Symbol, analysis: &Analysis) -> String {
+    if let Some(value) = symbol.value {
+        return format!("`{}` = {}", symbol.name, value);
+    }
+
+    let index = analysis
+        .linked
+        .as_ref()
+        .and_then(|linked| linked.labels.get(&symbol.name));
+
+    match index {
+        Some(index) => format!("`{}` labels instruction {}", symbol.name, index),
+        None => format!("`{}` is a label", symbol.name),
+    }
+}
+
+/// Describes the instruction on the line, counting from `0`, if there is one
+/// and the document links
+fn describe_line(line: usize, analysis: &Analysis) -> Option<String> {
+    let linked = analysis.linked.as_ref()?;
+    let index = linked.lines.iter().position(|&(_, at)| at == line + 1)?;
+
+    let instructions = linked.program.instructions();
+    let instruction = instructions[index];
+    let opcode = isa::lookup(&instruction);
+    let entering = analyses::entering(instructions)[index];
+
+    let register = match entering.register {
+        Some(register) => format!("the register at {}", register),
+        None => "the register unknown until it runs".to_string(),
+    };
+
+    Some(format!(
+        "**{}** ({}): {}\n\nStarts on cycle {} with {}.",
+        instruction,
+        cost(opcode),
+        opcode.summary,
+        entering.cycle,
+        register
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constants_are_found_past_their_directive() {
+        for (code, column) in [(".equ e 1", 5), (".equ q 1", 5), ("  .equ u 2", 7)] {
+            let symbols = symbols(code);
+
+            assert_eq!(symbols.len(), 1, "{}", code);
+            assert_eq!(symbols[0].column, column, "{}", code);
+        }
+
+        assert_eq!(symbols("  loop: noop")[0].column, 2);
+    }
+
+    #[test]
+    fn hovers_past_multibyte_characters() {
+        let text = "noop ; h\u{e9}llo \u{1f600} start\nstart: addx 5\n";
+        let messages = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#.to_string(),
+            format!(
+                r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///a.asm","text":{:?}}}}}}}"#,
+                text
+            ),
+            // Past the `é`, then halfway through the emoji, which is one
+            // character but two UTF-16 code units, then the space after it
+            hover(2, 0, 9),
+            hover(3, 0, 14),
+            hover(4, 0, 15),
+            // The `s` after that
+            hover(5, 0, 16),
+            r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+        ];
+
+        let output = serve(&messages);
+        let label = r#"{"contents":{"kind":"markdown","value":"`start` labels instruction 1"}}"#;
+
+        for id in 2..=4 {
+            assert!(output.contains(&format!(r#""id":{},"result""#, id)));
+            assert!(!output.contains(&format!(r#""id":{},"result":{}"#, id, label)));
+        }
+
+        assert!(output.contains(&format!(r#""id":5,"result":{}"#, label)));
+    }
+
+    #[test]
+    fn warns_about_handlers_that_may_loop() {
+        let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"initializationOptions":{"interrupts":[{"priority":1,"handler":"noop\nstx 4","triggers":[3]},{"priority":2,"handler":"stx 3","triggers":[4]},{"handler":"fly"}]}}}"#;
+        let messages = [
+            initialize.to_string(),
+            format!(
+                r#"{{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{{"textDocument":{{"uri":"file:///a.asm","text":{:?}}}}}}}"#,
+                "noop\naddx 1\nstx 3\n"
+            ),
+            r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+        ];
+
+        let output = serve(&messages);
+
+        assert!(output.contains(r#"{"range":{"start":{"line":2,"character":0},"end":{"line":2,"character":5}},"severity":2,"source":"legolos","message":"may never finish: the handlers of lines 0 and 1 can keep setting each other off"}"#), "{}", output);
+        assert!(output.contains(r#""message":"interrupt 2's handler doesn't assemble: "#));
+    }
+
+    /// Serves the messages, framed, and returns everything sent back
+    fn serve(messages: &[String]) -> String {
+        let input: String = messages
+            .iter()
+            .map(|message| format!("Content-Length: {}\r\n\r\n{}", message.len(), message))
+            .collect();
+        let mut output = Vec::new();
+
+        LspServer::new(input.as_bytes(), &mut output)
+            .serve()
+            .unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    fn hover(id: usize, line: usize, character: usize) -> String {
+        format!(
+            r#"{{"jsonrpc":"2.0","id":{},"method":"textDocument/hover","params":{{"textDocument":{{"uri":"file:///a.asm"}},"position":{{"line":{},"character":{}}}}}}}"#,
+            id, line, character
+        )
+    }
+}
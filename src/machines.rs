@@ -1,6 +1,198 @@
-use std::collections::VecDeque;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display, Formatter};
 
+use crate::devices::{Bus, BusError, Device, Keyboard};
 use crate::instructions::Instruction;
+use crate::interrupts::{InterruptController, Servicing};
+use crate::recorders::FlightRecorder;
+
+mod fixed;
+
+pub use fixed::{CapacityError, FixedMachine};
+
+/// Anything that can drive a `Screen`: a source of register values that
+/// advances one CPU cycle at a time. `VirtualMachine` is the real thing, but a
+/// screen only needs to know where the sprite is on every cycle.
+pub trait RegisterSource {
+    /// Advances the source by a single CPU cycle
+    fn cycle(&mut self);
+
+    /// Returns the register's value during the current cycle
+    fn read_register(&self) -> isize;
+
+    /// Returns the current cycle number. It starts at `1`.
+    fn ticks(&self) -> usize;
+
+    /// Returns `false` once there is nothing left to cycle
+    fn is_executing(&self) -> bool;
+
+    /// Returns the position in the program of the instruction occupying the
+    /// current cycle, if the source knows of one
+    fn instruction_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns what the program sent out during the last cycle, if the source
+    /// runs a program that can
+    fn signal(&self) -> Option<Signal> {
+        None
+    }
+
+    /// Returns the interrupt line whose handler occupies the current cycle,
+    /// if the source takes interrupts and is servicing one
+    fn interrupt(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Borrowing a source is as good as owning one, e.g. to drive a machine for
+/// a while and then keep it
+impl<M: RegisterSource + ?Sized> RegisterSource for &mut M {
+    fn cycle(&mut self) {
+        M::cycle(self)
+    }
+
+    fn read_register(&self) -> isize {
+        M::read_register(self)
+    }
+
+    fn ticks(&self) -> usize {
+        M::ticks(self)
+    }
+
+    fn is_executing(&self) -> bool {
+        M::is_executing(self)
+    }
+
+    fn instruction_index(&self) -> Option<usize> {
+        M::instruction_index(self)
+    }
+
+    fn signal(&self) -> Option<Signal> {
+        M::signal(self)
+    }
+
+    fn interrupt(&self) -> Option<usize> {
+        M::interrupt(self)
+    }
+}
+
+/// Something a program sends out of the machine for a device to act on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// An `out` instruction sent the register's value
+    Out(isize),
+
+    /// A `tone` instruction asked for the register's value as a frequency (in
+    /// hertz), lasting the given number of cycles
+    Tone { frequency: isize, duration: usize },
+}
+
+/// Describes the signal briefly, e.g. `out 4` or `tone 440Hz for 2 cycles`
+impl Display for Signal {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Signal::Out(value) => write!(formatter, "out {}", value),
+            Signal::Tone {
+                frequency,
+                duration,
+            } => write!(formatter, "tone {}Hz for {} cycles", frequency, duration),
+        }
+    }
+}
+
+/// What a custom instruction does, for the machine to call when it executes
+/// one. It takes a single cycle, like most instructions.
+pub trait CustomOp {
+    /// Returns the register's new value, given its value during the cycle and
+    /// the instruction's operand
+    fn execute(&mut self, register: isize, operand: isize) -> isize;
+}
+
+/// A closure is as good an op as any
+impl<F: FnMut(isize, isize) -> isize> CustomOp for F {
+    fn execute(&mut self, register: isize, operand: isize) -> isize {
+        self(register, operand)
+    }
+}
+
+/// The custom ops a machine knows, by opcode
+#[derive(Default)]
+struct CustomOps(BTreeMap<u8, Box<dyn CustomOp>>);
+
+/// Ops can't be printed, so show their opcodes instead
+impl Debug for CustomOps {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+/// Why the machine couldn't do what it was asked. Running off the ends of
+/// the register isn't one of them: like the register of a real CPU, it wraps
+/// round, so `addx 1` with the register at `isize::MAX` leaves it at
+/// `isize::MIN`. Every way of running a program does the same.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmError {
+    /// The program had finished, so there was nothing to cycle. `ticks` is
+    /// the cycle that never ran.
+    Finished { ticks: usize },
+
+    /// A device couldn't be attached to the machine's bus
+    Bus(BusError),
+}
+
+impl From<BusError> for VmError {
+    fn from(error: BusError) -> Self {
+        VmError::Bus(error)
+    }
+}
+
+impl Display for VmError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VmError::Finished { ticks } => {
+                write!(formatter, "the program finished before cycle {}", ticks)
+            }
+            VmError::Bus(error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+impl core::error::Error for VmError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            VmError::Finished { .. } => None,
+            VmError::Bus(error) => Some(error),
+        }
+    }
+}
+
+/// A machine's state between two cycles, for `VirtualMachine::restore()` to
+/// go back to. It's small, so debuggers can take one every so often and
+/// travel back in time without replaying the whole program. It leaves out
+/// whatever is attached to the machine: devices, and interrupt lines, keep
+/// their own state.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    program: Arc<[Instruction]>,
+    servicing: Vec<Servicing>,
+    in_flight: Option<Instruction>,
+    register: isize,
+    ticks: usize,
+    scheduled: usize,
+    signal: Option<Signal>,
+    recorder: FlightRecorder,
+}
+
+impl Checkpoint {
+    /// Returns the cycle the machine was about to run
+    pub fn ticks(&self) -> usize {
+        self.ticks
+    }
+}
 
 /// A virtual machine executes a sequence of `Instruction`s (i.e. a program). It
 /// maintains the value of a single register. Since some instructions take
@@ -9,8 +201,8 @@ use crate::instructions::Instruction;
 #[derive(Debug)]
 pub struct VirtualMachine {
     /// The program is a sequence of instructions that will be executed
-    /// sequentially.
-    program: VecDeque<Instruction>,
+    /// sequentially. It's only read, so any number of machines can share it.
+    program: Arc<[Instruction]>,
 
     /// An in-flight instruction is currently executing
     in_flight: Option<Instruction>,
@@ -21,15 +213,58 @@ pub struct VirtualMachine {
     /// Stores how many cycles this VM has executed. It is initially `0` and
     /// increases by one every time the CPU cycles (i.e. `cycle()` is called).
     ticks: usize,
+
+    /// The position in the program of the next instruction to be scheduled
+    scheduled: usize,
+
+    /// What the program sent out during the last cycle, if anything
+    signal: Option<Signal>,
+
+    /// The devices the program can load from and store to
+    bus: Bus,
+
+    /// Where `in` takes key presses from, if anywhere
+    keyboard: Option<Keyboard>,
+
+    /// What custom instructions do
+    ops: CustomOps,
+
+    /// The last cycles, for looking back after a fault
+    recorder: FlightRecorder,
+
+    /// Which interrupts to service, and how
+    interrupts: InterruptController,
+
+    /// The interrupts being serviced, innermost last. While there are any,
+    /// `program` is the innermost's handler.
+    servicing: Vec<Servicing>,
 }
 
 impl VirtualMachine {
     pub fn new(program: VecDeque<Instruction>) -> Self {
+        VirtualMachine::shared(Vec::from(program).into())
+    }
+
+    /// Creates a machine that runs a copy of the instructions. Construction
+    /// is where the machine allocates, once for the copy and once for its
+    /// flight recorder, which is made as big as it'll get. After that the
+    /// machine walks the program with a cursor, so cycling never touches the
+    /// heap. Neither does servicing interrupts, as long as their lines are
+    /// added before `with_interrupts()`; see there. `tests/allocations.rs`
+    /// counts.
+    pub fn from_slice(program: &[Instruction]) -> Self {
+        VirtualMachine::shared(program.into())
+    }
+
+    /// Creates a machine that runs a program others may be running too,
+    /// without a copy of its own. `Program::machine()` shares its program
+    /// this way, so a batch of thousands of machines needs only the one.
+    pub fn shared(program: Arc<[Instruction]>) -> Self {
         let in_flight = None;
 
-        // Start at tick one then increment after completing a cycle.
-        //
-        // TODO This problem begs for property-directed testing!
+        // Start at tick one then increment after completing a cycle. The
+        // `properties` module (with the `test-util` feature) checks this, and
+        // the rest of the machine, against a reference model.
         let ticks = 1;
 
         VirtualMachine {
@@ -37,13 +272,194 @@ impl VirtualMachine {
             in_flight,
             ticks,
             register: 1, // Initially `1` by specification
+            scheduled: 0,
+            signal: None,
+            bus: Bus::new(),
+            keyboard: None,
+            ops: CustomOps::default(),
+            recorder: FlightRecorder::default(),
+            interrupts: InterruptController::new(),
+            servicing: Vec::new(),
         }
     }
 
+    /// Replaces the program and starts over, as if the machine were new.
+    /// Attached devices, the keyboard, and custom ops stay; see `reset()`.
+    pub fn load(&mut self, program: Arc<[Instruction]>) {
+        self.servicing.clear();
+        self.program = program;
+        self.reset();
+    }
+
+    /// Starts the program over from its first cycle, without giving up the
+    /// flight recorder's room. Attached devices, the keyboard, and custom ops
+    /// stay as they are, along with whatever state they keep.
+    pub fn reset(&mut self) {
+        if let Some(outermost) = self.servicing.first() {
+            self.program = Arc::clone(&outermost.program);
+        }
+
+        self.servicing.clear();
+        self.in_flight = None;
+        self.register = 1;
+        self.ticks = 1;
+        self.scheduled = 0;
+        self.signal = None;
+        self.recorder.clear();
+    }
+
+    /// Returns the machine's state, to `restore()` later
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            program: Arc::clone(&self.program),
+            servicing: self.servicing.clone(),
+            in_flight: self.in_flight,
+            register: self.register,
+            ticks: self.ticks,
+            scheduled: self.scheduled,
+            signal: self.signal,
+            recorder: self.recorder.clone(),
+        }
+    }
+
+    /// Puts the machine back in the state of the checkpoint, which should
+    /// come from a machine running the same program. Attached devices, the
+    /// keyboard, and custom ops are left as they are.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) {
+        self.program = Arc::clone(&checkpoint.program);
+        self.servicing.clone_from(&checkpoint.servicing);
+        self.in_flight = checkpoint.in_flight;
+        self.register = checkpoint.register;
+        self.ticks = checkpoint.ticks;
+        self.scheduled = checkpoint.scheduled;
+        self.signal = checkpoint.signal;
+        self.recorder.clone_from(&checkpoint.recorder);
+    }
+
+    /// Detaches every device, the keyboard, the custom ops, and the interrupt
+    /// controller, leaving the machine as bare as a new one
+    pub(crate) fn detach_all(&mut self) {
+        self.bus = Bus::new();
+        self.keyboard = None;
+        self.ops = CustomOps::default();
+        self.interrupts = InterruptController::new();
+    }
+
+    /// Remembers the last `capacity` cycles rather than
+    /// `recorders::DEFAULT_CAPACITY`. The flight recorder is always on, but a
+    /// capacity of `0` turns it off.
+    pub fn with_flight_recorder(mut self, capacity: usize) -> Self {
+        self.recorder = FlightRecorder::new(capacity);
+        self
+    }
+
+    /// Returns the recorder of the last cycles, e.g. to dump after a fault
+    pub fn flight_recorder(&self) -> &FlightRecorder {
+        &self.recorder
+    }
+
+    /// Connects a keyboard for `in` to read from. Without one, `in` always
+    /// loads `0`, as if nothing was ever pressed.
+    pub fn with_keyboard(mut self, keyboard: Keyboard) -> Self {
+        self.keyboard = Some(keyboard);
+        self
+    }
+
+    /// Returns the connected keyboard, e.g. to press keys while the program
+    /// runs
+    pub fn keyboard_mut(&mut self) -> Option<&mut Keyboard> {
+        self.keyboard.as_mut()
+    }
+
+    /// Attaches a device to the machine's bus at the `length` addresses
+    /// starting at `start`. To look at the device afterwards, attach an
+    /// `Rc<RefCell<_>>` of it and keep a clone.
+    pub fn attach(
+        &mut self,
+        start: usize,
+        length: usize,
+        device: impl Device + 'static,
+    ) -> Result<(), VmError> {
+        Ok(self.bus.attach(start, length, device)?)
+    }
+
+    /// Connects an interrupt controller, whose lines run their handlers when
+    /// raised. Without one, the machine never leaves its program.
+    ///
+    /// Handlers only interrupt handlers of lower priority, so they nest no
+    /// deeper than there are lines, and the machine makes room for that many
+    /// now rather than while cycling. Lines added later, through
+    /// `interrupts_mut()`, may have to make room when they're first serviced.
+    pub fn with_interrupts(mut self, interrupts: InterruptController) -> Self {
+        self.servicing.reserve(interrupts.len());
+        self.interrupts = interrupts;
+        self
+    }
+
+    /// Returns the interrupt controller, e.g. to raise or mask its lines
+    pub fn interrupts(&self) -> &InterruptController {
+        &self.interrupts
+    }
+
+    /// Returns the interrupt controller mutably, e.g. to mask its lines
+    pub fn interrupts_mut(&mut self) -> &mut InterruptController {
+        &mut self.interrupts
+    }
+
+    /// Returns the line of the interrupt being serviced, the innermost if
+    /// handlers are nested
+    pub fn interrupt(&self) -> Option<usize> {
+        self.servicing.last().map(|servicing| servicing.line)
+    }
+
+    /// Returns the lines of every interrupt being serviced, outermost first
+    pub fn interrupts_servicing(&self) -> impl Iterator<Item = usize> + '_ {
+        self.servicing.iter().map(|servicing| servicing.line)
+    }
+
+    /// Gives custom instructions with the opcode something to do, replacing
+    /// whatever they did before. Like an address nobody owns on the bus, an
+    /// opcode nobody defined does nothing.
+    pub fn define_op(&mut self, opcode: u8, op: impl CustomOp + 'static) {
+        self.ops.0.insert(opcode, Box::new(op));
+    }
+
+    /// Replaces the machine's bus, and every device on it, e.g. with one laid
+    /// out by an `AddressMap`
+    pub fn with_bus(mut self, bus: Bus) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    /// Returns the bus of devices attached to the machine
+    pub fn bus(&self) -> &Bus {
+        &self.bus
+    }
+
+    /// Returns the bus mutably, e.g. to poke a device outside the program
+    pub fn bus_mut(&mut self) -> &mut Bus {
+        &mut self.bus
+    }
+
+    /// Appends an instruction to the end of the program. A machine that had
+    /// finished picks up where it left off, e.g. when instructions are typed
+    /// in one at a time. The program is copied to make room, leaving anyone
+    /// sharing it undisturbed, so this is for short programs.
+    pub fn push_instruction(&mut self, instruction: Instruction) {
+        let outermost = match self.servicing.first_mut() {
+            Some(servicing) => &mut servicing.program,
+            None => &mut self.program,
+        };
+
+        let mut program = outermost.to_vec();
+        program.push(instruction);
+        *outermost = program.into();
+    }
+
     /// Returns `false` when the program has finished executing (i.e. all
     /// instructions) have completed.
     pub fn is_executing(&self) -> bool {
-        !self.program.is_empty() || !self.in_flight.is_none()
+        self.scheduled < self.program.len() || self.in_flight.is_some()
     }
 
     /// Return the value currently stored in the register. When instructions
@@ -58,20 +474,106 @@ impl VirtualMachine {
         self.ticks
     }
 
+    /// Returns what the program sent out during the last cycle, e.g. the
+    /// register's value from an `out` instruction. Devices check it after
+    /// every cycle; it's gone after the next one.
+    pub fn signal(&self) -> Option<Signal> {
+        self.signal
+    }
+
+    /// Returns the position in the program of the instruction occupying the
+    /// current cycle. An in-flight instruction was the last one scheduled.
+    /// Otherwise it's the one about to be, if any are left. Handlers aren't
+    /// part of the program, so there's none while one runs; see
+    /// `interrupt()`.
+    pub fn instruction_index(&self) -> Option<usize> {
+        if !self.servicing.is_empty() {
+            None
+        } else if self.in_flight.is_some() {
+            Some(self.scheduled - 1)
+        } else if self.scheduled < self.program.len() {
+            Some(self.scheduled)
+        } else {
+            None
+        }
+    }
+
     /// Cycles the CPU by executing the next instruction. This will increase
     /// the cycle counter and possibly the register (depending on the
-    /// instruction).
+    /// instruction). Once the program has finished it does nothing; see
+    /// `try_cycle()` to find out.
     ///
     /// NB the cycle counter (i.e. `ticks`) is incremented only after the cycle
     /// is complete.
     pub fn cycle(&mut self) {
-        if self.in_flight.is_none() {
-            self.schedule();
-        } else {
-            self.execute();
+        let _ = self.try_cycle();
+    }
+
+    /// Cycles the CPU like `cycle()`, or returns `VmError::Finished` without
+    /// touching the machine if there's nothing left to execute
+    pub fn try_cycle(&mut self) -> Result<(), VmError> {
+        if !self.is_executing() {
+            return Err(VmError::Finished { ticks: self.ticks });
         }
 
+        self.signal = None;
+
+        let register = self.register;
+        let index = self.instruction_index();
+        let mut retired = None;
+
+        if let Some(instruction) = self.in_flight.take() {
+            self.execute(instruction);
+            retired = Some(instruction);
+        } else if let Some(&instruction) = self.program.get(self.scheduled) {
+            self.schedule(instruction);
+
+            if self.in_flight.is_none() {
+                retired = Some(instruction);
+            }
+        }
+
+        let retired = index.zip(retired);
+        self.recorder.record(self.ticks, register, retired);
+
+        self.bus.tick();
         self.ticks += 1;
+
+        if self.in_flight.is_none() {
+            self.settle();
+        }
+
+        Ok(())
+    }
+
+    /// Between two instructions, returns from handlers that have finished and
+    /// starts the most urgent interrupt that's due, until neither is left to
+    /// do. It takes no cycles.
+    fn settle(&mut self) {
+        loop {
+            let running = self.servicing.last().map(|servicing| servicing.priority);
+
+            if let Some((line, priority, handler)) = self.interrupts.take(running) {
+                let program = core::mem::replace(&mut self.program, handler);
+
+                self.servicing.push(Servicing {
+                    line,
+                    priority,
+                    program,
+                    scheduled: self.scheduled,
+                });
+                self.scheduled = 0;
+            } else if self.scheduled >= self.program.len() {
+                let Some(servicing) = self.servicing.pop() else {
+                    return;
+                };
+
+                self.program = servicing.program;
+                self.scheduled = servicing.scheduled;
+            } else {
+                return;
+            }
+        }
     }
 
     /// An instruction is currently executing. In this architecture, that means
@@ -83,29 +585,186 @@ impl VirtualMachine {
     ///
     /// This pattern would require refactoring if more instructions with varying
     /// execution lengths are added.
-    fn execute(&mut self) {
-        let instruction = self.in_flight.unwrap();
-
+    fn execute(&mut self, instruction: Instruction) {
         match instruction {
-            Instruction::Noop => (),
-            Instruction::Addx(number) => self.register += number,
+            Instruction::Noop
+            | Instruction::Out
+            | Instruction::Tone(_)
+            | Instruction::Ldx(_)
+            | Instruction::Stx(_)
+            | Instruction::In
+            | Instruction::Custom(..) => (),
+            Instruction::Addx(number) => self.register = self.register.wrapping_add(number),
         }
-
-        self.in_flight = None;
     }
 
-    /// No instructions are currently executing. Pull the next one from the
+    /// No instructions are currently executing. Start the next one from the
     /// program and execute or schedule it depending on the type.
-    fn schedule(&mut self) {
-        let instruction = self.program.pop_front();
-        let instruction = instruction.unwrap();
+    fn schedule(&mut self, instruction: Instruction) {
+        self.scheduled += 1;
 
         // `Noop` instructions take a single cycle to execute and have no side
-        // effects. Adding takes two cycles, so the instruction is scheduled to
-        // complete on the next cycle.
+        // effects. Output and tones also take a single cycle and send the
+        // register as it is during this one. So do loads and stores, which
+        // land on the bus right away, input, which doesn't wait for a key, and
+        // custom instructions. Adding takes two cycles, so the instruction is
+        // scheduled to complete on the next cycle.
         match instruction {
             Instruction::Noop => (),
+            Instruction::Out => self.signal = Some(Signal::Out(self.register)),
+            Instruction::Tone(duration) => {
+                self.signal = Some(Signal::Tone {
+                    frequency: self.register,
+                    duration,
+                })
+            }
+            Instruction::Ldx(address) => self.register = self.bus.read(address),
+            Instruction::Stx(address) => self.bus.write(address, self.register),
+            Instruction::In => {
+                let key = self.keyboard.as_mut().and_then(Keyboard::next_key);
+                self.register = key.map_or(0, |key| key as isize);
+            }
+            Instruction::Custom(opcode, operand) => {
+                if let Some(op) = self.ops.0.get_mut(&opcode) {
+                    self.register = op.execute(self.register, operand);
+                }
+            }
             Instruction::Addx(_) => self.in_flight = Some(instruction),
         }
     }
 }
+
+/// An idle machine with an empty program
+impl Default for VirtualMachine {
+    fn default() -> Self {
+        VirtualMachine::new(VecDeque::new())
+    }
+}
+
+impl RegisterSource for VirtualMachine {
+    fn cycle(&mut self) {
+        VirtualMachine::cycle(self)
+    }
+
+    fn read_register(&self) -> isize {
+        VirtualMachine::read_register(self)
+    }
+
+    fn ticks(&self) -> usize {
+        self.get_ticks()
+    }
+
+    fn is_executing(&self) -> bool {
+        VirtualMachine::is_executing(self)
+    }
+
+    fn instruction_index(&self) -> Option<usize> {
+        VirtualMachine::instruction_index(self)
+    }
+
+    fn signal(&self) -> Option<Signal> {
+        VirtualMachine::signal(self)
+    }
+
+    fn interrupt(&self) -> Option<usize> {
+        VirtualMachine::interrupt(self)
+    }
+}
+
+/// A register source that replays a scripted sequence of values instead of
+/// executing a program. The value at index `i` is the register during cycle
+/// `i + 1`. It's handy for driving a `Screen` without writing instructions.
+#[derive(Debug, Clone)]
+pub struct ScriptedRegisters {
+    values: Vec<isize>,
+    ticks: usize,
+}
+
+impl ScriptedRegisters {
+    pub fn new(values: Vec<isize>) -> Self {
+        ScriptedRegisters { values, ticks: 1 }
+    }
+}
+
+/// An empty script, starting on the first cycle like any other
+impl Default for ScriptedRegisters {
+    fn default() -> Self {
+        ScriptedRegisters::new(Vec::new())
+    }
+}
+
+impl RegisterSource for ScriptedRegisters {
+    fn cycle(&mut self) {
+        self.ticks += 1;
+    }
+
+    /// Once the script runs out the last value sticks, just like a machine's
+    /// register holds its value after the program finishes. An empty script
+    /// reads as the machine's initial value of `1`.
+    fn read_register(&self) -> isize {
+        let index = self
+            .ticks
+            .saturating_sub(1)
+            .min(self.values.len().saturating_sub(1));
+
+        self.values.get(index).copied().unwrap_or(1)
+    }
+
+    fn ticks(&self) -> usize {
+        self.ticks
+    }
+
+    fn is_executing(&self) -> bool {
+        self.ticks <= self.values.len()
+    }
+}
+
+/// A register source that never executes. It stands in for a machine when a
+/// screen is drawn from something else, like a recorded timeline or text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Detached;
+
+impl RegisterSource for Detached {
+    fn cycle(&mut self) {}
+
+    /// The register of a machine that never ran
+    fn read_register(&self) -> isize {
+        1
+    }
+
+    fn ticks(&self) -> usize {
+        1
+    }
+
+    fn is_executing(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screens::{Refresh, Screen};
+    use alloc::vec;
+
+    #[test]
+    fn register_wraps_past_its_ends() {
+        let mut machine = VirtualMachine::new(VecDeque::from(vec![
+            Instruction::Addx(isize::MAX),
+            Instruction::Addx(1),
+        ]));
+
+        while machine.is_executing() {
+            machine.cycle();
+        }
+
+        assert_eq!(machine.read_register(), isize::MIN + 1);
+    }
+
+    #[test]
+    fn default_scripted_registers_start_on_the_first_cycle() {
+        let mut screen = Screen::new(ScriptedRegisters::default());
+
+        assert_eq!(screen.refresh(), Ok(Refresh::Completed));
+    }
+}
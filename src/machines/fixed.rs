@@ -0,0 +1,214 @@
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use super::{RegisterSource, Signal};
+use crate::instructions::Instruction;
+
+/// A machine whose program lives in an array of `N` instructions rather than
+/// on the heap. It never allocates, so it suits targets without an allocator,
+/// and hot loops that run many short programs: `load()` swaps the program
+/// without giving up the storage.
+///
+/// It runs programs exactly like `VirtualMachine`, except that there's no bus,
+/// keyboard, or custom instruction to attach: `ldx` and `in` load `0`, and
+/// `stx` and custom instructions do nothing.
+#[derive(Debug, Clone)]
+pub struct FixedMachine<const N: usize> {
+    program: [Instruction; N],
+
+    /// How much of `program` is the program; the rest is unused
+    length: usize,
+
+    /// The position of the next instruction to be scheduled
+    next: usize,
+
+    in_flight: Option<Instruction>,
+    register: isize,
+    ticks: usize,
+    signal: Option<Signal>,
+}
+
+/// The program didn't fit in the machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError {
+    /// How many instructions the machine holds
+    pub capacity: usize,
+
+    /// How many instructions the program has
+    pub length: usize,
+}
+
+impl<const N: usize> FixedMachine<N> {
+    /// Creates a machine ready to run the program, unless it has more than
+    /// `N` instructions
+    pub fn new(program: &[Instruction]) -> Result<Self, CapacityError> {
+        let mut machine = FixedMachine {
+            program: [Instruction::Noop; N],
+            length: 0,
+            next: 0,
+            in_flight: None,
+            register: 1,
+            ticks: 1,
+            signal: None,
+        };
+
+        machine.load(program)?;
+        Ok(machine)
+    }
+
+    /// Replaces the program and starts over, as if the machine were new. A
+    /// program that doesn't fit leaves the machine as it was.
+    pub fn load(&mut self, program: &[Instruction]) -> Result<(), CapacityError> {
+        if program.len() > N {
+            return Err(CapacityError {
+                capacity: N,
+                length: program.len(),
+            });
+        }
+
+        self.program[..program.len()].copy_from_slice(program);
+        self.program[program.len()..].fill(Instruction::Noop);
+        self.length = program.len();
+        self.reset();
+
+        Ok(())
+    }
+
+    /// Starts the program over from its first cycle
+    pub fn reset(&mut self) {
+        self.next = 0;
+        self.in_flight = None;
+        self.register = 1;
+        self.ticks = 1;
+        self.signal = None;
+    }
+
+    /// Returns the program being run
+    pub fn program(&self) -> &[Instruction] {
+        &self.program[..self.length]
+    }
+
+    pub fn is_executing(&self) -> bool {
+        self.next < self.length || self.in_flight.is_some()
+    }
+
+    pub fn read_register(&self) -> isize {
+        self.register
+    }
+
+    pub fn get_ticks(&self) -> usize {
+        self.ticks
+    }
+
+    pub fn signal(&self) -> Option<Signal> {
+        self.signal
+    }
+
+    pub fn instruction_index(&self) -> Option<usize> {
+        if self.in_flight.is_some() {
+            Some(self.next - 1)
+        } else if self.next < self.length {
+            Some(self.next)
+        } else {
+            None
+        }
+    }
+
+    /// Cycles the CPU, just like `VirtualMachine::cycle()`. Once the program
+    /// has finished it does nothing.
+    pub fn cycle(&mut self) {
+        if !self.is_executing() {
+            return;
+        }
+
+        self.signal = None;
+
+        match self.in_flight.take() {
+            Some(Instruction::Addx(number)) => self.register = self.register.wrapping_add(number),
+            Some(_) => (),
+            None => self.schedule(),
+        }
+
+        self.ticks += 1;
+    }
+
+    fn schedule(&mut self) {
+        let instruction = self.program[self.next];
+        self.next += 1;
+
+        match instruction {
+            Instruction::Noop | Instruction::Stx(_) | Instruction::Custom(..) => (),
+            Instruction::Out => self.signal = Some(Signal::Out(self.register)),
+            Instruction::Tone(duration) => {
+                self.signal = Some(Signal::Tone {
+                    frequency: self.register,
+                    duration,
+                })
+            }
+            Instruction::Ldx(_) | Instruction::In => self.register = 0,
+            Instruction::Addx(_) => self.in_flight = Some(instruction),
+        }
+    }
+}
+
+impl<const N: usize> RegisterSource for FixedMachine<N> {
+    fn cycle(&mut self) {
+        FixedMachine::cycle(self)
+    }
+
+    fn read_register(&self) -> isize {
+        FixedMachine::read_register(self)
+    }
+
+    fn ticks(&self) -> usize {
+        self.get_ticks()
+    }
+
+    fn is_executing(&self) -> bool {
+        FixedMachine::is_executing(self)
+    }
+
+    fn instruction_index(&self) -> Option<usize> {
+        FixedMachine::instruction_index(self)
+    }
+
+    fn signal(&self) -> Option<Signal> {
+        FixedMachine::signal(self)
+    }
+}
+
+impl Display for CapacityError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            formatter,
+            "a program of {} instructions doesn't fit in a machine for {}",
+            self.length, self.capacity
+        )
+    }
+}
+
+impl Error for CapacityError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_after_a_shorter_program() {
+        let mut machine = FixedMachine::<4>::new(&[
+            Instruction::Addx(10),
+            Instruction::Addx(10),
+            Instruction::Noop,
+        ])
+        .unwrap();
+        machine.load(&[Instruction::Noop]).unwrap();
+
+        for _ in 0..10 {
+            machine.cycle();
+        }
+
+        assert!(!machine.is_executing());
+        assert_eq!(machine.read_register(), 1);
+        assert_eq!(machine.get_ticks(), 2);
+    }
+}
@@ -1,160 +1,23 @@
-use crate::{machines::VirtualMachine, screens::Screen};
+use std::env;
+use std::io::{self, ErrorKind};
+use std::process::ExitCode;
 
-mod instructions;
-mod machines;
-mod screens;
+mod commands;
 
-fn main() {
-    let input = SAMPLE;
-    let program = instructions::parse_instructions(input);
-    let machine = VirtualMachine::new(program);
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let mut screen = Screen::new(machine);
-    screen.refresh();
+    match commands::dispatch(&args, &mut io::stdout().lock()) {
+        Ok(status) => ExitCode::from(status),
 
-    println!("{}", screen);
-}
+        // Whoever was reading has seen all they wanted, e.g. `head`
+        Err(commands::CommandError::Output(error)) if error.kind() == ErrorKind::BrokenPipe => {
+            ExitCode::SUCCESS
+        }
 
-const SAMPLE: &str = r"noop
-noop
-addx 5
-noop
-noop
-addx 6
-addx 4
-addx -4
-addx 4
-addx -6
-addx 11
-addx -1
-addx 2
-addx 4
-addx 3
-noop
-addx 2
-addx -30
-addx 2
-addx 33
-noop
-addx -37
-noop
-noop
-noop
-addx 3
-addx 2
-addx 5
-addx 20
-addx 7
-addx -24
-addx 2
-noop
-addx 7
-addx -2
-addx -6
-addx 13
-addx 3
-addx -2
-addx 2
-noop
-addx -5
-addx 10
-addx 5
-addx -39
-addx 1
-addx 5
-noop
-addx 3
-noop
-addx -5
-addx 10
-addx -2
-addx 2
-noop
-noop
-addx 7
-noop
-noop
-noop
-noop
-addx 3
-noop
-addx 3
-addx 2
-addx 8
-addx -1
-addx -20
-addx 21
-addx -38
-addx 5
-addx 2
-noop
-noop
-noop
-addx 8
-noop
-noop
-addx -2
-addx 2
-addx -7
-addx 14
-addx 5
-noop
-noop
-noop
-addx -16
-addx 17
-addx 2
-addx -12
-addx 19
-noop
-noop
-addx -37
-noop
-noop
-noop
-addx 3
-addx 2
-addx 2
-addx 5
-addx 20
-addx -19
-addx 2
-noop
-noop
-noop
-addx 5
-addx 19
-addx -12
-addx 3
-addx -2
-addx 2
-addx -18
-addx 25
-addx -14
-addx -22
-addx 1
-noop
-noop
-noop
-addx 3
-addx 5
-addx -4
-addx 7
-addx 4
-noop
-addx 1
-noop
-noop
-addx 2
-addx -6
-addx 15
-addx -1
-addx 4
-noop
-noop
-addx 1
-addx 4
-addx -33
-noop
-addx 21
-noop";
\ No newline at end of file
+        Err(error) => {
+            eprintln!("legolos: {}", error);
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
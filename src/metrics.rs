@@ -0,0 +1,176 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+/// Counts what a long batch run is doing, for monitoring it while it goes.
+/// Everything is atomic, so machines running in parallel share one set of
+/// metrics and a server thread can read them at any time.
+#[derive(Debug)]
+pub struct Metrics {
+    started: Instant,
+    machines_started: AtomicU64,
+    machines_finished: AtomicU64,
+    cycles: AtomicU64,
+    faults: AtomicU64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Metrics {
+            started: Instant::now(),
+            machines_started: AtomicU64::new(0),
+            machines_finished: AtomicU64::new(0),
+            cycles: AtomicU64::new(0),
+            faults: AtomicU64::new(0),
+        }
+    }
+
+    /// Counts a machine starting to run a program
+    pub fn record_start(&self) {
+        self.machines_started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a machine finishing, after running the given number of cycles
+    pub fn record_finish(&self, cycles: usize) {
+        self.cycles.fetch_add(cycles as u64, Ordering::Relaxed);
+        self.machines_finished.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a program that couldn't be loaded or run
+    pub fn record_fault(&self) {
+        self.faults.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns how many machines have started and not yet finished
+    pub fn machines_running(&self) -> u64 {
+        let finished = self.machines_finished.load(Ordering::Relaxed);
+        let started = self.machines_started.load(Ordering::Relaxed);
+
+        started.saturating_sub(finished)
+    }
+
+    /// Returns how many cycles every finished machine ran between them
+    pub fn cycles(&self) -> u64 {
+        self.cycles.load(Ordering::Relaxed)
+    }
+
+    /// Returns how many faults have been counted
+    pub fn faults(&self) -> u64 {
+        self.faults.load(Ordering::Relaxed)
+    }
+
+    /// Returns the cycles run per second since the metrics were created
+    pub fn cycles_per_second(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+
+        match elapsed > 0.0 {
+            true => self.cycles() as f64 / elapsed,
+            false => 0.0,
+        }
+    }
+
+    /// Writes the metrics in Prometheus's text exposition format
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+
+        let mut metric = |name: &str, kind: &str, help: &str, value: &dyn std::fmt::Display| {
+            let _ = writeln!(text, "# HELP legolos_{} {}", name, help);
+            let _ = writeln!(text, "# TYPE legolos_{} {}", name, kind);
+            let _ = writeln!(text, "legolos_{} {}", name, value);
+        };
+
+        metric(
+            "machines_started_total",
+            "counter",
+            "Machines that have started running a program.",
+            &self.machines_started.load(Ordering::Relaxed),
+        );
+        metric(
+            "machines_finished_total",
+            "counter",
+            "Machines that have finished running a program.",
+            &self.machines_finished.load(Ordering::Relaxed),
+        );
+        metric(
+            "machines_running",
+            "gauge",
+            "Machines running a program right now.",
+            &self.machines_running(),
+        );
+        metric(
+            "cycles_total",
+            "counter",
+            "Cycles run by every finished machine.",
+            &self.cycles(),
+        );
+        metric(
+            "cycles_per_second",
+            "gauge",
+            "Cycles run per second since the run started.",
+            &self.cycles_per_second(),
+        );
+        metric(
+            "faults_total",
+            "counter",
+            "Programs that couldn't be loaded or run.",
+            &self.faults(),
+        );
+
+        text
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+/// Serves the metrics over HTTP at `/metrics`, for Prometheus to scrape, on a
+/// thread of its own. Returns the address it's listening on, which is handy
+/// when asked for port `0`. The thread lasts as long as the process.
+pub fn serve(metrics: Arc<Metrics>, address: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A scraper that hangs up early is its own problem
+            let _ = respond(&metrics, stream);
+        }
+    });
+
+    Ok(address)
+}
+
+/// Answers a single request. Only `GET /metrics` is anything but a 404.
+fn respond(metrics: &Metrics, mut stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+
+    // The headers don't matter, but they have to be read before answering
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut words = request.split_whitespace();
+    let (status, body) = match (words.next(), words.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render()),
+        _ => ("404 Not Found", "not found\n".to_string()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
@@ -0,0 +1,237 @@
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use crate::batches::{self, INTERESTING_CYCLES};
+use crate::instructions::Instruction;
+use crate::observers::{Cycle, CycleObserver, Fanout};
+use crate::programs::Program;
+use crate::screens::Frame;
+
+/// A single change to a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// The instruction at `index` was replaced with one with a different
+    /// operand
+    Operand { index: usize, to: Instruction },
+
+    /// The instructions at `index` and `index + 1` were swapped
+    Swap { index: usize },
+
+    /// The instruction at `index` was removed
+    Delete { index: usize },
+}
+
+/// A program with a single mutation applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutant {
+    pub mutation: Mutation,
+    pub program: Program,
+}
+
+/// What a mutant did differently from the original program. A mutant that
+/// changes neither survived: nothing checking the screen and signal
+/// strengths would notice it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub mutant: Mutant,
+
+    /// The mutant drew a different first frame
+    pub screen_changed: bool,
+
+    /// The mutant had a different signal strength at one of the interesting
+    /// cycles
+    pub signal_changed: bool,
+}
+
+/// How every mutant of a program fared
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub outcomes: Vec<Outcome>,
+}
+
+/// Generates every single-instruction mutant of the program, in order: for
+/// each instruction, its operand nudged up and down (or negated, for `addx`),
+/// then a swap with the next instruction if they differ, then its deletion.
+/// Operands that would go out of range, like a negative address, are skipped.
+pub fn mutants(program: &Program) -> Vec<Mutant> {
+    let instructions = program.instructions();
+    let mut mutants = Vec::new();
+
+    let mut add = |mutation: Mutation| {
+        let mut mutated = instructions.to_vec();
+
+        match mutation {
+            Mutation::Operand { index, to } => mutated[index] = to,
+            Mutation::Swap { index } => mutated.swap(index, index + 1),
+            Mutation::Delete { index } => {
+                mutated.remove(index);
+            }
+        }
+
+        mutants.push(Mutant {
+            mutation,
+            program: Program::new(mutated),
+        });
+    };
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        for to in tweaks(*instruction) {
+            add(Mutation::Operand { index, to });
+        }
+
+        if instructions
+            .get(index + 1)
+            .is_some_and(|next| next != instruction)
+        {
+            add(Mutation::Swap { index });
+        }
+
+        add(Mutation::Delete { index });
+    }
+
+    mutants
+}
+
+/// Returns the instruction with its operand changed every way worth trying
+fn tweaks(instruction: Instruction) -> Vec<Instruction> {
+    let nudge = |operand: usize, rebuild: fn(usize) -> Instruction| {
+        let mut tweaks = Vec::from([rebuild(operand + 1)]);
+
+        if let Some(smaller) = operand.checked_sub(1) {
+            tweaks.push(rebuild(smaller));
+        }
+
+        tweaks
+    };
+
+    match instruction {
+        Instruction::Addx(number) => {
+            let mut tweaks =
+                Vec::from([Instruction::Addx(number + 1), Instruction::Addx(number - 1)]);
+
+            if number != 0 {
+                tweaks.push(Instruction::Addx(-number));
+            }
+
+            tweaks
+        }
+        Instruction::Tone(operand) => nudge(operand, Instruction::Tone),
+        Instruction::Ldx(operand) => nudge(operand, Instruction::Ldx),
+        Instruction::Stx(operand) => nudge(operand, Instruction::Stx),
+        Instruction::Custom(opcode, operand) => Vec::from([
+            Instruction::Custom(opcode, operand + 1),
+            Instruction::Custom(opcode, operand - 1),
+        ]),
+        Instruction::Noop | Instruction::Out | Instruction::In => Vec::new(),
+    }
+}
+
+/// Runs the program and every one of its mutants, on machines with nothing
+/// attached, and reports which change the first frame or the signal
+/// strengths. A program whose mutants mostly survive is one a test checking
+/// only those would barely pin down. With the `rayon` feature the mutants
+/// run in parallel.
+pub fn mutate(program: &Program) -> Report {
+    let original = observe(program);
+
+    let outcomes = batches::map(&mutants(program), |mutant| {
+        let (frame, strengths) = observe(&mutant.program);
+
+        Outcome {
+            mutant: mutant.clone(),
+            screen_changed: frame != original.0,
+            signal_changed: strengths != original.1,
+        }
+    });
+
+    Report { outcomes }
+}
+
+/// Runs the program, returning its first frame and its signal strength at
+/// each interesting cycle it reaches
+fn observe(program: &Program) -> (Frame, Vec<isize>) {
+    let mut frame = Frame::new();
+    let mut strengths = Strengths(Vec::new());
+
+    Fanout::new(program.machine())
+        .with_observer(&mut frame)
+        .with_observer(&mut strengths)
+        .run();
+
+    (frame, strengths.0)
+}
+
+/// Records the signal strength at every interesting cycle
+struct Strengths(Vec<isize>);
+
+impl CycleObserver for Strengths {
+    fn observe(&mut self, cycle: &Cycle) {
+        if INTERESTING_CYCLES.contains(&cycle.number) {
+            self.0.push(cycle.number as isize * cycle.register);
+        }
+    }
+}
+
+impl Outcome {
+    /// Returns `true` if the mutant was noticed
+    pub fn killed(&self) -> bool {
+        self.screen_changed || self.signal_changed
+    }
+}
+
+impl Report {
+    /// Returns the mutants nothing noticed
+    pub fn survivors(&self) -> impl Iterator<Item = &Outcome> {
+        self.outcomes.iter().filter(|outcome| !outcome.killed())
+    }
+
+    /// Returns how many mutants were noticed
+    pub fn killed(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.killed())
+            .count()
+    }
+
+    /// Returns the share of mutants that were noticed, from `0.0` to `1.0`.
+    /// A program with no mutants scores `1.0`: there's nothing to miss.
+    pub fn score(&self) -> f64 {
+        match self.outcomes.len() {
+            0 => 1.0,
+            total => self.killed() as f64 / total as f64,
+        }
+    }
+}
+
+/// Describes the mutation, e.g. `line 3 deleted`. Lines count from `1`, like
+/// the assembler's.
+impl Display for Mutation {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Mutation::Operand { index, to } => {
+                write!(formatter, "line {}: operand changed to `{}`", index + 1, to)
+            }
+            Mutation::Swap { index } => {
+                write!(formatter, "lines {} and {} swapped", index + 1, index + 2)
+            }
+            Mutation::Delete { index } => write!(formatter, "line {} deleted", index + 1),
+        }
+    }
+}
+
+/// Lists every survivor, then the score
+impl Display for Report {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        for outcome in self.survivors() {
+            writeln!(formatter, "survived: {}", outcome.mutant.mutation)?;
+        }
+
+        write!(
+            formatter,
+            "{} of {} mutants killed ({:.0}%)",
+            self.killed(),
+            self.outcomes.len(),
+            self.score() * 100.0
+        )
+    }
+}
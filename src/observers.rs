@@ -0,0 +1,126 @@
+use alloc::vec::Vec;
+
+use crate::machines::{RegisterSource, Signal};
+
+/// What happened during a single CPU cycle, as seen from outside the machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The cycle's number. The first is `1`.
+    pub number: usize,
+
+    /// The register's value during the cycle, i.e. before any instruction
+    /// completing on it had its effect
+    pub register: isize,
+
+    /// The position in the program of the instruction occupying the cycle, if
+    /// the source knows of one
+    pub instruction: Option<usize>,
+
+    /// What the program sent out during the cycle, if anything
+    pub signal: Option<Signal>,
+
+    /// The interrupt line whose handler occupied the cycle, if any
+    pub interrupt: Option<usize>,
+}
+
+/// Anything that wants to know about every cycle a machine goes through, e.g.
+/// a second display, a tracer, or a stats collector. Attach as many as needed
+/// to a `Fanout`.
+pub trait CycleObserver {
+    fn observe(&mut self, cycle: &Cycle);
+}
+
+/// Records every cycle, which makes for a complete trace of the run
+impl CycleObserver for Vec<Cycle> {
+    fn observe(&mut self, cycle: &Cycle) {
+        self.push(*cycle);
+    }
+}
+
+/// Drives a machine for any number of observers. A single `cycle()` cycles
+/// the machine once and tells every observer about it, in the order they were
+/// attached, so nobody has to own the machine but the fanout.
+///
+/// Observers are borrowed for the fanout's lifetime. Once it's gone, they're
+/// free to be looked at again.
+pub struct Fanout<'a, M> {
+    machine: M,
+    observers: Vec<&'a mut dyn CycleObserver>,
+}
+
+impl<'a, M: RegisterSource> Fanout<'a, M> {
+    /// Creates a fanout for the given machine with nobody observing yet
+    pub fn new(machine: M) -> Self {
+        Fanout {
+            machine,
+            observers: Vec::new(),
+        }
+    }
+
+    /// Attaches another observer. It learns of every cycle from here on.
+    pub fn with_observer(mut self, observer: &'a mut dyn CycleObserver) -> Self {
+        self.attach(observer);
+        self
+    }
+
+    /// Attaches another observer. It learns of every cycle from here on.
+    pub fn attach(&mut self, observer: &'a mut dyn CycleObserver) {
+        self.observers.push(observer);
+    }
+
+    /// Cycles the machine once, if it's still executing, and returns what
+    /// happened after telling every observer about it
+    pub fn cycle(&mut self) -> Option<Cycle> {
+        if !self.machine.is_executing() {
+            return None;
+        }
+
+        let number = self.machine.ticks();
+        let register = self.machine.read_register();
+        let instruction = self.machine.instruction_index();
+        let interrupt = self.machine.interrupt();
+
+        self.machine.cycle();
+
+        let cycle = Cycle {
+            number,
+            register,
+            instruction,
+            signal: self.machine.signal(),
+            interrupt,
+        };
+
+        for observer in &mut self.observers {
+            observer.observe(&cycle);
+        }
+
+        Some(cycle)
+    }
+
+    /// Cycles the machine until it finishes and returns the number of cycles
+    /// it went through
+    pub fn run(&mut self) -> usize {
+        let mut cycles = 0;
+
+        while self.cycle().is_some() {
+            cycles += 1;
+        }
+
+        cycles
+    }
+
+    /// Returns the machine being driven
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    /// Returns the machine being driven mutably
+    pub fn machine_mut(&mut self) -> &mut M {
+        &mut self.machine
+    }
+
+    /// Drops the observers and returns the machine
+    pub fn into_machine(self) -> M {
+        self.machine
+    }
+}
@@ -0,0 +1,394 @@
+use std::error::Error;
+use std::ffi::{c_char, c_void, CStr};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::{fs, io, slice};
+
+use libloading::Library;
+
+use crate::devices::Device;
+use crate::machines::{CustomOp, VirtualMachine, VmError};
+
+/// The version of the interface below. A plugin built against another one
+/// isn't loaded.
+pub const ABI_VERSION: u32 = 1;
+
+/// The function every plugin exports, returning its `PluginDescriptor`
+pub const ENTRY_POINT: &str = "legolos_plugin";
+
+/// What a plugin has to offer. Rust's own ABI isn't stable between compilers,
+/// so plugins describe their devices and ops with plain C structs and
+/// functions, and can be written in anything that speaks C. A Rust plugin is
+/// a `cdylib` that implements `Device` or `CustomOp` as usual and lets the
+/// descriptors do the rest:
+///
+/// ```ignore
+/// static DEVICES: [DeviceDescriptor; 1] = [DeviceDescriptor::of::<Counter>(c"counter", 1)];
+/// static OPS: [OpDescriptor; 1] = [OpDescriptor::of::<Double>(c"double", 3)];
+///
+/// #[no_mangle]
+/// pub extern "C" fn legolos_plugin() -> PluginDescriptor {
+///     PluginDescriptor::new(&DEVICES, &OPS)
+/// }
+/// ```
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PluginDescriptor {
+    pub abi_version: u32,
+    pub devices: *const DeviceDescriptor,
+    pub device_count: usize,
+    pub ops: *const OpDescriptor,
+    pub op_count: usize,
+}
+
+impl PluginDescriptor {
+    pub const fn new(devices: &'static [DeviceDescriptor], ops: &'static [OpDescriptor]) -> Self {
+        PluginDescriptor {
+            abi_version: ABI_VERSION,
+            devices: devices.as_ptr(),
+            device_count: devices.len(),
+            ops: ops.as_ptr(),
+            op_count: ops.len(),
+        }
+    }
+}
+
+/// A kind of device a plugin can make any number of. Each one's state is an
+/// opaque pointer from `new`, handed back to the other functions.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceDescriptor {
+    /// A NUL-terminated name, unique within the plugin
+    pub name: *const c_char,
+
+    /// How many addresses the device takes on the bus
+    pub length: usize,
+
+    pub new: unsafe extern "C" fn() -> *mut c_void,
+    pub free: unsafe extern "C" fn(*mut c_void),
+    pub tick: unsafe extern "C" fn(*mut c_void),
+    pub read: unsafe extern "C" fn(*mut c_void, usize) -> isize,
+    pub write: unsafe extern "C" fn(*mut c_void, usize, isize),
+}
+
+/// The name is a `'static` string, so descriptors can live in statics
+unsafe impl Sync for DeviceDescriptor {}
+
+impl DeviceDescriptor {
+    /// Describes a device made with `Default::default()`
+    pub const fn of<D: Device + Default>(name: &'static CStr, length: usize) -> Self {
+        DeviceDescriptor {
+            name: name.as_ptr(),
+            length,
+            new: new_state::<D>,
+            free: free_state::<D>,
+            tick: tick_device::<D>,
+            read: read_device::<D>,
+            write: write_device::<D>,
+        }
+    }
+}
+
+/// A custom instruction a plugin defines, with state like a device's
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct OpDescriptor {
+    /// A NUL-terminated name, unique within the plugin
+    pub name: *const c_char,
+
+    /// The opcode programs use for it, as in `op3 7`
+    pub opcode: u8,
+
+    pub new: unsafe extern "C" fn() -> *mut c_void,
+    pub free: unsafe extern "C" fn(*mut c_void),
+    pub execute: unsafe extern "C" fn(*mut c_void, isize, isize) -> isize,
+}
+
+/// The name is a `'static` string, so descriptors can live in statics
+unsafe impl Sync for OpDescriptor {}
+
+impl OpDescriptor {
+    /// Describes an op made with `Default::default()`
+    pub const fn of<O: CustomOp + Default>(name: &'static CStr, opcode: u8) -> Self {
+        OpDescriptor {
+            name: name.as_ptr(),
+            opcode,
+            new: new_state::<O>,
+            free: free_state::<O>,
+            execute: execute_op::<O>,
+        }
+    }
+}
+
+unsafe extern "C" fn new_state<T: Default>() -> *mut c_void {
+    Box::into_raw(Box::<T>::default()).cast()
+}
+
+unsafe extern "C" fn free_state<T>(state: *mut c_void) {
+    drop(Box::from_raw(state.cast::<T>()))
+}
+
+unsafe extern "C" fn tick_device<D: Device>(state: *mut c_void) {
+    (*state.cast::<D>()).tick()
+}
+
+unsafe extern "C" fn read_device<D: Device>(state: *mut c_void, offset: usize) -> isize {
+    (*state.cast::<D>()).read(offset)
+}
+
+unsafe extern "C" fn write_device<D: Device>(state: *mut c_void, offset: usize, value: isize) {
+    (*state.cast::<D>()).write(offset, value)
+}
+
+unsafe extern "C" fn execute_op<O: CustomOp>(
+    state: *mut c_void,
+    register: isize,
+    operand: isize,
+) -> isize {
+    (*state.cast::<O>()).execute(register, operand)
+}
+
+/// A shared library loaded as a plugin. Everything made from it keeps it
+/// loaded, so the plugin itself can be dropped once its devices are attached.
+pub struct Plugin {
+    path: PathBuf,
+    library: Rc<Library>,
+    devices: Vec<(String, DeviceDescriptor)>,
+    ops: Vec<(String, OpDescriptor)>,
+}
+
+impl Plugin {
+    /// Loads the plugin at `path` and reads what it offers
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initializers, and nothing checks that its
+    /// `legolos_plugin()` and descriptors really have the types above, so the
+    /// library must be a plugin built for this version of the interface.
+    pub unsafe fn load(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let path = path.as_ref();
+        let library = Library::new(path).map_err(PluginError::Library)?;
+
+        let entry = library
+            .get::<unsafe extern "C" fn() -> PluginDescriptor>(ENTRY_POINT.as_bytes())
+            .map_err(PluginError::Library)?;
+        let descriptor = entry();
+
+        if descriptor.abi_version != ABI_VERSION {
+            return Err(PluginError::Version(descriptor.abi_version));
+        }
+
+        let devices = described(descriptor.devices, descriptor.device_count)
+            .iter()
+            .map(|device| Ok((name(device.name)?, *device)))
+            .collect::<Result<_, PluginError>>()?;
+
+        let ops = described(descriptor.ops, descriptor.op_count)
+            .iter()
+            .map(|op| Ok((name(op.name)?, *op)))
+            .collect::<Result<_, PluginError>>()?;
+
+        Ok(Plugin {
+            path: path.to_path_buf(),
+            library: Rc::new(library),
+            devices,
+            ops,
+        })
+    }
+
+    /// Returns where the plugin was loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the name of every kind of device the plugin makes, with how
+    /// many addresses each takes
+    pub fn devices(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.devices
+            .iter()
+            .map(|(name, device)| (name.as_str(), device.length))
+    }
+
+    /// Returns the name and opcode of every op the plugin defines
+    pub fn ops(&self) -> impl Iterator<Item = (&str, u8)> {
+        self.ops.iter().map(|(name, op)| (name.as_str(), op.opcode))
+    }
+
+    /// Makes a new device of the kind with the given name
+    pub fn device(&self, name: &str) -> Option<PluginDevice> {
+        let (_name, descriptor) = self.devices.iter().find(|(found, _)| found == name)?;
+
+        Some(PluginDevice {
+            _library: Rc::clone(&self.library),
+            descriptor: *descriptor,
+            // SAFETY: `load()`'s caller vouched for the descriptor
+            state: unsafe { (descriptor.new)() },
+        })
+    }
+
+    /// Makes a new op with the given name
+    pub fn op(&self, name: &str) -> Option<PluginOp> {
+        let (_name, descriptor) = self.ops.iter().find(|(found, _)| found == name)?;
+
+        Some(PluginOp {
+            _library: Rc::clone(&self.library),
+            descriptor: *descriptor,
+            // SAFETY: `load()`'s caller vouched for the descriptor
+            state: unsafe { (descriptor.new)() },
+        })
+    }
+
+    /// Attaches one of every device the plugin makes to the machine's bus,
+    /// one after another from `start`, and defines every op. Returns the
+    /// address after the last device.
+    pub fn install(
+        &self,
+        machine: &mut VirtualMachine,
+        start: usize,
+    ) -> Result<usize, PluginError> {
+        let mut address = start;
+
+        for (name, length) in self.devices() {
+            let device = self.device(name).expect("the plugin just listed it");
+            machine
+                .attach(address, length, device)
+                .map_err(PluginError::Attach)?;
+            address += length;
+        }
+
+        for (name, opcode) in self.ops() {
+            machine.define_op(opcode, self.op(name).expect("the plugin just listed it"));
+        }
+
+        Ok(address)
+    }
+}
+
+/// Returns every file in the directory that looks like a shared library on
+/// this platform, in order, e.g. to load a directory of plugins
+pub fn discover(directory: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+
+        if path.extension() == Some(std::env::consts::DLL_EXTENSION.as_ref()) {
+            found.push(path);
+        }
+    }
+
+    found.sort();
+    Ok(found)
+}
+
+/// Returns the descriptors a plugin pointed to. There may be none, in which
+/// case the pointer needn't be valid.
+unsafe fn described<'a, T>(descriptors: *const T, count: usize) -> &'a [T] {
+    match count {
+        0 => &[],
+        _ => slice::from_raw_parts(descriptors, count),
+    }
+}
+
+unsafe fn name(name: *const c_char) -> Result<String, PluginError> {
+    CStr::from_ptr(name)
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| PluginError::Name)
+}
+
+/// A device made by a plugin
+pub struct PluginDevice {
+    _library: Rc<Library>,
+    descriptor: DeviceDescriptor,
+    state: *mut c_void,
+}
+
+impl Device for PluginDevice {
+    fn tick(&mut self) {
+        // SAFETY: the state came from the descriptor's own `new`
+        unsafe { (self.descriptor.tick)(self.state) }
+    }
+
+    fn read(&mut self, offset: usize) -> isize {
+        // SAFETY: as above
+        unsafe { (self.descriptor.read)(self.state, offset) }
+    }
+
+    fn write(&mut self, offset: usize, value: isize) {
+        // SAFETY: as above
+        unsafe { (self.descriptor.write)(self.state, offset, value) }
+    }
+}
+
+/// The library is still loaded: the field holding it drops after this
+impl Drop for PluginDevice {
+    fn drop(&mut self) {
+        // SAFETY: the state came from the descriptor's own `new`
+        unsafe { (self.descriptor.free)(self.state) }
+    }
+}
+
+/// An op defined by a plugin
+pub struct PluginOp {
+    _library: Rc<Library>,
+    descriptor: OpDescriptor,
+    state: *mut c_void,
+}
+
+impl CustomOp for PluginOp {
+    fn execute(&mut self, register: isize, operand: isize) -> isize {
+        // SAFETY: the state came from the descriptor's own `new`
+        unsafe { (self.descriptor.execute)(self.state, register, operand) }
+    }
+}
+
+/// The library is still loaded: the field holding it drops after this
+impl Drop for PluginOp {
+    fn drop(&mut self) {
+        // SAFETY: the state came from the descriptor's own `new`
+        unsafe { (self.descriptor.free)(self.state) }
+    }
+}
+
+/// Why a plugin couldn't be loaded
+#[derive(Debug)]
+pub enum PluginError {
+    /// The library didn't load or doesn't export `legolos_plugin()`
+    Library(libloading::Error),
+
+    /// The plugin was built for another version of the interface
+    Version(u32),
+
+    /// A device or op's name isn't UTF-8
+    Name,
+
+    /// One of the plugin's devices couldn't be attached to the machine
+    Attach(VmError),
+}
+
+impl Display for PluginError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::Library(error) => write!(formatter, "{}", error),
+            PluginError::Version(version) => write!(
+                formatter,
+                "the plugin is for version {} of the interface, not {}",
+                version, ABI_VERSION
+            ),
+            PluginError::Name => write!(formatter, "a device or op's name isn't UTF-8"),
+            PluginError::Attach(error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+impl Error for PluginError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PluginError::Library(error) => Some(error),
+            PluginError::Attach(error) => Some(error),
+            PluginError::Version(_) | PluginError::Name => None,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use alloc::vec::Vec;
+
+use crate::machines::VirtualMachine;
+use crate::programs::Program;
+use crate::screens::Screen;
+
+/// Keeps machines and screens that finished their runs, to run the next
+/// programs on instead of building new ones. A service simulating many short
+/// programs a second then stops paying for a flight recorder and a screen's
+/// pixels with every one: after the first few, runs reuse what's recycled.
+///
+/// Recycled machines lose their devices, keyboard, and custom ops, since
+/// those keep state from the last run. Recycled screens keep their size and
+/// settings, e.g. their overflow policy, so keep a pool for each kind.
+#[derive(Default)]
+pub struct MachinePool {
+    machines: Vec<VirtualMachine>,
+    screens: Vec<Screen>,
+}
+
+impl MachinePool {
+    pub fn new() -> Self {
+        MachinePool::default()
+    }
+
+    /// Returns a machine ready to run the program from its first cycle,
+    /// recycled if there's one to spare. It shares the program's
+    /// instructions, like `Program::machine()`.
+    pub fn machine(&mut self, program: &Program) -> VirtualMachine {
+        match self.machines.pop() {
+            Some(mut machine) => {
+                machine.load(program.shared());
+                machine
+            }
+            None => program.machine(),
+        }
+    }
+
+    /// Returns a blank screen driven by a machine ready to run the program,
+    /// recycled if there's one to spare
+    pub fn screen(&mut self, program: &Program) -> Screen {
+        match self.screens.pop() {
+            Some(mut screen) => {
+                screen.machine_mut().load(program.shared());
+                screen.reset();
+                screen
+            }
+            None => Screen::new(self.machine(program)),
+        }
+    }
+
+    /// Takes back a machine that's done, for `machine()` to hand out again
+    pub fn recycle(&mut self, mut machine: VirtualMachine) {
+        machine.detach_all();
+        self.machines.push(machine);
+    }
+
+    /// Takes back a screen that's done, with its machine, for `screen()` to
+    /// hand out again
+    pub fn recycle_screen(&mut self, mut screen: Screen) {
+        screen.machine_mut().detach_all();
+        self.screens.push(screen);
+    }
+
+    /// Returns how many machines and screens are waiting to be handed out
+    pub fn len(&self) -> usize {
+        self.machines.len() + self.screens.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty() && self.screens.is_empty()
+    }
+}
@@ -0,0 +1,290 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::{fs, io, path::Path, path::PathBuf};
+
+use crate::assembly::{self, AsmError};
+use crate::binary::{self, DecodeError};
+use crate::containers::{self, Container, ContainerError};
+use crate::fnv;
+use crate::instructions::{self, Instruction};
+use crate::machines::VirtualMachine;
+
+mod compilation;
+mod fingerprints;
+mod reports;
+#[cfg(feature = "serde")]
+mod serialization;
+
+pub use compilation::CompiledProgram;
+pub use fingerprints::{Fingerprint, FingerprintMismatch, InvalidFingerprint};
+pub use reports::{OpcodeCount, Report};
+
+/// A program: the instructions a machine runs, in order. It's only ever
+/// read, so one program can start any number of machines, e.g. on different
+/// threads, and they all share its instructions rather than copying them.
+/// Cloning it is cheap for the same reason.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Program {
+    instructions: Arc<[Instruction]>,
+}
+
+impl Program {
+    pub fn new(instructions: Vec<Instruction>) -> Self {
+        Program {
+            instructions: instructions.into(),
+        }
+    }
+
+    /// Returns the program's instructions, in order
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Returns the number of instructions in the program
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Returns a machine ready to run the program from its first cycle,
+    /// sharing the program's instructions
+    pub fn machine(&self) -> VirtualMachine {
+        VirtualMachine::shared(self.shared())
+    }
+
+    /// Returns a hash of the instructions. It's FNV-1a over the program's
+    /// binary encoding, so like `Screen::content_hash()` it doesn't depend on
+    /// the platform or the Rust version, and programs that differ only in
+    /// comments or formatting hash the same.
+    pub fn content_hash(&self) -> u64 {
+        fnv::fnv1a(binary::encode(self.instructions.iter()))
+    }
+
+    /// Returns the instructions for another machine to share
+    pub(crate) fn shared(&self) -> Arc<[Instruction]> {
+        Arc::clone(&self.instructions)
+    }
+
+    /// Reads a program in any of the formats the command line accepts: a
+    /// container, the binary format, the puzzle's input, or assembly. Text is
+    /// read as leniently as `instructions::parse_instructions_from_bytes()`
+    /// reads it: a byte order mark is skipped, lines may end in `\r\n`, and
+    /// bytes that aren't UTF-8 are replaced rather than rejected.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        if containers::is_container(bytes) {
+            return Ok(Container::from_bytes(bytes)?.program().clone());
+        }
+
+        if binary::is_binary(bytes) {
+            return Ok(binary::decode(bytes)?.into());
+        }
+
+        // Only assembly has labels, directives, and comments, so text that's
+        // one instruction per line is the puzzle's input
+        if let Ok(instructions) = instructions::parse_instructions_from_bytes(bytes) {
+            return Ok(instructions.into());
+        }
+
+        let source = bytes.strip_prefix(instructions::BOM).unwrap_or(bytes);
+        Ok(String::from_utf8_lossy(source).parse()?)
+    }
+
+    /// Reads the program from a file, in any of the formats `from_bytes()`
+    /// reads. When it doesn't read, the error says which file and, for
+    /// assembly, which line.
+    #[cfg(feature = "std")]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+
+        let bytes = fs::read(path).map_err(|error| LoadError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+        Program::from_bytes(&bytes).map_err(|error| LoadError::Program {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl VirtualMachine {
+    /// Reads the program from a file, like `Program::from_path()`, and
+    /// returns a machine ready to run it
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        Ok(Program::from_path(path)?.machine())
+    }
+}
+
+impl From<Vec<Instruction>> for Program {
+    fn from(instructions: Vec<Instruction>) -> Self {
+        Program::new(instructions)
+    }
+}
+
+impl From<VecDeque<Instruction>> for Program {
+    fn from(instructions: VecDeque<Instruction>) -> Self {
+        Program::new(instructions.into())
+    }
+}
+
+impl FromIterator<Instruction> for Program {
+    fn from_iter<I: IntoIterator<Item = Instruction>>(instructions: I) -> Self {
+        Program::new(instructions.into_iter().collect())
+    }
+}
+
+/// Assembles the program, just like `assembly::assemble()`
+impl FromStr for Program {
+    type Err = AsmError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        assembly::assemble(source).map(Program::from)
+    }
+}
+
+/// Writes the program as assembly, one instruction per line
+impl Display for Program {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str(&assembly::disassemble(self.instructions.iter()))
+    }
+}
+
+/// Why bytes couldn't be read as a program, in whichever format they looked
+/// like
+#[derive(Debug)]
+pub enum ProgramError {
+    /// They looked like assembly, which didn't assemble
+    Asm(AsmError),
+
+    /// They looked like the binary format, which didn't decode
+    Decode(DecodeError),
+
+    /// They looked like a container, which couldn't be read
+    Container(ContainerError),
+}
+
+/// Why a file couldn't be read as a program
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read at all
+    Io { path: PathBuf, error: io::Error },
+
+    /// The file was read but isn't a program
+    Program { path: PathBuf, error: ProgramError },
+}
+
+impl From<AsmError> for ProgramError {
+    fn from(error: AsmError) -> Self {
+        ProgramError::Asm(error)
+    }
+}
+
+impl From<DecodeError> for ProgramError {
+    fn from(error: DecodeError) -> Self {
+        ProgramError::Decode(error)
+    }
+}
+
+impl From<ContainerError> for ProgramError {
+    fn from(error: ContainerError) -> Self {
+        ProgramError::Container(error)
+    }
+}
+
+impl Display for ProgramError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ProgramError::Asm(error) => write!(formatter, "can't assemble: {}", error),
+            ProgramError::Decode(error) => write!(formatter, "can't decode: {}", error),
+            ProgramError::Container(error) => write!(formatter, "can't unpack: {}", error),
+        }
+    }
+}
+
+impl Error for ProgramError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProgramError::Asm(error) => Some(error),
+            ProgramError::Decode(error) => Some(error),
+            ProgramError::Container(error) => Some(error),
+        }
+    }
+}
+
+/// Leads with the file, and for assembly the line, the way compilers do, e.g.
+/// ``demo.asm:3: unknown instruction `jmp` ``
+#[cfg(feature = "std")]
+impl Display for LoadError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LoadError::Io { path, error } => write!(formatter, "{}: {}", path.display(), error),
+            LoadError::Program {
+                path,
+                error: ProgramError::Asm(error),
+            } => write!(
+                formatter,
+                "{}:{}: {}",
+                path.display(),
+                error.line,
+                error.problem
+            ),
+            LoadError::Program { path, error } => {
+                write!(formatter, "{}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io { error, .. } => Some(error),
+            LoadError::Program { error, .. } => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn reads_text_saved_by_other_editors() {
+        let program = Program::from(vec![Instruction::Addx(3), Instruction::Noop]);
+
+        for bytes in [
+            &b"addx 3\nnoop\n"[..],
+            b"\xef\xbb\xbfaddx 3\r\nnoop\r\n",
+            b"\xef\xbb\xbfaddx 3 ; three\r\nnoop\r\n",
+            b"\xef\xbb\xbf\r\n  addx   3\r\n\r\nnoop",
+        ] {
+            assert_eq!(
+                Program::from_bytes(bytes).unwrap(),
+                program,
+                "{:?}",
+                String::from_utf8_lossy(bytes)
+            );
+        }
+    }
+
+    #[test]
+    fn reports_the_line_assembly_fails_on() {
+        let error = Program::from_bytes(b"\xef\xbb\xbfnoop\r\njump 3\r\n").unwrap_err();
+
+        assert!(matches!(error, ProgramError::Asm(AsmError { line: 2, .. })));
+    }
+}
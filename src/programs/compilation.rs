@@ -0,0 +1,114 @@
+use alloc::vec::Vec;
+
+use crate::instructions::Instruction;
+
+use super::Program;
+
+/// A program compiled for running fast and often, from `Program::compile()`.
+/// Every cycle is worked out ahead of time as what it does to the register:
+/// how much of it stays (all or none) and what's added. Running it is then a
+/// pass over a flat list, without matching on instructions, popping them off
+/// a queue, or keeping track of one in flight.
+///
+/// It runs the program as a machine with nothing attached would: loads and
+/// input read `0`, and custom instructions leave the register be.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompiledProgram {
+    /// What every cycle does, as `(keep, add)`: the register after it is
+    /// `register * keep + add`, wrapping like the machine's
+    cycles: Vec<(isize, isize)>,
+}
+
+impl Program {
+    /// Compiles the program for `CompiledProgram::run_compiled()`, which is
+    /// worth it for a long program run many times over
+    pub fn compile(&self) -> CompiledProgram {
+        let mut cycles = Vec::with_capacity(self.len());
+
+        for instruction in self.instructions() {
+            // Instructions only change the register on their last cycle
+            for _ in 1..instruction.cycles() {
+                cycles.push((1, 0));
+            }
+
+            cycles.push(match instruction {
+                Instruction::Addx(number) => (1, *number),
+                Instruction::Ldx(_) | Instruction::In => (0, 0),
+                Instruction::Noop
+                | Instruction::Out
+                | Instruction::Tone(_)
+                | Instruction::Stx(_)
+                | Instruction::Custom(..) => (1, 0),
+            });
+        }
+
+        CompiledProgram { cycles }
+    }
+}
+
+impl CompiledProgram {
+    /// Returns the number of cycles the program takes
+    pub fn len(&self) -> usize {
+        self.cycles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cycles.is_empty()
+    }
+
+    /// Returns what every cycle does, as `(keep, add)`
+    #[cfg(feature = "cranelift")]
+    pub(crate) fn cycles(&self) -> &[(isize, isize)] {
+        &self.cycles
+    }
+
+    /// Runs the program and returns the register's value during every cycle,
+    /// just like `timelines::register_timeline()` of a machine running it
+    pub fn run_compiled(&self) -> Vec<isize> {
+        let mut timeline = Vec::with_capacity(self.cycles.len());
+        let mut register = 1;
+
+        for &(keep, add) in &self.cycles {
+            timeline.push(register);
+            register = step(register, keep, add);
+        }
+
+        timeline
+    }
+
+    /// Runs the program and returns the register once it's finished
+    pub fn final_register(&self) -> isize {
+        self.cycles
+            .iter()
+            .fold(1, |register, &(keep, add)| step(register, keep, add))
+    }
+}
+
+/// Returns the register after a cycle that keeps `keep` of it and adds `add`
+fn step(register: isize, keep: isize, add: isize) -> isize {
+    register.wrapping_mul(keep).wrapping_add(add)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::timelines::register_timeline;
+
+    #[test]
+    fn compiled_programs_wrap_like_the_machine() {
+        let program = Program::from(vec![
+            Instruction::Addx(isize::MAX),
+            Instruction::Addx(isize::MAX),
+            Instruction::Noop,
+        ]);
+        let compiled = program.compile();
+
+        assert_eq!(
+            compiled.run_compiled(),
+            register_timeline(program.machine())
+        );
+        assert_eq!(compiled.final_register(), -1);
+    }
+}
@@ -0,0 +1,110 @@
+use alloc::string::String;
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use super::Program;
+
+/// Identifies a program by its instructions, for anything kept apart from
+/// the program that only makes sense for that one program, e.g. a replay of
+/// its cycles, a cache of what it drew, or the program inside a container.
+/// Record the fingerprint along with it and `check()` it before applying it,
+/// to catch it meeting the wrong program.
+///
+/// It's `Program::content_hash()`, so it's stable across platforms and
+/// versions of Rust, and programs that differ only in comments or formatting
+/// share one. It's written as 16 hexadecimal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint(u64);
+
+impl Program {
+    /// Returns the program's fingerprint
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint(self.content_hash())
+    }
+}
+
+impl Fingerprint {
+    /// Makes a fingerprint from the number `to_u64()` returned, e.g. one read
+    /// back from a file
+    pub fn from_u64(value: u64) -> Self {
+        Fingerprint(value)
+    }
+
+    pub fn to_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Returns an error unless this is the program's fingerprint
+    pub fn check(self, program: &Program) -> Result<(), FingerprintMismatch> {
+        let actual = program.fingerprint();
+
+        match actual == self {
+            true => Ok(()),
+            false => Err(FingerprintMismatch {
+                expected: self,
+                actual,
+            }),
+        }
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "{:016x}", self.0)
+    }
+}
+
+/// Reads the 16 hexadecimal digits `Display` writes, in either case
+impl FromStr for Fingerprint {
+    type Err = InvalidFingerprint;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // `from_str_radix()` alone would take a sign and fewer digits
+        if s.len() != 16 || !s.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(InvalidFingerprint(s.into()));
+        }
+
+        u64::from_str_radix(s, 16)
+            .map(Fingerprint)
+            .map_err(|_| InvalidFingerprint(s.into()))
+    }
+}
+
+/// Something meant for one program met another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FingerprintMismatch {
+    /// The fingerprint recorded along with it
+    pub expected: Fingerprint,
+
+    /// The fingerprint of the program it met
+    pub actual: Fingerprint,
+}
+
+impl Display for FingerprintMismatch {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            formatter,
+            "expected the program with fingerprint {}, not {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl Error for FingerprintMismatch {}
+
+/// Text that was meant to be a fingerprint isn't 16 hexadecimal digits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidFingerprint(pub String);
+
+impl Display for InvalidFingerprint {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            formatter,
+            "`{}` isn't a fingerprint (expected 16 hexadecimal digits)",
+            self.0
+        )
+    }
+}
+
+impl Error for InvalidFingerprint {}
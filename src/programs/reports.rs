@@ -0,0 +1,132 @@
+use alloc::collections::BTreeMap;
+use core::fmt::{Display, Formatter};
+use core::ops::Range;
+
+use crate::analyses;
+use crate::screens::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+use super::Program;
+
+/// How many pixels a frame of the specification's screen has
+const FRAME: usize = SCREEN_WIDTH * SCREEN_HEIGHT;
+
+/// What a program costs, worked out from its instructions alone, to check it
+/// over before running it: how many of each opcode it has, how many cycles it
+/// takes, and whether that's enough to draw a whole frame of the
+/// specification's screen, from `Program::report()`.
+///
+/// Nothing jumps, so every program is a straight line and the cycle count is
+/// exact. It's for the program alone, though: a machine servicing interrupts
+/// spends cycles in their handlers on top.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// How many instructions the program has
+    pub instructions: usize,
+
+    /// The instructions grouped by opcode, keyed by the opcode's mnemonic
+    pub opcodes: BTreeMap<&'static str, OpcodeCount>,
+
+    /// How many cycles the program takes
+    pub cycles: usize,
+
+    /// The register once the program's done, if it can be known without
+    /// running it; see `analyses::leaving()`
+    pub register: Option<isize>,
+
+    /// The instructions that would start after the frame's last pixel, and so
+    /// never run on a screen that stops there, as screens do by default
+    pub unreachable: Range<usize>,
+}
+
+/// How many of a program's instructions have an opcode, and what they cost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpcodeCount {
+    pub instructions: usize,
+
+    /// How many cycles they take between them
+    pub cycles: usize,
+}
+
+impl Program {
+    /// Works out what the program costs, without running it
+    pub fn report(&self) -> Report {
+        let mut opcodes = BTreeMap::new();
+
+        for instruction in self.instructions() {
+            let opcode: &mut OpcodeCount = opcodes.entry(instruction.mnemonic()).or_default();
+
+            opcode.instructions += 1;
+            opcode.cycles += instruction.cycles();
+        }
+
+        Report {
+            instructions: self.len(),
+            opcodes,
+            cycles: analyses::worst_case_cycles(self.instructions()),
+            register: analyses::leaving(self.instructions())
+                .last()
+                .copied()
+                .unwrap_or(Some(1)),
+            unreachable: analyses::unreachable(self.instructions(), FRAME),
+        }
+    }
+}
+
+impl Report {
+    /// Returns `true` if the program runs for at least a frame, i.e. every
+    /// pixel of the specification's screen gets drawn
+    pub fn covers_frame(&self) -> bool {
+        self.cycles >= FRAME
+    }
+
+    /// Returns how many pixels at the end of the frame go undrawn because the
+    /// program finishes first
+    pub fn undrawn(&self) -> usize {
+        FRAME.saturating_sub(self.cycles)
+    }
+}
+
+/// Writes a summary for reading in a terminal, the opcodes last
+impl Display for Report {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(formatter, "instructions: {}", self.instructions)?;
+        writeln!(formatter, "cycles: {}", self.cycles)?;
+
+        match self.undrawn() {
+            0 => writeln!(formatter, "frame: all {} pixels drawn", FRAME)?,
+            undrawn => writeln!(
+                formatter,
+                "frame: {} of {} pixels drawn, the last {} not",
+                FRAME - undrawn,
+                FRAME,
+                undrawn
+            )?,
+        }
+
+        if !self.unreachable.is_empty() {
+            writeln!(
+                formatter,
+                "past the frame: {} instructions, from #{}",
+                self.unreachable.len(),
+                self.unreachable.start
+            )?;
+        }
+
+        match self.register {
+            Some(register) => writeln!(formatter, "final register: {}", register)?,
+            None => writeln!(formatter, "final register: unknown until run")?,
+        }
+
+        writeln!(formatter, "\nopcodes:")?;
+
+        for (mnemonic, opcode) in &self.opcodes {
+            writeln!(
+                formatter,
+                "    {:<5} {:>5} in program {:>7} cycles",
+                mnemonic, opcode.instructions, opcode.cycles
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::assembly;
+use crate::instructions::Instruction;
+
+use super::Program;
+
+/// What a program looks like serialized: a list of its instructions under a
+/// name, since formats like TOML need a table at the top
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Program")]
+struct Listing {
+    instructions: Vec<Step>,
+}
+
+/// What an instruction looks like serialized: its mnemonic as written in
+/// assembly, e.g. `addx` or `op3`, and its operand if it takes one
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Instruction")]
+struct Step {
+    op: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    operand: Option<Operand>,
+}
+
+/// An operand as a plain number. Addresses and durations are unsigned and can
+/// be bigger than any `isize`, so they're kept unsigned rather than wrapping
+/// round to a negative number that wouldn't deserialize.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, expecting = "expected an integer operand")]
+enum Operand {
+    Signed(isize),
+    Unsigned(usize),
+}
+
+/// Serializes as `{ "instructions": [{ "op": "addx", "operand": 3 }, ...] }`,
+/// or the equivalent in other formats, for programs written by scripts that
+/// would rather not write assembly
+impl Serialize for Program {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let listing = Listing {
+            instructions: self.instructions.iter().map(Step::from).collect(),
+        };
+
+        listing.serialize(serializer)
+    }
+}
+
+/// Instructions are checked just like the assembler checks them, e.g. a
+/// `noop` with an operand or an `ldx` from a negative address is an error
+impl<'de> Deserialize<'de> for Program {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let listing = Listing::deserialize(deserializer)?;
+
+        listing
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let operand = step.operand.as_ref().map(|operand| match operand {
+                    Operand::Signed(number) => number.to_string(),
+                    Operand::Unsigned(number) => number.to_string(),
+                });
+
+                assembly::assemble_instruction(&step.op, operand.as_deref()).map_err(|problem| {
+                    D::Error::custom(format!("instruction {}: {}", index + 1, problem))
+                })
+            })
+            .collect()
+    }
+}
+
+impl From<&Instruction> for Step {
+    fn from(instruction: &Instruction) -> Self {
+        let (op, operand) = match *instruction {
+            Instruction::Noop | Instruction::Out | Instruction::In => {
+                (instruction.mnemonic().to_string(), None)
+            }
+            Instruction::Addx(number) => (
+                instruction.mnemonic().to_string(),
+                Some(Operand::Signed(number)),
+            ),
+            Instruction::Tone(operand) | Instruction::Ldx(operand) | Instruction::Stx(operand) => (
+                instruction.mnemonic().to_string(),
+                Some(Operand::Unsigned(operand)),
+            ),
+            Instruction::Custom(opcode, operand) => (
+                format!("{}{}", instruction.mnemonic(), opcode),
+                Some(Operand::Signed(operand)),
+            ),
+        };
+
+        Step { op, operand }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn unsigned_operands_round_trip() {
+        let program = Program::from(vec![
+            Instruction::Tone(usize::MAX),
+            Instruction::Ldx(isize::MAX as usize + 1),
+            Instruction::Stx(3),
+            Instruction::Addx(-4),
+            Instruction::Custom(3, -7),
+        ]);
+
+        let json = serde_json::to_string(&program).unwrap();
+
+        assert_eq!(serde_json::from_str::<Program>(&json).unwrap(), program);
+    }
+}
@@ -0,0 +1,340 @@
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use crate::assembly;
+use crate::instructions::Instruction;
+use crate::machines::VirtualMachine;
+use crate::observers::Fanout;
+use crate::screens::{self, Screen};
+
+/// Checks the machine against a reference model on many generated programs.
+/// Each program is run on a `VirtualMachine` and compared, cycle by cycle,
+/// with `model()`: the simplest possible reading of the specification. A
+/// failing program is shrunk to as few instructions, with operands as small,
+/// as still fail, so the counterexample is easy to read.
+///
+/// It's deterministic: the same seed generates the same programs, so a
+/// failure reported in CI reproduces anywhere.
+#[derive(Debug, Clone)]
+pub struct Harness {
+    seed: u64,
+    cases: usize,
+    max_length: usize,
+}
+
+/// The machine broke one of the invariants on this program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// The shrunk program
+    pub program: Vec<Instruction>,
+
+    /// Which invariant broke
+    pub invariant: Invariant,
+
+    /// What happened instead
+    pub detail: String,
+
+    /// The seed the failing program was generated from, before shrinking
+    pub seed: u64,
+}
+
+/// What the machine must always do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Invariant {
+    /// Every cycle's number is one more than the last one's, starting at `1`
+    TicksIncrease,
+
+    /// The register during every cycle is what the model says: `1` plus the
+    /// operands of every `addx` that has completed, unless something loaded
+    /// over it since
+    RegisterMatchesModel,
+
+    /// The program takes as many cycles as its instructions add up to
+    CyclesAddUp,
+
+    /// Running the program twice draws the same screen, and it's the screen
+    /// the model's register values draw
+    ScreenIsDeterministic,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Harness {
+            seed: 0x1e90_1054,
+            cases: 256,
+            max_length: 150,
+        }
+    }
+
+    /// Generates different programs. Each case's seed is derived from it.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets how many programs to generate
+    pub fn with_cases(mut self, cases: usize) -> Self {
+        self.cases = cases;
+        self
+    }
+
+    /// Sets the most instructions a generated program has. The default is
+    /// long enough to run off the end of the screen.
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Runs every case, stopping at the first failure
+    pub fn run(&self) -> Result<(), Failure> {
+        let mut seeds = Rng(self.seed);
+
+        for _ in 0..self.cases {
+            let seed = seeds.next();
+            let program = generate(&mut Rng(seed), self.max_length);
+
+            if let Err((invariant, _detail)) = check(&program) {
+                let program = shrink(program, invariant);
+                let (invariant, detail) = check(&program).expect_err("shrinking keeps it failing");
+
+                return Err(Failure {
+                    program,
+                    invariant,
+                    detail,
+                    seed,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs every case like `run()` and panics with the counterexample if
+    /// one fails, for calling straight from a test
+    pub fn assert(&self) {
+        if let Err(failure) = self.run() {
+            panic!("{}", failure);
+        }
+    }
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Harness::new()
+    }
+}
+
+/// The reference model: the register's value during every cycle of the
+/// program, on a machine with nothing attached. It's written straight from the
+/// specification, with none of the machine's scheduling.
+pub fn model(program: &[Instruction]) -> Vec<isize> {
+    let mut register: isize = 1;
+    let mut timeline = Vec::new();
+
+    for instruction in program {
+        for _ in 0..instruction.cycles() {
+            timeline.push(register);
+        }
+
+        match instruction {
+            Instruction::Addx(number) => register = register.wrapping_add(*number),
+            // Nothing is attached, so loads and input both get `0`
+            Instruction::Ldx(_) | Instruction::In => register = 0,
+            Instruction::Noop
+            | Instruction::Out
+            | Instruction::Tone(_)
+            | Instruction::Stx(_)
+            | Instruction::Custom(..) => (),
+        }
+    }
+
+    timeline
+}
+
+/// Checks every invariant on a single program, returning the first that
+/// breaks and how
+pub fn check(program: &[Instruction]) -> Result<(), (Invariant, String)> {
+    let expected = model(program);
+    let mut machine = VirtualMachine::new(VecDeque::from(program.to_vec()));
+    let mut fanout = Fanout::new(&mut machine);
+    let mut number = 0;
+
+    while let Some(cycle) = fanout.cycle() {
+        number += 1;
+
+        if cycle.number != number {
+            return Err((
+                Invariant::TicksIncrease,
+                format!("cycle {} was numbered {}", number, cycle.number),
+            ));
+        }
+
+        match expected.get(number - 1) {
+            Some(&register) if register == cycle.register => (),
+            Some(&register) => {
+                return Err((
+                    Invariant::RegisterMatchesModel,
+                    format!(
+                        "the register was {} during cycle {}, not {}",
+                        cycle.register, number, register
+                    ),
+                ))
+            }
+            None => {
+                return Err((
+                    Invariant::CyclesAddUp,
+                    format!(
+                        "cycle {} ran after the program should have finished",
+                        number
+                    ),
+                ))
+            }
+        }
+    }
+
+    if number != expected.len() {
+        return Err((
+            Invariant::CyclesAddUp,
+            format!("it took {} cycles, not {}", number, expected.len()),
+        ));
+    }
+
+    let first = draw(program);
+    let second = draw(program);
+    let modelled = screens::render_from_timeline(&expected);
+
+    if first.content_hash() != second.content_hash() {
+        return Err((
+            Invariant::ScreenIsDeterministic,
+            format!("two runs drew different screens:\n{}", first.diff(&second)),
+        ));
+    }
+
+    if !first.diff(&modelled).is_empty() {
+        return Err((
+            Invariant::ScreenIsDeterministic,
+            format!(
+                "the screen differs from the model's:\n{}",
+                first.diff(&modelled)
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn draw(program: &[Instruction]) -> Screen<VirtualMachine> {
+    let mut screen = Screen::new(VirtualMachine::new(VecDeque::from(program.to_vec())));
+    screen
+        .refresh()
+        .expect("the default overflow policy stops rather than fails");
+
+    screen
+}
+
+/// Generates a program of up to `max_length` instructions, mostly `addx` and
+/// `noop` like the puzzle's, with a few of everything else
+fn generate(rng: &mut Rng, max_length: usize) -> Vec<Instruction> {
+    let length = rng.below(max_length as u64 + 1) as usize;
+
+    (0..length)
+        .map(|_| match rng.below(20) {
+            0..=9 => Instruction::Addx(rng.below(41) as isize - 20),
+            10..=14 => Instruction::Noop,
+            15 => Instruction::Out,
+            16 => Instruction::Tone(rng.below(8) as usize),
+            17 => Instruction::Ldx(rng.below(4) as usize),
+            18 => Instruction::Stx(rng.below(4) as usize),
+            _ => Instruction::In,
+        })
+        .collect()
+}
+
+/// Removes instructions, then shrinks `addx` operands towards `0`, for as long
+/// as the program still breaks the same invariant
+fn shrink(mut program: Vec<Instruction>, invariant: Invariant) -> Vec<Instruction> {
+    let fails =
+        |program: &[Instruction]| matches!(check(program), Err((broken, _)) if broken == invariant);
+
+    let mut index = 0;
+    while index < program.len() {
+        let mut candidate = program.clone();
+        candidate.remove(index);
+
+        match fails(&candidate) {
+            true => program = candidate,
+            false => index += 1,
+        }
+    }
+
+    for index in 0..program.len() {
+        while let Instruction::Addx(number) = program[index] {
+            if number == 0 {
+                break;
+            }
+
+            let mut candidate = program.clone();
+            candidate[index] = Instruction::Addx(number / 2);
+
+            if !fails(&candidate) {
+                break;
+            }
+
+            program = candidate;
+        }
+    }
+
+    program
+}
+
+/// SplitMix64: tiny, fast, and good enough to pick instructions with, without
+/// a dependency
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+/// Shows the invariant, what happened, and the program as assembly, ready to
+/// paste into a file and debug
+impl Display for Failure {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(
+            formatter,
+            "{} broke on a program generated from seed {:#x}: {}",
+            self.invariant, self.seed, self.detail
+        )?;
+        writeln!(formatter, "shrunk to {} instructions:", self.program.len())?;
+        write!(formatter, "{}", assembly::disassemble(&self.program))
+    }
+}
+
+impl Error for Failure {}
+
+impl Display for Invariant {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Invariant::TicksIncrease => "ticks increase",
+            Invariant::RegisterMatchesModel => "the register matches the model",
+            Invariant::CyclesAddUp => "cycles add up",
+            Invariant::ScreenIsDeterministic => "the screen is deterministic",
+        };
+
+        write!(formatter, "`{}`", name)
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::VecDeque;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::assembly;
+use crate::instructions::Instruction;
+use crate::machines::VirtualMachine;
+use crate::screens::Screen;
+use crate::timelines;
+
+/// Assembles the program, raising `ValueError` if it doesn't
+fn assemble(source: &str) -> PyResult<VecDeque<Instruction>> {
+    assembly::assemble(source).map_err(|error| PyValueError::new_err(error.to_string()))
+}
+
+/// A single instruction, as Python sees it
+#[pyclass(name = "Instruction", frozen)]
+struct PyInstruction {
+    instruction: Instruction,
+}
+
+#[pymethods]
+impl PyInstruction {
+    /// The instruction's name without its operand, e.g. `addx`
+    #[getter]
+    fn mnemonic(&self) -> &'static str {
+        self.instruction.mnemonic()
+    }
+
+    /// The instruction's operand, or `None` if it takes none
+    #[getter]
+    fn operand(&self) -> Option<isize> {
+        match self.instruction {
+            Instruction::Addx(number) => Some(number),
+            Instruction::Tone(operand) | Instruction::Ldx(operand) | Instruction::Stx(operand) => {
+                Some(operand as isize)
+            }
+            Instruction::Custom(_, operand) => Some(operand),
+            Instruction::Noop | Instruction::Out | Instruction::In => None,
+        }
+    }
+
+    /// The number of cycles the instruction takes
+    #[getter]
+    fn cycles(&self) -> usize {
+        self.instruction.cycles()
+    }
+
+    fn __str__(&self) -> String {
+        self.instruction.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Instruction('{}')", self.instruction)
+    }
+}
+
+/// A machine running a program, a cycle at a time or all at once
+#[pyclass(name = "VirtualMachine", unsendable)]
+struct PyVirtualMachine {
+    machine: VirtualMachine,
+}
+
+#[pymethods]
+impl PyVirtualMachine {
+    #[new]
+    fn new(source: &str) -> PyResult<Self> {
+        let program = assemble(source)?;
+
+        Ok(PyVirtualMachine {
+            machine: VirtualMachine::new(program),
+        })
+    }
+
+    /// Runs a single cycle. Returns `False` if the program had finished.
+    fn cycle(&mut self) -> bool {
+        match self.machine.is_executing() {
+            true => {
+                self.machine.cycle();
+                true
+            }
+            false => false,
+        }
+    }
+
+    /// Runs the program until it finishes and returns how many cycles that
+    /// took
+    fn run(&mut self) -> usize {
+        let start = self.machine.get_ticks();
+
+        while self.machine.is_executing() {
+            self.machine.cycle();
+        }
+
+        self.machine.get_ticks() - start
+    }
+
+    /// Runs the program until it finishes and returns the register's value
+    /// during every cycle on the way, e.g. to plot
+    fn register_timeline(&mut self) -> Vec<isize> {
+        timelines::register_timeline(&mut self.machine)
+    }
+
+    /// The register's value during the cycle about to run
+    #[getter]
+    fn register(&self) -> isize {
+        self.machine.read_register()
+    }
+
+    /// The number of the cycle about to run. The first is `1`.
+    #[getter]
+    fn ticks(&self) -> usize {
+        self.machine.get_ticks()
+    }
+
+    #[getter]
+    fn is_executing(&self) -> bool {
+        self.machine.is_executing()
+    }
+}
+
+/// The screen a program draws, all of it drawn when it's created
+#[pyclass(name = "Screen", unsendable)]
+struct PyScreen {
+    screen: Screen<VirtualMachine>,
+}
+
+#[pymethods]
+impl PyScreen {
+    /// Runs the program on a screen of the given size. It raises
+    /// `ValueError` if the program doesn't assemble.
+    #[new]
+    #[pyo3(signature = (source, width = 40, height = 6))]
+    fn new(source: &str, width: usize, height: usize) -> PyResult<Self> {
+        let program = assemble(source)?;
+
+        let mut screen = Screen::with_size(VirtualMachine::new(program), width, height);
+        screen
+            .refresh()
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+
+        Ok(PyScreen { screen })
+    }
+
+    #[getter]
+    fn width(&self) -> usize {
+        self.screen.width()
+    }
+
+    #[getter]
+    fn height(&self) -> usize {
+        self.screen.height()
+    }
+
+    fn is_lit(&self, x: usize, y: usize) -> bool {
+        self.screen.is_lit(x, y)
+    }
+
+    /// Returns every row of pixels, `True` where they're lit. It's ready for
+    /// plotting, e.g. with `matplotlib.pyplot.imshow()`.
+    fn pixels(&self) -> Vec<Vec<bool>> {
+        (0..self.screen.height())
+            .map(|y| {
+                (0..self.screen.width())
+                    .map(|x| self.screen.is_lit(x, y))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the register's value during every cycle the screen drew
+    fn timeline(&self) -> Vec<isize> {
+        self.screen.timeline()
+    }
+
+    fn __str__(&self) -> String {
+        self.screen.to_string()
+    }
+}
+
+/// Assembles the program into its instructions
+#[pyfunction(name = "assemble")]
+fn assemble_program(source: &str) -> PyResult<Vec<PyInstruction>> {
+    let program = assemble(source)?;
+
+    Ok(program
+        .into_iter()
+        .map(|instruction| PyInstruction { instruction })
+        .collect())
+}
+
+/// Runs the program and returns the register's value during every cycle
+#[pyfunction]
+fn register_timeline(source: &str) -> PyResult<Vec<isize>> {
+    let program = assemble(source)?;
+    Ok(timelines::register_timeline(VirtualMachine::new(program)))
+}
+
+/// The `legolos` module, as `maturin` builds it
+#[pymodule]
+fn legolos(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyInstruction>()?;
+    module.add_class::<PyVirtualMachine>()?;
+    module.add_class::<PyScreen>()?;
+    module.add_function(wrap_pyfunction!(assemble_program, module)?)?;
+    module.add_function(wrap_pyfunction!(register_timeline, module)?)?;
+
+    Ok(())
+}
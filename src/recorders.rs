@@ -0,0 +1,130 @@
+use alloc::collections::VecDeque;
+use core::fmt::{Display, Formatter};
+
+use crate::instructions::Instruction;
+use crate::screens::{self, SCREEN_WIDTH};
+
+/// How many cycles a machine remembers unless told otherwise
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// Remembers the last cycles a machine ran, like an aircraft's flight
+/// recorder, so a fault deep into a long run comes with what led up to it.
+/// Every `VirtualMachine` has one; see `VirtualMachine::flight_recorder()`.
+/// Older cycles are dropped as newer ones come in, so it never grows past
+/// its capacity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlightRecorder {
+    capacity: usize,
+    events: VecDeque<Event>,
+}
+
+/// What happened during a single cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    /// The cycle's number. The first is `1`.
+    pub cycle: usize,
+
+    /// The register during the cycle
+    pub register: isize,
+
+    /// The instruction that finished during the cycle, with its position in
+    /// the program, if one did. The first cycle of an `addx` retires nothing.
+    pub retired: Option<(usize, Instruction)>,
+
+    /// Whether the beam lit its pixel during the cycle, on the
+    /// specification's screen. The machine doesn't know of any other.
+    pub lit: bool,
+}
+
+impl FlightRecorder {
+    /// Creates a recorder remembering the last `capacity` cycles. A capacity
+    /// of `0` remembers nothing.
+    pub fn new(capacity: usize) -> Self {
+        FlightRecorder {
+            capacity,
+            events: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the remembered cycles, oldest first
+    pub fn events(&self) -> impl Iterator<Item = &Event> {
+        self.events.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Forgets every cycle, keeping the room for them
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Records a cycle, forgetting the oldest if it's full
+    pub(crate) fn record(
+        &mut self,
+        cycle: usize,
+        register: isize,
+        retired: Option<(usize, Instruction)>,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+
+        let column = ((cycle - 1) % SCREEN_WIDTH) as isize;
+
+        self.events.push_back(Event {
+            cycle,
+            register,
+            retired,
+            lit: screens::sprite_columns(register, 3).contains(&column),
+        });
+    }
+}
+
+impl Default for FlightRecorder {
+    fn default() -> Self {
+        FlightRecorder::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Dumps the remembered cycles, one per line, oldest first
+impl Display for FlightRecorder {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(formatter, "the last {} cycles:", self.events.len())?;
+
+        for event in &self.events {
+            write!(
+                formatter,
+                "    cycle {:>6}  register {:>6}  {}",
+                event.cycle,
+                event.register,
+                match event.lit {
+                    true => '#',
+                    false => '.',
+                }
+            )?;
+
+            match event.retired {
+                Some((index, instruction)) => {
+                    writeln!(formatter, "  retired #{} `{}`", index, instruction)?
+                }
+                None => writeln!(formatter)?,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+use crate::machines::{Detached, RegisterSource, VirtualMachine};
+use crate::observers::Fanout;
+use crate::programs::Program;
+use crate::screens::Screen;
+
+/// The longest line read, in bytes, line ending and all. Commands and
+/// instructions are a few bytes.
+pub const MAX_LINE: usize = 4 << 10;
+
+/// The largest program `load` accepts, in bytes. The sample is under 1 KiB.
+pub const MAX_PROGRAM: usize = 1 << 20;
+
+/// How long a connection may sit without sending anything before it's hung
+/// up on
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Every command the server understands, sent in answer to `help`
+pub const HELP: &str = "\
+load              replace the program with the lines that follow, up to `.`
+step [cycles]     run that many cycles (1 by default), or until it finishes
+run               run until the program finishes
+register          read the register
+cycle             read the current cycle's number
+screen            fetch the screen as drawn so far
+reset             start the program over
+help              list these commands
+quit              hang up";
+
+/// A minimal line-based protocol for driving a machine from another process,
+/// in any language that can open a socket. Every command is a line, and so
+/// is every reply: `ok`, with whatever was asked for, or `error` and why.
+/// `screen` and `help` are the exceptions: their `ok` says how many lines
+/// follow. See `HELP` for the commands.
+///
+/// The machine starts out without a program. Each session has its own, with
+/// nothing attached, drawing on a screen of the specification's size.
+pub struct Session<S> {
+    reader: BufReader<S>,
+    writer: S,
+    program: Program,
+    machine: VirtualMachine,
+    screen: Screen<Detached>,
+}
+
+impl<S: io::Read + Write> Session<S> {
+    /// Creates a session for the given connection. `reader` and `writer` are
+    /// usually both ends of the same socket, e.g. a `TcpStream` and its
+    /// `try_clone()`.
+    pub fn new(reader: S, writer: S) -> Self {
+        let program = Program::default();
+
+        Session {
+            reader: BufReader::new(reader),
+            writer,
+            machine: program.machine(),
+            program,
+            screen: Screen::new(Detached),
+        }
+    }
+
+    /// Answers commands until the other end says `quit` or hangs up
+    pub fn serve(mut self) -> io::Result<()> {
+        while let Some(line) = self.read_line()? {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            let reply = match words.as_slice() {
+                [] => continue,
+                ["quit"] => return writeln!(self.writer, "ok"),
+                ["load"] => self.load()?,
+                ["step"] => format!("ok {}", self.step(1)),
+                ["step", cycles] => match cycles.parse() {
+                    Ok(cycles) => format!("ok {}", self.step(cycles)),
+                    Err(_) => format!("error `{}` isn't a number of cycles", cycles),
+                },
+                ["run"] => format!("ok {}", self.step(usize::MAX)),
+                ["register"] => format!("ok {}", self.machine.read_register()),
+                ["cycle"] => format!("ok {}", self.machine.ticks()),
+                ["screen"] => format!("ok {}\n{}", self.screen.height(), self.screen)
+                    .trim_end()
+                    .to_string(),
+                ["reset"] => {
+                    self.reset();
+                    "ok".to_string()
+                }
+                ["help"] => format!("ok {}\n{}", HELP.lines().count(), HELP),
+                [command, ..] if is_command(command) => {
+                    format!("error bad arguments to `{}`, try `help`", command)
+                }
+                [command, ..] => format!("error unknown command `{}`, try `help`", command),
+            };
+
+            writeln!(self.writer, "{}", reply)?;
+            self.writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a line without its line ending, or `None` once the other end
+    /// hangs up. A line longer than `MAX_LINE` ends the session with an
+    /// error, since there's no telling where the next one starts.
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+
+        let read = (&mut self.reader)
+            .take(MAX_LINE as u64)
+            .read_line(&mut line)?;
+
+        match read {
+            0 => Ok(None),
+            MAX_LINE if !line.ends_with('\n') => {
+                writeln!(self.writer, "error lines are limited to {} bytes", MAX_LINE)?;
+                Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "a line was too long",
+                ))
+            }
+            _ => Ok(Some(line.trim_end_matches(['\r', '\n']).to_string())),
+        }
+    }
+
+    /// Reads a program up to a line with a lone `.` and starts it over.
+    /// Returns the reply.
+    fn load(&mut self) -> io::Result<String> {
+        let mut source = String::new();
+        let mut too_large = false;
+
+        loop {
+            match self.read_line()? {
+                Some(line) if line == "." => break,
+                // The rest of the program is still read, so the session can
+                // go on from the line after it
+                Some(line) if too_large || source.len() + line.len() >= MAX_PROGRAM => {
+                    too_large = true;
+                }
+                Some(line) => {
+                    source.push_str(&line);
+                    source.push('\n');
+                }
+                None => return Err(ErrorKind::UnexpectedEof.into()),
+            }
+        }
+
+        if too_large {
+            return Ok(format!(
+                "error programs are limited to {} bytes",
+                MAX_PROGRAM
+            ));
+        }
+
+        Ok(match source.parse::<Program>() {
+            Ok(program) => {
+                self.program = program;
+                self.reset();
+                format!("ok {}", self.program.len())
+            }
+            Err(error) => format!("error {}", error),
+        })
+    }
+
+    /// Runs up to `cycles` cycles and returns how many there were
+    fn step(&mut self, cycles: usize) -> usize {
+        let mut fanout = Fanout::new(&mut self.machine).with_observer(&mut self.screen);
+        let mut stepped = 0;
+
+        while stepped < cycles && fanout.cycle().is_some() {
+            stepped += 1;
+        }
+
+        stepped
+    }
+
+    fn reset(&mut self) {
+        self.machine = self.program.machine();
+        self.screen = Screen::new(Detached);
+    }
+}
+
+/// Listens at the address and serves every connection in a session of its
+/// own, on a thread of its own, for as long as the process runs. Returns the
+/// address it's listening at, which is useful after binding port `0`.
+pub fn serve(address: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || {
+                // A client that hangs up mid-command is its own problem
+                let _ = session(stream);
+            });
+        }
+    });
+
+    Ok(address)
+}
+
+/// Returns `true` if `HELP` lists the command
+fn is_command(command: &str) -> bool {
+    HELP.lines()
+        .any(|line| line.split_whitespace().next() == Some(command))
+}
+
+fn session(stream: TcpStream) -> io::Result<()> {
+    stream.set_nodelay(true)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    Session::new(stream.try_clone()?, stream).serve()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn drives_a_machine() {
+        let replies = serve_lines(
+            "load\naddx 3\nnoop\n.\nstep\nregister\nrun\nregister\ncycle\nfly\nstep x\nquit\nregister\n",
+        );
+
+        assert_eq!(
+            replies,
+            "ok 2\nok 1\nok 1\nok 2\nok 4\nok 4\n\
+             error unknown command `fly`, try `help`\n\
+             error `x` isn't a number of cycles\nok\n"
+        );
+    }
+
+    #[test]
+    fn refuses_lines_and_programs_too_long() {
+        let replies = serve_lines(&format!("{}\nregister\n", "a".repeat(MAX_LINE)));
+        assert_eq!(
+            replies,
+            format!("error lines are limited to {} bytes\n", MAX_LINE)
+        );
+
+        let program = "noop\n".repeat(MAX_PROGRAM / 5 + 1);
+        let replies = serve_lines(&format!("load\n{}.\nregister\n", program));
+        assert_eq!(
+            replies,
+            format!(
+                "error programs are limited to {} bytes\nok 1\n",
+                MAX_PROGRAM
+            )
+        );
+    }
+
+    /// Serves a session reading the lines, and returns its replies
+    fn serve_lines(lines: &str) -> String {
+        let replies = Rc::new(RefCell::new(Vec::new()));
+        let end = |input| Connection {
+            input,
+            output: Rc::clone(&replies),
+        };
+
+        let _ = Session::new(end(lines.as_bytes()), end(&[])).serve();
+
+        let replies = replies.borrow().clone();
+        String::from_utf8(replies).unwrap()
+    }
+
+    /// One end of a connection in memory
+    struct Connection<'a> {
+        input: &'a [u8],
+        output: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Read for Connection<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buffer)
+        }
+    }
+
+    impl Write for Connection<'_> {
+        fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+            self.output.borrow_mut().write(buffer)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
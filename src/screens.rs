@@ -1,118 +1,675 @@
-use std::fmt::{Display, Formatter};
+use alloc::{vec, vec::Vec};
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+use core::ops::RangeInclusive;
+use core::str::FromStr;
 
-use crate::machines::VirtualMachine;
+use crate::fnv;
+use crate::machines::{Detached, RegisterSource, VirtualMachine};
+use crate::observers::{Cycle, CycleObserver};
+
+mod compositing;
+mod coverage;
+mod diffs;
+mod encoding;
+mod formatting;
+mod frame;
+mod lazy;
+#[cfg(feature = "evcxr")]
+mod notebook;
+mod ocr;
+mod overflow;
+mod packing;
+#[cfg(feature = "egui")]
+mod painting;
+#[cfg(feature = "plotters")]
+mod plotting;
+mod provenance;
+mod regions;
+#[cfg(feature = "serde")]
+mod serialization;
+mod transforms;
+#[cfg(feature = "ratatui")]
+mod widget;
+
+pub use compositing::{Blend, Marker, Overlay, OverlayDisplay};
+pub use coverage::Coverage;
+pub use diffs::{PixelDiff, ScreenDiff};
+pub use encoding::DecodeScreenError;
+pub use formatting::{DisplayOptions, ScreenDisplay};
+pub use frame::Frame;
+pub use lazy::LazyScreen;
+pub use ocr::OcrError;
+pub use overflow::{OverflowError, OverflowPolicy, Refresh};
+#[cfg(feature = "egui")]
+pub use painting::ScreenView;
+#[cfg(feature = "plotters")]
+pub use plotting::PlotError;
+pub use provenance::Provenance;
+pub use regions::SubScreen;
+pub use transforms::Axis;
+#[cfg(feature = "ratatui")]
+pub use widget::{ScreenWidget, Theme};
 
 /// A single pixel on the screen. It can only be lit or dark.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Pixel {
-    Lit,
-    Dark,
+    Lit = 1,
+    Dark = 0,
 }
 
-impl Into<char> for Pixel {
+impl From<Pixel> for char {
     /// Transform the pixel into the character it should display on the screen
-    fn into(self) -> char {
-        match self {
+    fn from(pixel: Pixel) -> Self {
+        match pixel {
             Pixel::Lit => '#',
             Pixel::Dark => '.',
         }
     }
 }
 
+impl TryFrom<char> for Pixel {
+    type Error = char;
+
+    /// The inverse of displaying a pixel. Anything other than `#` or `.` is
+    /// handed back as the error.
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '#' => Ok(Pixel::Lit),
+            '.' => Ok(Pixel::Dark),
+            other => Err(other),
+        }
+    }
+}
+
 /// Defined by the specification
-const SCREEN_WIDTH: usize = 40;
+pub const SCREEN_WIDTH: usize = 40;
 
 /// Defined by the specification
-const SCREEN_HEIGHT: usize = 6;
+pub const SCREEN_HEIGHT: usize = 6;
+
+/// The most pixels a screen can have when its size comes from outside the
+/// program, e.g. from a file. A screen allocates for every pixel, and for
+/// every column besides, so a size read from anywhere untrusted is checked
+/// against this, in both directions and overall, before making the screen.
+pub const MAX_PIXELS: usize = 1 << 24;
+
+/// Returns `true` if a screen of the size keeps within `MAX_PIXELS`
+pub(crate) fn fits(width: usize, height: usize) -> bool {
+    width <= MAX_PIXELS
+        && height <= MAX_PIXELS
+        && width
+            .checked_mul(height)
+            .is_some_and(|area| area <= MAX_PIXELS)
+}
+
+/// What the screen saw on a single cycle. Keeping these around lets the
+/// screen be drawn again, e.g. at a different size, without the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Beat {
+    cycle: usize,
+    register: isize,
+    instruction: Option<usize>,
+}
 
 /// A screen is a visual output controlled by an underlying machine. In this
 /// case, the underlying machine executes a program which instructs the screen
-/// when and where to light pixels.
-pub struct Screen {
-    machine: VirtualMachine,
+/// when and where to light pixels. Any `RegisterSource` can stand in for the
+/// machine, e.g. a scripted sequence of register values.
+///
+/// The specification's screen is 40×6 but any size works, even after the
+/// machine has run: see `resize()`.
+pub struct Screen<M = VirtualMachine> {
+    machine: M,
     sprite_middle: isize,
-    pixels: [Pixel; SCREEN_WIDTH * SCREEN_HEIGHT],
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+
+    /// Every cycle the screen has drawn, oldest first
+    timeline: Vec<Beat>,
+
+    /// What happened at each pixel when the beam passed over it. It's only
+    /// recorded when asked for with `with_provenance()`.
+    provenance: Option<Vec<Option<Provenance>>>,
+
+    /// How many cycles the sprite covered each column
+    coverage: Coverage,
+
+    /// What to do when the machine keeps cycling past the last pixel
+    overflow: OverflowPolicy,
+
+    /// How many pixels wide the sprite is. The specification's is `3`.
+    sprite_width: usize,
+
+    /// How many times the screen wrapped into a new frame so far
+    frame: usize,
 }
 
-impl Screen {
+/// Returns the columns a sprite of the given width covers when its middle is
+/// at `middle`. An even-width sprite has one more column right of its middle.
+pub(crate) fn sprite_columns(middle: isize, width: usize) -> RangeInclusive<isize> {
+    let width = width as isize;
+    let left = middle.saturating_sub((width - 1) / 2);
+
+    left..=left.saturating_add(width - 1)
+}
+
+impl<M: RegisterSource> Screen<M> {
     /// Creates a new screen controlled by the given VM and its program
-    pub fn new(machine: VirtualMachine) -> Self {
+    pub fn new(machine: M) -> Self {
+        Screen::with_size(machine, SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+
+    /// Creates a new screen of any size, rather than the specification's
+    pub fn with_size(machine: M, width: usize, height: usize) -> Self {
         // Initially, every pixel is dark
-        let pixels = [Pixel::Dark; SCREEN_WIDTH * SCREEN_HEIGHT];
+        let pixels = vec![Pixel::Dark; width * height];
 
         // A sprite is three pixels wide. Its middle is the easiest way to track
-        // its position. Initially, the middle is at index `1` so one pixel to
-        // the left (index `0`) and one to the right (index `2`) are also
-        // displayed.
-        let middle = 1;
+        // its position. It follows the register, which for a fresh VM is `1`,
+        // so one pixel to the left (index `0`) and one to the right (index
+        // `2`) are also displayed.
+        let middle = machine.read_register();
 
         Screen {
             machine,
             pixels,
+            width,
+            height,
             sprite_middle: middle,
+            timeline: Vec::new(),
+            provenance: None,
+            coverage: Coverage::new(width),
+            overflow: OverflowPolicy::default(),
+            sprite_width: 3,
+            frame: 0,
         }
     }
 
+    /// Sets what happens when the machine keeps cycling after the last pixel
+    /// is drawn. By default the screen stops refreshing.
+    pub fn with_overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// Sets how many pixels wide the sprite is, rather than the
+    /// specification's three. The register still tracks the sprite's middle;
+    /// an even width has one more pixel to the right of it than the left.
+    pub fn with_sprite_width(mut self, width: usize) -> Self {
+        self.sprite_width = width;
+        self
+    }
+
+    /// Records, for every pixel the beam passes over, the cycle and
+    /// instruction responsible for lighting it or leaving it dark. See
+    /// `provenance()`.
+    pub fn with_provenance(mut self) -> Self {
+        self.provenance = Some(vec![None; self.width * self.height]);
+        self
+    }
+
+    /// Returns the machine driving this screen. The screen stays attached to
+    /// it, so this is the way to inspect e.g. its ticks after a refresh.
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    /// Returns the machine driving this screen for modification, e.g. to
+    /// adjust it before the next refresh.
+    pub fn machine_mut(&mut self) -> &mut M {
+        &mut self.machine
+    }
+
+    /// Detaches the screen from its machine and returns the machine so it can
+    /// be inspected or reused. The rendered pixels are discarded.
+    pub fn into_machine(self) -> M {
+        self.machine
+    }
+
+    /// Forgets everything drawn, as if the screen were new, but keeps its
+    /// size, settings, and buffers. Reset the machine first to draw its
+    /// program again from the start.
+    pub fn reset(&mut self) {
+        self.clear();
+        self.timeline.clear();
+        self.coverage.clear();
+        self.frame = 0;
+        self.sprite_middle = self.machine.read_register();
+    }
+
     /// Refresh the screen so it is ready to be displayed. Underneath, this
     /// cycles the VM to determine if a pixel should be lit or not.
-    pub fn refresh(&mut self) {
+    ///
+    /// Programs don't have to fit the screen exactly. If the VM is still
+    /// executing once the last pixel is drawn, the screen's `OverflowPolicy`
+    /// decides what happens and the result says which it was.
+    pub fn refresh(&mut self) -> Result<Refresh, OverflowError> {
+        let mut wraps = 0;
+
         while self.machine.is_executing() {
-            self.light();
+            let beat = Beat {
+                cycle: self.machine.ticks(),
+                register: self.sprite_middle,
+                instruction: self.machine.instruction_index(),
+            };
+
+            if let Some(stopped) = self.draw(beat, &mut wraps)? {
+                return Ok(stopped);
+            }
+
+            self.timeline.push(beat);
             self.machine.cycle();
             self.sprite_middle = self.machine.read_register();
         }
+
+        match wraps {
+            0 => Ok(Refresh::Completed),
+            _ => Ok(Refresh::Wrapped(wraps)),
+        }
+    }
+}
+
+impl<M> Screen<M> {
+    /// Returns the number of pixels in each row
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the register's value during every cycle the screen has drawn,
+    /// oldest first. It's everything needed to draw the screen again, e.g.
+    /// with `render_from_timeline()`.
+    pub fn timeline(&self) -> Vec<isize> {
+        self.timeline.iter().map(|beat| beat.register).collect()
+    }
+
+    /// Changes the screen's dimensions and draws every cycle it has seen so
+    /// far again, as if the machine had been attached to a screen of this
+    /// size all along. The machine itself isn't touched, so interactive
+    /// viewers can flip between sizes freely.
+    ///
+    /// The overflow policy applies just like during a refresh. Stopping or
+    /// failing early doesn't lose anything: resizing back draws it all.
+    pub fn resize(&mut self, width: usize, height: usize) -> Result<Refresh, OverflowError> {
+        self.width = width;
+        self.height = height;
+        self.pixels = vec![Pixel::Dark; width * height];
+        self.coverage = Coverage::new(width);
+        self.frame = 0;
+
+        if self.provenance.is_some() {
+            self.provenance = Some(vec![None; width * height]);
+        }
+
+        let mut wraps = 0;
+
+        for index in 0..self.timeline.len() {
+            if let Some(stopped) = self.draw(self.timeline[index], &mut wraps)? {
+                return Ok(stopped);
+            }
+        }
+
+        match wraps {
+            0 => Ok(Refresh::Completed),
+            _ => Ok(Refresh::Wrapped(wraps)),
+        }
+    }
+
+    /// Forgets every cycle after the first `cycles` and draws the rest again,
+    /// e.g. when a debugger goes back in time
+    pub(crate) fn rewind(&mut self, cycles: usize) {
+        self.timeline.truncate(cycles);
+
+        // Whatever the policy, the cycles kept were all drawn before
+        let _ = self.resize(self.width, self.height);
+    }
+
+    /// Draws a single cycle, first applying the overflow policy if the beam
+    /// has run off the end of the frame. Returns how the refresh ended if it
+    /// has to stop here.
+    fn draw(&mut self, beat: Beat, wraps: &mut usize) -> Result<Option<Refresh>, OverflowError> {
+        let area = self.width * self.height;
+
+        // Nothing fits on a screen without pixels, and wrapping wouldn't make
+        // room, so the beam is off the end straight away whatever the policy
+        if area == 0 {
+            let cycle = beat.cycle;
+
+            return match self.overflow {
+                OverflowPolicy::Error => Err(OverflowError { cycle }),
+                OverflowPolicy::Stop | OverflowPolicy::Wrap => Ok(Some(Refresh::Stopped { cycle })),
+            };
+        }
+
+        let frame = (beat.cycle - 1) / area;
+
+        if frame > self.frame {
+            let cycle = beat.cycle;
+
+            match self.overflow {
+                OverflowPolicy::Stop => return Ok(Some(Refresh::Stopped { cycle })),
+                OverflowPolicy::Error => return Err(OverflowError { cycle }),
+                OverflowPolicy::Wrap => {
+                    self.clear();
+                    self.frame = frame;
+                    *wraps += 1;
+                }
+            }
+        }
+
+        self.light(beat);
+
+        Ok(None)
+    }
+
+    /// Darkens every pixel and forgets their provenance, ready for a new frame
+    fn clear(&mut self) {
+        self.pixels.fill(Pixel::Dark);
+
+        if let Some(provenance) = &mut self.provenance {
+            provenance.fill(None);
+        }
+    }
+
+    /// Returns the columns the sprite covers when its middle is at `middle`
+    fn sprite_columns(&self, middle: isize) -> RangeInclusive<isize> {
+        sprite_columns(middle, self.sprite_width)
     }
 
     /// Lights a pixel if the VM signals for it
-    fn light(&mut self) {
+    fn light(&mut self, beat: Beat) {
         // The screen updates pixels according to the program executing in the
         // underlying VM. It cycles the VM 240 times -- once for each pixel on
         // the screen. At each cycle, the index for the pixel is the machine's
-        // tick (or cycle count). Since it's an index, subtract one.
-        let screen_index = self.machine.get_ticks() - 1;
-        let middle = self.sprite_middle;
+        // tick (or cycle count). Since it's an index, subtract one. Once the
+        // screen wraps, the index starts over in each new frame.
+        let screen_index = (beat.cycle - 1) % (self.width * self.height);
+        let middle = beat.register;
 
-        if screen_index >= SCREEN_HEIGHT * SCREEN_WIDTH {
-            // TODO this is required for the sample but not the puzzle input
-            return;
-        }
+        let sprite = self.sprite_columns(middle);
+        self.coverage.record(sprite.clone());
 
         // The screen index maps into a flat array, but the screen is vertical.
         // The row index is found using the screen width.
-        let row_index = screen_index % SCREEN_WIDTH;
+        let row_index = screen_index % self.width;
         let row_index = row_index as isize;
 
-        // The sprite is three pixels wide and tracked by its center position.
-        // Light the pixel when the underlying program's register value (given
-        // `screen_index` and `row_index`) aligns with the sprite's position on
-        // the row.
-        let should_light =
-            row_index == middle || row_index == middle - 1 || row_index == middle + 1;
-
-        let index = screen_index as usize;
+        // The sprite is three pixels wide (unless set otherwise) and tracked
+        // by its center position. Light the pixel when the underlying
+        // program's register value (given `screen_index` and `row_index`)
+        // aligns with the sprite's position on the row.
+        let should_light = sprite.contains(&row_index);
 
         if should_light {
-            self.pixels[index] = Pixel::Lit;
+            self.pixels[screen_index] = Pixel::Lit;
+        }
+
+        if let Some(provenance) = &mut self.provenance {
+            provenance[screen_index] = Some(Provenance {
+                cycle: beat.cycle,
+                instruction: beat.instruction,
+                register: middle,
+                lit: should_light,
+            });
         }
     }
+
+    /// Returns `true` if the pixel in column `x` of row `y` is lit. Pixels
+    /// off the screen are dark.
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x] == Pixel::Lit
+    }
+
+    /// Lights or darkens the pixel in column `x` of row `y`, e.g. to draw on
+    /// top of a render. Pixels off the screen are ignored.
+    pub fn set_pixel(&mut self, x: usize, y: usize, lit: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+
+        self.pixels[y * self.width + x] = if lit { Pixel::Lit } else { Pixel::Dark };
+    }
+
+    /// Returns a hash of the displayed pixels. It's FNV-1a over the screen's
+    /// dimensions followed by one byte per pixel, so it doesn't depend on the
+    /// platform, the Rust version, or `std`'s randomized hashers. Regression
+    /// suites can store it instead of a full golden image.
+    pub fn content_hash(&self) -> u64 {
+        let dimensions = [self.width as u64, self.height as u64];
+        let dimensions = dimensions.iter().flat_map(|d| d.to_le_bytes());
+        let pixels = self.pixels.iter().map(|pixel| *pixel as u8);
+
+        fnv::fnv1a(dimensions.chain(pixels))
+    }
+}
+
+/// Draws a screen straight from the register's value during each cycle (e.g.
+/// as returned by `timelines::register_timeline()`), without a machine. The
+/// value at index `i` is the register during cycle `i + 1`. Cycles past the
+/// end of the screen are ignored.
+pub fn render_from_timeline(timeline: &[isize]) -> Screen<Detached> {
+    let mut screen = Screen::new(Detached);
+
+    screen.timeline = timeline
+        .iter()
+        .enumerate()
+        .map(|(index, register)| Beat {
+            cycle: index + 1,
+            register: *register,
+            instruction: None,
+        })
+        .collect();
+
+    screen
+        .resize(SCREEN_WIDTH, SCREEN_HEIGHT)
+        .expect("the default overflow policy stops rather than fails");
+
+    screen
 }
 
-impl Display for Screen {
-    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut column = 0;
+/// Draws each cycle as it's observed, the same as `refresh()` would. This way
+/// a screen can share a machine with other observers; give it a `Detached`
+/// machine of its own. Cycles the overflow policy won't draw are dropped.
+impl<M> CycleObserver for Screen<M> {
+    fn observe(&mut self, cycle: &Cycle) {
+        let beat = Beat {
+            cycle: cycle.number,
+            register: cycle.register,
+            instruction: cycle.instruction,
+        };
 
-        for pixel in self.pixels {
-            if column == 40 {
-                writeln!(formatter).unwrap();
-                column = 0;
+        let mut wraps = 0;
+
+        if let Ok(None) = self.draw(beat, &mut wraps) {
+            self.timeline.push(beat);
+        }
+    }
+}
+
+/// Displays one line per row, lit pixels as `#` and dark ones as `.`. Use
+/// `display_with()` for other layouts.
+impl<M> Display for Screen<M> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        self.display_with(&DisplayOptions::default()).fmt(formatter)
+    }
+}
+
+/// Two screens are equal when they display the same pixels. The machines
+/// driving them (and where their sprites ended up) don't matter.
+impl<M> PartialEq for Screen<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.pixels == other.pixels
+    }
+}
+
+impl<M> Eq for Screen<M> {}
+
+/// Parses a screen from the text it displays, e.g. a golden render kept in a
+/// test. The screen is as wide as the rows and as tall as there are rows. It's
+/// attached to a default (i.e. idle) machine.
+impl<M: RegisterSource + Default> FromStr for Screen<M> {
+    type Err = ParseScreenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().collect();
+        let width = lines
+            .first()
+            .ok_or(ParseScreenError::Empty)?
+            .chars()
+            .count();
+
+        let mut screen = Screen::with_size(M::default(), width, lines.len());
+
+        for (row, line) in lines.iter().enumerate() {
+            let length = line.chars().count();
+
+            if length != width {
+                return Err(ParseScreenError::Ragged { row, length, width });
             }
 
-            let pixel: char = pixel.into();
-            write!(formatter, "{}", pixel).unwrap();
+            for (column, c) in line.chars().enumerate() {
+                let pixel =
+                    Pixel::try_from(c).map_err(|character| ParseScreenError::UnknownPixel {
+                        row,
+                        column,
+                        character,
+                    })?;
 
-            column += 1;
+                screen.pixels[row * width + column] = pixel;
+            }
         }
 
-        writeln!(formatter)
+        Ok(screen)
     }
-}
\ No newline at end of file
+}
+
+/// The ways the text form of a screen can be malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseScreenError {
+    /// There are no rows at all
+    Empty,
+
+    /// A row isn't as wide as the first one
+    Ragged {
+        row: usize,
+        length: usize,
+        width: usize,
+    },
+
+    /// A character is neither a lit (`#`) nor a dark (`.`) pixel
+    UnknownPixel {
+        row: usize,
+        column: usize,
+        character: char,
+    },
+}
+
+impl Display for ParseScreenError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseScreenError::Empty => write!(formatter, "a screen needs at least one row"),
+            ParseScreenError::Ragged { row, length, width } => write!(
+                formatter,
+                "expected {} pixels in row {} but found {}",
+                width, row, length
+            ),
+            ParseScreenError::UnknownPixel {
+                row,
+                column,
+                character,
+            } => write!(
+                formatter,
+                "unknown pixel {:?} at row {}, column {}",
+                character, row, column
+            ),
+        }
+    }
+}
+
+impl Error for ParseScreenError {}
+
+/// Every way drawing, reading, or decoding a screen can fail, for callers that
+/// don't mind which
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScreenError {
+    Overflow(OverflowError),
+    Parse(ParseScreenError),
+    Decode(DecodeScreenError),
+    Ocr(OcrError),
+}
+
+impl From<OverflowError> for ScreenError {
+    fn from(error: OverflowError) -> Self {
+        ScreenError::Overflow(error)
+    }
+}
+
+impl From<ParseScreenError> for ScreenError {
+    fn from(error: ParseScreenError) -> Self {
+        ScreenError::Parse(error)
+    }
+}
+
+impl From<DecodeScreenError> for ScreenError {
+    fn from(error: DecodeScreenError) -> Self {
+        ScreenError::Decode(error)
+    }
+}
+
+impl From<OcrError> for ScreenError {
+    fn from(error: OcrError) -> Self {
+        ScreenError::Ocr(error)
+    }
+}
+
+impl Display for ScreenError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ScreenError::Overflow(error) => write!(formatter, "{}", error),
+            ScreenError::Parse(error) => write!(formatter, "{}", error),
+            ScreenError::Decode(error) => write!(formatter, "{}", error),
+            ScreenError::Ocr(error) => write!(formatter, "{}", error),
+        }
+    }
+}
+
+impl Error for ScreenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ScreenError::Overflow(error) => Some(error),
+            ScreenError::Parse(error) => Some(error),
+            ScreenError::Decode(error) => Some(error),
+            ScreenError::Ocr(error) => Some(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::Instruction;
+    use crate::programs::Program;
+
+    #[test]
+    fn screen_without_pixels_stops_whatever_the_policy() {
+        let program = Program::from(vec![Instruction::Noop; 3]);
+
+        for (width, height) in [(0, 6), (40, 0), (0, 0)] {
+            for policy in [OverflowPolicy::Stop, OverflowPolicy::Wrap] {
+                let mut screen =
+                    Screen::with_size(program.machine(), width, height).with_overflow(policy);
+                assert_eq!(screen.refresh(), Ok(Refresh::Stopped { cycle: 1 }));
+            }
+
+            let mut screen = Screen::with_size(program.machine(), width, height)
+                .with_overflow(OverflowPolicy::Error);
+            assert!(screen.refresh().is_err());
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+use super::{OverflowPolicy, Pixel, Screen};
+
+/// How `Screen::overlay()` combines a pixel with the one laid over it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Blend {
+    /// Lit if either is lit, like drawing one screen on top of the other
+    #[default]
+    Or,
+
+    /// Lit only if both are lit, like masking one screen with the other
+    And,
+
+    /// Lit if exactly one is lit, which lights up where the screens differ
+    Xor,
+}
+
+impl Blend {
+    fn apply(&self, below: bool, above: bool) -> bool {
+        match self {
+            Blend::Or => below || above,
+            Blend::And => below && above,
+            Blend::Xor => below != above,
+        }
+    }
+}
+
+impl<M> Screen<M> {
+    /// Blends `other` into this screen, pixel by pixel, lining up their top
+    /// left corners. Pixels outside `other` are blended as if it were dark
+    /// there, so `And` clears them while `Or` and `Xor` leave them be.
+    pub fn overlay<N>(&mut self, other: &Screen<N>, blend: Blend) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let below = self.is_lit(x, y);
+                let above = other.is_lit(x, y);
+
+                self.set_pixel(x, y, blend.apply(below, above));
+            }
+        }
+    }
+
+    /// Returns an overlay marking where the beam will draw next and the
+    /// sprite's window on that row. A screen that can't draw any more (e.g.
+    /// it stopped at the end of its frame) has no beam to mark.
+    pub fn debug_overlay(&self) -> Overlay {
+        let mut overlay = Overlay::new(self.width, self.height);
+
+        let Some((column, row)) = self.beam() else {
+            return overlay;
+        };
+
+        for x in self.sprite_columns(self.sprite_middle) {
+            if let Ok(x) = usize::try_from(x) {
+                overlay.mark(x, row, Marker::Sprite);
+            }
+        }
+
+        overlay.mark(column, row, Marker::Beam);
+        overlay
+    }
+
+    /// Returns the column and row the beam will draw next, if the screen can
+    /// draw any more
+    pub(super) fn beam(&self) -> Option<(usize, usize)> {
+        let area = self.width * self.height;
+        let mut beam = self.timeline.len();
+
+        if self.overflow == OverflowPolicy::Wrap && area > 0 {
+            beam %= area;
+        }
+
+        match beam < area {
+            true => Some((beam % self.width, beam / self.width)),
+            false => None,
+        }
+    }
+
+    /// Returns something that displays the screen with `overlay`'s markers
+    /// drawn on top. The screen's pixels stay as they are.
+    pub fn display_with_overlay<'a>(&'a self, overlay: &'a Overlay) -> OverlayDisplay<'a, M> {
+        OverlayDisplay {
+            screen: self,
+            overlay,
+        }
+    }
+}
+
+/// Something drawn on top of a screen to help debug it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    /// Where the beam is, drawn as `@`
+    Beam,
+
+    /// Inside the sprite's window, drawn as `+` over lit pixels and `-` over
+    /// dark ones so the pixel still shows through
+    Sprite,
+
+    /// Anything else, drawn as the given character
+    Custom(char),
+}
+
+/// A layer of markers the same size as a screen. It's kept apart from the
+/// screen's pixels, so marking never changes what the program drew.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overlay {
+    width: usize,
+    height: usize,
+    markers: Vec<Option<Marker>>,
+}
+
+impl Overlay {
+    /// Creates an overlay without any markers
+    pub fn new(width: usize, height: usize) -> Self {
+        Overlay {
+            width,
+            height,
+            markers: vec![None; width * height],
+        }
+    }
+
+    /// Marks a position, replacing any marker already there. Positions off
+    /// the overlay are ignored.
+    pub fn mark(&mut self, x: usize, y: usize, marker: Marker) {
+        if x < self.width && y < self.height {
+            self.markers[y * self.width + x] = Some(marker);
+        }
+    }
+
+    /// Returns the marker at a position, if any
+    pub fn marker(&self, x: usize, y: usize) -> Option<Marker> {
+        match x < self.width && y < self.height {
+            true => self.markers[y * self.width + x],
+            false => None,
+        }
+    }
+
+    /// Removes every marker
+    pub fn clear(&mut self) {
+        self.markers.fill(None);
+    }
+}
+
+/// A screen paired with an overlay to draw on top of it. See
+/// `Screen::display_with_overlay()`.
+pub struct OverlayDisplay<'a, M> {
+    screen: &'a Screen<M>,
+    overlay: &'a Overlay,
+}
+
+impl<M> Display for OverlayDisplay<'_, M> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        let screen = self.screen;
+
+        for y in 0..screen.height {
+            let row: String = (0..screen.width)
+                .map(|x| {
+                    let lit = screen.is_lit(x, y);
+
+                    match self.overlay.marker(x, y) {
+                        Some(Marker::Beam) => '@',
+                        Some(Marker::Sprite) if lit => '+',
+                        Some(Marker::Sprite) => '-',
+                        Some(Marker::Custom(c)) => c,
+                        None if lit => Pixel::Lit.into(),
+                        None => Pixel::Dark.into(),
+                    }
+                })
+                .collect();
+
+            writeln!(formatter, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::{Display, Formatter};
+use core::ops::RangeInclusive;
+
+use super::Screen;
+
+/// How many cycles the sprite spent over each column of the screen. A column
+/// the sprite never covers can never light up, whatever row the beam is on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    columns: Vec<usize>,
+}
+
+impl Coverage {
+    pub(super) fn new(width: usize) -> Self {
+        Coverage {
+            columns: vec![0; width],
+        }
+    }
+
+    /// Forgets every cycle counted
+    pub(super) fn clear(&mut self) {
+        self.columns.fill(0);
+    }
+
+    /// Counts one cycle where the sprite covered `columns`
+    pub(super) fn record(&mut self, columns: RangeInclusive<isize>) {
+        for column in columns {
+            if (0..self.columns.len() as isize).contains(&column) {
+                self.columns[column as usize] += 1;
+            }
+        }
+    }
+
+    /// Returns the number of cycles the sprite covered each column
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// Returns the columns the sprite never covered
+    pub fn uncovered(&self) -> Vec<usize> {
+        (0..self.columns.len())
+            .filter(|column| self.columns[*column] == 0)
+            .collect()
+    }
+}
+
+impl<M> Screen<M> {
+    /// Returns how many cycles the sprite covered each column so far
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+}
+
+/// The report is a heatmap: one character per column, from blank (never
+/// covered) to `@` (covered the most), followed by the columns that were
+/// never covered.
+impl Display for Coverage {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        const SHADES: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+        let most = self.columns.iter().copied().max().unwrap_or(0).max(1);
+
+        for count in self.columns.iter().copied() {
+            // Anything covered at all gets at least the faintest shade so it
+            // can't be confused with a column that never was
+            let shade = match count {
+                0 => 0,
+                _ => (count * (SHADES.len() - 1)).div_ceil(most),
+            };
+
+            write!(formatter, "{}", SHADES[shade])?;
+        }
+
+        writeln!(formatter)?;
+
+        let uncovered = self.uncovered();
+
+        if uncovered.is_empty() {
+            return writeln!(formatter, "every column was covered");
+        }
+
+        let uncovered: Vec<String> = uncovered.iter().map(|c| c.to_string()).collect();
+        writeln!(formatter, "never covered: {}", uncovered.join(", "))
+    }
+}
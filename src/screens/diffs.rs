@@ -0,0 +1,131 @@
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use super::Screen;
+
+/// How a single pixel compares between two screens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelDiff {
+    /// Both screens agree, whether lit or dark
+    Same(bool),
+
+    /// Lit on this screen but dark on the other
+    Extra,
+
+    /// Dark on this screen but lit on the other
+    Missing,
+}
+
+impl From<PixelDiff> for char {
+    fn from(diff: PixelDiff) -> Self {
+        match diff {
+            PixelDiff::Same(true) => '#',
+            PixelDiff::Same(false) => '.',
+            PixelDiff::Extra => '+',
+            PixelDiff::Missing => '-',
+        }
+    }
+}
+
+/// The pixel-by-pixel comparison of two screens. When displayed, pixels that
+/// match appear as usual while mismatches stand out as `+` (unexpectedly lit)
+/// or `-` (unexpectedly dark). The alternate form (`{:#}`) also dims matching
+/// pixels and colors mismatches for terminals.
+#[derive(Debug, Clone)]
+pub struct ScreenDiff {
+    width: usize,
+    height: usize,
+    pixels: Vec<PixelDiff>,
+}
+
+impl ScreenDiff {
+    /// Returns the number of pixels that differ
+    pub fn mismatches(&self) -> usize {
+        self.pixels
+            .iter()
+            .filter(|pixel| !matches!(pixel, PixelDiff::Same(_)))
+            .count()
+    }
+
+    /// Returns `true` when the screens displayed exactly the same pixels
+    pub fn is_empty(&self) -> bool {
+        self.mismatches() == 0
+    }
+
+    /// Returns the comparison of the pixel in column `x` of row `y`
+    pub fn pixel(&self, x: usize, y: usize) -> Option<PixelDiff> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        Some(self.pixels[y * self.width + x])
+    }
+}
+
+impl<M> Screen<M> {
+    /// Compares this screen against `other` (e.g. a golden render), pixel by
+    /// pixel. Mismatches are reported from this screen's point of view. If
+    /// the sizes differ, the comparison covers both and pixels off either
+    /// screen count as dark.
+    pub fn diff<N>(&self, other: &Screen<N>) -> ScreenDiff {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = match (self.is_lit(x, y), other.is_lit(x, y)) {
+                    (mine, theirs) if mine == theirs => PixelDiff::Same(mine),
+                    (true, _) => PixelDiff::Extra,
+                    (false, _) => PixelDiff::Missing,
+                };
+
+                pixels.push(pixel);
+            }
+        }
+
+        ScreenDiff {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+impl Display for ScreenDiff {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        // ANSI escapes for dim, red, green, and back to normal
+        const DIM: &str = "\x1b[2m";
+        const RED: &str = "\x1b[1;31m";
+        const GREEN: &str = "\x1b[1;32m";
+        const RESET: &str = "\x1b[0m";
+
+        let colored = formatter.alternate();
+
+        for row in self.pixels.chunks(self.width.max(1)) {
+            for pixel in row {
+                let c: char = (*pixel).into();
+
+                if !colored {
+                    write!(formatter, "{}", c)?;
+                    continue;
+                }
+
+                let color = match pixel {
+                    PixelDiff::Same(_) => DIM,
+                    PixelDiff::Extra => GREEN,
+                    PixelDiff::Missing => RED,
+                };
+
+                write!(formatter, "{}{}{}", color, c, RESET)?;
+            }
+
+            writeln!(formatter)?;
+        }
+
+        match self.mismatches() {
+            1 => writeln!(formatter, "1 mismatched pixel"),
+            n => writeln!(formatter, "{} mismatched pixels", n),
+        }
+    }
+}
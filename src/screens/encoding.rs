@@ -0,0 +1,107 @@
+use alloc::{string::String, vec::Vec};
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use crate::base64;
+use crate::machines::RegisterSource;
+use crate::varint;
+
+use super::Screen;
+
+impl<M> Screen<M> {
+    /// Encodes the framebuffer as a short base64 string, small enough to keep
+    /// golden images on one line. It holds the screen's dimensions followed by
+    /// its pixels packed eight to a byte, so a 40×6 frame takes 44 characters.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::new();
+
+        varint::write(&mut bytes, self.width);
+        varint::write(&mut bytes, self.height);
+        bytes.extend(self.pack());
+
+        base64::encode(&bytes)
+    }
+}
+
+impl<M: RegisterSource + Default> Screen<M> {
+    /// The inverse of `encode()`. The screen is attached to a default (i.e.
+    /// idle) machine, just like a parsed one.
+    pub fn decode(encoded: &str) -> Result<Self, DecodeScreenError> {
+        let bytes = base64::decode(encoded.trim()).ok_or(DecodeScreenError::NotBase64)?;
+        let mut bytes = bytes.as_slice();
+
+        let width = varint::read(&mut bytes).ok_or(DecodeScreenError::Truncated)?;
+        let height = varint::read(&mut bytes).ok_or(DecodeScreenError::Truncated)?;
+
+        if !super::fits(width, height) {
+            return Err(DecodeScreenError::TooBig { width, height });
+        }
+
+        let pixels = Screen::<M>::unpack(width, height, bytes)
+            .ok_or(DecodeScreenError::WrongSize { width, height })?;
+
+        let mut screen = Screen::with_size(M::default(), width, height);
+        screen.pixels = pixels;
+
+        Ok(screen)
+    }
+}
+
+/// The ways an encoded screen can be malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeScreenError {
+    /// The string isn't base64
+    NotBase64,
+
+    /// The data ends before the screen's dimensions do
+    Truncated,
+
+    /// The pixels don't fill a screen of the encoded size
+    WrongSize { width: usize, height: usize },
+
+    /// The screen would have more pixels than `screens::MAX_PIXELS` allows
+    TooBig { width: usize, height: usize },
+}
+
+impl Display for DecodeScreenError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeScreenError::NotBase64 => write!(formatter, "the screen isn't valid base64"),
+            DecodeScreenError::Truncated => {
+                write!(formatter, "the screen's dimensions are cut short")
+            }
+            DecodeScreenError::WrongSize { width, height } => write!(
+                formatter,
+                "the pixels don't fill a {}×{} screen",
+                width, height
+            ),
+            DecodeScreenError::TooBig { width, height } => write!(
+                formatter,
+                "a {}×{} screen is too big (at most {} pixels)",
+                width,
+                height,
+                super::MAX_PIXELS
+            ),
+        }
+    }
+}
+
+impl Error for DecodeScreenError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machines::Detached;
+
+    #[test]
+    fn decoding_refuses_screens_too_big_to_make() {
+        // A width of 2^40 and a height of 0
+        assert_eq!(
+            Screen::<Detached>::decode("gICAgIAgAA==").err(),
+            Some(DecodeScreenError::TooBig {
+                width: 1 << 40,
+                height: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Formatter};
+
+use super::{Pixel, Screen};
+
+/// How to lay a screen out as text. The defaults match `Display`: one line
+/// per row, lit pixels as `#`, dark ones as `.`, and a newline after the last
+/// row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayOptions {
+    lit: char,
+    dark: char,
+    row_separator: String,
+    trailing_newline: bool,
+    border: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        DisplayOptions {
+            lit: Pixel::Lit.into(),
+            dark: Pixel::Dark.into(),
+            row_separator: "\n".to_string(),
+            trailing_newline: true,
+            border: false,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Sets the characters drawn for lit and dark pixels
+    pub fn pixels(mut self, lit: char, dark: char) -> Self {
+        self.lit = lit;
+        self.dark = dark;
+        self
+    }
+
+    /// Sets what goes between rows, e.g. `"/"` to fit a screen on one line
+    pub fn row_separator(mut self, separator: &str) -> Self {
+        self.row_separator = separator.to_string();
+        self
+    }
+
+    /// Sets whether a newline follows the last row
+    pub fn trailing_newline(mut self, enabled: bool) -> Self {
+        self.trailing_newline = enabled;
+        self
+    }
+
+    /// Sets whether a box is drawn around the screen. The box's edges are
+    /// separated from the rows just like rows are from each other.
+    pub fn border(mut self, enabled: bool) -> Self {
+        self.border = enabled;
+        self
+    }
+}
+
+/// A screen paired with the options to display it with. See
+/// `Screen::display_with()`.
+pub struct ScreenDisplay<'a, M> {
+    screen: &'a Screen<M>,
+    options: &'a DisplayOptions,
+}
+
+impl<M> Screen<M> {
+    /// Returns something that displays the screen laid out with `options`
+    /// rather than the default format
+    pub fn display_with<'a>(&'a self, options: &'a DisplayOptions) -> ScreenDisplay<'a, M> {
+        ScreenDisplay {
+            screen: self,
+            options,
+        }
+    }
+}
+
+impl<M> Display for ScreenDisplay<'_, M> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        let screen = self.screen;
+        let options = self.options;
+
+        let edge = format!("+{}+", "-".repeat(screen.width));
+        let mut lines = Vec::with_capacity(screen.height + 2);
+
+        if options.border {
+            lines.push(edge.clone());
+        }
+
+        for y in 0..screen.height {
+            let row: String = (0..screen.width)
+                .map(|x| match screen.is_lit(x, y) {
+                    true => options.lit,
+                    false => options.dark,
+                })
+                .collect();
+
+            match options.border {
+                true => lines.push(format!("|{}|", row)),
+                false => lines.push(row),
+            }
+        }
+
+        if options.border {
+            lines.push(edge);
+        }
+
+        write!(formatter, "{}", lines.join(&options.row_separator))?;
+
+        if options.trailing_newline {
+            writeln!(formatter)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use core::fmt::{Display, Formatter};
+
+use super::{OverflowError, OverflowPolicy, Pixel, Refresh, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::images::Raster;
+use crate::machines::RegisterSource;
+use crate::observers::{Cycle, CycleObserver};
+
+/// A screen of `W`×`H` pixels held in an array rather than on the heap, to go
+/// with a `FixedMachine`. It draws like a `Screen` with the specification's
+/// three-pixel sprite, but keeps no timeline, coverage, or provenance, and it
+/// doesn't own its machine: refresh it from one machine after another
+/// without allocating anything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame<const W: usize = SCREEN_WIDTH, const H: usize = SCREEN_HEIGHT> {
+    pixels: [[bool; W]; H],
+    overflow: OverflowPolicy,
+}
+
+impl<const W: usize, const H: usize> Frame<W, H> {
+    /// Creates a frame with every pixel dark
+    pub fn new() -> Self {
+        Frame {
+            pixels: [[false; W]; H],
+            overflow: OverflowPolicy::default(),
+        }
+    }
+
+    /// Sets what happens when the machine keeps cycling after the last pixel
+    /// is drawn. By default the frame stops refreshing.
+    pub fn with_overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// Cycles the machine until it finishes, lighting pixels as it goes, just
+    /// like `Screen::refresh()`. The frame isn't cleared first: call
+    /// `clear()` to draw a new program from scratch.
+    pub fn refresh(&mut self, machine: &mut impl RegisterSource) -> Result<Refresh, OverflowError> {
+        let area = W * H;
+        let mut wraps = 0;
+
+        while machine.is_executing() {
+            let cycle = machine.ticks();
+
+            // The beam runs off the end of the frame at the start of every
+            // frame after the first, or straight away if there's no frame
+            if area == 0 || (cycle > 1 && (cycle - 1).is_multiple_of(area)) {
+                match self.overflow {
+                    OverflowPolicy::Stop => return Ok(Refresh::Stopped { cycle }),
+                    OverflowPolicy::Error => return Err(OverflowError { cycle }),
+                    OverflowPolicy::Wrap if area == 0 => return Ok(Refresh::Stopped { cycle }),
+                    OverflowPolicy::Wrap => {
+                        self.clear();
+                        wraps += 1;
+                    }
+                }
+            }
+
+            self.light(cycle, machine.read_register());
+            machine.cycle();
+        }
+
+        match wraps {
+            0 => Ok(Refresh::Completed),
+            _ => Ok(Refresh::Wrapped(wraps)),
+        }
+    }
+
+    /// Lights the pixel the beam is at during the cycle if the sprite, with
+    /// its middle at `register`, covers it
+    fn light(&mut self, cycle: usize, register: isize) {
+        let index = (cycle - 1) % (W * H);
+        let (row, column) = (index / W, index % W);
+
+        if (register.saturating_sub(1)..=register.saturating_add(1)).contains(&(column as isize)) {
+            self.pixels[row][column] = true;
+        }
+    }
+
+    /// Darkens every pixel
+    pub fn clear(&mut self) {
+        self.pixels = [[false; W]; H];
+    }
+
+    pub fn width(&self) -> usize {
+        W
+    }
+
+    pub fn height(&self) -> usize {
+        H
+    }
+
+    /// Returns `true` if the pixel in column `x` of row `y` is lit. Pixels
+    /// off the frame are dark.
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < W && y < H && self.pixels[y][x]
+    }
+}
+
+impl<const W: usize, const H: usize> Default for Frame<W, H> {
+    fn default() -> Self {
+        Frame::new()
+    }
+}
+
+/// Draws every cycle it's told of, e.g. alongside other observers. An
+/// observer can't stop the machine, so past the end of the frame it wraps if
+/// the policy is `Wrap` and ignores the rest of the cycles otherwise.
+impl<const W: usize, const H: usize> CycleObserver for Frame<W, H> {
+    fn observe(&mut self, cycle: &Cycle) {
+        let area = W * H;
+
+        if area == 0 {
+            return;
+        }
+
+        if cycle.number > area && self.overflow != OverflowPolicy::Wrap {
+            return;
+        }
+
+        if cycle.number > 1 && (cycle.number - 1).is_multiple_of(area) {
+            self.clear();
+        }
+
+        self.light(cycle.number, cycle.register);
+    }
+}
+
+impl<const W: usize, const H: usize> Raster for Frame<W, H> {
+    fn width(&self) -> usize {
+        W
+    }
+
+    fn height(&self) -> usize {
+        H
+    }
+
+    fn intensity(&self, x: usize, y: usize) -> u8 {
+        match self.is_lit(x, y) {
+            true => u8::MAX,
+            false => 0,
+        }
+    }
+}
+
+/// Draws the frame just like a `Screen`, one row per line
+impl<const W: usize, const H: usize> Display for Frame<W, H> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        for row in &self.pixels {
+            for &lit in row {
+                let pixel = match lit {
+                    true => Pixel::Lit,
+                    false => Pixel::Dark,
+                };
+
+                write!(formatter, "{}", char::from(pixel))?;
+            }
+
+            writeln!(formatter)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use alloc::vec::Vec;
+
+use crate::instructions::Instruction;
+
+use super::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// A screen that works out each pixel on demand instead of running the whole
+/// program. Building it only sums up the program: which cycle each
+/// instruction starts on and the register's value while it executes. Any
+/// pixel is then a binary search away, which suits looking at a single row or
+/// a small region of a long program.
+///
+/// There's no machine, so there's no bus or keyboard either. Loads and input
+/// leave the register as it was, which only matches a real run if the program
+/// doesn't use them.
+#[derive(Debug, Clone)]
+pub struct LazyScreen {
+    width: usize,
+    height: usize,
+
+    /// The cycle each instruction starts on, in program order
+    starts: Vec<usize>,
+
+    /// The register's value during each instruction. Instructions only change
+    /// it once they complete, so it holds for all their cycles.
+    registers: Vec<isize>,
+
+    /// The number of cycles the whole program takes
+    cycles: usize,
+}
+
+impl<'a> FromIterator<&'a Instruction> for LazyScreen {
+    fn from_iter<T: IntoIterator<Item = &'a Instruction>>(program: T) -> Self {
+        LazyScreen::new(program)
+    }
+}
+
+impl LazyScreen {
+    /// Sums up the program for a screen of the specification's size
+    pub fn new<'a>(program: impl IntoIterator<Item = &'a Instruction>) -> Self {
+        let mut starts = Vec::new();
+        let mut registers = Vec::new();
+
+        // Same as a fresh VM: the first cycle is `1` and so is the register
+        let mut cycle = 1;
+        let mut register: isize = 1;
+
+        for instruction in program {
+            starts.push(cycle);
+            registers.push(register);
+
+            cycle += instruction.cycles();
+
+            if let Instruction::Addx(number) = instruction {
+                register = register.wrapping_add(*number);
+            }
+        }
+
+        LazyScreen {
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+            starts,
+            registers,
+            cycles: cycle - 1,
+        }
+    }
+
+    /// Changes the dimensions the pixels are worked out for. Nothing needs to
+    /// be recomputed.
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Returns the register's value during `cycle`, or `None` if the program
+    /// has finished by then
+    pub fn register_at(&self, cycle: usize) -> Option<isize> {
+        if cycle == 0 || cycle > self.cycles {
+            return None;
+        }
+
+        // The last instruction starting on or before the cycle is the one
+        // executing during it
+        let index = self.starts.partition_point(|start| *start <= cycle) - 1;
+
+        Some(self.registers[index])
+    }
+
+    /// Returns `true` if the pixel in column `x` of row `y` would be lit. The
+    /// beam only draws the first frame, and pixels it never reaches because
+    /// the program finished first are dark.
+    pub fn pixel_at(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let cycle = y * self.width + x + 1;
+
+        match self.register_at(cycle) {
+            Some(middle) => (middle - x as isize).abs() <= 1,
+            None => false,
+        }
+    }
+
+    /// Returns every pixel in row `y`, left to right
+    pub fn row(&self, y: usize) -> Vec<bool> {
+        (0..self.width).map(|x| self.pixel_at(x, y)).collect()
+    }
+}
@@ -0,0 +1,31 @@
+use super::Screen;
+use crate::{base64, images};
+
+/// How far the image is scaled up, so a 40×6 screen is legible in a notebook
+const SCALE: usize = 8;
+
+impl<M> Screen<M> {
+    /// Shows the screen as an image when it's the value of a cell in a Rust
+    /// Jupyter notebook, rather than its `Debug` text. evcxr calls this
+    /// itself; the notebook picks whichever of the HTML or the PNG it shows
+    /// best.
+    pub fn evcxr_display(&self) {
+        let png = base64::encode(&images::png(self, SCALE));
+
+        println!("EVCXR_BEGIN_CONTENT text/html");
+        println!(
+            "<img src=\"data:image/png;base64,{}\" width=\"{}\" height=\"{}\" \
+             style=\"image-rendering: pixelated\" alt=\"{}×{} screen\">",
+            png,
+            self.width * SCALE,
+            self.height * SCALE,
+            self.width,
+            self.height
+        );
+        println!("EVCXR_END_CONTENT");
+
+        println!("EVCXR_BEGIN_CONTENT image/png");
+        println!("{}", png);
+        println!("EVCXR_END_CONTENT");
+    }
+}
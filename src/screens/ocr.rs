@@ -0,0 +1,86 @@
+use alloc::string::{String, ToString};
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+use crate::font::{self, GLYPH_ADVANCE, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+use super::{Screen, SubScreen};
+
+impl<M> Screen<M> {
+    /// Reads the letters drawn on the screen. The top of the screen is split
+    /// into cells one glyph wide (plus a column of spacing) and each cell must
+    /// match a glyph in the font exactly. Blank cells read as spaces, though
+    /// trailing ones are dropped.
+    pub fn decode_text(&self) -> Result<String, OcrError> {
+        let mut text = String::new();
+
+        for position in 0..self.width / GLYPH_ADVANCE {
+            let cell = self.crop(position * GLYPH_ADVANCE, 0, GLYPH_WIDTH, GLYPH_HEIGHT);
+            let rows = cell.glyph_rows();
+
+            let glyph = font::recognize(rows).ok_or(OcrError::UnknownGlyph { position, rows })?;
+
+            text.push(glyph.letter());
+        }
+
+        Ok(text.trim_end().to_string())
+    }
+}
+
+impl<M> SubScreen<'_, M> {
+    /// Reads the glyph in the region's top left corner, if it matches one in
+    /// the font exactly. Crop a single cell to read one letter at a time.
+    pub fn decode_glyph(&self) -> Option<char> {
+        font::recognize(self.glyph_rows()).map(|glyph| glyph.letter())
+    }
+
+    /// Packs the glyph-sized cell in the region's top left corner into the
+    /// font's row format
+    fn glyph_rows(&self) -> [u8; GLYPH_HEIGHT] {
+        let mut rows = [0; GLYPH_HEIGHT];
+
+        for (y, row) in rows.iter_mut().enumerate() {
+            for x in 0..GLYPH_WIDTH {
+                if self.is_lit(x, y) {
+                    *row |= 0b1000 >> x;
+                }
+            }
+        }
+
+        rows
+    }
+}
+
+/// Reading the screen fails when a cell doesn't hold a known glyph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The cell at `position` (counting glyphs from the left) didn't match any
+    /// glyph. Its pixels are included in the font's row format.
+    UnknownGlyph {
+        position: usize,
+        rows: [u8; GLYPH_HEIGHT],
+    },
+}
+
+impl Display for OcrError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            OcrError::UnknownGlyph { position, rows } => {
+                writeln!(formatter, "unknown glyph at position {}:", position)?;
+
+                for row in rows {
+                    for x in 0..GLYPH_WIDTH {
+                        let lit = row & (0b1000 >> x) != 0;
+                        write!(formatter, "{}", if lit { '#' } else { '.' })?;
+                    }
+
+                    writeln!(formatter)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for OcrError {}
@@ -0,0 +1,52 @@
+use core::error::Error;
+use core::fmt::{Display, Formatter};
+
+/// What a screen does when its machine keeps cycling after the beam has drawn
+/// the last pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Clear the screen and keep drawing from the top-left pixel, as if the
+    /// next frame had begun
+    Wrap,
+
+    /// Stop refreshing, leaving the rest of the program unexecuted
+    #[default]
+    Stop,
+
+    /// Stop refreshing and report an `OverflowError`
+    Error,
+}
+
+/// How a refresh ended when it didn't fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refresh {
+    /// The machine finished within the frame
+    Completed,
+
+    /// The machine ran past the end of the frame this many times. Only the
+    /// last frame is displayed.
+    Wrapped(usize),
+
+    /// The machine ran past the end of the frame and was left before
+    /// executing this cycle
+    Stopped { cycle: usize },
+}
+
+/// The machine ran past the end of the frame when the policy was `Error`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowError {
+    /// The first cycle without a pixel to draw
+    pub cycle: usize,
+}
+
+impl Display for OverflowError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            formatter,
+            "the machine was still executing at cycle {} but the screen was full",
+            self.cycle
+        )
+    }
+}
+
+impl Error for OverflowError {}
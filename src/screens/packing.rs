@@ -0,0 +1,40 @@
+use alloc::vec::Vec;
+
+use super::{Pixel, Screen};
+
+impl<M> Screen<M> {
+    /// Packs the pixels eight to a byte, row by row from the top-left pixel.
+    /// The most significant bit comes first and the last byte is padded with
+    /// dark pixels.
+    pub(super) fn pack(&self) -> Vec<u8> {
+        self.pixels
+            .chunks(8)
+            .map(|pixels| {
+                pixels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, pixel)| **pixel == Pixel::Lit)
+                    .fold(0, |byte, (bit, _)| byte | (0x80 >> bit))
+            })
+            .collect()
+    }
+
+    /// The inverse of `pack()`. Returns `None` unless there are exactly
+    /// enough bytes for `width * height` pixels.
+    pub(super) fn unpack(width: usize, height: usize, bytes: &[u8]) -> Option<Vec<Pixel>> {
+        let count = width.checked_mul(height)?;
+
+        if bytes.len() != count.div_ceil(8) {
+            return None;
+        }
+
+        let pixels = (0..count)
+            .map(|index| match bytes[index / 8] & (0x80 >> (index % 8)) {
+                0 => Pixel::Dark,
+                _ => Pixel::Lit,
+            })
+            .collect();
+
+        Some(pixels)
+    }
+}
@@ -0,0 +1,87 @@
+use egui::{Color32, Rect, Response, Sense, Stroke, StrokeKind, Ui, Vec2, Widget};
+
+use super::Screen;
+
+/// A screen painted into an egui UI, every pixel a square of `scale` points.
+/// Add it on every frame to follow the screen as it refreshes. See
+/// `Screen::view()`.
+pub struct ScreenView<'a, M> {
+    screen: &'a Screen<M>,
+    scale: f32,
+    lit: Color32,
+    dark: Color32,
+    cursor: Option<Color32>,
+}
+
+impl<M> Screen<M> {
+    /// Returns a widget painting the screen green on black at 8 points per
+    /// pixel, without a beam cursor
+    pub fn view(&self) -> ScreenView<'_, M> {
+        ScreenView {
+            screen: self,
+            scale: 8.0,
+            lit: Color32::from_rgb(0x33, 0xff, 0x66),
+            dark: Color32::BLACK,
+            cursor: None,
+        }
+    }
+}
+
+impl<M> ScreenView<'_, M> {
+    /// Sets the size of a pixel, in points
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale.max(1.0);
+        self
+    }
+
+    /// Sets the colors of lit and dark pixels
+    pub fn colors(mut self, lit: Color32, dark: Color32) -> Self {
+        self.lit = lit;
+        self.dark = dark;
+        self
+    }
+
+    /// Outlines the pixel the beam will draw next in the given color. Nothing
+    /// is outlined once the screen can't draw any more.
+    pub fn cursor(mut self, color: Color32) -> Self {
+        self.cursor = Some(color);
+        self
+    }
+
+    /// Returns where the pixel in column `x` of row `y` is painted
+    fn pixel_rect(&self, origin: Rect, x: usize, y: usize) -> Rect {
+        let corner = origin.min + Vec2::new(x as f32, y as f32) * self.scale;
+        Rect::from_min_size(corner, Vec2::splat(self.scale))
+    }
+}
+
+impl<M> Widget for ScreenView<'_, M> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let size = Vec2::new(self.screen.width as f32, self.screen.height as f32) * self.scale;
+        let (area, response) = ui.allocate_exact_size(size, Sense::hover());
+
+        if !ui.is_rect_visible(area) {
+            return response;
+        }
+
+        let painter = ui.painter_at(area);
+        painter.rect_filled(area, 0.0, self.dark);
+
+        for y in 0..self.screen.height {
+            for x in 0..self.screen.width {
+                if self.screen.is_lit(x, y) {
+                    painter.rect_filled(self.pixel_rect(area, x, y), 0.0, self.lit);
+                }
+            }
+        }
+
+        if let (Some(color), Some((x, y))) = (self.cursor, self.screen.beam()) {
+            let stroke = Stroke::new((self.scale / 8.0).max(1.0), color);
+            let rect = self.pixel_rect(area, x, y);
+
+            painter.rect_stroke(rect, 0.0, stroke, StrokeKind::Inside);
+        }
+
+        response
+    }
+}
@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use super::Screen;
+use crate::batches::INTERESTING_CYCLES;
+
+/// The size of a plot, in pixels for PNGs or user units for SVGs
+const SIZE: (u32, u32) = (1024, 480);
+
+impl<M> Screen<M> {
+    /// Plots the register's value during every cycle the screen has drawn as
+    /// a line chart, marking the cycles whose signal strength the puzzle
+    /// samples. The extension picks the format: `.png` or `.svg`.
+    pub fn plot_timeline(&self, path: impl AsRef<Path>) -> Result<(), PlotError> {
+        let path = path.as_ref();
+        let timeline = self.timeline();
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("png") => plot(
+                BitMapBackend::new(path, SIZE).into_drawing_area(),
+                &timeline,
+            ),
+            Some("svg") => plot(SVGBackend::new(path, SIZE).into_drawing_area(), &timeline),
+            extension => Err(PlotError::UnknownFormat(
+                extension.unwrap_or_default().to_string(),
+            )),
+        }
+    }
+}
+
+fn plot<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    timeline: &[isize],
+) -> Result<(), PlotError> {
+    let drawing =
+        |error: DrawingAreaErrorKind<DB::ErrorType>| PlotError::Drawing(error.to_string());
+
+    // Leave a little room above and below the line so it doesn't run along
+    // the frame
+    let lowest = timeline.iter().copied().min().unwrap_or(0).min(0) - 1;
+    let highest = timeline.iter().copied().max().unwrap_or(0).max(0) + 1;
+    let cycles = timeline.len().max(1);
+
+    root.fill(&WHITE).map_err(drawing)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("register by cycle", ("sans-serif", 20))
+        .margin(12)
+        .x_label_area_size(32)
+        .y_label_area_size(40)
+        .build_cartesian_2d(1..cycles, lowest..highest)
+        .map_err(drawing)?;
+
+    chart
+        .configure_mesh()
+        .x_desc("cycle")
+        .y_desc("register")
+        .draw()
+        .map_err(drawing)?;
+
+    let points = || {
+        timeline
+            .iter()
+            .enumerate()
+            .map(|(index, &register)| (index + 1, register))
+    };
+
+    chart
+        .draw_series(LineSeries::new(points(), &BLUE))
+        .map_err(drawing)?;
+
+    chart
+        .draw_series(
+            points()
+                .filter(|(cycle, _)| INTERESTING_CYCLES.contains(cycle))
+                .map(|point| Circle::new(point, 4, RED.filled())),
+        )
+        .map_err(drawing)?;
+
+    root.present().map_err(drawing)
+}
+
+/// Why a timeline couldn't be plotted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlotError {
+    /// The path's extension isn't `png` or `svg`
+    UnknownFormat(String),
+
+    /// The chart couldn't be drawn or written
+    Drawing(String),
+}
+
+impl Display for PlotError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlotError::UnknownFormat(extension) => write!(
+                formatter,
+                "can't plot to `.{}`; use `.png` or `.svg`",
+                extension
+            ),
+            PlotError::Drawing(problem) => write!(formatter, "can't plot: {}", problem),
+        }
+    }
+}
+
+impl Error for PlotError {}
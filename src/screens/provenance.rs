@@ -0,0 +1,34 @@
+use super::Screen;
+
+/// The story of a single pixel: when the beam passed over it, which
+/// instruction was executing, and where the sprite was. A pixel that should
+/// have been lit but wasn't points at the instruction that left the sprite
+/// elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Provenance {
+    /// The cycle during which the beam drew the pixel
+    pub cycle: usize,
+
+    /// The position in the program of the instruction occupying that cycle,
+    /// if the machine reports one
+    pub instruction: Option<usize>,
+
+    /// The register (i.e. the sprite's middle) during that cycle
+    pub register: isize,
+
+    /// Whether the sprite overlapped the beam and lit the pixel
+    pub lit: bool,
+}
+
+impl<M> Screen<M> {
+    /// Returns the provenance of the pixel in column `x` of row `y`. It's only
+    /// available for screens created `with_provenance()` and for pixels the
+    /// beam has reached.
+    pub fn provenance(&self, x: usize, y: usize) -> Option<Provenance> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.provenance.as_ref()?[y * self.width + x]
+    }
+}
@@ -0,0 +1,111 @@
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+
+use super::{Pixel, Screen};
+use crate::images::Raster;
+use crate::machines::Detached;
+
+impl<M> Screen<M> {
+    /// Returns a view of the `width`×`height` region whose top left corner is
+    /// at column `x` of row `y`. The region is cut down to fit the screen, so
+    /// it may be smaller than asked for, or even empty.
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> SubScreen<'_, M> {
+        let left = x.min(self.width);
+        let top = y.min(self.height);
+
+        SubScreen {
+            screen: self,
+            left,
+            top,
+            width: width.min(self.width - left),
+            height: height.min(self.height - top),
+        }
+    }
+}
+
+/// A rectangular region of a screen, borrowed rather than copied. Coordinates
+/// are relative to the region's top left corner. See `Screen::crop()`.
+pub struct SubScreen<'a, M> {
+    screen: &'a Screen<M>,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<M> SubScreen<'_, M> {
+    /// Returns the column of the screen the region starts at
+    pub fn left(&self) -> usize {
+        self.left
+    }
+
+    /// Returns the row of the screen the region starts at
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Returns the number of columns in the region
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows in the region
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns `true` if the pixel in column `x` of row `y` of the region is
+    /// lit. Pixels off the region are dark, even if they're on the screen.
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.screen.is_lit(self.left + x, self.top + y)
+    }
+
+    /// Copies the region's pixels into a screen of its own
+    pub fn to_screen(&self) -> Screen<Detached> {
+        let mut screen = Screen::with_size(Detached, self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                screen.set_pixel(x, y, self.is_lit(x, y));
+            }
+        }
+
+        screen
+    }
+}
+
+/// Exports just the region
+impl<M> Raster for SubScreen<'_, M> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn intensity(&self, x: usize, y: usize) -> u8 {
+        match self.is_lit(x, y) {
+            true => u8::MAX,
+            false => 0,
+        }
+    }
+}
+
+/// Displays the region the same way as a whole screen
+impl<M> Display for SubScreen<'_, M> {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        for y in 0..self.height {
+            let row: String = (0..self.width)
+                .map(|x| match self.is_lit(x, y) {
+                    true => char::from(Pixel::Lit),
+                    false => char::from(Pixel::Dark),
+                })
+                .collect();
+
+            writeln!(formatter, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
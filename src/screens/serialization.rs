@@ -0,0 +1,68 @@
+use alloc::vec::Vec;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::machines::RegisterSource;
+
+use super::Screen;
+
+/// What a screen looks like serialized: its dimensions and its pixels packed
+/// eight to a byte, so a 40×6 frame takes 30 bytes
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Screen")]
+struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+/// Only the framebuffer is serialized. The machine, the timeline, and any
+/// provenance are left behind.
+impl<M> Serialize for Screen<M> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let frame = Frame {
+            width: self.width,
+            height: self.height,
+            pixels: self.pack(),
+        };
+
+        frame.serialize(serializer)
+    }
+}
+
+/// A deserialized screen is attached to a default (i.e. idle) machine, just
+/// like a parsed one
+impl<'de, M: RegisterSource + Default> Deserialize<'de> for Screen<M> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let frame = Frame::deserialize(deserializer)?;
+
+        if !super::fits(frame.width, frame.height) {
+            return Err(D::Error::custom(
+                "the screen is bigger than screens::MAX_PIXELS allows",
+            ));
+        }
+
+        let pixels = Screen::<M>::unpack(frame.width, frame.height, &frame.pixels)
+            .ok_or_else(|| D::Error::custom("the pixels don't match the screen's size"))?;
+
+        let mut screen = Screen::with_size(M::default(), frame.width, frame.height);
+        screen.pixels = pixels;
+
+        Ok(screen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machines::Detached;
+
+    #[test]
+    fn screens_too_big_to_make_dont_deserialize() {
+        let screen = serde_json::from_str::<Screen<Detached>>(
+            r#"{"width":1099511627776,"height":0,"pixels":[]}"#,
+        );
+
+        assert!(screen.is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use super::Screen;
+use crate::machines::Detached;
+
+/// The line a screen is mirrored across
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Across the middle row, swapping top and bottom
+    Horizontal,
+
+    /// Across the middle column, swapping left and right
+    Vertical,
+}
+
+impl<M> Screen<M> {
+    /// Returns the screen's pixels rotated clockwise by `degrees`, or
+    /// counterclockwise if negative. Only quarter turns make sense for a grid
+    /// of pixels, so anything else returns `None`. A quarter turn swaps the
+    /// width and height.
+    ///
+    /// The result is only a framebuffer: there's no machine behind it and so
+    /// no timeline, provenance, or coverage.
+    pub fn rotated(&self, degrees: i32) -> Option<Screen<Detached>> {
+        if degrees % 90 != 0 {
+            return None;
+        }
+
+        let (width, height) = (self.width, self.height);
+
+        let same = || Screen::with_size(Detached, width, height);
+        let turned = || Screen::with_size(Detached, height, width);
+
+        // Each arm works out where the rotated screen's pixels come from
+        let rotated = match degrees.rem_euclid(360) {
+            0 => self.transformed(same(), |x, y| (x, y)),
+            90 => self.transformed(turned(), |x, y| (y, height - 1 - x)),
+            180 => self.transformed(same(), |x, y| (width - 1 - x, height - 1 - y)),
+            _ => self.transformed(turned(), |x, y| (width - 1 - y, x)),
+        };
+
+        Some(rotated)
+    }
+
+    /// Returns the screen's pixels mirrored across `axis`. Like `rotated()`,
+    /// the result is only a framebuffer.
+    pub fn mirrored(&self, axis: Axis) -> Screen<Detached> {
+        let (width, height) = (self.width, self.height);
+        let mirrored = Screen::with_size(Detached, width, height);
+
+        match axis {
+            Axis::Horizontal => self.transformed(mirrored, |x, y| (x, height - 1 - y)),
+            Axis::Vertical => self.transformed(mirrored, |x, y| (width - 1 - x, y)),
+        }
+    }
+
+    /// Lights each pixel of `into` to match the pixel of this screen at
+    /// `source(x, y)`
+    fn transformed(
+        &self,
+        mut into: Screen<Detached>,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Screen<Detached> {
+        for y in 0..into.height {
+            for x in 0..into.width {
+                let (from_x, from_y) = source(x, y);
+                into.set_pixel(x, y, self.is_lit(from_x, from_y));
+            }
+        }
+
+        into
+    }
+}
@@ -0,0 +1,108 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use super::{Pixel, Screen};
+
+/// How a screen's pixels look in a terminal UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    lit: char,
+    dark: char,
+    lit_style: Style,
+    dark_style: Style,
+}
+
+/// Solid blocks glowing green on black, like a phosphor CRT
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            lit: '█',
+            dark: ' ',
+            lit_style: Style::new().fg(Color::Green).bg(Color::Black),
+            dark_style: Style::new().bg(Color::Black),
+        }
+    }
+}
+
+impl Theme {
+    /// The same characters as `Display` in the terminal's own colors
+    pub fn plain() -> Self {
+        Theme {
+            lit: Pixel::Lit.into(),
+            dark: Pixel::Dark.into(),
+            lit_style: Style::new(),
+            dark_style: Style::new(),
+        }
+    }
+
+    /// Sets the characters drawn for lit and dark pixels
+    pub fn pixels(mut self, lit: char, dark: char) -> Self {
+        self.lit = lit;
+        self.dark = dark;
+        self
+    }
+
+    /// Sets the styles (e.g. colors) of lit and dark pixels
+    pub fn styles(mut self, lit: Style, dark: Style) -> Self {
+        self.lit_style = lit;
+        self.dark_style = dark;
+        self
+    }
+}
+
+/// A screen drawn as a ratatui widget, one terminal cell per pixel. The part
+/// of the screen that doesn't fit the area is cut off. See `Screen::widget()`.
+pub struct ScreenWidget<'a, M> {
+    screen: &'a Screen<M>,
+    theme: Theme,
+}
+
+impl<M> Screen<M> {
+    /// Returns a widget drawing the screen with the default theme. Draw it on
+    /// every frame to follow the screen as it refreshes.
+    pub fn widget(&self) -> ScreenWidget<'_, M> {
+        ScreenWidget {
+            screen: self,
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl<M> ScreenWidget<'_, M> {
+    /// Sets the theme the screen is drawn with
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+}
+
+impl<M> Widget for ScreenWidget<'_, M> {
+    fn render(self, area: Rect, buffer: &mut Buffer) {
+        let width = (self.screen.width).min(area.width as usize);
+        let height = (self.screen.height).min(area.height as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let (symbol, style) = match self.screen.is_lit(x, y) {
+                    true => (self.theme.lit, self.theme.lit_style),
+                    false => (self.theme.dark, self.theme.dark_style),
+                };
+
+                let position = (area.x + x as u16, area.y + y as u16);
+
+                if let Some(cell) = buffer.cell_mut(position) {
+                    cell.set_char(symbol).set_style(style);
+                }
+            }
+        }
+    }
+}
+
+/// Draws the screen with the default theme
+impl<M> Widget for &Screen<M> {
+    fn render(self, area: Rect, buffer: &mut Buffer) {
+        self.widget().render(area, buffer)
+    }
+}
@@ -0,0 +1,220 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST, INT};
+
+use crate::observers::{Cycle, CycleObserver};
+
+/// How many operations a script may spend on a single cycle, so a script that
+/// never returns stops the run with an error rather than hanging it
+const OPERATIONS_PER_CYCLE: u64 = 100_000;
+
+/// A rhai script hooked into a run, so a run can be customized without
+/// recompiling. The script defines `on_cycle(cycle)`, which is called after
+/// every cycle with a map of the cycle's `number`, `register`, `instruction`
+/// (its position in the program, or `()`), and `signal` (e.g. `"out 4"`, or
+/// `()`).
+///
+/// Functions in rhai can't see the script's variables, so anything the hook
+/// needs to remember between cycles goes on `this`, a map that starts empty
+/// and lasts the whole run. The hook can also call `annotate(text)` to note
+/// something about the cycle and `pause()` to ask whoever is running the
+/// machine to stop and wait:
+///
+/// ```text
+/// fn on_cycle(cycle) {
+///     if cycle.register > (this.highest ?? 0) {
+///         this.highest = cycle.register;
+///         annotate(`new high of ${cycle.register}`);
+///     }
+///
+///     if cycle.number == 220 { pause(); }
+/// }
+/// ```
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+
+    /// The script's `this`
+    state: Dynamic,
+
+    /// What the script asked for during the cycle it's hooked into
+    requests: Rc<RefCell<Requests>>,
+
+    annotations: Vec<Annotation>,
+    paused: bool,
+
+    /// Why the hook stopped running, if it has. It isn't called again after.
+    error: Option<ScriptError>,
+}
+
+/// What `annotate()` and `pause()` leave for the observer to collect
+#[derive(Default)]
+struct Requests {
+    annotations: Vec<String>,
+    pause: bool,
+}
+
+/// A note the script made about a cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub cycle: usize,
+    pub text: String,
+}
+
+impl Script {
+    /// Compiles the script and runs its top level once, e.g. to print a
+    /// banner. It must define `on_cycle(cycle)`.
+    pub fn new(source: &str) -> Result<Self, ScriptError> {
+        let requests = Rc::new(RefCell::new(Requests::default()));
+        let mut engine = Engine::new();
+        engine.set_max_operations(OPERATIONS_PER_CYCLE);
+
+        let pauses = Rc::clone(&requests);
+        engine.register_fn("pause", move || pauses.borrow_mut().pause = true);
+
+        let annotations = Rc::clone(&requests);
+        engine.register_fn("annotate", move |text: &str| {
+            annotations.borrow_mut().annotations.push(text.to_string())
+        });
+
+        let ast = engine
+            .compile(source)
+            .map_err(|error| ScriptError::Compile(error.to_string()))?;
+
+        if !ast
+            .iter_functions()
+            .any(|function| function.name == "on_cycle" && function.params.len() == 1)
+        {
+            return Err(ScriptError::MissingHook);
+        }
+
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|error| ScriptError::Runtime {
+                cycle: None,
+                message: error.to_string(),
+            })?;
+
+        Ok(Script {
+            engine,
+            ast,
+            scope,
+            state: Dynamic::from(Map::new()),
+            requests,
+            annotations: Vec::new(),
+            paused: false,
+            error: None,
+        })
+    }
+
+    /// Returns every note the script has made so far, in the order it made
+    /// them
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    /// Returns whether the script asked for a pause since this was last
+    /// called. It's up to the caller to actually pause.
+    pub fn take_pause(&mut self) -> bool {
+        std::mem::take(&mut self.paused)
+    }
+
+    /// Returns why the hook failed, if it has. The run carries on without it.
+    pub fn error(&self) -> Option<&ScriptError> {
+        self.error.as_ref()
+    }
+
+    /// Calls `on_cycle()` with the cycle, then collects what it asked for
+    fn hook(&mut self, cycle: &Cycle) -> Result<(), ScriptError> {
+        let unit_or = |value: Option<Dynamic>| value.unwrap_or(Dynamic::UNIT);
+
+        let mut map = Map::new();
+        map.insert("number".into(), (cycle.number as INT).into());
+        map.insert("register".into(), (cycle.register as INT).into());
+        map.insert(
+            "instruction".into(),
+            unit_or(cycle.instruction.map(|index| (index as INT).into())),
+        );
+        map.insert(
+            "signal".into(),
+            unit_or(cycle.signal.map(|signal| signal.to_string().into())),
+        );
+
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            "on_cycle",
+            (map,),
+        );
+
+        let mut requests = self.requests.borrow_mut();
+        self.paused |= std::mem::take(&mut requests.pause);
+        self.annotations
+            .extend(requests.annotations.drain(..).map(|text| Annotation {
+                cycle: cycle.number,
+                text,
+            }));
+
+        result.map(|_| ()).map_err(|error| ScriptError::Runtime {
+            cycle: Some(cycle.number),
+            message: error.to_string(),
+        })
+    }
+}
+
+/// The hook runs after every cycle until it fails
+impl CycleObserver for Script {
+    fn observe(&mut self, cycle: &Cycle) {
+        if self.error.is_none() {
+            self.error = self.hook(cycle).err();
+        }
+    }
+}
+
+/// Why a script couldn't be hooked in, or stopped running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptError {
+    /// The script isn't valid rhai
+    Compile(String),
+
+    /// The script doesn't define `on_cycle(cycle)`
+    MissingHook,
+
+    /// The script failed while running, either at the top level or during the
+    /// given cycle
+    Runtime {
+        cycle: Option<usize>,
+        message: String,
+    },
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScriptError::Compile(message) => write!(formatter, "{}", message),
+            ScriptError::MissingHook => {
+                write!(formatter, "the script doesn't define `on_cycle(cycle)`")
+            }
+            ScriptError::Runtime {
+                cycle: Some(cycle),
+                message,
+            } => write!(formatter, "cycle {}: {}", cycle, message),
+            ScriptError::Runtime {
+                cycle: None,
+                message,
+            } => write!(formatter, "{}", message),
+        }
+    }
+}
+
+impl Error for ScriptError {}
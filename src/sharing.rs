@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::machines::{Detached, RegisterSource};
+use crate::observers::{Cycle, CycleObserver};
+use crate::screens::{Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// A machine one thread cycles while others watch it, e.g. a GUI's render
+/// loop. The machine itself stays with the thread that owns this; every cycle
+/// is published behind a lock for any number of `MachineView`s to read. That
+/// way the machine doesn't need to be `Send`, which a `VirtualMachine` with
+/// devices attached isn't: build it on the thread that will cycle it and hand
+/// out views.
+pub struct SharedMachine<M> {
+    machine: M,
+    state: Arc<Mutex<State>>,
+}
+
+/// A read-only handle on a `SharedMachine`, for another thread to display it.
+/// Clone it for as many threads as need one. It sees every cycle as soon as
+/// the machine has gone through it.
+#[derive(Clone)]
+pub struct MachineView {
+    state: Arc<Mutex<State>>,
+}
+
+/// What the machine looked like after its last cycle
+struct State {
+    register: isize,
+    ticks: usize,
+    executing: bool,
+    screen: Screen<Detached>,
+}
+
+impl<M: RegisterSource> SharedMachine<M> {
+    /// Shares the machine, drawing it on a screen of the specification's size
+    pub fn new(machine: M) -> Self {
+        SharedMachine::with_size(machine, SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+
+    /// Shares the machine, drawing it on a screen of any size
+    pub fn with_size(machine: M, width: usize, height: usize) -> Self {
+        let state = State {
+            register: machine.read_register(),
+            ticks: machine.ticks(),
+            executing: machine.is_executing(),
+            screen: Screen::with_size(Detached, width, height),
+        };
+
+        SharedMachine {
+            machine,
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Returns a handle on the machine for another thread to read
+    pub fn view(&self) -> MachineView {
+        MachineView {
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    /// Cycles the machine once, if it's still executing, and publishes the
+    /// result to every view before returning what happened
+    pub fn cycle(&mut self) -> Option<Cycle> {
+        if !self.machine.is_executing() {
+            return None;
+        }
+
+        let number = self.machine.ticks();
+        let register = self.machine.read_register();
+        let instruction = self.machine.instruction_index();
+        let interrupt = self.machine.interrupt();
+
+        self.machine.cycle();
+
+        let cycle = Cycle {
+            number,
+            register,
+            instruction,
+            signal: self.machine.signal(),
+            interrupt,
+        };
+
+        let mut state = lock(&self.state);
+        state.screen.observe(&cycle);
+        state.register = self.machine.read_register();
+        state.ticks = self.machine.ticks();
+        state.executing = self.machine.is_executing();
+
+        Some(cycle)
+    }
+
+    /// Cycles the machine until it finishes and returns the number of cycles
+    /// it went through
+    pub fn run(&mut self) -> usize {
+        let mut cycles = 0;
+
+        while self.cycle().is_some() {
+            cycles += 1;
+        }
+
+        cycles
+    }
+
+    /// Returns the machine being shared. Only the thread cycling it can see
+    /// it; views see what was published.
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    pub fn into_machine(self) -> M {
+        self.machine
+    }
+}
+
+impl MachineView {
+    /// Returns the register's value during the machine's current cycle
+    pub fn register(&self) -> isize {
+        lock(&self.state).register
+    }
+
+    /// Returns the machine's current cycle number. It starts at `1`.
+    pub fn ticks(&self) -> usize {
+        lock(&self.state).ticks
+    }
+
+    /// Returns `false` once the machine has nothing left to cycle
+    pub fn is_executing(&self) -> bool {
+        lock(&self.state).executing
+    }
+
+    /// Calls `f` with the screen as drawn so far. The machine waits for `f`
+    /// to return before publishing its next cycle, so keep it short, e.g.
+    /// copying the pixels out or painting them. Don't ask the view anything
+    /// else from `f`; it'd wait on the same lock forever.
+    pub fn with_screen<R>(&self, f: impl FnOnce(&Screen<Detached>) -> R) -> R {
+        f(&lock(&self.state).screen)
+    }
+}
+
+/// Locks the published state. If the cycling thread panicked while holding
+/// the lock, the state is at worst part of a cycle behind, which is still
+/// worth displaying, so the poison is ignored.
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,165 @@
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+
+use crate::instructions::Instruction;
+use crate::observers::{Cycle, CycleObserver};
+
+/// Collects where a program spends its cycles: how often every instruction
+/// ran and how many cycles it occupied. Attach it to a `Fanout` to profile a
+/// run, then ask it about the program afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    program: Vec<Instruction>,
+
+    /// Per instruction (by its position in the program), how many times it
+    /// started executing and how many cycles it occupied in total
+    lines: Vec<LineStatistics>,
+
+    /// The instruction occupying the last cycle observed and how many more
+    /// cycles it has to go, to tell one execution from the next
+    current: Option<(usize, usize)>,
+
+    /// Every cycle observed, including any no instruction occupied
+    cycles: usize,
+}
+
+/// How a single instruction of the program fared over a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LineStatistics {
+    /// How many times the instruction started executing
+    pub executions: usize,
+
+    /// How many cycles the instruction occupied over all its executions
+    pub cycles: usize,
+}
+
+/// How all the instructions with the same opcode fared over a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpcodeStatistics {
+    /// How many of the program's instructions have the opcode
+    pub instructions: usize,
+
+    /// How many times one of them started executing
+    pub executions: usize,
+
+    /// How many cycles they occupied in total
+    pub cycles: usize,
+}
+
+impl Statistics {
+    /// Creates statistics for a run of the given program. They're all zero
+    /// until cycles are observed.
+    pub fn new(program: impl IntoIterator<Item = Instruction>) -> Self {
+        let program: Vec<_> = program.into_iter().collect();
+        let lines = vec![LineStatistics::default(); program.len()];
+
+        Statistics {
+            program,
+            lines,
+            current: None,
+            cycles: 0,
+        }
+    }
+
+    /// Returns the program being profiled
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    /// Returns the number of cycles observed
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Returns the number of instructions that started executing
+    pub fn executions(&self) -> usize {
+        self.lines.iter().map(|line| line.executions).sum()
+    }
+
+    /// Returns the average number of cycles per instruction executed (CPI),
+    /// or `None` if nothing was
+    pub fn cycles_per_instruction(&self) -> Option<f64> {
+        match self.executions() {
+            0 => None,
+            executions => Some(self.cycles as f64 / executions as f64),
+        }
+    }
+
+    /// Returns how the instruction at the given position in the program fared
+    pub fn line(&self, index: usize) -> Option<LineStatistics> {
+        self.lines.get(index).copied()
+    }
+
+    /// Returns every instruction's position and statistics, in program order
+    pub fn lines(&self) -> impl Iterator<Item = (usize, LineStatistics)> + '_ {
+        self.lines.iter().copied().enumerate()
+    }
+
+    /// Returns the `count` instructions that occupied the most cycles, the
+    /// hottest first. Ties go to the earlier instruction, and instructions
+    /// that never ran are left out.
+    pub fn hot_lines(&self, count: usize) -> Vec<(usize, LineStatistics)> {
+        let mut lines: Vec<_> = self.lines().filter(|(_, line)| line.cycles > 0).collect();
+
+        lines.sort_by(|(a, a_line), (b, b_line)| b_line.cycles.cmp(&a_line.cycles).then(a.cmp(b)));
+        lines.truncate(count);
+        lines
+    }
+
+    /// Returns the cycles the instruction at the given position is expected
+    /// to cost: its executions times the cycles each takes
+    pub fn estimated_cycles(&self, index: usize) -> Option<usize> {
+        let instruction = self.program.get(index)?;
+        let line = self.lines.get(index)?;
+
+        Some(line.executions * instruction.cycles())
+    }
+
+    /// Groups the statistics by opcode, keyed by the opcode's mnemonic
+    pub fn histogram(&self) -> BTreeMap<&'static str, OpcodeStatistics> {
+        let mut histogram = BTreeMap::new();
+
+        for (instruction, line) in self.program.iter().zip(&self.lines) {
+            let opcode: &mut OpcodeStatistics =
+                histogram.entry(instruction.mnemonic()).or_default();
+
+            opcode.instructions += 1;
+            opcode.executions += line.executions;
+            opcode.cycles += line.cycles;
+        }
+
+        histogram
+    }
+}
+
+/// An instruction starts executing on a cycle when a different one occupied
+/// the previous cycle, or the same one finished on it (e.g. it's run again)
+impl CycleObserver for Statistics {
+    fn observe(&mut self, cycle: &Cycle) {
+        self.cycles += 1;
+
+        let Some(index) = cycle.instruction else {
+            self.current = None;
+            return;
+        };
+
+        let remaining = match self.current {
+            Some((current, remaining)) if current == index && remaining > 0 => remaining,
+            _ => {
+                let cycles = self.program.get(index).map_or(1, Instruction::cycles);
+
+                if let Some(line) = self.lines.get_mut(index) {
+                    line.executions += 1;
+                }
+
+                cycles
+            }
+        };
+
+        if let Some(line) = self.lines.get_mut(index) {
+            line.cycles += 1;
+        }
+
+        self.current = Some((index, remaining - 1));
+    }
+}
@@ -0,0 +1,310 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::base64;
+use crate::images;
+use crate::json::Json;
+use crate::machines::Detached;
+use crate::observers::{CycleObserver, Fanout};
+use crate::programs::Program;
+use crate::screens::{OverflowPolicy, Screen};
+use crate::sha1;
+
+/// What the handshake appends to the client's key before hashing it, from
+/// RFC 6455
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The most the handshake's request line and headers may take up together,
+/// in bytes. Browsers send well under 1 KiB.
+const MAX_HANDSHAKE: u64 = 16 << 10;
+
+/// How long a client may take to finish its handshake
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// WebSocket opcodes for the messages sent
+const TEXT: u8 = 0x1;
+const BINARY: u8 = 0x2;
+const CLOSE: u8 = 0x8;
+
+/// Streams a program's run over WebSockets, so a browser can watch the CRT
+/// draw it live while it runs natively. Every connection gets a fresh
+/// machine and is sent, as text messages, a JSON event for every cycle:
+///
+/// ```json
+/// {"event":"cycle","cycle":1,"register":1,"instruction":0,"signal":null,"x":0,"y":0,"lit":true}
+/// ```
+///
+/// Every time the screen is drawn in full, and once more at the end, the
+/// screen follows as a PNG in a binary message. A program that runs longer
+/// than a frame wraps around and draws the next. A last event says how many
+/// cycles there were, and then the connection is closed.
+#[derive(Debug, Clone)]
+pub struct Streamer {
+    program: Program,
+    rate: u32,
+    scale: usize,
+}
+
+impl Streamer {
+    /// Streams the program at 60 cycles a second, which draws a frame in
+    /// four seconds, with PNGs scaled up 8 times
+    pub fn new(program: Program) -> Self {
+        Streamer {
+            program,
+            rate: 60,
+            scale: 8,
+        }
+    }
+
+    /// Runs the given number of cycles a second. `0` runs as fast as the
+    /// connection can keep up.
+    pub fn with_rate(mut self, cycles_per_second: u32) -> Self {
+        self.rate = cycles_per_second;
+        self
+    }
+
+    /// Scales the PNGs up so every pixel is a square of this many
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Listens at the address and streams to every connection on a thread of
+    /// its own, for as long as the process runs. Returns the address it's
+    /// listening at, which is useful after binding port `0`.
+    pub fn serve(self, address: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let streamer = Arc::new(self);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let streamer = Arc::clone(&streamer);
+
+                thread::spawn(move || {
+                    // A page that's closed mid-run is its own problem
+                    let _ = streamer.stream(stream);
+                });
+            }
+        });
+
+        Ok(address)
+    }
+
+    /// Upgrades the connection and streams a run over it
+    pub fn stream(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+
+        if !handshake(&mut stream)? {
+            return Ok(());
+        }
+
+        let mut screen = Screen::new(Detached).with_overflow(OverflowPolicy::Wrap);
+        let pixels = screen.width() * screen.height();
+        let mut fanout = Fanout::new(self.program.machine());
+        let mut cycles = 0;
+
+        while let Some(cycle) = fanout.cycle() {
+            screen.observe(&cycle);
+            cycles += 1;
+
+            let index = (cycle.number - 1) % pixels;
+            let (x, y) = (index % screen.width(), index / screen.width());
+
+            let event = Json::object([
+                ("event", "cycle".into()),
+                ("cycle", cycle.number.into()),
+                ("register", cycle.register.into()),
+                (
+                    "instruction",
+                    cycle.instruction.map_or(Json::Null, Json::from),
+                ),
+                (
+                    "signal",
+                    cycle
+                        .signal
+                        .map_or(Json::Null, |signal| signal.to_string().into()),
+                ),
+                ("x", x.into()),
+                ("y", y.into()),
+                ("lit", screen.is_lit(x, y).into()),
+            ]);
+
+            send(&mut stream, TEXT, event.to_string().as_bytes())?;
+
+            if index + 1 == pixels {
+                send(&mut stream, BINARY, &images::png(&screen, self.scale))?;
+            }
+
+            if self.rate > 0 {
+                thread::sleep(Duration::from_secs(1) / self.rate);
+            }
+        }
+
+        if cycles % pixels != 0 {
+            send(&mut stream, BINARY, &images::png(&screen, self.scale))?;
+        }
+
+        let finished = Json::object([("event", "finished".into()), ("cycles", cycles.into())]);
+        send(&mut stream, TEXT, finished.to_string().as_bytes())?;
+
+        // A normal closure, status 1000
+        send(&mut stream, CLOSE, &1000u16.to_be_bytes())
+    }
+}
+
+/// Answers the opening handshake. Returns `false`, having said why, if the
+/// request isn't for a WebSocket.
+fn handshake(stream: &mut TcpStream) -> io::Result<bool> {
+    // A client that goes quiet would otherwise keep its thread forever
+    stream.set_read_timeout(Some(TIMEOUT))?;
+
+    let mut reader = BufReader::new(&*stream).take(MAX_HANDSHAKE);
+    let mut key = None;
+
+    let mut line = String::new();
+    let mut whole = read_line(&mut reader, &mut line)?;
+
+    while whole {
+        whole = read_line(&mut reader, &mut line)?;
+
+        if whole && line.trim_end().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    if !whole {
+        return match reader.limit() {
+            0 => refuse(
+                stream,
+                "431 Request Header Fields Too Large",
+                "the handshake is too long\n",
+            ),
+            _ => refuse(stream, "400 Bad Request", "the handshake stopped short\n"),
+        };
+    }
+
+    let Some(key) = key else {
+        return refuse(
+            stream,
+            "426 Upgrade Required",
+            "this streams over WebSockets\n",
+        );
+    };
+
+    let accept = base64::encode(&sha1::digest(format!("{}{}", key, GUID).as_bytes()));
+
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    )?;
+
+    Ok(true)
+}
+
+/// Reads the next line of the handshake into `line`. Returns `false` if it
+/// was cut short, either by `MAX_HANDSHAKE` or by the client hanging up.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<bool> {
+    line.clear();
+    reader.read_line(line)?;
+
+    Ok(line.ends_with('\n'))
+}
+
+/// Answers a request that isn't a WebSocket handshake, saying why. Returns
+/// `false`, for `handshake()` to return in turn.
+fn refuse(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<bool> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nUpgrade: websocket\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+
+    Ok(false)
+}
+
+/// Sends a whole message in a single, unmasked frame, as servers do
+fn send(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut header = vec![0x80 | opcode];
+
+    match payload.len() {
+        length if length < 126 => header.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            header.push(126);
+            header.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            header.push(127);
+            header.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+
+    stream.write_all(&header)?;
+    stream.write_all(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Shutdown;
+
+    use super::*;
+    use crate::instructions::Instruction;
+
+    #[test]
+    fn hands_shake_only_with_websockets() {
+        let program = Program::from(vec![Instruction::Noop]);
+        let address = Streamer::new(program)
+            .with_rate(0)
+            .serve("127.0.0.1:0")
+            .unwrap();
+
+        // The example from RFC 6455
+        let response = request(
+            address,
+            "GET / HTTP/1.1\r\nUpgrade: websocket\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+        );
+        assert!(response.starts_with(
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n"
+        ));
+        assert!(response.contains(r#"{"event":"finished","cycles":1}"#));
+
+        for (head, status) in [
+            ("GET / HTTP/1.1\r\n\r\n".to_string(), "426 "),
+            (
+                "GET / HTTP/1.1\r\nUpgrade: websocket\r\n".to_string(),
+                "400 ",
+            ),
+            ("a".repeat(MAX_HANDSHAKE as usize), "431 "),
+        ] {
+            let response = request(address, &head);
+            assert!(
+                response.starts_with(&format!("HTTP/1.1 {}", status)),
+                "{}",
+                response
+            );
+        }
+    }
+
+    /// Sends the request, then returns everything sent back, lossily
+    fn request(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        String::from_utf8_lossy(&response).to_string()
+    }
+}
@@ -0,0 +1,476 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, Write};
+
+use crate::machines::{Detached, RegisterSource, Signal};
+use crate::observers::Cycle;
+use crate::screens::Screen;
+
+/// How many cycles either side of a timeline's first mismatch are shown
+const CONTEXT: usize = 3;
+
+/// Panics with the screens side by side, and where they differ, if the
+/// screen doesn't show the expected text. The expectation is written like the
+/// screen displays, with `#` and `.`, and may be indented and surrounded by
+/// blank lines to sit nicely in a test.
+///
+/// ```ignore
+/// assert_screen_eq!(screen, "
+///     ##..##..##
+///     ###...###.
+/// ");
+/// ```
+#[macro_export]
+macro_rules! assert_screen_eq {
+    ($screen:expr, $expected:expr $(,)?) => {
+        if let Some(report) = $crate::testing::screen_mismatch(&$screen, $expected) {
+            panic!("{}", report);
+        }
+    };
+}
+
+/// Panics with the cycles around the first difference, lined up, if the
+/// timelines differ. Both sides are anything that's a slice of register
+/// values, like `Screen::timeline()` or `register_timeline()`.
+#[macro_export]
+macro_rules! assert_timeline_eq {
+    ($timeline:expr, $expected:expr $(,)?) => {
+        if let Some(report) = $crate::testing::timeline_mismatch(&$timeline, &$expected) {
+            panic!("{}", report);
+        }
+    };
+}
+
+/// Panics with the first violated expectation unless the register has each
+/// value during its cycle. The source is a timeline, as a slice, array, or
+/// `Vec`, or a machine, which is cycled up to each cycle in turn.
+///
+/// ```ignore
+/// assert_register_at!(machine, 20 => 21, 60 => 19);
+/// ```
+#[macro_export]
+macro_rules! assert_register_at {
+    ($source:expr, $($cycle:expr => $value:expr),+ $(,)?) => {
+        if let Err(violation) = $crate::testing::expect_timeline(&[$(($cycle, $value)),+])
+            .check(&mut $source)
+        {
+            panic!("{}", violation);
+        }
+    };
+}
+
+/// Anything the register can be read from at a given cycle
+pub trait RegisterAt {
+    /// Returns the register during the cycle (the first is `1`), or `None` if
+    /// there's no such cycle, or no longer one
+    fn register_at(&mut self, cycle: usize) -> Option<isize>;
+}
+
+/// Reads the timeline, where index `i` is cycle `i + 1`
+impl RegisterAt for [isize] {
+    fn register_at(&mut self, cycle: usize) -> Option<isize> {
+        self.get(cycle.checked_sub(1)?).copied()
+    }
+}
+
+impl RegisterAt for Vec<isize> {
+    fn register_at(&mut self, cycle: usize) -> Option<isize> {
+        self.as_mut_slice().register_at(cycle)
+    }
+}
+
+impl<const N: usize> RegisterAt for [isize; N] {
+    fn register_at(&mut self, cycle: usize) -> Option<isize> {
+        self.as_mut_slice().register_at(cycle)
+    }
+}
+
+/// Cycles the source up to the cycle. Cycles that already went by can't be
+/// read, so check them in order.
+impl<M: RegisterSource> RegisterAt for M {
+    fn register_at(&mut self, cycle: usize) -> Option<isize> {
+        while self.is_executing() && self.ticks() < cycle {
+            self.cycle();
+        }
+
+        match self.is_executing() && self.ticks() == cycle {
+            true => Some(self.read_register()),
+            false => None,
+        }
+    }
+}
+
+/// Registers expected at particular cycles, checked against a source by
+/// `check()`. See `expect_timeline()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineExpectation {
+    /// `(cycle, register)` pairs, earliest first
+    expectations: Vec<(usize, isize)>,
+}
+
+/// The first expectation the source didn't meet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub cycle: usize,
+    pub expected: isize,
+
+    /// The register during the cycle, or `None` if there was no such cycle
+    pub actual: Option<isize>,
+}
+
+/// States what the register should be during some cycles, as `(cycle,
+/// register)` pairs in any order, to check against a timeline or machine:
+///
+/// ```ignore
+/// expect_timeline(&[(20, 21), (60, 19)]).assert(&mut machine);
+/// ```
+pub fn expect_timeline(expectations: &[(usize, isize)]) -> TimelineExpectation {
+    let mut expectations = expectations.to_vec();
+    expectations.sort_by_key(|&(cycle, _)| cycle);
+
+    TimelineExpectation { expectations }
+}
+
+impl TimelineExpectation {
+    /// Checks every expectation, earliest cycle first, returning the first
+    /// that isn't met
+    pub fn check<S: RegisterAt + ?Sized>(&self, source: &mut S) -> Result<(), Violation> {
+        for &(cycle, expected) in &self.expectations {
+            let actual = source.register_at(cycle);
+
+            if actual != Some(expected) {
+                return Err(Violation {
+                    cycle,
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks every expectation like `check()`, panicking with the first
+    /// that isn't met
+    pub fn assert<S: RegisterAt + ?Sized>(&self, source: &mut S) {
+        if let Err(violation) = self.check(source) {
+            panic!("{}", violation);
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.actual {
+            Some(actual) => write!(
+                formatter,
+                "the register during cycle {} was {}, not {}",
+                self.cycle, actual, self.expected
+            ),
+            None => write!(
+                formatter,
+                "expected the register to be {} during cycle {}, but there's no such cycle",
+                self.expected, self.cycle
+            ),
+        }
+    }
+}
+
+/// A machine that checks itself against a golden trace as it runs, e.g. one
+/// recorded with `traces::record()` before a change to the machine or a new
+/// instruction. It faults on the first cycle that differs in any way, from
+/// the register to what the program sent out, and won't cycle again, so the
+/// regression is caught at the exact cycle it appears.
+///
+/// It's a `RegisterSource` too, so it can drive a `Screen`: the screen stops
+/// with the machine on the faulty cycle, and `fault()` says what went wrong.
+pub struct GoldenMachine<M> {
+    machine: M,
+    golden: Vec<Cycle>,
+
+    /// How many cycles have been checked
+    checked: usize,
+
+    fault: Option<GoldenMismatch>,
+}
+
+/// The first cycle that differed from the golden trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoldenMismatch {
+    pub cycle: usize,
+
+    /// The cycle as recorded, or `None` if the golden trace had ended
+    pub expected: Option<Cycle>,
+
+    /// The cycle as it ran, or `None` if the machine had finished
+    pub actual: Option<Cycle>,
+}
+
+impl<M: RegisterSource> GoldenMachine<M> {
+    pub fn new(machine: M, golden: Vec<Cycle>) -> Self {
+        GoldenMachine {
+            machine,
+            golden,
+            checked: 0,
+            fault: None,
+        }
+    }
+
+    /// Cycles the machine once and checks the cycle against the golden trace.
+    /// Returns the cycle, `None` once both have finished, or the mismatch,
+    /// which is returned again on every call after. It's boxed, holding two
+    /// whole cycles.
+    pub fn try_cycle(&mut self) -> Result<Option<Cycle>, Box<GoldenMismatch>> {
+        if let Some(fault) = self.fault {
+            return Err(Box::new(fault));
+        }
+
+        let expected = self.golden.get(self.checked).copied();
+
+        let actual = match self.machine.is_executing() {
+            true => {
+                let number = self.machine.ticks();
+                let register = self.machine.read_register();
+                let instruction = self.machine.instruction_index();
+                let interrupt = self.machine.interrupt();
+
+                self.machine.cycle();
+
+                Some(Cycle {
+                    number,
+                    register,
+                    instruction,
+                    signal: self.machine.signal(),
+                    interrupt,
+                })
+            }
+            false => None,
+        };
+
+        if actual != expected {
+            let fault = GoldenMismatch {
+                cycle: self.checked + 1,
+                expected,
+                actual,
+            };
+
+            self.fault = Some(fault);
+            return Err(Box::new(fault));
+        }
+
+        if actual.is_some() {
+            self.checked += 1;
+        }
+
+        Ok(actual)
+    }
+
+    /// Runs the machine until it finishes, checking every cycle, and returns
+    /// how many there were
+    pub fn run(&mut self) -> Result<usize, Box<GoldenMismatch>> {
+        while self.try_cycle()?.is_some() {}
+
+        Ok(self.checked)
+    }
+
+    /// Returns the mismatch the machine faulted on, if it has
+    pub fn fault(&self) -> Option<GoldenMismatch> {
+        self.fault
+    }
+
+    pub fn machine(&self) -> &M {
+        &self.machine
+    }
+
+    pub fn into_machine(self) -> M {
+        self.machine
+    }
+}
+
+impl<M: RegisterSource> RegisterSource for GoldenMachine<M> {
+    /// Cycles like `try_cycle()`, which `fault()` reports on. The golden
+    /// trace running on past the machine's last cycle is only noticed by
+    /// `try_cycle()` and `run()`.
+    fn cycle(&mut self) {
+        let _ = self.try_cycle();
+    }
+
+    fn read_register(&self) -> isize {
+        self.machine.read_register()
+    }
+
+    fn ticks(&self) -> usize {
+        self.machine.ticks()
+    }
+
+    fn is_executing(&self) -> bool {
+        self.fault.is_none() && self.machine.is_executing()
+    }
+
+    fn instruction_index(&self) -> Option<usize> {
+        self.machine.instruction_index()
+    }
+
+    fn signal(&self) -> Option<Signal> {
+        self.machine.signal()
+    }
+
+    fn interrupt(&self) -> Option<usize> {
+        self.machine.interrupt()
+    }
+}
+
+impl Display for GoldenMismatch {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        let describe = |cycle: &Cycle| {
+            let mut text = format!("the register at {}", cycle.register);
+
+            if let Some(instruction) = cycle.instruction {
+                text.push_str(&format!(" running instruction {}", instruction));
+            }
+
+            if let Some(signal) = cycle.signal {
+                text.push_str(&format!(" and sending {}", signal));
+            }
+
+            text
+        };
+
+        match (&self.expected, &self.actual) {
+            (Some(expected), Some(actual)) => write!(
+                formatter,
+                "cycle {} had {}, but the golden trace has {}",
+                self.cycle,
+                describe(actual),
+                describe(expected)
+            ),
+            (Some(expected), None) => write!(
+                formatter,
+                "the machine finished before cycle {}, but the golden trace has it, with {}",
+                self.cycle,
+                describe(expected)
+            ),
+            (None, Some(actual)) => write!(
+                formatter,
+                "the golden trace ended before cycle {}, but the machine ran it, with {}",
+                self.cycle,
+                describe(actual)
+            ),
+            (None, None) => write!(formatter, "cycle {} matched", self.cycle),
+        }
+    }
+}
+
+/// Describes how the screen differs from the expected text, or returns `None`
+/// if it doesn't. It's what `assert_screen_eq!` panics with.
+pub fn screen_mismatch<M>(screen: &Screen<M>, expected: &str) -> Option<String> {
+    let rows: Vec<&str> = expected
+        .lines()
+        .map(str::trim)
+        .filter(|row| !row.is_empty())
+        .collect();
+
+    let expected: Screen<Detached> = match rows.join("\n").parse() {
+        Ok(expected) => expected,
+        Err(error) => return Some(format!("the expected screen is malformed: {}", error)),
+    };
+
+    let diff = screen.diff(&expected);
+
+    if diff.is_empty() {
+        return None;
+    }
+
+    let width = screen.width().max(expected.width());
+    let height = screen.height().max(expected.height());
+    let column = width.max("expected".len());
+
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "screens differ: {} of {} pixels (+ is unexpectedly lit, - unexpectedly dark)",
+        diff.mismatches(),
+        width * height
+    );
+    let _ = writeln!(
+        report,
+        "    {:<column$}  {:<column$}  diff",
+        "actual",
+        "expected",
+        column = column
+    );
+
+    for y in 0..height {
+        let _ = writeln!(
+            report,
+            "{:>2}  {:<column$}  {:<column$}  {}",
+            y,
+            row(width, |x| pixel(screen.is_lit(x, y))),
+            row(width, |x| pixel(expected.is_lit(x, y))),
+            row(width, |x| diff.pixel(x, y).map_or(' ', char::from)),
+            column = column
+        );
+    }
+
+    Some(report)
+}
+
+/// Draws a row of a screen's report by asking for each column's character
+fn row(width: usize, column: impl Fn(usize) -> char) -> String {
+    (0..width).map(column).collect()
+}
+
+fn pixel(lit: bool) -> char {
+    match lit {
+        true => '#',
+        false => '.',
+    }
+}
+
+/// Describes how the timeline differs from the expected one, or returns
+/// `None` if it doesn't. It's what `assert_timeline_eq!` panics with.
+pub fn timeline_mismatch(timeline: &[isize], expected: &[isize]) -> Option<String> {
+    let length = timeline.len().max(expected.len());
+    let first = (0..length).find(|&i| timeline.get(i) != expected.get(i))?;
+    let mismatches = (0..length)
+        .filter(|&i| timeline.get(i) != expected.get(i))
+        .count();
+
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "timelines differ at cycle {} ({} of {} cycles differ; lengths {} and {})",
+        first + 1,
+        mismatches,
+        length,
+        timeline.len(),
+        expected.len()
+    );
+    let _ = writeln!(
+        report,
+        "  {:>7}  {:>8}  {:>8}",
+        "cycle", "actual", "expected"
+    );
+
+    let value = |value: Option<&isize>| value.map_or(String::from("-"), |value| value.to_string());
+
+    for i in first.saturating_sub(CONTEXT)..(first + CONTEXT + 1).min(length) {
+        let marker = match timeline.get(i) != expected.get(i) {
+            true => '>',
+            false => ' ',
+        };
+
+        let _ = writeln!(
+            report,
+            "{} {:>7}  {:>8}  {:>8}",
+            marker,
+            i + 1,
+            value(timeline.get(i)),
+            value(expected.get(i))
+        );
+    }
+
+    Some(report)
+}
@@ -0,0 +1,370 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use crate::instructions::Instruction;
+use crate::machines::RegisterSource;
+use crate::observers::{Cycle, Fanout};
+use crate::programs::Program;
+use crate::screens::SCREEN_WIDTH;
+
+/// Runs the source to completion and returns the register's value during
+/// every cycle. The value at index `i` is the register during cycle `i + 1`,
+/// which is the form `screens::render_from_timeline()` draws from.
+pub fn register_timeline(mut source: impl RegisterSource) -> Vec<isize> {
+    let mut timeline = Vec::new();
+
+    while source.is_executing() {
+        timeline.push(source.read_register());
+        source.cycle();
+    }
+
+    timeline
+}
+
+/// A summary of a register timeline's values
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineStats {
+    pub min: isize,
+    pub max: isize,
+    pub mean: f64,
+
+    /// The population variance: the mean squared distance from the mean
+    pub variance: f64,
+
+    /// Every cycle the register was at its minimum, earliest first. Cycles
+    /// count from `1`.
+    pub min_cycles: Vec<usize>,
+
+    /// Every cycle the register was at its maximum, earliest first
+    pub max_cycles: Vec<usize>,
+}
+
+/// Characterizes a timeline, like `register_timeline()` returns, without
+/// exporting it elsewhere: its extremes and when they happened, its mean, and
+/// how much it varies. Returns `None` for an empty timeline, which has none
+/// of those.
+pub fn timeline_stats(timeline: &[isize]) -> Option<TimelineStats> {
+    let min = *timeline.iter().min()?;
+    let max = *timeline.iter().max()?;
+
+    let cycles_at = |value: isize| {
+        timeline
+            .iter()
+            .enumerate()
+            .filter(|&(_, &register)| register == value)
+            .map(|(index, _)| index + 1)
+            .collect()
+    };
+
+    let count = timeline.len() as f64;
+    let mean = timeline
+        .iter()
+        .map(|&register| register as f64)
+        .sum::<f64>()
+        / count;
+    let variance = timeline
+        .iter()
+        .map(|&register| {
+            let distance = register as f64 - mean;
+            distance * distance
+        })
+        .sum::<f64>()
+        / count;
+
+    Some(TimelineStats {
+        min,
+        max,
+        mean,
+        variance,
+        min_cycles: cycles_at(min),
+        max_cycles: cycles_at(max),
+    })
+}
+
+/// Where two programs' register timelines first part ways
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    /// The first cycle the register differs, or that only one program runs
+    pub cycle: usize,
+
+    pub a: Side,
+    pub b: Side,
+}
+
+/// One program's half of a divergence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Side {
+    /// The register during the diverging cycle, or `None` if the program had
+    /// already finished
+    pub register: Option<isize>,
+
+    /// The instruction that finished just before the diverging cycle, with
+    /// its position in the program. Registers only change as instructions
+    /// finish, and they were equal until then, so it's what set this
+    /// program's register apart.
+    pub cause: Option<(usize, Instruction)>,
+}
+
+/// Compares two programs by what they do rather than how they're written:
+/// both run on machines with nothing attached, cycle by cycle, and the first
+/// cycle where the register differs (or one program has finished and the
+/// other hasn't) is reported along with the instructions responsible.
+/// Returns `None` if the timelines are the same, however different the
+/// instructions, e.g. after a refactor that kept the behavior.
+pub fn diff_programs(a: &Program, b: &Program) -> Option<Divergence> {
+    let trace = |program: &Program| {
+        let mut cycles: Vec<Cycle> = Vec::new();
+        Fanout::new(program.machine())
+            .with_observer(&mut cycles)
+            .run();
+        cycles
+    };
+
+    let (a_cycles, b_cycles) = (trace(a), trace(b));
+    let length = a_cycles.len().max(b_cycles.len());
+
+    let index = (0..length).find(|&index| {
+        let register = |cycles: &[Cycle]| cycles.get(index).map(|cycle| cycle.register);
+        register(&a_cycles) != register(&b_cycles)
+    })?;
+
+    let side = |program: &Program, cycles: &[Cycle]| Side {
+        register: cycles.get(index).map(|cycle| cycle.register),
+        cause: index
+            .checked_sub(1)
+            .and_then(|previous| cycles[previous].instruction)
+            .and_then(|position| Some((position, *program.instructions().get(position)?))),
+    };
+
+    Some(Divergence {
+        cycle: index + 1,
+        a: side(a, &a_cycles),
+        b: side(b, &b_cycles),
+    })
+}
+
+/// Describes the divergence on one line, e.g. `cycle 7: the register is 4 in
+/// a (after line 3, `addx 3`) but 6 in b (after line 3, `addx 5`)`
+impl Display for Divergence {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "cycle {}: ", self.cycle)?;
+
+        match (self.a.register, self.b.register) {
+            (Some(a), Some(b)) => {
+                write!(formatter, "the register is {} in a", a)?;
+                write_cause(formatter, &self.a)?;
+                write!(formatter, " but {} in b", b)?;
+                write_cause(formatter, &self.b)
+            }
+            (None, Some(b)) => write!(
+                formatter,
+                "a has finished but b runs on, with the register at {}",
+                b
+            ),
+            (Some(a), None) => write!(
+                formatter,
+                "b has finished but a runs on, with the register at {}",
+                a
+            ),
+            (None, None) => write!(formatter, "both have finished"),
+        }
+    }
+}
+
+fn write_cause(formatter: &mut Formatter<'_>, side: &Side) -> core::fmt::Result {
+    match side.cause {
+        Some((position, instruction)) => write!(
+            formatter,
+            " (after line {}, `{}`)",
+            position + 1,
+            instruction
+        ),
+        None => Ok(()),
+    }
+}
+
+/// How many cycles either side of a divergence `compare_traces()` keeps
+const CONTEXT: usize = 3;
+
+/// Where two recorded traces first part ways
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceDivergence {
+    /// The first cycle that differs, or that only one trace has
+    pub cycle: usize,
+
+    /// Whether the register differed during the cycle. Like the other two,
+    /// it's `false` if one trace had already finished.
+    pub register: bool,
+
+    /// Whether different instructions finished on the cycle, or one did and
+    /// the other didn't
+    pub retired: bool,
+
+    /// Whether the beam lit the pixel on one screen and not the other
+    pub pixel: bool,
+
+    /// The cycles from a few before the divergence to a few after, in each
+    /// trace, for context. Either stops short where its trace does.
+    pub a: Vec<Cycle>,
+    pub b: Vec<Cycle>,
+}
+
+/// Compares two recorded traces, e.g. from `traces::record()`, cycle by cycle:
+/// the register, the instruction that finished, and whether the pixel was
+/// lit on a screen of the specification's width. Returns where they first
+/// differ, or `None` if they never do. Unlike `diff_programs()` the traces
+/// can come from anywhere, like runs from before and after a change to the
+/// machine itself.
+pub fn compare_traces(a: &[Cycle], b: &[Cycle]) -> Option<TraceDivergence> {
+    let length = a.len().max(b.len());
+
+    let (index, register, retired, pixel) = (0..length).find_map(|index| {
+        // Once one trace has finished, that's the difference
+        if index == a.len().min(b.len()) {
+            return Some((index, false, false, false));
+        }
+
+        let register = a[index].register != b[index].register;
+        let retired = retired(a, index) != retired(b, index);
+        let pixel = lit(a, index) != lit(b, index);
+
+        (register || retired || pixel).then_some((index, register, retired, pixel))
+    })?;
+
+    // One more cycle than is shown, to tell whether the last shown finished
+    // its instruction
+    let context = |trace: &[Cycle]| {
+        let start = index.saturating_sub(CONTEXT).min(trace.len());
+        let end = (index + CONTEXT + 2).min(trace.len());
+        trace[start..end].to_vec()
+    };
+
+    Some(TraceDivergence {
+        cycle: index + 1,
+        register,
+        retired,
+        pixel,
+        a: context(a),
+        b: context(b),
+    })
+}
+
+/// Returns the instruction that finished on the cycle at `index`, if any. It
+/// finishes when the next cycle is someone else's, or there isn't one.
+fn retired(trace: &[Cycle], index: usize) -> Option<usize> {
+    let instruction = trace.get(index)?.instruction?;
+
+    match trace.get(index + 1) {
+        Some(next) if next.instruction == Some(instruction) => None,
+        _ => Some(instruction),
+    }
+}
+
+/// Returns whether the cycle at `index` lit its pixel
+fn lit(trace: &[Cycle], index: usize) -> Option<bool> {
+    let cycle = trace.get(index)?;
+    let column = (cycle.number - 1) % SCREEN_WIDTH;
+
+    Some((column as isize).abs_diff(cycle.register) <= 1)
+}
+
+/// Says what differs, then lists the cycles around it side by side, marking
+/// the first that differs:
+///
+/// ```text
+/// cycle 5: the register and the pixel differ
+///   cycle  a                  b
+///       2  x=1   #  1 done    x=1   #  1 done
+///       3  x=1   #  2         x=1   #  2
+///       4  x=1   .  2 done    x=1   .  2 done
+/// >     5  x=4   #  3 done    x=6   .  3 done
+///       6  x=4   #  4 done    x=6   #  4 done
+///       7  x=4   .  5 done    x=6   #  5 done
+/// ```
+impl Display for TraceDivergence {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut differences = Vec::new();
+
+        if self.register {
+            differences.push("the register");
+        }
+
+        if self.retired {
+            differences.push("the instruction finishing");
+        }
+
+        if self.pixel {
+            differences.push("the pixel");
+        }
+
+        match differences.as_slice() {
+            [] if self.a.len() < self.b.len() => {
+                writeln!(formatter, "cycle {}: a has finished", self.cycle)?
+            }
+            [] => writeln!(formatter, "cycle {}: b has finished", self.cycle)?,
+            [one] => writeln!(formatter, "cycle {}: {} differs", self.cycle, one)?,
+            [first, second] => writeln!(
+                formatter,
+                "cycle {}: {} and {} differ",
+                self.cycle, first, second
+            )?,
+            [first, second, third, ..] => writeln!(
+                formatter,
+                "cycle {}: {}, {}, and {} differ",
+                self.cycle, first, second, third
+            )?,
+        }
+
+        writeln!(formatter, "  cycle  {:<17}  b", "a")?;
+
+        let first = self.cycle.saturating_sub(CONTEXT).max(1);
+
+        for number in first..=self.cycle + CONTEXT {
+            let a = side(&self.a, number);
+            let b = side(&self.b, number);
+
+            if a.is_none() && b.is_none() {
+                break;
+            }
+
+            let marker = if number == self.cycle { '>' } else { ' ' };
+
+            let line = format!(
+                "{} {:>5}  {:<17}  {}",
+                marker,
+                number,
+                a.unwrap_or_default(),
+                b.unwrap_or_default()
+            );
+
+            writeln!(formatter, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes one trace's cycle for `TraceDivergence`'s listing, e.g.
+/// `x=4  #  2 done`
+fn side(context: &[Cycle], number: usize) -> Option<String> {
+    let index = context.iter().position(|cycle| cycle.number == number)?;
+    let cycle = &context[index];
+    let pixel = match lit(context, index) {
+        Some(true) => '#',
+        _ => '.',
+    };
+
+    let mut text = format!("x={:<3} {}", cycle.register, pixel);
+
+    if let Some(instruction) = cycle.instruction {
+        text.push_str(&format!("  {}", instruction));
+    }
+
+    if retired(context, index).is_some() {
+        text.push_str(" done");
+    }
+
+    Some(text)
+}
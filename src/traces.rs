@@ -0,0 +1,433 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::expressions::Expression;
+use crate::instructions::Instruction;
+use crate::machines::RegisterSource;
+use crate::observers::{Cycle, Fanout};
+use crate::screens::SCREEN_WIDTH;
+
+/// Runs the source to completion and returns every cycle it went through
+pub fn record(source: impl RegisterSource) -> Vec<Cycle> {
+    let mut trace = Vec::new();
+    Fanout::new(source).with_observer(&mut trace).run();
+    trace
+}
+
+/// The formats a trace can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TraceFormat {
+    /// Aligned columns for reading in a terminal
+    #[default]
+    Text,
+
+    /// One JSON object per cycle, for scripts
+    Jsonl,
+
+    /// A header and one row per cycle, for spreadsheets
+    Csv,
+
+    /// A Value Change Dump, for waveform viewers like GTKWave. Each cycle is
+    /// one nanosecond.
+    Vcd,
+
+    /// A Gantt chart of which instruction occupied which cycles, one row per
+    /// instruction and one column per cycle, for reading in a terminal. The
+    /// cycles an instruction stalls for are told apart from the one it
+    /// finishes on.
+    Gantt,
+
+    /// The same Gantt chart as an SVG, for a browser
+    GanttSvg,
+}
+
+impl FromStr for TraceFormat {
+    type Err = UnknownTraceFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(TraceFormat::Text),
+            "jsonl" => Ok(TraceFormat::Jsonl),
+            "csv" => Ok(TraceFormat::Csv),
+            "vcd" => Ok(TraceFormat::Vcd),
+            "gantt" => Ok(TraceFormat::Gantt),
+            "gantt-svg" => Ok(TraceFormat::GanttSvg),
+            _ => Err(UnknownTraceFormat(s.to_string())),
+        }
+    }
+}
+
+/// The name given for a trace format isn't one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTraceFormat(pub String);
+
+impl Display for UnknownTraceFormat {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            formatter,
+            "unknown trace format `{}` (expected text, jsonl, csv, vcd, gantt, or gantt-svg)",
+            self.0
+        )
+    }
+}
+
+impl Error for UnknownTraceFormat {}
+
+/// Writes the trace of a run of `program`. Every cycle records the register,
+/// the instruction occupying it (or the interrupt whose handler did), whatever
+/// the program sent out, and the pixel the beam drew on the specification's
+/// screen: its column and whether it was lit.
+pub fn write_trace(
+    out: impl Write,
+    trace: &[Cycle],
+    program: &[Instruction],
+    format: TraceFormat,
+) -> io::Result<()> {
+    write_annotated_trace(out, trace, program, format, &[])
+}
+
+/// Writes the trace like `write_trace()`, along with what every expression
+/// works out as for every cycle, e.g. `x * tick`. Text, JSONL, and CSV traces
+/// show them; the others leave them out.
+pub fn write_annotated_trace(
+    mut out: impl Write,
+    trace: &[Cycle],
+    program: &[Instruction],
+    format: TraceFormat,
+    annotations: &[Expression],
+) -> io::Result<()> {
+    let rows = trace
+        .iter()
+        .map(|cycle| Row::new(cycle, program, annotations));
+
+    match format {
+        TraceFormat::Text => write_text(&mut out, rows, annotations),
+        TraceFormat::Jsonl => write_jsonl(&mut out, rows, annotations),
+        TraceFormat::Csv => write_csv(&mut out, rows, annotations),
+        TraceFormat::Vcd => write_vcd(&mut out, rows),
+        TraceFormat::Gantt => write_gantt(&mut out, bars(rows)),
+        TraceFormat::GanttSvg => write_gantt_svg(&mut out, bars(rows)),
+    }
+}
+
+/// A cycle with everything worked out that a trace shows
+struct Row {
+    cycle: usize,
+    register: isize,
+    index: Option<usize>,
+    instruction: Option<String>,
+    signal: Option<String>,
+    interrupt: Option<usize>,
+    column: usize,
+    lit: bool,
+
+    /// What each annotation worked out as, in order
+    annotations: Vec<isize>,
+}
+
+impl Row {
+    fn new(cycle: &Cycle, program: &[Instruction], annotations: &[Expression]) -> Self {
+        let column = (cycle.number - 1) % SCREEN_WIDTH;
+
+        Row {
+            cycle: cycle.number,
+            register: cycle.register,
+            index: cycle.instruction,
+            instruction: cycle
+                .instruction
+                .and_then(|index| program.get(index))
+                .map(Instruction::to_string),
+            signal: cycle.signal.map(|signal| signal.to_string()),
+            interrupt: cycle.interrupt,
+            column,
+            lit: (column as isize).abs_diff(cycle.register) <= 1,
+            annotations: annotations
+                .iter()
+                .map(|annotation| annotation.evaluate(cycle))
+                .collect(),
+        }
+    }
+}
+
+/// The cycles an instruction occupied, for a Gantt chart
+struct Bar {
+    index: usize,
+    instruction: String,
+
+    /// The first cycle, counting from `1`
+    start: usize,
+
+    /// How many cycles. All but the last are stalls.
+    cycles: usize,
+}
+
+/// Groups the cycles by the instruction occupying them
+fn bars(rows: impl Iterator<Item = Row>) -> Vec<Bar> {
+    let mut bars: Vec<Bar> = Vec::new();
+
+    for row in rows {
+        let (Some(index), Some(instruction)) = (row.index, row.instruction) else {
+            continue;
+        };
+
+        match bars.last_mut() {
+            Some(bar) if bar.index == index => bar.cycles += 1,
+            _ => bars.push(Bar {
+                index,
+                instruction,
+                start: row.cycle,
+                cycles: 1,
+            }),
+        }
+    }
+
+    bars
+}
+
+fn write_gantt(out: &mut impl Write, bars: Vec<Bar>) -> io::Result<()> {
+    let labels: Vec<String> = bars
+        .iter()
+        .map(|bar| format!("{:>4}  {}", bar.index, bar.instruction))
+        .collect();
+    let width = labels.iter().map(String::len).max().unwrap_or(0);
+    let cycles = bars.last().map_or(0, |bar| bar.start + bar.cycles - 1);
+
+    // A tick every ten cycles
+    let ruler: String = (1..=cycles)
+        .map(|cycle| if cycle % 10 == 0 { '|' } else { '.' })
+        .collect();
+    writeln!(out, "{:width$}  {}", "cycle", ruler)?;
+
+    for (label, bar) in labels.iter().zip(&bars) {
+        writeln!(
+            out,
+            "{:width$}  {:start$}{}#",
+            label,
+            "",
+            "=".repeat(bar.cycles - 1),
+            start = bar.start - 1
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(out, "= stalled  # finished")
+}
+
+fn write_gantt_svg(out: &mut impl Write, bars: Vec<Bar>) -> io::Result<()> {
+    // Pixels per cycle and per row, and room for the instructions on the left
+    const CYCLE: usize = 4;
+    const ROW: usize = 14;
+    const LABELS: usize = 120;
+
+    let cycles = bars.last().map_or(0, |bar| bar.start + bar.cycles - 1);
+    let width = LABELS + cycles * CYCLE;
+    let height = bars.len() * ROW;
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"monospace\" font-size=\"10\">",
+        w = width,
+        h = height
+    )?;
+
+    for (row, bar) in bars.iter().enumerate() {
+        let y = row * ROW;
+        let x = LABELS + (bar.start - 1) * CYCLE;
+        let stalls = bar.cycles - 1;
+
+        writeln!(
+            out,
+            "<text x=\"2\" y=\"{}\">{} {}</text>",
+            y + ROW - 4,
+            bar.index,
+            bar.instruction
+        )?;
+
+        if stalls > 0 {
+            writeln!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#f4b942\"><title>stalled</title></rect>",
+                x,
+                y + 2,
+                stalls * CYCLE,
+                ROW - 4
+            )?;
+        }
+
+        writeln!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#2f7ed8\"><title>cycle {}</title></rect>",
+            x + stalls * CYCLE,
+            y + 2,
+            CYCLE,
+            ROW - 4,
+            bar.start + stalls
+        )?;
+    }
+
+    writeln!(out, "</svg>")
+}
+
+fn write_text(
+    out: &mut impl Write,
+    rows: impl Iterator<Item = Row>,
+    annotations: &[Expression],
+) -> io::Result<()> {
+    writeln!(out, "cycle  register  column  pixel  instruction")?;
+
+    for row in rows {
+        write!(
+            out,
+            "{:>5}  {:>8}  {:>6}  {:^5}  {}",
+            row.cycle,
+            row.register,
+            row.column,
+            if row.lit { '#' } else { '.' },
+            row.instruction.as_deref().unwrap_or("-"),
+        )?;
+
+        if let Some(line) = row.interrupt {
+            write!(out, "  (interrupt {})", line)?;
+        }
+
+        for (annotation, value) in annotations.iter().zip(&row.annotations) {
+            write!(out, "  [{} = {}]", annotation, value)?;
+        }
+
+        if let Some(signal) = row.signal {
+            write!(out, "  -> {}", signal)?;
+        }
+
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn write_jsonl(
+    out: &mut impl Write,
+    rows: impl Iterator<Item = Row>,
+    annotations: &[Expression],
+) -> io::Result<()> {
+    // Everything but the instruction, signal, and annotations is a number or
+    // boolean, and none of those contains anything JSON needs escaped
+    let string = |value: Option<String>| match value {
+        Some(value) => format!("\"{}\"", value),
+        None => "null".to_string(),
+    };
+
+    for row in rows {
+        // Only there if there are annotations, keyed by the expressions
+        let values: Vec<_> = annotations
+            .iter()
+            .zip(&row.annotations)
+            .map(|(annotation, value)| format!("\"{}\":{}", annotation, value))
+            .collect();
+
+        let extra = match values.is_empty() {
+            true => String::new(),
+            false => format!(",\"annotations\":{{{}}}", values.join(",")),
+        };
+
+        writeln!(
+            out,
+            "{{\"cycle\":{},\"register\":{},\"index\":{},\"instruction\":{},\"signal\":{},\"interrupt\":{},\"column\":{},\"lit\":{}{}}}",
+            row.cycle,
+            row.register,
+            row.index.map_or("null".to_string(), |index| index.to_string()),
+            string(row.instruction),
+            string(row.signal),
+            row.interrupt.map_or("null".to_string(), |line| line.to_string()),
+            row.column,
+            row.lit,
+            extra,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Annotations get a column each after the rest, headed by the expression
+fn write_csv(
+    out: &mut impl Write,
+    rows: impl Iterator<Item = Row>,
+    annotations: &[Expression],
+) -> io::Result<()> {
+    write!(
+        out,
+        "cycle,register,index,instruction,signal,column,lit,interrupt"
+    )?;
+
+    for annotation in annotations {
+        write!(out, ",{}", annotation)?;
+    }
+
+    writeln!(out)?;
+
+    for row in rows {
+        write!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            row.cycle,
+            row.register,
+            row.index.map_or(String::new(), |index| index.to_string()),
+            row.instruction.unwrap_or_default(),
+            row.signal.unwrap_or_default(),
+            row.column,
+            row.lit,
+            row.interrupt.map_or(String::new(), |line| line.to_string()),
+        )?;
+
+        for value in &row.annotations {
+            write!(out, ",{}", value)?;
+        }
+
+        writeln!(out)?;
+    }
+
+    Ok(())
+}
+
+fn write_vcd(out: &mut impl Write, rows: impl Iterator<Item = Row>) -> io::Result<()> {
+    writeln!(out, "$version legolos $end")?;
+    writeln!(out, "$timescale 1ns $end")?;
+    writeln!(out, "$scope module machine $end")?;
+    writeln!(out, "$var integer 64 r register $end")?;
+    writeln!(out, "$var integer 64 i instruction $end")?;
+    writeln!(out, "$var wire 1 l lit $end")?;
+    writeln!(out, "$upscope $end")?;
+    writeln!(out, "$enddefinitions $end")?;
+
+    // Only changes are dumped, so remember what was dumped last
+    let mut last: Option<Row> = None;
+
+    for row in rows {
+        writeln!(out, "#{}", row.cycle)?;
+
+        if last.as_ref().map(|last| last.register) != Some(row.register) {
+            writeln!(out, "b{:b} r", row.register)?;
+        }
+
+        if last.as_ref().map(|last| last.index) != Some(row.index) {
+            match row.index {
+                Some(index) => writeln!(out, "b{:b} i", index)?,
+                None => writeln!(out, "bx i")?,
+            }
+        }
+
+        if last.as_ref().map(|last| last.lit) != Some(row.lit) {
+            writeln!(out, "{}l", row.lit as u8)?;
+        }
+
+        last = Some(row);
+    }
+
+    // Close out the last cycle so viewers show it in full
+    if let Some(last) = last {
+        writeln!(out, "#{}", last.cycle + 1)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+
+/// Writes the number seven bits at a time, least significant first, with the
+/// high bit set on every byte but the last (i.e. LEB128)
+pub fn write(bytes: &mut Vec<u8>, mut number: usize) {
+    while number >= 0x80 {
+        bytes.push(number as u8 | 0x80);
+        number >>= 7;
+    }
+
+    bytes.push(number as u8);
+}
+
+/// The inverse of `write()`, consuming the bytes it reads. Returns `None` if
+/// they're cut short or make a number too big for a `usize`.
+pub fn read(bytes: &mut &[u8]) -> Option<usize> {
+    let mut number = 0usize;
+
+    for shift in (0..usize::BITS).step_by(7) {
+        let (byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+
+        // The last byte has room for fewer than seven bits, and any it sets
+        // past them would be shifted out
+        let bits = (byte & 0x7f) as usize;
+        let shifted = bits.checked_shl(shift)?;
+        if shifted >> shift != bits {
+            return None;
+        }
+
+        number |= shifted;
+
+        if byte & 0x80 == 0 {
+            return Some(number);
+        }
+    }
+
+    None
+}
+
+/// Writes a signed number, zigzagged so small negative numbers stay small:
+/// `0, -1, 1, -2, ...` become `0, 1, 2, 3, ...`
+pub fn write_signed(bytes: &mut Vec<u8>, number: isize) {
+    let zigzag = (number << 1) ^ (number >> (isize::BITS - 1));
+    write(bytes, zigzag as usize);
+}
+
+/// The inverse of `write_signed()`
+pub fn read_signed(bytes: &mut &[u8]) -> Option<isize> {
+    let zigzag = read(bytes)?;
+    Some((zigzag >> 1) as isize ^ -((zigzag & 1) as isize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn round_trips_numbers_to_their_ends() {
+        for number in [0, 1, 0x7f, 0x80, 300, usize::MAX / 2, usize::MAX] {
+            let mut bytes = Vec::new();
+            write(&mut bytes, number);
+
+            let mut rest = bytes.as_slice();
+            assert_eq!(read(&mut rest), Some(number));
+            assert!(rest.is_empty());
+        }
+
+        for number in [0, -1, 1, -64, 64, isize::MIN, isize::MAX] {
+            let mut bytes = Vec::new();
+            write_signed(&mut bytes, number);
+            assert_eq!(read_signed(&mut bytes.as_slice()), Some(number));
+        }
+    }
+
+    #[test]
+    fn reads_leave_whatever_follows() {
+        let mut bytes: &[u8] = &[0xac, 0x02, 0x05];
+
+        assert_eq!(read(&mut bytes), Some(300));
+        assert_eq!(bytes, [0x05]);
+    }
+
+    #[test]
+    fn refuses_numbers_cut_short_or_too_big() {
+        assert_eq!(read(&mut [].as_slice()), None);
+        assert_eq!(read(&mut [0x80, 0x80].as_slice()), None);
+
+        let mut bytes = Vec::new();
+        write(&mut bytes, usize::MAX);
+        let last = bytes.len() - 1;
+
+        // One more bit than fits, then a byte too many
+        bytes[last] = (bytes[last] << 1) | 1;
+        assert_eq!(read(&mut bytes.as_slice()), None);
+
+        let mut bytes = vec![0xff; last];
+        bytes.extend([0x80, 0x00]);
+        assert_eq!(read(&mut bytes.as_slice()), None);
+    }
+}
@@ -0,0 +1,116 @@
+use wasm_bindgen::prelude::*;
+
+use crate::assembly;
+use crate::debugger::Debugger;
+use crate::machines::VirtualMachine;
+use crate::screens::Screen;
+use crate::timelines;
+
+/// Runs the assembly program and returns the screen it draws, as text.
+/// Throws if the program doesn't assemble or draws past the screen.
+#[wasm_bindgen(js_name = runProgram)]
+pub fn run_program(source: &str) -> Result<String, JsError> {
+    let program = assembly::assemble(source)?;
+
+    let mut screen = Screen::new(VirtualMachine::new(program));
+    screen.refresh()?;
+
+    Ok(screen.to_string())
+}
+
+/// Runs the assembly program and returns the register's value during every
+/// cycle. The value at index `i` is the register during cycle `i + 1`.
+#[wasm_bindgen(js_name = registerTimeline)]
+pub fn register_timeline(source: &str) -> Result<Vec<i32>, JsError> {
+    let program = assembly::assemble(source)?;
+    let timeline = timelines::register_timeline(VirtualMachine::new(program));
+
+    Ok(timeline
+        .into_iter()
+        .map(|register| register as i32)
+        .collect())
+}
+
+/// A program that runs a cycle at a time, for pages that animate the beam.
+/// It can step backwards too.
+#[wasm_bindgen]
+pub struct Machine {
+    debugger: Debugger,
+}
+
+#[wasm_bindgen]
+impl Machine {
+    /// Assembles the program, ready to run from its first cycle. Throws if it
+    /// doesn't assemble.
+    #[wasm_bindgen(constructor)]
+    pub fn new(source: &str) -> Result<Machine, JsError> {
+        let program = assembly::assemble(source)?;
+
+        Ok(Machine {
+            debugger: Debugger::new(program),
+        })
+    }
+
+    /// Runs a single cycle. Returns `false` if the program had finished.
+    pub fn step(&mut self) -> bool {
+        self.debugger.step().is_some()
+    }
+
+    /// Undoes the last cycle. Returns `false` if none have run.
+    #[wasm_bindgen(js_name = stepBack)]
+    pub fn step_back(&mut self) -> bool {
+        self.debugger.reverse_step()
+    }
+
+    /// Runs the program until it finishes
+    pub fn run(&mut self) {
+        while self.debugger.step().is_some() {}
+    }
+
+    /// Starts the program over
+    pub fn reset(&mut self) {
+        self.debugger.seek(0);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn register(&self) -> i32 {
+        self.debugger.machine().read_register() as i32
+    }
+
+    /// The number of the cycle about to run. The first is `1`.
+    #[wasm_bindgen(getter)]
+    pub fn cycle(&self) -> usize {
+        self.debugger.machine().get_ticks()
+    }
+
+    #[wasm_bindgen(getter, js_name = isExecuting)]
+    pub fn is_executing(&self) -> bool {
+        self.debugger.machine().is_executing()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> usize {
+        self.debugger.screen().width()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> usize {
+        self.debugger.screen().height()
+    }
+
+    /// Returns the screen drawn so far, as text
+    pub fn screen(&self) -> String {
+        self.debugger.screen().to_string()
+    }
+
+    /// Returns the screen drawn so far as one byte per pixel, row by row:
+    /// `1` if it's lit, `0` if not. It's ready to be copied into a canvas's
+    /// image data.
+    pub fn pixels(&self) -> Vec<u8> {
+        let screen = self.debugger.screen();
+
+        (0..screen.height())
+            .flat_map(|y| (0..screen.width()).map(move |x| screen.is_lit(x, y) as u8))
+            .collect()
+    }
+}
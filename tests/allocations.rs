@@ -0,0 +1,94 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use legolos::instructions::Instruction;
+use legolos::interrupts::InterruptController;
+use legolos::machines::VirtualMachine;
+
+/// Counts the allocations made on each thread, so tests running alongside
+/// don't get in each other's counts
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        System.realloc(ptr, layout, size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns how many allocations `run` made
+fn allocations<T>(run: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let value = run();
+    (value, ALLOCATIONS.with(Cell::get) - before)
+}
+
+fn program() -> Vec<Instruction> {
+    let mut program = Vec::new();
+    for value in -50..50 {
+        program.push(Instruction::Addx(value));
+        program.push(Instruction::Noop);
+    }
+    program
+}
+
+#[test]
+fn construction_allocates_only_the_program_and_recorder() {
+    let program = program();
+
+    let (_, count) = allocations(|| VirtualMachine::from_slice(&program));
+
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn cycling_never_allocates() {
+    let program = program();
+    let mut machine = VirtualMachine::from_slice(&program);
+
+    let (_, count) = allocations(|| while machine.try_cycle().is_ok() {});
+
+    assert_eq!(count, 0);
+    assert!(!machine.is_executing());
+}
+
+#[test]
+fn servicing_interrupts_never_allocates() {
+    let program = program();
+    let mut interrupts = InterruptController::new();
+    let low = interrupts.add_line(1, vec![Instruction::Addx(1); 3]);
+    let high = interrupts.add_line(2, vec![Instruction::Noop; 2]);
+    let mut machine = VirtualMachine::from_slice(&program).with_interrupts(interrupts);
+
+    let (cycles, count) = allocations(|| {
+        let mut cycles = 0;
+        while machine.try_cycle().is_ok() {
+            cycles += 1;
+            match cycles % 20 {
+                0 => machine.interrupts().raise(low),
+                3 | 5 => machine.interrupts().raise(high),
+                _ => {}
+            }
+        }
+        cycles
+    });
+
+    assert_eq!(count, 0);
+    assert!(cycles > 300);
+}
@@ -0,0 +1,15 @@
+#![cfg(feature = "test-util")]
+
+use legolos::properties::Harness;
+
+#[test]
+fn machine_matches_model() {
+    Harness::new().assert();
+}
+
+#[test]
+fn machine_matches_model_for_other_seeds() {
+    for seed in 1..=4 {
+        Harness::new().with_seed(seed).assert();
+    }
+}